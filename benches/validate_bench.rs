@@ -2,6 +2,7 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use plexify::commands::validate::ValidateCommand;
 use plexify::ignore::IgnoreFilter;
 use std::fs;
+use std::path::PathBuf;
 use tempfile::TempDir;
 use tokio::runtime::Runtime;
 use walkdir::WalkDir;
@@ -48,6 +49,57 @@ fn create_test_media_library_with_ignored_dirs(size: usize) -> TempDir {
     temp_dir
 }
 
+/// Deep synthetic tree with a .plexifyignore at every level, so many pattern
+/// sources apply to each path
+fn create_deep_ignore_tree(depth: usize, breadth: usize, files_per_dir: usize) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let mut dirs = vec![temp_dir.path().to_path_buf()];
+
+    for level in 0..depth {
+        let mut next = Vec::new();
+        for dir in &dirs {
+            fs::write(
+                dir.join(".plexifyignore"),
+                format!("*.tmp\nskip_{level}/\n!keep_{level}.mkv\nextras_*"),
+            )
+            .unwrap();
+            for i in 0..files_per_dir {
+                fs::write(dir.join(format!("file_{i}.mkv")), "").unwrap();
+            }
+            for b in 0..breadth {
+                let child = dir.join(format!("dir_{level}_{b}"));
+                fs::create_dir_all(&child).unwrap();
+                next.push(child);
+            }
+        }
+        dirs = next;
+    }
+
+    temp_dir
+}
+
+fn bench_ignore_deep_tree(c: &mut Criterion) {
+    let temp_dir = create_deep_ignore_tree(6, 3, 5);
+    let root = temp_dir.path();
+    let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
+    let files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+
+    c.bench_function("ignore_deep_tree", |b| {
+        b.iter(|| {
+            let ignored = files
+                .iter()
+                .filter(|path| filter.should_ignore(path, false))
+                .count();
+            black_box(ignored);
+        });
+    });
+}
+
 fn bench_directory_skip_old_approach(c: &mut Criterion) {
     c.bench_function("directory_skip_old_approach", |b| {
         b.iter(|| {
@@ -57,14 +109,12 @@ fn bench_directory_skip_old_approach(c: &mut Criterion) {
 
             // Old approach: visit all files but ignore them individually
             let mut count = 0;
-            for entry in WalkDir::new(&root).follow_links(false).into_iter() {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if !path.is_dir() {
-                        // Only check files, not directories
-                        if !filter.should_ignore(path) {
-                            count += 1;
-                        }
+            for entry in WalkDir::new(root).follow_links(false).into_iter().flatten() {
+                let path = entry.path();
+                if !path.is_dir() {
+                    // Only check files, not directories
+                    if !filter.should_ignore(path, false) {
+                        count += 1;
                     }
                 }
             }
@@ -82,7 +132,7 @@ fn bench_directory_skip_new_approach(c: &mut Criterion) {
 
             // New approach: skip entire directories before traversing
             let mut count = 0;
-            for entry in WalkDir::new(&root)
+            for entry in WalkDir::new(root)
                 .follow_links(false)
                 .into_iter()
                 .filter_entry(|e| {
@@ -95,14 +145,13 @@ fn bench_directory_skip_new_approach(c: &mut Criterion) {
                     }
                     true
                 })
+                .flatten()
             {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if !path.is_dir() {
-                        // Only count files
-                        if !filter.should_ignore(path) {
-                            count += 1;
-                        }
+                let path = entry.path();
+                if !path.is_dir() {
+                    // Only count files
+                    if !filter.should_ignore(path, false) {
+                        count += 1;
                     }
                 }
             }
@@ -201,6 +250,7 @@ criterion_group!(
     bench_validate_medium,
     bench_validate_large,
     bench_directory_skip_old_approach,
-    bench_directory_skip_new_approach
+    bench_directory_skip_new_approach,
+    bench_ignore_deep_tree
 );
 criterion_main!(benches);
@@ -1,17 +1,40 @@
 use anyhow::{anyhow, Result};
 use std::path::PathBuf;
-use tracing::{info, warn};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use tracing::{error, info, warn};
 
-use crate::job::MediaFileType;
+use crate::job::{AudioMode, Job, MediaFileType, OutputTemplate, SubtitleMode, VideoCodec};
 use crate::queue::JobQueue;
 
 use super::job_processor::{JobProcessResult, JobProcessor, JobProcessorConfig};
 
+/// Outcome of adding a stream of files, one per line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AddSummary {
+    pub created: usize,
+    /// Nothing to do, e.g. the output exists or the job is already queued
+    pub skipped: usize,
+    pub failed: usize,
+}
+
 /// Command to create a job for an individual media file
+#[derive(Clone)]
 pub struct AddCommand {
     file_path: PathBuf,
     work_root: PathBuf,
     preset: Option<String>,
+    output_template: Option<OutputTemplate>,
+    audio_mode: AudioMode,
+    tonemap: bool,
+    auto_chapters: bool,
+    downmix_stereo: bool,
+    tag_metadata: bool,
+    ass_subtitles: SubtitleMode,
+    burn_subtitles: bool,
+    dry_run: bool,
+    notes: Option<String>,
+    codec: VideoCodec,
+    sample_secs: Option<u64>,
 }
 
 impl AddCommand {
@@ -20,10 +43,94 @@ impl AddCommand {
             file_path,
             work_root,
             preset,
+            output_template: None,
+            audio_mode: AudioMode::default(),
+            tonemap: false,
+            auto_chapters: false,
+            downmix_stereo: false,
+            tag_metadata: false,
+            ass_subtitles: SubtitleMode::default(),
+            burn_subtitles: false,
+            dry_run: false,
+            notes: None,
+            codec: VideoCodec::default(),
+            sample_secs: None,
         }
     }
 
-    pub async fn execute(&self) -> Result<()> {
+    /// Queue a preview encode of the first `sample_secs` seconds instead of the whole file
+    pub fn with_sample(mut self, sample_secs: Option<u64>) -> Self {
+        self.sample_secs = sample_secs;
+        self
+    }
+
+    /// Encode the job in this codec, with the preset CRF scaled to match
+    pub fn with_codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Attach a note to the job, e.g. why it has non-default settings
+    pub fn with_notes(mut self, notes: Option<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Tag outputs with a title parsed from the path and `encoder=plexify`
+    pub fn with_tag_metadata(mut self, tag_metadata: bool) -> Self {
+        self.tag_metadata = tag_metadata;
+        self
+    }
+
+    /// Report the job that would be created without touching the queue
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Burn subtitles into the video for players that cannot show soft subtitles
+    pub fn with_burn_subtitles(mut self, burn_subtitles: bool) -> Self {
+        self.burn_subtitles = burn_subtitles;
+        self
+    }
+
+    /// Mux `.ass` sidecar subtitles as mov_text (losing styling) or burn them in
+    pub fn with_ass_subtitles(mut self, ass_subtitles: SubtitleMode) -> Self {
+        self.ass_subtitles = ass_subtitles;
+        self
+    }
+
+    /// Downmix 5.1 and other multichannel audio to stereo
+    pub fn with_downmix_stereo(mut self, downmix_stereo: bool) -> Self {
+        self.downmix_stereo = downmix_stereo;
+        self
+    }
+
+    /// Tone-map HDR sources to SDR (detected with ffprobe when the job runs)
+    pub fn with_tonemap(mut self, tonemap: bool) -> Self {
+        self.tonemap = tonemap;
+        self
+    }
+
+    /// Add chapter markers at silent gaps, found with ffmpeg's silencedetect when the job runs
+    pub fn with_auto_chapters(mut self, auto_chapters: bool) -> Self {
+        self.auto_chapters = auto_chapters;
+        self
+    }
+
+    /// Write the job's audio in this mode
+    pub fn with_audio_mode(mut self, audio_mode: AudioMode) -> Self {
+        self.audio_mode = audio_mode;
+        self
+    }
+
+    /// Render the job's output path from a template
+    pub fn with_output_template(mut self, output_template: Option<OutputTemplate>) -> Self {
+        self.output_template = output_template;
+        self
+    }
+
+    pub async fn execute(&self) -> Result<JobProcessResult> {
         if !self.file_path.exists() {
             return Err(anyhow!("File does not exist: {:?}", self.file_path));
         }
@@ -51,10 +158,22 @@ impl AddCommand {
             .to_path_buf();
 
         let queue = JobQueue::new(media_root.clone(), self.work_root.clone());
-        queue.init().await?;
+        if !self.dry_run {
+            queue.init().await?;
+        }
 
         // Get configuration settings for the job
-        let config = JobProcessorConfig::from_preset(self.preset.as_deref())?;
+        let config = JobProcessorConfig::from_preset_for(self.preset.as_deref(), self.codec)?
+            .with_output_template(self.output_template.clone())
+            .with_audio_mode(self.audio_mode)
+            .with_tonemap(self.tonemap)
+            .with_auto_chapters(self.auto_chapters)
+            .with_downmix_stereo(self.downmix_stereo)
+            .with_tag_metadata(self.tag_metadata)
+            .with_ass_subtitles(self.ass_subtitles)
+            .with_burn_subtitles(self.burn_subtitles)
+            .with_notes(self.notes.clone())
+            .with_sample(self.sample_secs);
 
         // Get relative path from media root
         let relative_path = self
@@ -65,6 +184,18 @@ impl AddCommand {
 
         // Process the file
         let processor = JobProcessor::new(&queue, &config, &media_root);
+        if self.dry_run {
+            let mut planned = Vec::new();
+            let result = processor
+                .plan_media_file(&relative_path, file_type.clone(), &mut planned)
+                .await?;
+            let job = match planned.pop() {
+                Some(job) => job,
+                None => processor.build_job(&relative_path, file_type)?,
+            };
+            self.report_dry_run(&job, &result);
+            return Ok(result);
+        }
         let result = processor
             .process_media_file(&relative_path, file_type.clone())
             .await?;
@@ -84,7 +215,25 @@ impl AddCommand {
                         relative_path
                     );
                 }
+                MediaFileType::Mp4 => {
+                    info!("✅ Successfully created remux job for: {:?}", relative_path);
+                }
+                MediaFileType::Subtitle => {
+                    info!(
+                        "✅ Successfully created subtitle conversion job for: {:?}",
+                        relative_path
+                    );
+                }
             },
+            JobProcessResult::AlreadyFaststart => {
+                info!("✅ No action needed - MP4 already streams without remux.");
+            }
+            JobProcessResult::UnreadableMp4 => {
+                warn!("⚠️ Nothing queued - could not check the MP4 layout.");
+            }
+            JobProcessResult::AcceptableCodec => {
+                info!("✅ No action needed - video is already in an accepted codec.");
+            }
             JobProcessResult::OutputExists => {
                 warn!("⚠️ Output file already exists for: {:?}", relative_path);
                 info!("✅ No action needed - output file already exists.");
@@ -93,15 +242,98 @@ impl AddCommand {
                 warn!("⚠️ Job already exists in queue for: {:?}", relative_path);
                 info!("✅ No action needed - job already queued.");
             }
+            JobProcessResult::AlreadyDone => {
+                info!("✅ No action needed - plexify already converted this file.");
+            }
             JobProcessResult::MissingSubtitle => {
                 return Err(anyhow!(
                     "Missing required subtitle file (.vtt) for WebM file: {:?}",
                     self.file_path
                 ));
             }
+            JobProcessResult::InvalidSubtitle => {
+                return Err(anyhow!(
+                    "Subtitle file (.vtt) for WebM file {:?} is empty or not WebVTT",
+                    self.file_path
+                ));
+            }
         }
 
-        Ok(())
+        Ok(result)
+    }
+
+    /// Add every path read from `reader`, one per line, as soon as each line
+    /// arrives (e.g. `find ... | plexify add --input-glob-from-stdin`). A file
+    /// that cannot be added is reported and the rest are still processed.
+    pub async fn execute_lines<R: AsyncBufRead + Unpin>(&self, reader: R) -> Result<AddSummary> {
+        let mut summary = AddSummary::default();
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            // Relative paths (as printed by `find .`) would end up relative in the job
+            let file_path = std::path::absolute(line)?;
+            let command = Self {
+                file_path: file_path.clone(),
+                ..self.clone()
+            };
+            match command.execute().await {
+                Ok(JobProcessResult::Created) => summary.created += 1,
+                Ok(_) => summary.skipped += 1,
+                Err(e) => {
+                    error!("❌ {:?}: {}", file_path, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        info!(
+            "📥 Added {} job(s) from stdin, {} skipped, {} failed",
+            summary.created, summary.skipped, summary.failed
+        );
+        if summary.failed > 0 {
+            return Err(anyhow!("{} file(s) could not be added", summary.failed));
+        }
+        Ok(summary)
+    }
+
+    /// Log the job a dry run would create and whether it would be skipped
+    fn report_dry_run(&self, job: &Job, result: &JobProcessResult) {
+        let quality = &job.quality_settings;
+        let subtitle = match &job.subtitle_path {
+            Some(path) if path.exists() => format!("{path:?}"),
+            Some(path) => format!("{path:?} (missing)"),
+            None => "embedded".to_string(),
+        };
+        let outcome = match result {
+            JobProcessResult::Created => "would be queued",
+            JobProcessResult::OutputExists => "skipped: output already exists",
+            JobProcessResult::AlreadyQueued => "skipped: already queued",
+            JobProcessResult::AlreadyDone => "skipped: already converted (done marker)",
+            JobProcessResult::MissingSubtitle => "skipped: missing subtitle file",
+            JobProcessResult::InvalidSubtitle => "skipped: subtitle file is empty or not WebVTT",
+            JobProcessResult::AlreadyFaststart => "skipped: MP4 already streams without remux",
+            JobProcessResult::UnreadableMp4 => "skipped: could not read the MP4 layout",
+            JobProcessResult::AcceptableCodec => "skipped: video already in an accepted codec",
+        };
+
+        info!("🔍 Dry run for {:?}, nothing was queued", self.file_path);
+        info!("   Type: {:?}", job.file_type);
+        info!("   Output: {:?}", job.output_path);
+        info!(
+            "   Preset: {} (preset {}, CRF {}, audio {})",
+            self.preset.as_deref().unwrap_or("from environment"),
+            quality.ffmpeg_preset,
+            quality.ffmpeg_crf,
+            quality.ffmpeg_audio_bitrate
+        );
+        info!("   Subtitle: {}", subtitle);
+        if let Some(secs) = job.sample_secs {
+            info!("   Sample: first {}s", secs);
+        }
+        info!("   Result: {}", outcome);
     }
 }
 
@@ -120,7 +352,7 @@ mod tests {
         let webm_file = media_path.join("video.webm");
         let vtt_file = media_path.join("video.vtt");
         fs::write(&webm_file, "").unwrap();
-        fs::write(&vtt_file, "").unwrap();
+        fs::write(&vtt_file, "WEBVTT\n").unwrap();
 
         let add_cmd = AddCommand::new(webm_file, temp_dir.path().to_path_buf(), None);
 
@@ -290,4 +522,93 @@ mod tests {
             .to_string()
             .contains("Path is not a file"));
     }
+
+    #[tokio::test]
+    async fn test_add_dry_run_does_not_queue() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_path = temp_dir.path().join("media");
+        let work_path = temp_dir.path().join("work");
+        fs::create_dir_all(&media_path).unwrap();
+        fs::write(media_path.join("video.webm"), "").unwrap();
+        fs::write(media_path.join("movie.mkv"), "").unwrap();
+        fs::write(media_path.join("movie.mp4"), "").unwrap();
+
+        let dry_run = |file: &str| {
+            AddCommand::new(media_path.join(file), work_path.clone(), None).with_dry_run(true)
+        };
+
+        // A missing subtitle is reported instead of failing
+        let result = dry_run("video.webm").execute().await.unwrap();
+        assert_eq!(result, JobProcessResult::MissingSubtitle);
+
+        let result = dry_run("movie.mkv").execute().await.unwrap();
+        assert_eq!(result, JobProcessResult::OutputExists);
+
+        fs::remove_file(media_path.join("movie.mp4")).unwrap();
+        let result = dry_run("movie.mkv").execute().await.unwrap();
+        assert_eq!(result, JobProcessResult::Created);
+
+        assert!(!work_path.join("_queue").exists());
+    }
+
+    #[tokio::test]
+    async fn test_add_lines_enqueues_each_file_as_it_arrives() {
+        use tokio::io::{AsyncWriteExt, BufReader};
+
+        let temp_dir = TempDir::new().unwrap();
+        let media_path = temp_dir.path().join("media");
+        let work_path = temp_dir.path().join("work");
+        fs::create_dir_all(&media_path).unwrap();
+        for name in ["a.mkv", "b.mkv", "c.mkv", "done.mkv", "done.mp4"] {
+            fs::write(media_path.join(name), "").unwrap();
+        }
+        let queue_dir = work_path.join("_queue");
+        let job_count = || {
+            fs::read_dir(&queue_dir).map_or(0, |entries| {
+                entries
+                    .filter(|entry| {
+                        entry.as_ref().unwrap().path().extension() == Some("job".as_ref())
+                    })
+                    .count()
+            })
+        };
+
+        let (mut writer, reader) = tokio::io::duplex(64);
+        let command = AddCommand::new(PathBuf::new(), work_path.clone(), None);
+        let adding =
+            tokio::spawn(async move { command.execute_lines(BufReader::new(reader)).await });
+
+        // Each job shows up while the pipe is still open
+        for (index, name) in ["a.mkv", "b.mkv"].iter().enumerate() {
+            let line = format!("{}\n", media_path.join(name).display());
+            writer.write_all(line.as_bytes()).await.unwrap();
+            tokio::time::timeout(std::time::Duration::from_secs(5), async {
+                while job_count() < index + 1 {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("job was not created before the next line");
+        }
+
+        // Blank lines are ignored and converted files are skipped
+        let rest = format!(
+            "\n{}\n{}\n",
+            media_path.join("c.mkv").display(),
+            media_path.join("done.mkv").display()
+        );
+        writer.write_all(rest.as_bytes()).await.unwrap();
+        drop(writer);
+
+        let summary = adding.await.unwrap().unwrap();
+        assert_eq!(
+            summary,
+            AddSummary {
+                created: 3,
+                skipped: 1,
+                failed: 0,
+            }
+        );
+        assert_eq!(job_count(), 3);
+    }
 }
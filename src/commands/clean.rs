@@ -1,13 +1,25 @@
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
-use tracing::info;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+use walkdir::WalkDir;
 
+use crate::config::Config;
+use crate::error::PlexifyError;
+use crate::ffmpeg::FFmpegProcessor;
+use crate::job::{DONE_MARKER_EXTENSION, KEPT_ORIGINAL_MARKER_EXTENSION, SUBTITLE_EXTENSIONS};
 use crate::queue::JobQueue;
 
+use super::prune::prune_empty_dirs;
+
+/// Outputs shorter than this fraction of the source duration are treated as truncated
+const MIN_OUTPUT_DURATION_RATIO: f64 = 0.9;
+
 /// Command to clean up temporary files and directories
 pub struct CleanCommand {
     media_root: PathBuf,
     work_root: PathBuf,
+    orphans: bool,
+    prune_empty: bool,
 }
 
 impl CleanCommand {
@@ -15,21 +27,53 @@ impl CleanCommand {
         Self {
             media_root,
             work_root,
+            orphans: false,
+            prune_empty: false,
         }
     }
 
+    /// Instead of clearing the queue, delete `.disabled` sources whose converted output is verified
+    pub fn with_orphans(mut self, orphans: bool) -> Self {
+        self.orphans = orphans;
+        self
+    }
+
+    /// Remove directories left empty once cleanup is done
+    pub fn with_prune_empty(mut self, prune_empty: bool) -> Self {
+        self.prune_empty = prune_empty;
+        self
+    }
+
     pub async fn execute(&self) -> Result<()> {
         if !self.media_root.exists() {
-            return Err(anyhow!(
-                "Media directory does not exist: {:?}",
-                self.media_root
-            ));
+            return Err(PlexifyError::MediaRootMissing(self.media_root.clone()).into());
         }
 
         if !self.media_root.is_dir() {
-            return Err(anyhow!("Path is not a directory: {:?}", self.media_root));
+            return Err(PlexifyError::NotADirectory(self.media_root.clone()).into());
+        }
+
+        if self.orphans {
+            self.clean_orphans().await?;
+        } else {
+            self.clean_queue().await?;
+        }
+
+        if self.prune_empty {
+            // Never prune inside a separate work directory
+            let protected: Vec<PathBuf> = if self.work_root != self.media_root {
+                vec![self.work_root.clone()]
+            } else {
+                Vec::new()
+            };
+            prune_empty_dirs(&self.media_root, &protected)?;
         }
 
+        Ok(())
+    }
+
+    /// Remove the queue directories and worker log
+    async fn clean_queue(&self) -> Result<()> {
         info!("🧹 Cleaning up temporary files...");
 
         let queue = JobQueue::new(self.media_root.clone(), self.work_root.clone());
@@ -45,13 +89,145 @@ impl CleanCommand {
         info!("✅ Cleanup complete.");
         Ok(())
     }
+
+    /// Delete disabled sources (and their disabled subtitles) once the converted
+    /// output has been verified with ffprobe. Anything unverified is kept.
+    async fn clean_orphans(&self) -> Result<()> {
+        info!("🧹 Looking for disabled sources with verified outputs...");
+
+        let processor = FFmpegProcessor::new(Config::from_env(), false);
+        let mut removed = 0;
+        let mut kept = 0;
+        let mut bytes_freed = 0;
+
+        for source in self.find_disabled_sources() {
+            match self.verify_output(&processor, &source).await {
+                Ok(output) => {
+                    let size = tokio::fs::metadata(&source)
+                        .await
+                        .map(|m| m.len())
+                        .unwrap_or(0);
+                    tokio::fs::remove_file(&source).await?;
+                    info!("🗑️ Removed {:?} (verified output: {:?})", source, output);
+                    bytes_freed += size;
+                    removed += 1;
+
+                    // A disabled source may have a disabled subtitle sidecar alongside it
+                    for ext in SUBTITLE_EXTENSIONS {
+                        let disabled_subtitle =
+                            Self::original_path(&source).with_extension(format!("{ext}.disabled"));
+                        if disabled_subtitle.exists() {
+                            tokio::fs::remove_file(&disabled_subtitle).await?;
+                            debug!("Removed disabled subtitle: {:?}", disabled_subtitle);
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Keeping {:?}: {}", source, e);
+                    kept += 1;
+                }
+            }
+        }
+
+        info!(
+            "✅ Removed {} disabled sources ({:.1} MB freed), kept {} without a verified output.",
+            removed,
+            bytes_freed as f64 / 1_048_576.0,
+            kept
+        );
+
+        // Done markers are only meaningful while their output is still around
+        for marker in self.find_files(&format!(".{DONE_MARKER_EXTENSION}")) {
+            if !Self::original_path(&marker).exists() {
+                tokio::fs::remove_file(&marker).await?;
+                info!("🗑️ Removed stale done marker {:?}", marker);
+            }
+        }
+        for marker in self.find_files(&format!(".{KEPT_ORIGINAL_MARKER_EXTENSION}")) {
+            if !Self::original_path(&marker).exists() {
+                tokio::fs::remove_file(&marker).await?;
+                info!("🗑️ Removed stale kept-original marker {:?}", marker);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find `*.webm.disabled` and `*.mkv.disabled` files in the media tree
+    fn find_disabled_sources(&self) -> Vec<PathBuf> {
+        let mut sources = self.find_files(".webm.disabled");
+        sources.extend(self.find_files(".mkv.disabled"));
+        sources
+    }
+
+    /// Find files in the media tree (outside the work root) whose name ends with `suffix`
+    fn find_files(&self, suffix: &str) -> Vec<PathBuf> {
+        WalkDir::new(&self.media_root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| !e.path().starts_with(&self.work_root) || e.depth() == 0)
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|path| path.to_string_lossy().to_lowercase().ends_with(suffix))
+            .collect()
+    }
+
+    /// The path without its last extension (`video.mkv.disabled` -> `video.mkv`)
+    fn original_path(disabled: &Path) -> PathBuf {
+        disabled.with_extension("")
+    }
+
+    /// Check that the converted output for a disabled source exists and is playable.
+    /// Returns the output path when verified.
+    async fn verify_output(&self, processor: &FFmpegProcessor, source: &Path) -> Result<PathBuf> {
+        let output = Self::original_path(source).with_extension("mp4");
+
+        let metadata = tokio::fs::metadata(&output)
+            .await
+            .map_err(|_| anyhow!("no converted output at {output:?}"))?;
+        if metadata.len() == 0 {
+            return Err(anyhow!("converted output {output:?} is empty"));
+        }
+
+        let output_duration = processor.get_duration(&output).await?;
+        if output_duration <= 0.0 {
+            return Err(anyhow!("converted output {output:?} has no duration"));
+        }
+
+        // Guard against truncated conversions when the source can still be probed
+        if let Ok(source_duration) = processor.get_duration(source).await {
+            if output_duration < source_duration * MIN_OUTPUT_DURATION_RATIO {
+                return Err(anyhow!(
+                    "converted output is {output_duration:.1}s but the source is {source_duration:.1}s"
+                ));
+            }
+        }
+
+        Ok(output)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(unix)]
+    use crate::test_support::{self, EnvGuard};
+    use serial_test::serial;
+    use std::fs;
     use tempfile::TempDir;
 
+    /// Install a fake ffprobe that reports a fixed duration (or fails for "broken" files)
+    #[cfg(unix)]
+    fn fake_ffprobe(dir: &Path) -> EnvGuard {
+        test_support::fake_tool(
+            "PLEXIFY_FFPROBE_PATH",
+            dir,
+            "ffprobe",
+            "case \"$*\" in *broken*) exit 1;; esac\necho 120.5\n",
+        )
+    }
+
     #[tokio::test]
     async fn test_clean_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -70,4 +246,123 @@ mod tests {
         let result = clean_cmd.execute().await;
         assert!(result.is_err());
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_orphans_deleted_when_output_verified() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(media_root.join("Show")).unwrap();
+        let _ffprobe = fake_ffprobe(temp_dir.path());
+
+        fs::write(media_root.join("Show/episode.webm.disabled"), "source").unwrap();
+        fs::write(media_root.join("Show/episode.vtt.disabled"), "subs").unwrap();
+        fs::write(media_root.join("Show/episode.mp4"), "output").unwrap();
+
+        CleanCommand::new(media_root.clone(), temp_dir.path().join("work"))
+            .with_orphans(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(!media_root.join("Show/episode.webm.disabled").exists());
+        assert!(!media_root.join("Show/episode.vtt.disabled").exists());
+        assert!(media_root.join("Show/episode.mp4").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_orphans_kept_without_verified_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        let _ffprobe = fake_ffprobe(temp_dir.path());
+
+        // No output at all
+        fs::write(media_root.join("missing.mkv.disabled"), "source").unwrap();
+        // Empty output
+        fs::write(media_root.join("empty.mkv.disabled"), "source").unwrap();
+        fs::write(media_root.join("empty.mp4"), "").unwrap();
+        // Output that ffprobe can't read
+        fs::write(media_root.join("broken.mkv.disabled"), "source").unwrap();
+        fs::write(media_root.join("broken.mp4"), "garbage").unwrap();
+
+        CleanCommand::new(media_root.clone(), temp_dir.path().join("work"))
+            .with_orphans(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(media_root.join("missing.mkv.disabled").exists());
+        assert!(media_root.join("empty.mkv.disabled").exists());
+        assert!(media_root.join("broken.mkv.disabled").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_orphans_removes_stale_done_markers() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        let _ffprobe = fake_ffprobe(temp_dir.path());
+
+        fs::write(media_root.join("kept.mp4"), "output").unwrap();
+        fs::write(media_root.join("kept.mp4.plexify-done"), "id").unwrap();
+        fs::write(media_root.join("gone.mp4.plexify-done"), "id").unwrap();
+        fs::write(media_root.join("source.mkv"), "source").unwrap();
+        fs::write(media_root.join("source.mkv.plexify-kept"), "").unwrap();
+        fs::write(media_root.join("deleted.mkv.plexify-kept"), "").unwrap();
+
+        CleanCommand::new(media_root.clone(), temp_dir.path().join("work"))
+            .with_orphans(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(media_root.join("kept.mp4.plexify-done").exists());
+        assert!(!media_root.join("gone.mp4.plexify-done").exists());
+        assert!(media_root.join("source.mkv.plexify-kept").exists());
+        assert!(!media_root.join("deleted.mkv.plexify-kept").exists());
+    }
+
+    #[tokio::test]
+    async fn test_clean_prune_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf());
+        queue.init().await.unwrap();
+
+        fs::create_dir_all(media_root.join("Downloads/partial")).unwrap();
+        fs::create_dir_all(media_root.join("Extras")).unwrap();
+        fs::write(media_root.join("Extras/.plexifyignore"), "*").unwrap();
+
+        CleanCommand::new(media_root.to_path_buf(), media_root.to_path_buf())
+            .with_prune_empty(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(!media_root.join("Downloads").exists());
+        assert!(!media_root.join("_queue").exists());
+        assert!(media_root.join("Extras/.plexifyignore").exists());
+        assert!(media_root.exists());
+    }
+
+    #[tokio::test]
+    async fn test_orphans_does_not_clear_queue() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+
+        CleanCommand::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf())
+            .with_orphans(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(temp_dir.path().join("_queue").exists());
+    }
 }
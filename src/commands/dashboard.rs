@@ -0,0 +1,404 @@
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::cursor::{Hide, Show};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::io::{self, Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::ffmpeg::FFmpegProgress;
+use crate::queue::{JobQueue, QueueCounts};
+
+/// Finished jobs listed under "Recent"
+const RECENT_JOBS: usize = 5;
+/// Log lines kept for the bottom of the dashboard
+const LOG_LINES: usize = 8;
+/// How often the dashboard is redrawn and the queue recounted
+const REDRAW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The job the worker is encoding right now
+#[derive(Debug, Clone)]
+pub struct CurrentJob {
+    pub name: String,
+    pub started: Instant,
+    /// Fraction encoded so far (0.0-1.0), once ffmpeg has reported it
+    pub progress: Option<f64>,
+    /// Encoding speed relative to realtime
+    pub speed: Option<f64>,
+}
+
+/// A job that finished while the dashboard was up
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentJob {
+    pub name: String,
+    pub succeeded: bool,
+    pub elapsed: Duration,
+}
+
+/// Everything the dashboard shows, fed by the work loop and ffmpeg's progress
+#[derive(Debug, Default)]
+pub struct DashboardState {
+    pub current: Option<CurrentJob>,
+    pub counts: QueueCounts,
+    /// Most recently finished first
+    pub recent: VecDeque<RecentJob>,
+    /// Oldest first
+    pub logs: VecDeque<String>,
+}
+
+impl DashboardState {
+    pub fn start_job(&mut self, name: &str) {
+        self.current = Some(CurrentJob {
+            name: name.to_string(),
+            started: Instant::now(),
+            progress: None,
+            speed: None,
+        });
+    }
+
+    pub fn update_progress(&mut self, progress: &FFmpegProgress) {
+        if let Some(current) = &mut self.current {
+            if let Some(fraction) = progress.fraction() {
+                current.progress = Some(fraction);
+            }
+            if let Some(speed) = progress.speed {
+                current.speed = Some(speed);
+            }
+        }
+    }
+
+    /// Move the current job to the recent list
+    pub fn finish_job(&mut self, succeeded: bool) {
+        if let Some(current) = self.current.take() {
+            self.recent.push_front(RecentJob {
+                name: current.name,
+                succeeded,
+                elapsed: current.started.elapsed(),
+            });
+            self.recent.truncate(RECENT_JOBS);
+        }
+    }
+
+    /// Forget the current job without recording it (e.g. it went back to the queue)
+    pub fn clear_job(&mut self) {
+        self.current = None;
+    }
+
+    pub fn set_counts(&mut self, counts: QueueCounts) {
+        self.counts = counts;
+    }
+
+    /// Keep the lines of a log event, dropping the oldest once there are too many
+    pub fn push_log(&mut self, text: &str) {
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            self.logs.push_back(line.to_string());
+        }
+        while self.logs.len() > LOG_LINES {
+            self.logs.pop_front();
+        }
+    }
+
+    /// Estimated time left on the current job, based on how long it has taken so far
+    pub fn eta(&self) -> Option<Duration> {
+        let current = self.current.as_ref()?;
+        let progress = current.progress.filter(|progress| *progress > 0.0)?;
+        let elapsed = current.started.elapsed().as_secs_f64();
+        Some(Duration::from_secs_f64(
+            elapsed * (1.0 - progress) / progress,
+        ))
+    }
+
+    /// Draw the dashboard: counts, the current job's progress, recent jobs and logs
+    fn draw(&self, frame: &mut Frame) {
+        let [header, current, recent, logs] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(4),
+            Constraint::Length(RECENT_JOBS as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let counts = self.counts;
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("plexify worker  ", Style::new().bold()),
+                Span::styled(counts.pending.to_string(), Style::new().cyan()),
+                Span::raw(" queued · "),
+                Span::styled(counts.in_progress.to_string(), Style::new().yellow()),
+                Span::raw(" in progress · "),
+                Span::styled(counts.completed.to_string(), Style::new().green()),
+                Span::raw(" completed · "),
+                Span::styled(counts.failed.to_string(), Style::new().red()),
+                Span::raw(" failed"),
+            ])),
+            header,
+        );
+
+        match &self.current {
+            Some(job) => {
+                let mut label = format!(
+                    "{:.0}%  {} elapsed",
+                    job.progress.unwrap_or(0.0) * 100.0,
+                    format_clock(job.started.elapsed())
+                );
+                if let Some(speed) = job.speed {
+                    label.push_str(&format!("  {:.2}x", speed));
+                }
+                if let Some(eta) = self.eta() {
+                    label.push_str(&format!("  ETA {}", format_clock(eta)));
+                }
+                frame.render_widget(
+                    Gauge::default()
+                        .block(Block::bordered().title(format!("▶ {}", job.name)))
+                        .gauge_style(Style::new().green())
+                        .ratio(job.progress.unwrap_or(0.0).clamp(0.0, 1.0))
+                        .label(label),
+                    current,
+                );
+            }
+            None => frame.render_widget(
+                Paragraph::new("Waiting for jobs...")
+                    .style(Style::new().dim())
+                    .block(Block::bordered()),
+                current,
+            ),
+        }
+
+        let recent_jobs: Vec<Line> = self
+            .recent
+            .iter()
+            .map(|job| {
+                let marker = if job.succeeded { "✅" } else { "❌" };
+                Line::from(format!(
+                    "{} {} ({})",
+                    marker,
+                    job.name,
+                    format_clock(job.elapsed)
+                ))
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(recent_jobs).block(Block::bordered().title("Recent")),
+            recent,
+        );
+
+        let log_lines: Vec<Line> = self
+            .logs
+            .iter()
+            .map(|line| Line::raw(line.as_str()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(log_lines).block(Block::bordered().title("Log")),
+            logs,
+        );
+    }
+}
+
+/// `1:02:03` or `02:03`
+fn format_clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<DashboardState>,
+    /// While the dashboard is drawn, log lines are kept in the state instead of printed
+    active: AtomicBool,
+}
+
+/// Live status view redrawn in place for `work --tui`. Cloning gives another handle to the
+/// same dashboard, and it doubles as the log writer so logs don't tear the screen.
+#[derive(Debug, Clone, Default)]
+pub struct Dashboard {
+    shared: Arc<Shared>,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, DashboardState> {
+        // A panic while holding the lock leaves the state usable for display
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Start redrawing the dashboard, recounting the queue on every redraw
+    pub fn start(&self, queue: JobQueue) -> DashboardRenderer {
+        self.shared.active.store(true, Ordering::Relaxed);
+        let dashboard = self.clone();
+        let task = tokio::spawn(async move {
+            let mut terminal = match Self::enter_terminal() {
+                Ok(terminal) => terminal,
+                Err(e) => {
+                    debug!("Could not set up the dashboard: {}", e);
+                    return;
+                }
+            };
+            let mut interval = tokio::time::interval(REDRAW_INTERVAL);
+            loop {
+                interval.tick().await;
+                match queue.counts().await {
+                    Ok(counts) => dashboard.state().set_counts(counts),
+                    Err(e) => debug!("Could not count jobs: {}", e),
+                }
+                let _ = terminal.draw(|frame| dashboard.state().draw(frame));
+            }
+        });
+        DashboardRenderer {
+            task,
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Switch to the alternate screen so the dashboard doesn't scroll the shell away.
+    /// Raw mode is left off so Ctrl+C still reaches the worker's signal handling.
+    fn enter_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Terminal::new(CrosstermBackend::new(io::stdout()))
+    }
+}
+
+/// The task drawing the dashboard; stopping it hands stdout back to the logs
+pub struct DashboardRenderer {
+    task: JoinHandle<()>,
+    shared: Arc<Shared>,
+}
+
+impl DashboardRenderer {
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+/// Dropping the renderer also restores the terminal, so an early return out of the
+/// work loop doesn't leave the shell on the alternate screen
+impl Drop for DashboardRenderer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        self.shared.active.store(false, Ordering::Relaxed);
+    }
+}
+
+impl<'a> MakeWriter<'a> for Dashboard {
+    type Writer = DashboardWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        DashboardWriter {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+pub struct DashboardWriter {
+    shared: Arc<Shared>,
+}
+
+impl Write for DashboardWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.shared.active.load(Ordering::Relaxed) {
+            let mut state = self
+                .shared
+                .state
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            state.push_log(&String::from_utf8_lossy(buf));
+            Ok(buf.len())
+        } else {
+            io::stdout().write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(secs: u64, speed: f64) -> FFmpegProgress {
+        FFmpegProgress {
+            out_time: Duration::from_secs(secs),
+            speed: Some(speed),
+            total_duration: Some(Duration::from_secs(100)),
+        }
+    }
+
+    #[test]
+    fn test_job_lifecycle_updates_state() {
+        let mut state = DashboardState::default();
+        // Progress without a job is ignored
+        state.update_progress(&progress(10, 1.0));
+        assert!(state.current.is_none());
+        assert!(state.eta().is_none());
+
+        state.start_job("Show - S01E01.mkv");
+        assert!(state.eta().is_none());
+        state.update_progress(&progress(25, 1.5));
+        let current = state.current.as_ref().unwrap();
+        assert_eq!(current.progress, Some(0.25));
+        assert_eq!(current.speed, Some(1.5));
+        assert!(state.eta().is_some());
+
+        state.finish_job(true);
+        assert!(state.current.is_none());
+        assert_eq!(state.recent.len(), 1);
+        assert_eq!(state.recent[0].name, "Show - S01E01.mkv");
+        assert!(state.recent[0].succeeded);
+
+        // A job handed back to the queue is not listed as finished
+        state.start_job("Show - S01E02.mkv");
+        state.clear_job();
+        assert!(state.current.is_none());
+        assert_eq!(state.recent.len(), 1);
+    }
+
+    #[test]
+    fn test_recent_jobs_and_logs_are_capped() {
+        let mut state = DashboardState::default();
+        for episode in 1..=RECENT_JOBS + 2 {
+            state.start_job(&format!("E{:02}", episode));
+            state.finish_job(episode % 2 == 0);
+        }
+        assert_eq!(state.recent.len(), RECENT_JOBS);
+        assert_eq!(state.recent[0].name, format!("E{:02}", RECENT_JOBS + 2));
+
+        state.push_log("first\nsecond\n");
+        assert_eq!(state.logs, ["first", "second"]);
+        for line in 0..LOG_LINES {
+            state.push_log(&format!("line {}\n", line));
+        }
+        assert_eq!(state.logs.len(), LOG_LINES);
+        assert_eq!(
+            state.logs.back().unwrap(),
+            &format!("line {}", LOG_LINES - 1)
+        );
+    }
+
+    #[test]
+    fn test_format_clock() {
+        assert_eq!(format_clock(Duration::from_secs(83)), "01:23");
+        assert_eq!(format_clock(Duration::from_secs(3723)), "1:02:03");
+    }
+}
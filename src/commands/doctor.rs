@@ -0,0 +1,289 @@
+use anyhow::{anyhow, Result};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::{debug, info};
+
+use crate::config::Config;
+use crate::ffmpeg::FFmpegProcessor;
+use crate::job::{
+    Job, MediaFileType, PostProcessingSettings, QualityPreset, QualitySettings, VideoCodec,
+};
+use crate::output;
+
+/// Length of the synthetic test clip in seconds
+const SAMPLE_DURATION_SECS: u32 = 2;
+
+/// Result of a single diagnostic check
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Results of all diagnostic checks
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    pub fn all_passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    fn record(&mut self, name: &'static str, result: Result<String>) -> bool {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(e) => (false, format!("{e:#}")),
+        };
+        self.checks.push(DoctorCheck {
+            name,
+            passed,
+            detail,
+        });
+        passed
+    }
+
+    fn skip(&mut self, name: &'static str, reason: &str) {
+        self.checks.push(DoctorCheck {
+            name,
+            passed: false,
+            detail: format!("skipped: {reason}"),
+        });
+    }
+}
+
+/// Command to check that ffmpeg and ffprobe work end-to-end with plexify
+pub struct DoctorCommand {
+    config: Config,
+}
+
+impl DoctorCommand {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    pub async fn execute(&self) -> Result<DoctorReport> {
+        info!("🩺 Checking ffmpeg setup...");
+        let mut report = DoctorReport::default();
+
+        let ffmpeg_ok = report.record(
+            "ffmpeg",
+            Self::binary_version(&self.config.ffmpeg_path).await,
+        );
+        let ffprobe_ok = report.record(
+            "ffprobe",
+            Self::binary_version(&self.config.ffprobe_path).await,
+        );
+
+        if !ffmpeg_ok {
+            report.skip("encode", "ffmpeg is not available");
+            report.skip("verify", "ffmpeg is not available");
+            return Ok(report);
+        }
+
+        let work_dir =
+            std::env::temp_dir().join(format!("plexify-doctor-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&work_dir).await?;
+
+        let result = self
+            .run_transcode_checks(&work_dir, ffprobe_ok, &mut report)
+            .await;
+
+        if let Err(e) = tokio::fs::remove_dir_all(&work_dir).await {
+            debug!(
+                "Failed to remove doctor work directory {:?}: {}",
+                work_dir, e
+            );
+        }
+
+        result.map(|_| report)
+    }
+
+    /// Generate a sample clip, convert it through the normal job pipeline and
+    /// verify the output with ffprobe
+    async fn run_transcode_checks(
+        &self,
+        work_dir: &Path,
+        ffprobe_ok: bool,
+        report: &mut DoctorReport,
+    ) -> Result<()> {
+        let sample = match self.generate_sample(work_dir).await {
+            Ok(sample) => sample,
+            Err(e) => {
+                report.record("encode", Err(e.context("could not generate a test clip")));
+                report.skip("verify", "nothing was encoded");
+                return Ok(());
+            }
+        };
+
+        let job = Job::new(
+            sample,
+            MediaFileType::Mkv,
+            QualitySettings::from_preset(QualityPreset::UltraFast, VideoCodec::default()),
+            PostProcessingSettings {
+                disable_source_files: false,
+            },
+            work_dir,
+        );
+        let processor = FFmpegProcessor::new(self.config.clone(), false);
+
+        let encoded = report.record(
+            "encode",
+            processor
+                .process_job(&job, Some(work_dir), None)
+                .await
+                .map(|_| format!("converted test clip to {:?}", job.output_path)),
+        );
+
+        if !encoded {
+            report.skip("verify", "encoding failed");
+        } else if !ffprobe_ok {
+            report.skip("verify", "ffprobe is not available");
+        } else {
+            let duration = processor.get_duration(&job.output_path).await;
+            report.record(
+                "verify",
+                duration.and_then(|duration| {
+                    if duration > 0.0 {
+                        Ok(format!("output plays for {duration:.1}s"))
+                    } else {
+                        Err(anyhow!("output has no duration"))
+                    }
+                }),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// First line of `<binary> -version`
+    async fn binary_version(binary: &str) -> Result<String> {
+        let output = Command::new(binary)
+            .arg("-version")
+            .output()
+            .await
+            .map_err(|e| anyhow!("could not run {binary}: {e}"))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("{binary} -version exited with {}", output.status));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// Create a short MKV with video, audio and a subtitle track, like a typical input
+    async fn generate_sample(&self, work_dir: &Path) -> Result<PathBuf> {
+        let subtitle = work_dir.join("sample.srt");
+        tokio::fs::write(
+            &subtitle,
+            format!("1\n00:00:00,000 --> 00:00:0{SAMPLE_DURATION_SECS},000\nplexify doctor\n"),
+        )
+        .await?;
+
+        let sample = work_dir.join("sample.mkv");
+        let output = Command::new(&self.config.ffmpeg_path)
+            .args(["-hide_banner", "-loglevel", "error", "-f", "lavfi", "-i"])
+            .arg(format!(
+                "testsrc=duration={SAMPLE_DURATION_SECS}:size=320x240:rate=25"
+            ))
+            .args(["-f", "lavfi", "-i"])
+            .arg(format!("sine=duration={SAMPLE_DURATION_SECS}"))
+            .arg("-i")
+            .arg(&subtitle)
+            .args(["-map", "0", "-map", "1", "-map", "2", "-y"])
+            .arg(&sample)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(sample)
+    }
+
+    pub fn print_report(&self, report: &DoctorReport) {
+        print!("{}", output::for_terminal(&self.render_report(report)));
+    }
+
+    /// Render the doctor report as it is printed
+    pub fn render_report(&self, report: &DoctorReport) -> String {
+        let mut out = String::new();
+        writeln!(out, "\n🩺 Plexify Doctor").unwrap();
+        writeln!(out, "═════════════════").unwrap();
+
+        for check in &report.checks {
+            let marker = if check.passed { "✅" } else { "❌" };
+            writeln!(out, "{} {:<8} {}", marker, check.name, check.detail).unwrap();
+        }
+
+        if report.all_passed() {
+            writeln!(out, "\n✅ Your setup is ready to transcode.").unwrap();
+        } else {
+            writeln!(out, "\n❌ Some checks failed. Set PLEXIFY_FFMPEG_PATH / PLEXIFY_FFPROBE_PATH if the binaries are not on your PATH.").unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ffmpeg_available() -> bool {
+        std::process::Command::new("ffmpeg")
+            .arg("-version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    #[tokio::test]
+    async fn test_doctor_reports_missing_ffmpeg() {
+        let config = Config {
+            ffmpeg_path: "/nonexistent/ffmpeg".to_string(),
+            ffprobe_path: "/nonexistent/ffprobe".to_string(),
+            ..Config::default()
+        };
+
+        let doctor = DoctorCommand::new(config);
+        let report = doctor.execute().await.unwrap();
+
+        assert!(!report.all_passed());
+        let names: Vec<_> = report.checks.iter().map(|check| check.name).collect();
+        assert_eq!(names, vec!["ffmpeg", "ffprobe", "encode", "verify"]);
+        assert!(report.checks.iter().all(|check| !check.passed));
+        assert!(report.checks[2].detail.starts_with("skipped"));
+
+        // What `--color never` prints
+        let text = crate::output::plain_text(&doctor.render_report(&report));
+        assert!(text.contains("Some checks failed"));
+        assert!(!text.contains('❌'));
+    }
+
+    #[tokio::test]
+    async fn test_doctor_happy_path() {
+        if !ffmpeg_available() {
+            eprintln!("Skipping doctor happy path test: ffmpeg not installed");
+            return;
+        }
+
+        let report = DoctorCommand::new(Config::default())
+            .execute()
+            .await
+            .unwrap();
+
+        for check in &report.checks {
+            assert!(check.passed, "{} failed: {}", check.name, check.detail);
+        }
+    }
+}
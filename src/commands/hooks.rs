@@ -0,0 +1,290 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use crate::job::Job;
+
+/// Variables that can be used in a hook command
+const HOOK_VARIABLES: &[&str] = &[
+    "id", "name", "input", "output", "series", "season", "episode", "error",
+];
+
+/// Hooks still running after this long are killed, so a hung script cannot stall the worker
+const HOOK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// A command run after a job, such as a script that refreshes a Plex library or
+/// sends an alert
+///
+/// Available variables:
+/// - `{id}`: the job id
+/// - `{name}`: the job's display name (e.g. `Show - S01E02`)
+/// - `{input}`, `{output}`: absolute input and output paths
+/// - `{series}`, `{season}`, `{episode}`: parsed from `Series/` and `Anime/` paths,
+///   empty for other files (season and episode are zero-padded to two digits)
+/// - `{error}`: why the job failed (empty for successful jobs)
+///
+/// The command is split into words like a shell would (with `'` and `"` quoting)
+/// before variables are substituted, so a path with spaces stays one argument.
+/// It is run directly, not through a shell, and killed if it runs for more
+/// than five minutes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hook {
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl Hook {
+    /// Parse a hook command, rejecting unknown variables and unbalanced quotes
+    pub fn parse(command: &str) -> Result<Self> {
+        let args = split_words(command)?;
+        if args.is_empty() {
+            return Err(anyhow!("Hook command is empty"));
+        }
+        for arg in &args {
+            let mut rest = arg.as_str();
+            while let Some(start) = rest.find('{') {
+                let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+                    break;
+                };
+                let name = &rest[start + 1..end];
+                if !HOOK_VARIABLES.contains(&name) {
+                    return Err(anyhow!(
+                        "Unknown hook variable '{{{name}}}'. Available: {}",
+                        HOOK_VARIABLES
+                            .iter()
+                            .map(|v| format!("{{{v}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+                rest = &rest[end + 1..];
+            }
+        }
+
+        Ok(Self {
+            command: command.to_string(),
+            args,
+            timeout: HOOK_TIMEOUT,
+        })
+    }
+
+    /// The program and arguments with every variable substituted for this job
+    pub fn render(&self, job: &Job, media_root: Option<&Path>, error: Option<&str>) -> Vec<String> {
+        let input = job.full_input_path(media_root);
+        let episode = Job::episode_metadata_from_path(&input);
+        let value = |name: &str| match name {
+            "id" => job.id.to_string(),
+            "name" => job.display_name(),
+            "input" => input.to_string_lossy().into_owned(),
+            "output" => job
+                .full_output_path(media_root)
+                .to_string_lossy()
+                .into_owned(),
+            "series" => episode
+                .as_ref()
+                .map(|m| m.series_name.clone())
+                .unwrap_or_default(),
+            "season" => episode
+                .as_ref()
+                .map(|m| format!("{:02}", m.season_number))
+                .unwrap_or_default(),
+            "error" => error.unwrap_or_default().to_string(),
+            _ => episode
+                .as_ref()
+                .map(|m| format!("{:02}", m.episode_number))
+                .unwrap_or_default(),
+        };
+
+        self.args
+            .iter()
+            .map(|arg| substitute(arg, &value))
+            .collect()
+    }
+
+    /// Run the hook for a job. Failures are logged and otherwise ignored, so a
+    /// broken hook never fails the job it ran for.
+    pub async fn run(
+        &self,
+        label: &str,
+        job: &Job,
+        media_root: Option<&Path>,
+        error: Option<&str>,
+    ) {
+        let args = self.render(job, media_root, error);
+        debug!("Running {} hook: {:?}", label, args);
+        let output = Command::new(&args[0])
+            .args(&args[1..])
+            .kill_on_drop(true)
+            .output();
+        let Ok(output) = tokio::time::timeout(self.timeout, output).await else {
+            warn!(
+                "⚠️ {} hook for {} timed out after {:?}, killed it",
+                label,
+                job.display_name(),
+                self.timeout
+            );
+            return;
+        };
+        match output {
+            Ok(output) if output.status.success() => {
+                info!("🪝 Ran {} hook for: {}", label, job.display_name());
+            }
+            Ok(output) => warn!(
+                "⚠️ {} hook exited with {} for {}: {}",
+                label,
+                output.status,
+                job.display_name(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => warn!("⚠️ Could not run {} hook {:?}: {}", label, args[0], e),
+        }
+    }
+}
+
+impl FromStr for Hook {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.command)
+    }
+}
+
+/// Replace each `{variable}` in `arg` with its value in one pass, so braces inside
+/// a substituted value (e.g. a path containing `{output}`) are left alone
+fn substitute(arg: &str, value: &dyn Fn(&str) -> String) -> String {
+    let mut substituted = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        let name = &rest[start + 1..end];
+        substituted.push_str(&rest[..start]);
+        if HOOK_VARIABLES.contains(&name) {
+            substituted.push_str(&value(name));
+        } else {
+            substituted.push_str(&rest[start..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+/// Split a command line into words on whitespace, honoring single and double quotes
+pub fn split_words(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(anyhow!("Unclosed quote in command '{command}'"));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::{MediaFileType, PostProcessingSettings, QualitySettings};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_hook_parse_and_render() {
+        assert_eq!(
+            split_words(r#"notify "a b" 'c "d"' e"#).unwrap(),
+            vec!["notify", "a b", r#"c "d""#, "e"]
+        );
+        assert!(Hook::parse("refresh 'unclosed").is_err());
+        assert!(Hook::parse("   ").is_err());
+        assert!(Hook::parse("refresh {nope}").is_err());
+
+        let hook =
+            Hook::parse("refresh --path {output} --show '{series} S{season}E{episode}'").unwrap();
+        let job = Job::new(
+            PathBuf::from("Series/Breaking Bad/Season 01/Breaking Bad S01E02.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/media"),
+        );
+        assert_eq!(
+            hook.render(&job, None, None),
+            vec![
+                "refresh",
+                "--path",
+                "/media/Series/Breaking Bad/Season 01/Breaking Bad S01E02.mp4",
+                "--show",
+                "Breaking Bad S01E02",
+            ]
+        );
+
+        let hook = Hook::parse("alert {id} {error}").unwrap();
+        assert_eq!(
+            hook.render(&job, None, Some("Input file does not exist")),
+            vec!["alert", &job.id.to_string(), "Input file does not exist"]
+        );
+        assert_eq!(hook.render(&job, None, None)[2], "");
+
+        // A value that looks like a variable is not expanded again
+        assert_eq!(
+            hook.render(&job, None, Some("bad name {output}"))[2],
+            "bad name {output}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hung_hook_is_killed() {
+        let hook = Hook {
+            timeout: Duration::from_millis(200),
+            ..Hook::parse("sleep 10").unwrap()
+        };
+        let job = Job::new(
+            PathBuf::from("movie.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/media"),
+        );
+
+        let start = std::time::Instant::now();
+        hook.run("on-complete", &job, None, None).await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
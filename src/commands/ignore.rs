@@ -0,0 +1,177 @@
+use anyhow::{anyhow, Result};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::ignore::{IgnoreExplanation, IgnoreFilter};
+use crate::output;
+
+/// Command to explain which .plexifyignore patterns apply to a path
+pub struct IgnoreExplainCommand {
+    media_root: PathBuf,
+    path: PathBuf,
+}
+
+impl IgnoreExplainCommand {
+    pub fn new(media_root: PathBuf, path: PathBuf) -> Self {
+        Self { media_root, path }
+    }
+
+    pub fn execute(&self) -> Result<IgnoreExplanation> {
+        if !self.media_root.is_dir() {
+            return Err(anyhow!(
+                "Media directory does not exist: {:?}",
+                self.media_root
+            ));
+        }
+
+        // Relative paths are resolved against the media root
+        let path = if self.path.is_absolute() {
+            self.path.clone()
+        } else {
+            self.media_root.join(&self.path)
+        };
+
+        if !path.starts_with(&self.media_root) {
+            return Err(anyhow!(
+                "Path {:?} is not inside the media directory {:?}",
+                path,
+                self.media_root
+            ));
+        }
+
+        info!("Loading .plexifyignore patterns from {:?}", self.media_root);
+        let filter = IgnoreFilter::new(self.media_root.clone())?;
+
+        Ok(filter.explain(&path))
+    }
+
+    pub fn print_explanation(&self, explanation: &IgnoreExplanation) {
+        print!(
+            "{}",
+            output::for_terminal(&self.render_explanation(explanation))
+        );
+    }
+
+    /// Render the explanation as it is printed
+    pub fn render_explanation(&self, explanation: &IgnoreExplanation) -> String {
+        let mut out = String::new();
+        let kind = if explanation.is_dir {
+            "directory"
+        } else {
+            "file"
+        };
+        writeln!(out, "\n🔍 {} ({})", explanation.path.display(), kind).unwrap();
+
+        if explanation.matches.is_empty() {
+            writeln!(out, "   No patterns matched").unwrap();
+        }
+
+        for pattern_match in &explanation.matches {
+            let effect = if pattern_match.negation {
+                "include"
+            } else {
+                "ignore"
+            };
+            writeln!(
+                out,
+                "   {}:{}  {}  → {}",
+                pattern_match.source.display(),
+                pattern_match.line,
+                pattern_match.pattern,
+                effect
+            )
+            .unwrap();
+        }
+
+        if let Some(parent) = &explanation.ignored_parent {
+            writeln!(out, "   Parent directory is ignored: {}", parent.display()).unwrap();
+        }
+
+        if explanation.ignored {
+            writeln!(out, "\n🚫 IGNORED").unwrap();
+        } else {
+            writeln!(out, "\n✅ INCLUDED").unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_explain_negated_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::write(
+            root.join(".plexifyignore"),
+            "# media\n*.mkv\n!important.mkv",
+        )
+        .unwrap();
+        fs::write(root.join("important.mkv"), "").unwrap();
+        fs::write(root.join("video.mkv"), "").unwrap();
+
+        let explanation =
+            IgnoreExplainCommand::new(root.to_path_buf(), PathBuf::from("important.mkv"))
+                .execute()
+                .unwrap();
+
+        assert!(!explanation.ignored);
+        assert!(!explanation.is_dir);
+        assert_eq!(explanation.ignored_parent, None);
+        assert_eq!(explanation.matches.len(), 2);
+
+        assert_eq!(explanation.matches[0].pattern, "*.mkv");
+        assert_eq!(explanation.matches[0].line, 2);
+        assert!(!explanation.matches[0].negation);
+        assert_eq!(explanation.matches[0].source, root.join(".plexifyignore"));
+
+        assert_eq!(explanation.matches[1].pattern, "!important.mkv");
+        assert_eq!(explanation.matches[1].line, 3);
+        assert!(explanation.matches[1].negation);
+
+        let explanation = IgnoreExplainCommand::new(root.to_path_buf(), root.join("video.mkv"))
+            .execute()
+            .unwrap();
+        assert!(explanation.ignored);
+        assert_eq!(explanation.matches.len(), 1);
+    }
+
+    #[test]
+    fn test_explain_ignored_parent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::write(root.join(".plexifyignore"), "Downloads/").unwrap();
+        fs::create_dir_all(root.join("Downloads")).unwrap();
+        fs::write(root.join("Downloads/video.mkv"), "").unwrap();
+
+        let explain =
+            IgnoreExplainCommand::new(root.to_path_buf(), PathBuf::from("Downloads/video.mkv"));
+        let explanation = explain.execute().unwrap();
+
+        assert!(explanation.ignored);
+        assert!(explanation.matches.is_empty());
+        assert_eq!(explanation.ignored_parent, Some(root.join("Downloads")));
+
+        // What `--color never` prints
+        let text = crate::output::plain_text(&explain.render_explanation(&explanation));
+        assert!(text.contains("\nIGNORED"));
+        assert!(!text.contains('🚫'));
+    }
+
+    #[test]
+    fn test_explain_path_outside_media_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let result =
+            IgnoreExplainCommand::new(temp_dir.path().to_path_buf(), PathBuf::from("/etc/passwd"))
+                .execute();
+
+        assert!(result.is_err());
+    }
+}
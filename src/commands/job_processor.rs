@@ -1,27 +1,52 @@
 use anyhow::Result;
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tracing::{debug, info, warn};
 
-use crate::job::{Job, MediaFileType, PostProcessingSettings, QualitySettings};
+use crate::config::Config;
+use crate::ffmpeg::{needs_faststart, FFmpegProcessor};
+use crate::job::{
+    env_snapshot, AudioMode, Checksum, ChecksumAlgorithm, Job, MediaFileType, OutputTemplate,
+    PostProcessingSettings, QualitySettings, SubtitleMode, VideoCodec,
+};
 use crate::queue::JobQueue;
 
 /// Shared job processing configuration
 pub struct JobProcessorConfig {
     pub quality_settings: QualitySettings,
     pub post_processing: PostProcessingSettings,
+    pub output_template: Option<OutputTemplate>,
+    pub audio_mode: AudioMode,
+    pub tonemap: bool,
+    pub auto_chapters: bool,
+    pub downmix_stereo: bool,
+    pub tag_metadata: bool,
+    pub ass_subtitles: SubtitleMode,
+    pub burn_subtitles: bool,
+    pub skip_if_codec: Vec<String>,
+    pub checksum: ChecksumAlgorithm,
+    pub notes: Option<String>,
+    pub sample_secs: Option<u64>,
 }
 
 impl JobProcessorConfig {
     /// Create job processor config from preset or environment
     pub fn from_preset(preset: Option<&str>) -> Result<Self> {
+        Self::from_preset_for(preset, VideoCodec::default())
+    }
+
+    /// Create job processor config from preset or environment, with the CRF scaled
+    /// for the codec
+    pub fn from_preset_for(preset: Option<&str>, codec: VideoCodec) -> Result<Self> {
         let quality_settings = match preset {
             Some(preset_name) => {
-                info!("Using quality preset: '{}'", preset_name);
-                QualitySettings::from_preset_name(preset_name)?
+                info!("Using quality preset: '{}' ({:?})", preset_name, codec);
+                QualitySettings::from_preset_name(preset_name, codec)?
             }
             None => {
                 info!("Using quality settings from environment variables");
-                QualitySettings::from_env()
+                QualitySettings::from_env(codec)
             }
         };
         let post_processing = PostProcessingSettings::default();
@@ -29,11 +54,102 @@ impl JobProcessorConfig {
         Ok(Self {
             quality_settings,
             post_processing,
+            output_template: None,
+            audio_mode: AudioMode::default(),
+            tonemap: false,
+            auto_chapters: false,
+            downmix_stereo: false,
+            tag_metadata: false,
+            ass_subtitles: SubtitleMode::default(),
+            burn_subtitles: false,
+            skip_if_codec: Vec::new(),
+            checksum: ChecksumAlgorithm::default(),
+            notes: None,
+            sample_secs: None,
         })
     }
+
+    /// Skip MP4 remuxes whose video is already in one of these codecs (probed with ffprobe)
+    pub fn with_skip_if_codec(mut self, skip_if_codec: Vec<String>) -> Self {
+        self.skip_if_codec = skip_if_codec;
+        self
+    }
+
+    /// Record a checksum of the input of new jobs
+    pub fn with_checksum(mut self, checksum: ChecksumAlgorithm) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Make new jobs encode only a preview of this many seconds
+    pub fn with_sample(mut self, sample_secs: Option<u64>) -> Self {
+        self.sample_secs = sample_secs;
+        self
+    }
+
+    /// Attach a note to new jobs
+    pub fn with_notes(mut self, notes: Option<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Burn every subtitle (sidecar or embedded) into the video of new jobs
+    pub fn with_burn_subtitles(mut self, burn_subtitles: bool) -> Self {
+        self.burn_subtitles = burn_subtitles;
+        self
+    }
+
+    /// Mux or burn in `.ass` sidecar subtitles of new jobs
+    pub fn with_ass_subtitles(mut self, ass_subtitles: SubtitleMode) -> Self {
+        self.ass_subtitles = ass_subtitles;
+        self
+    }
+
+    /// Tag outputs of new jobs with a title and encoder
+    pub fn with_tag_metadata(mut self, tag_metadata: bool) -> Self {
+        self.tag_metadata = tag_metadata;
+        self
+    }
+
+    /// Downmix audio to stereo for new jobs
+    pub fn with_downmix_stereo(mut self, downmix_stereo: bool) -> Self {
+        self.downmix_stereo = downmix_stereo;
+        self
+    }
+
+    /// Tone-map HDR sources to SDR for new jobs
+    pub fn with_tonemap(mut self, tonemap: bool) -> Self {
+        self.tonemap = tonemap;
+        self
+    }
+
+    /// Add chapters at silent gaps to new jobs
+    pub fn with_auto_chapters(mut self, auto_chapters: bool) -> Self {
+        self.auto_chapters = auto_chapters;
+        self
+    }
+
+    /// Write audio for new jobs in this mode
+    pub fn with_audio_mode(mut self, audio_mode: AudioMode) -> Self {
+        if audio_mode != AudioMode::default() {
+            info!("Using audio mode: {:?}", audio_mode);
+        }
+        self.audio_mode = audio_mode;
+        self
+    }
+
+    /// Render output paths from a template instead of placing them next to the input
+    pub fn with_output_template(mut self, output_template: Option<OutputTemplate>) -> Self {
+        if let Some(template) = &output_template {
+            info!("Using output template: '{}'", template);
+        }
+        self.output_template = output_template;
+        self
+    }
 }
 
 /// Result of attempting to process a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JobProcessResult {
     /// Job was successfully created and enqueued
     Created,
@@ -41,8 +157,24 @@ pub enum JobProcessResult {
     OutputExists,
     /// Job was skipped because it already exists in queue
     AlreadyQueued,
+    /// Job was skipped because a marker says plexify already handled this input
+    AlreadyDone,
     /// Job was skipped because required subtitle file is missing
     MissingSubtitle,
+    /// Job was skipped because its `.vtt` subtitle is empty or not WebVTT
+    InvalidSubtitle,
+    /// MP4 was skipped because its index is already at the front
+    AlreadyFaststart,
+    /// MP4 was skipped because its layout could not be read to check the index
+    UnreadableMp4,
+    /// Job was skipped because the video is already in an acceptable codec
+    AcceptableCodec,
+}
+
+/// A job ready to enqueue, or the reason a media file needs none
+enum PreparedJob {
+    Ready(Box<Job>),
+    Skipped(JobProcessResult),
 }
 
 /// Shared job processor that handles the common logic between add and scan commands
@@ -50,6 +182,10 @@ pub struct JobProcessor<'a> {
     pub queue: &'a JobQueue,
     pub config: &'a JobProcessorConfig,
     pub media_root: &'a Path,
+    /// Runs ffprobe for `--skip-if-codec`
+    prober: FFmpegProcessor,
+    /// Inputs queued or in progress, read once for runs that check many files
+    queued_inputs: Option<Mutex<HashSet<PathBuf>>>,
 }
 
 impl<'a> JobProcessor<'a> {
@@ -58,45 +194,252 @@ impl<'a> JobProcessor<'a> {
             queue,
             config,
             media_root,
+            prober: FFmpegProcessor::new(Config::from_env(), false),
+            queued_inputs: None,
+        }
+    }
+
+    /// Read the queued inputs once up front instead of the whole queue for every file.
+    /// Only for short runs like `scan`, since jobs other workers queue later are missed.
+    pub async fn with_queued_inputs(mut self) -> Result<Self> {
+        self.queued_inputs = Some(Mutex::new(self.queue.active_inputs().await?));
+        Ok(self)
+    }
+
+    /// Whether a job for the same input is already queued or being worked on
+    async fn is_queued(&self, job: &Job) -> Result<bool> {
+        match &self.queued_inputs {
+            Some(inputs) => Ok(inputs.lock().unwrap().contains(&job.input_path)),
+            None => self.queue.job_exists(job).await,
         }
     }
 
+    async fn enqueue(&self, job: &Job) -> Result<()> {
+        self.queue.enqueue_job(job).await?;
+        if let Some(inputs) = &self.queued_inputs {
+            inputs.lock().unwrap().insert(job.input_path.clone());
+        }
+        Ok(())
+    }
+
     /// Process a single media file and create a job if needed
     pub async fn process_media_file(
         &self,
         relative_path: &Path,
         file_type: MediaFileType,
     ) -> Result<JobProcessResult> {
-        // Create the job
+        match self.prepare_job(relative_path, file_type, &[]).await? {
+            PreparedJob::Ready(job) => {
+                self.enqueue(&job).await?;
+                Ok(JobProcessResult::Created)
+            }
+            PreparedJob::Skipped(result) => Ok(result),
+        }
+    }
+
+    /// Create one job joining the parts of a split MKV episode, in the given order
+    pub async fn process_split_episode(&self, parts: &[PathBuf]) -> Result<JobProcessResult> {
+        match self
+            .prepare_job(&parts[0], MediaFileType::Mkv, &parts[1..])
+            .await?
+        {
+            PreparedJob::Ready(job) => {
+                self.enqueue(&job).await?;
+                Ok(JobProcessResult::Created)
+            }
+            PreparedJob::Skipped(result) => Ok(result),
+        }
+    }
+
+    /// Like `process_split_episode`, but collect the job into a plan instead of enqueueing it
+    pub async fn plan_split_episode(
+        &self,
+        parts: &[PathBuf],
+        plan: &mut Vec<Job>,
+    ) -> Result<JobProcessResult> {
+        match self
+            .prepare_job(&parts[0], MediaFileType::Mkv, &parts[1..])
+            .await?
+        {
+            PreparedJob::Ready(job) => {
+                plan.push(*job);
+                Ok(JobProcessResult::Created)
+            }
+            PreparedJob::Skipped(result) => Ok(result),
+        }
+    }
+
+    /// Like `process_media_file`, but collect the job into a plan instead of enqueueing it
+    pub async fn plan_media_file(
+        &self,
+        relative_path: &Path,
+        file_type: MediaFileType,
+        plan: &mut Vec<Job>,
+    ) -> Result<JobProcessResult> {
+        match self.prepare_job(relative_path, file_type, &[]).await? {
+            PreparedJob::Ready(job) => {
+                plan.push(*job);
+                Ok(JobProcessResult::Created)
+            }
+            PreparedJob::Skipped(result) => Ok(result),
+        }
+    }
+
+    /// Enqueue an already built job (e.g. from a plan) unless its output exists or it is queued.
+    /// An in-place remux writes over its input, so its output always exists.
+    pub async fn enqueue_checked(&self, job: &Job) -> Result<JobProcessResult> {
+        if !job.replaces_input() && job.output_exists(Some(self.media_root)) {
+            return Ok(JobProcessResult::OutputExists);
+        }
+        if self.is_queued(job).await? {
+            return Ok(JobProcessResult::AlreadyQueued);
+        }
+        self.enqueue(job).await?;
+        Ok(JobProcessResult::Created)
+    }
+
+    /// Build the job for a media file with the configured settings
+    pub fn build_job(&self, relative_path: &Path, file_type: MediaFileType) -> Result<Job> {
         let job = Job::new(
             relative_path.to_path_buf(),
             file_type.clone(),
             self.config.quality_settings.clone(),
             self.config.post_processing.clone(),
             self.media_root,
-        );
+        )
+        .with_audio_mode(self.config.audio_mode)
+        .with_tonemap(self.config.tonemap)
+        .with_auto_chapters(self.config.auto_chapters)
+        .with_downmix_stereo(self.config.downmix_stereo)
+        .with_tag_metadata(self.config.tag_metadata)
+        .with_notes(self.config.notes.clone())
+        .with_env_snapshot(env_snapshot());
+        let input_path = job.full_input_path(Some(self.media_root));
+        let job = match file_type {
+            // A missing .vtt is reported when the job is checked
+            MediaFileType::WebM => {
+                let subtitle_path = Job::external_subtitle(&input_path)
+                    .unwrap_or_else(|| input_path.with_extension("vtt"));
+                job.with_subtitle_path(Some(subtitle_path))
+            }
+            // Styled ASS sidecars usually replace the embedded subtitles
+            MediaFileType::Mkv => {
+                let ass_path = input_path.with_extension("ass");
+                job.with_subtitle_path(ass_path.exists().then_some(ass_path))
+            }
+            MediaFileType::Mp4 | MediaFileType::Subtitle => job,
+        };
+        // In-place remuxes copy the video, so there is nothing to burn subtitles into
+        let job = if self.config.burn_subtitles && !job.replaces_input() {
+            job.with_subtitle_mode(SubtitleMode::Burn)
+        } else if job.has_ass_subtitle() {
+            job.with_subtitle_mode(self.config.ass_subtitles)
+        } else {
+            job
+        };
+        // Converted subtitles stay next to their input, where players look for them
+        let job = match &self.config.output_template {
+            Some(template) if file_type != MediaFileType::Subtitle => {
+                job.with_output_template(template, self.media_root)?
+            }
+            _ => job,
+        };
+        Ok(match self.config.sample_secs {
+            Some(secs) if file_type != MediaFileType::Subtitle => job.with_sample(secs),
+            _ => job,
+        })
+    }
+
+    /// Build the job for a media file (joined with any further parts), or say why
+    /// there is nothing to do
+    async fn prepare_job(
+        &self,
+        relative_path: &Path,
+        file_type: MediaFileType,
+        extra_parts: &[PathBuf],
+    ) -> Result<PreparedJob> {
+        let job = self.build_job(relative_path, file_type.clone())?;
+        let job = if extra_parts.is_empty() {
+            job
+        } else {
+            job.with_extra_inputs(extra_parts, self.media_root)
+        };
 
-        // Check if output already exists
-        if job.output_exists(Some(self.media_root)) {
+        if job.subtitle_path.is_none() && file_type != MediaFileType::Subtitle {
+            if let Some(image_subtitle) = Job::image_subtitle(&job.input_path) {
+                warn!(
+                    "⚠️ Ignoring image-based subtitles {:?}: MP4 cannot carry them",
+                    image_subtitle
+                );
+            }
+        }
+
+        if job.replaces_input() {
+            // Remuxing in place is only worth it when the index is at the end
+            let input_path = job.full_input_path(Some(self.media_root));
+            match needs_faststart(&input_path).await {
+                Ok(true) => {}
+                Ok(false) => return Ok(PreparedJob::Skipped(JobProcessResult::AlreadyFaststart)),
+                Err(e) => {
+                    warn!("Could not read MP4 layout of {:?}: {}", relative_path, e);
+                    return Ok(PreparedJob::Skipped(JobProcessResult::UnreadableMp4));
+                }
+            }
+        } else if job.output_exists(Some(self.media_root)) {
+            // Check if output already exists
             debug!("Output already exists for: {:?}", relative_path);
-            return Ok(JobProcessResult::OutputExists);
+            return Ok(PreparedJob::Skipped(JobProcessResult::OutputExists));
+        } else if Job::done_marker_path(&job.full_output_path(Some(self.media_root))).exists() {
+            // Finished before, and the output has since been moved elsewhere
+            debug!("Done marker found for: {:?}", relative_path);
+            return Ok(PreparedJob::Skipped(JobProcessResult::AlreadyDone));
+        }
+        if Job::kept_original_marker_path(&job.full_input_path(Some(self.media_root))).exists() {
+            // An earlier encode came out larger than this input and was discarded
+            debug!("Kept-original marker found for: {:?}", relative_path);
+            return Ok(PreparedJob::Skipped(JobProcessResult::AlreadyDone));
         }
 
         // Check if job already exists in queue
-        if self.queue.job_exists(&job).await? {
+        if self.is_queued(&job).await? {
             debug!("Job already exists for: {:?}", relative_path);
-            return Ok(JobProcessResult::AlreadyQueued);
+            return Ok(PreparedJob::Skipped(JobProcessResult::AlreadyQueued));
         }
 
         // For WebM files, check if required subtitle file exists
         if file_type == MediaFileType::WebM && !job.has_required_subtitle(Some(self.media_root))? {
-            return Ok(JobProcessResult::MissingSubtitle);
+            return Ok(PreparedJob::Skipped(JobProcessResult::MissingSubtitle));
+        }
+        if !job.has_valid_subtitle(Some(self.media_root))? {
+            return Ok(PreparedJob::Skipped(JobProcessResult::InvalidSubtitle));
         }
 
-        // Create the job
-        self.queue.enqueue_job(&job).await?;
+        // Probe last, only for files that would otherwise be queued. Other inputs
+        // still need converting to MP4, whatever their codec.
+        if !self.config.skip_if_codec.is_empty() && file_type == MediaFileType::Mp4 {
+            let input_path = job.full_input_path(Some(self.media_root));
+            match self.prober.get_video_codec(&input_path).await {
+                Ok(codec) if codec_is_acceptable(codec.as_deref(), &self.config.skip_if_codec) => {
+                    return Ok(PreparedJob::Skipped(JobProcessResult::AcceptableCodec));
+                }
+                Ok(_) => {}
+                Err(e) => warn!(
+                    "Could not probe the video codec of {:?}, queueing it anyway: {}",
+                    relative_path, e
+                ),
+            }
+        }
 
-        Ok(JobProcessResult::Created)
+        // Hashing reads the whole file, so it is only done for jobs that get queued
+        let input_checksum = Checksum::compute(
+            &job.full_input_path(Some(self.media_root)),
+            self.config.checksum,
+        )
+        .await?;
+
+        Ok(PreparedJob::Ready(Box::new(
+            job.with_input_checksum(input_checksum),
+        )))
     }
 
     /// Log the result of job processing with appropriate messages
@@ -117,19 +460,46 @@ impl<'a> JobProcessor<'a> {
                         relative_path
                     );
                 }
+                MediaFileType::Mp4 => {
+                    info!("➕ Queueing faststart remux for: {:?}", relative_path);
+                }
+                MediaFileType::Subtitle => {
+                    info!("➕ Queueing subtitle conversion for: {:?}", relative_path);
+                }
             },
+            JobProcessResult::AlreadyFaststart => {
+                debug!("MP4 already streams without remux: {:?}", relative_path);
+            }
+            JobProcessResult::UnreadableMp4 => {
+                // Already warned about with the read error
+            }
+            JobProcessResult::AcceptableCodec => {
+                info!(
+                    "⏭️ Skipping {:?}: video is already in an accepted codec",
+                    relative_path
+                );
+            }
             JobProcessResult::OutputExists => {
                 // Only debug log for scan command, add command handles this differently
             }
             JobProcessResult::AlreadyQueued => {
                 // Only debug log for scan command, add command handles this differently
             }
+            JobProcessResult::AlreadyDone => {
+                debug!("Already converted by plexify: {:?}", relative_path);
+            }
             JobProcessResult::MissingSubtitle => {
                 warn!(
                     "⚠️ SKIPPING: Missing subtitle file for '{:?}'",
                     relative_path
                 );
             }
+            JobProcessResult::InvalidSubtitle => {
+                warn!(
+                    "⚠️ SKIPPING: Subtitle file for '{:?}' is empty or not WebVTT (no WEBVTT header)",
+                    relative_path
+                );
+            }
         }
     }
 
@@ -152,6 +522,15 @@ impl<'a> JobProcessor<'a> {
     }
 }
 
+/// Whether a probed video codec is one of the accepted ones (case-insensitive)
+fn codec_is_acceptable(codec: Option<&str>, accepted: &[String]) -> bool {
+    codec.is_some_and(|codec| {
+        accepted
+            .iter()
+            .any(|accepted| accepted.eq_ignore_ascii_case(codec))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,6 +551,16 @@ mod tests {
         assert_eq!(config.quality_settings.ffmpeg_preset, "veryfast");
     }
 
+    #[test]
+    fn test_codec_is_acceptable() {
+        let accepted = vec!["h264".to_string(), "HEVC".to_string()];
+        assert!(codec_is_acceptable(Some("h264"), &accepted));
+        assert!(codec_is_acceptable(Some("hevc"), &accepted));
+        assert!(!codec_is_acceptable(Some("vp9"), &accepted));
+        assert!(!codec_is_acceptable(None, &accepted));
+        assert!(!codec_is_acceptable(Some("h264"), &[]));
+    }
+
     #[test]
     fn test_determine_file_type() {
         let webm_path = std::path::Path::new("video.webm");
@@ -215,6 +604,129 @@ mod tests {
         assert!(matches!(result, JobProcessResult::Created));
     }
 
+    #[tokio::test]
+    async fn test_done_marker_skips_moved_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        fs::write(media_root.join("video.mkv"), "").unwrap();
+        // Converted before, then the output was moved out of the library
+        fs::write(Job::done_marker_path(&media_root.join("video.mp4")), "").unwrap();
+
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf());
+        queue.init().await.unwrap();
+        let config = JobProcessorConfig::from_preset(None).unwrap();
+        let processor = JobProcessor::new(&queue, &config, media_root);
+
+        let result = processor
+            .process_media_file(Path::new("video.mkv"), MediaFileType::Mkv)
+            .await
+            .unwrap();
+        assert_eq!(result, JobProcessResult::AlreadyDone);
+
+        // Same for an input whose output was discarded for being larger
+        fs::write(media_root.join("small.mkv"), "").unwrap();
+        fs::write(
+            Job::kept_original_marker_path(&media_root.join("small.mkv")),
+            "",
+        )
+        .unwrap();
+        let result = processor
+            .process_media_file(Path::new("small.mkv"), MediaFileType::Mkv)
+            .await
+            .unwrap();
+        assert_eq!(result, JobProcessResult::AlreadyDone);
+        assert!(queue.pending_jobs().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_build_job_finds_subtitle_sidecars() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        for name in [
+            "styled.webm",
+            "styled.ass",
+            "both.webm",
+            "both.vtt",
+            "both.ass",
+            "movie.mkv",
+            "movie.ass",
+            "plain.mkv",
+        ] {
+            fs::write(media_root.join(name), "").unwrap();
+        }
+
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf());
+        let config = JobProcessorConfig::from_preset(None).unwrap();
+        let processor = JobProcessor::new(&queue, &config, media_root);
+        let job = |name: &str, file_type| processor.build_job(Path::new(name), file_type).unwrap();
+
+        let styled = job("styled.webm", MediaFileType::WebM);
+        assert_eq!(styled.subtitle_path, Some(media_root.join("styled.ass")));
+        assert!(styled.has_ass_subtitle());
+        assert!(styled.has_required_subtitle(None).unwrap());
+
+        // VTT wins when both exist
+        let both = job("both.webm", MediaFileType::WebM);
+        assert_eq!(both.subtitle_path, Some(media_root.join("both.vtt")));
+
+        let movie = job("movie.mkv", MediaFileType::Mkv);
+        assert_eq!(movie.subtitle_path, Some(media_root.join("movie.ass")));
+        assert_eq!(job("plain.mkv", MediaFileType::Mkv).subtitle_path, None);
+    }
+
+    #[tokio::test]
+    async fn test_queued_inputs_are_read_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        fs::write(media_root.join("queued.mkv"), "").unwrap();
+        fs::write(media_root.join("new.mkv"), "").unwrap();
+
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf());
+        queue.init().await.unwrap();
+        let config = JobProcessorConfig::from_preset(None).unwrap();
+        let queued = JobProcessor::new(&queue, &config, media_root)
+            .build_job(Path::new("queued.mkv"), MediaFileType::Mkv)
+            .unwrap();
+        queue.enqueue_job(&queued).await.unwrap();
+
+        let processor = JobProcessor::new(&queue, &config, media_root)
+            .with_queued_inputs()
+            .await
+            .unwrap();
+        for (name, expected) in [
+            ("queued.mkv", JobProcessResult::AlreadyQueued),
+            ("new.mkv", JobProcessResult::Created),
+            // Jobs the processor queued itself count too
+            ("new.mkv", JobProcessResult::AlreadyQueued),
+        ] {
+            let result = processor
+                .process_media_file(Path::new(name), MediaFileType::Mkv)
+                .await
+                .unwrap();
+            assert_eq!(result, expected, "{name}");
+        }
+        assert_eq!(queue.pending_count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_mp4_is_not_reported_as_faststart() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        fs::write(media_root.join("video.mp4"), "not an mp4").unwrap();
+
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf());
+        queue.init().await.unwrap();
+        let config = JobProcessorConfig::from_preset(None).unwrap();
+        let processor = JobProcessor::new(&queue, &config, media_root);
+
+        let result = processor
+            .process_media_file(Path::new("video.mp4"), MediaFileType::Mp4)
+            .await
+            .unwrap();
+        assert_eq!(result, JobProcessResult::UnreadableMp4);
+        assert!(queue.pending_jobs().await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_process_media_file_webm_with_subtitle() {
         let temp_dir = TempDir::new().unwrap();
@@ -224,7 +736,7 @@ mod tests {
         let webm_file = media_root.join("video.webm");
         let vtt_file = media_root.join("video.vtt");
         fs::write(&webm_file, "").unwrap();
-        fs::write(&vtt_file, "").unwrap();
+        fs::write(&vtt_file, "WEBVTT\n").unwrap();
 
         let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf());
         queue.init().await.unwrap();
@@ -264,4 +776,28 @@ mod tests {
 
         assert!(matches!(result, JobProcessResult::MissingSubtitle));
     }
+
+    #[tokio::test]
+    async fn test_process_media_file_webm_invalid_subtitle() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        fs::write(media_root.join("empty.webm"), "").unwrap();
+        fs::write(media_root.join("empty.vtt"), "").unwrap();
+        fs::write(media_root.join("garbled.webm"), "").unwrap();
+        fs::write(media_root.join("garbled.vtt"), "not a subtitle").unwrap();
+
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf());
+        queue.init().await.unwrap();
+        let config = JobProcessorConfig::from_preset(None).unwrap();
+        let processor = JobProcessor::new(&queue, &config, media_root);
+
+        for name in ["empty.webm", "garbled.webm"] {
+            let result = processor
+                .process_media_file(Path::new(name), MediaFileType::WebM)
+                .await
+                .unwrap();
+            assert_eq!(result, JobProcessResult::InvalidSubtitle);
+        }
+        assert!(queue.pending_jobs().await.unwrap().is_empty());
+    }
 }
@@ -0,0 +1,97 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{info, warn};
+
+/// Once throttled, load has to drop below this fraction of the maximum before
+/// new jobs are claimed again, so the worker does not flap around the threshold
+const RESUME_LOAD_FRACTION: f64 = 0.8;
+
+/// Holds off claiming new jobs while the system load average is above a limit,
+/// e.g. while the machine is being used for something else
+#[derive(Debug)]
+pub struct LoadGovernor {
+    max_load: f64,
+    throttled: AtomicBool,
+}
+
+impl LoadGovernor {
+    pub fn new(max_load: f64) -> Self {
+        Self {
+            max_load,
+            throttled: AtomicBool::new(false),
+        }
+    }
+
+    /// Warn that `--max-load` has no effect where the load average cannot be read.
+    /// Called once at worker startup.
+    pub fn warn_if_unsupported(&self) {
+        if Self::current_load().is_none() {
+            warn!(
+                "--max-load {:.2} has no effect: the load average cannot be read on this platform (needs /proc/loadavg)",
+                self.max_load
+            );
+        }
+    }
+
+    /// Sample the load average and decide whether to hold off on new jobs.
+    /// Never throttles where the load average cannot be read.
+    pub fn check(&self) -> bool {
+        let Some(load) = Self::current_load() else {
+            return false;
+        };
+
+        let was_throttled = self.is_throttled();
+        let throttled = should_throttle(load, self.max_load, was_throttled);
+        if throttled && !was_throttled {
+            info!(
+                "🐢 System load {:.2} is above --max-load {:.2}, not claiming new jobs",
+                load, self.max_load
+            );
+        } else if !throttled && was_throttled {
+            info!("🐇 System load is down to {:.2}, resuming", load);
+        }
+        self.throttled.store(throttled, Ordering::Relaxed);
+        throttled
+    }
+
+    /// Result of the last `check`
+    pub fn is_throttled(&self) -> bool {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// One-minute load average, where the platform exposes it
+    pub fn current_load() -> Option<f64> {
+        std::fs::read_to_string("/proc/loadavg")
+            .ok()?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+}
+
+/// Whether to hold off claiming jobs at `load`, given whether we already are
+pub fn should_throttle(load: f64, max_load: f64, throttled: bool) -> bool {
+    if throttled {
+        load > max_load * RESUME_LOAD_FRACTION
+    } else {
+        load > max_load
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_throttle_with_hysteresis() {
+        // Not throttled: only a load above the limit starts throttling
+        assert!(!should_throttle(3.9, 4.0, false));
+        assert!(!should_throttle(4.0, 4.0, false));
+        assert!(should_throttle(4.1, 4.0, false));
+
+        // Throttled: stay throttled until load is well below the limit
+        assert!(should_throttle(3.9, 4.0, true));
+        assert!(should_throttle(3.3, 4.0, true));
+        assert!(!should_throttle(3.1, 4.0, true));
+    }
+}
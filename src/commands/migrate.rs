@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::job::JOB_SCHEMA_VERSION;
+use crate::queue::{JobQueue, MigrationReport};
+
+/// Command to upgrade job files written by older versions of plexify
+pub struct MigrateCommand {
+    work_root: PathBuf,
+}
+
+impl MigrateCommand {
+    pub fn new(work_root: PathBuf) -> Self {
+        Self { work_root }
+    }
+
+    pub async fn execute(&self) -> Result<MigrationReport> {
+        let queue = JobQueue::new(self.work_root.clone(), self.work_root.clone());
+        let report = queue.migrate_jobs().await?;
+        info!(
+            "🔧 Migrated {} job file(s) to schema version {}, {} already current",
+            report.migrated, JOB_SCHEMA_VERSION, report.current
+        );
+        if report.newer > 0 || report.unreadable > 0 {
+            return Err(anyhow!(
+                "{} job file(s) were written by a newer plexify and {} could not be read",
+                report.newer,
+                report.unreadable
+            ));
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::{Job, MediaFileType, PostProcessingSettings, QualitySettings};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_migrate_upgrades_old_job_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_root = temp_dir.path().to_path_buf();
+        let queue = JobQueue::new(work_root.clone(), work_root.clone());
+        queue.init().await.unwrap();
+
+        let job = Job::new(
+            PathBuf::from("episode.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            &work_root,
+        );
+        queue.enqueue_job(&job).await.unwrap();
+
+        // Written before job files had a version
+        let mut old = serde_json::to_value(&job).unwrap();
+        old.as_object_mut().unwrap().remove("version");
+        old["id"] = "old-job".into();
+        let old_path = queue.completed_dir.join("old.job");
+        std::fs::write(&old_path, old.to_string()).unwrap();
+
+        // Workers could claim a job while it is rewritten
+        let before = std::fs::read_to_string(&old_path).unwrap();
+        assert!(MigrateCommand::new(work_root.clone())
+            .execute()
+            .await
+            .is_err());
+        assert_eq!(std::fs::read_to_string(&old_path).unwrap(), before);
+
+        queue.pause().await.unwrap();
+        let report = MigrateCommand::new(work_root.clone())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            MigrationReport {
+                migrated: 1,
+                current: 1,
+                ..MigrationReport::default()
+            }
+        );
+        let migrated: Job =
+            serde_json::from_str(&std::fs::read_to_string(&old_path).unwrap()).unwrap();
+        assert_eq!(migrated.version, JOB_SCHEMA_VERSION);
+        assert_eq!(migrated.id, "old-job");
+
+        // Nothing left to do, but a job from the future is reported
+        let mut newer = serde_json::to_value(&job).unwrap();
+        newer["version"] = (JOB_SCHEMA_VERSION + 1).into();
+        let newer_path = queue.queue_dir.join("newer.job");
+        std::fs::write(&newer_path, newer.to_string()).unwrap();
+        let result = MigrateCommand::new(work_root).execute().await;
+        assert!(result.is_err());
+        let untouched: Job =
+            serde_json::from_str(&std::fs::read_to_string(&newer_path).unwrap()).unwrap();
+        assert_eq!(untouched.version, JOB_SCHEMA_VERSION + 1);
+    }
+}
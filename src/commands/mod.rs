@@ -1,6 +1,22 @@
 pub mod add;
 pub mod clean;
+pub mod dashboard;
+pub mod doctor;
+pub mod hooks;
+pub mod ignore;
 pub mod job_processor;
+pub mod load_governor;
+pub mod migrate;
+pub mod path_filter;
+pub mod pause;
+pub mod presets;
+pub mod prune;
+pub mod read_slots;
+pub mod rename_template;
 pub mod scan;
+pub mod throttle;
+pub mod undo;
 pub mod validate;
+pub mod verify;
+pub mod watch;
 pub mod work;
@@ -0,0 +1,84 @@
+use glob::{MatchOptions, Pattern};
+use std::path::Path;
+
+/// `*` stays within one path component; `**` spans directories
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Restricts which files (relative to the media root) a command processes
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Option<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl PathFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only allow paths matching this glob (e.g. `Series/Breaking Bad/**`)
+    pub fn with_include(mut self, include: Option<Pattern>) -> Self {
+        self.include = include;
+        self
+    }
+
+    /// Skip paths matching any of these globs
+    pub fn with_exclude(mut self, exclude: Vec<Pattern>) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    /// Check whether a path relative to the media root passes the filter
+    pub fn allows(&self, relative_path: &Path) -> bool {
+        let included = match &self.include {
+            Some(pattern) => pattern.matches_path_with(relative_path, MATCH_OPTIONS),
+            None => true,
+        };
+        included
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| pattern.matches_path_with(relative_path, MATCH_OPTIONS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_include_glob() {
+        let filter =
+            PathFilter::new().with_include(Some(Pattern::new("Series/Breaking Bad/**").unwrap()));
+
+        assert!(filter.allows(Path::new("Series/Breaking Bad/Season 01/episode.mkv")));
+        assert!(!filter.allows(Path::new("Series/Better Call Saul/Season 01/episode.mkv")));
+        assert!(!filter.allows(Path::new("Movies/Breaking Bad/movie.mkv")));
+
+        // A single * does not cross directories
+        let filter = PathFilter::new().with_include(Some(Pattern::new("Movies/*.mkv").unwrap()));
+        assert!(filter.allows(Path::new("Movies/movie.mkv")));
+        assert!(!filter.allows(Path::new("Movies/Heat (1995)/movie.mkv")));
+
+        assert!(PathFilter::new().allows(Path::new("anything.mkv")));
+    }
+
+    #[test]
+    fn test_exclude_globs() {
+        let filter = PathFilter::new()
+            .with_include(Some(Pattern::new("Series/**").unwrap()))
+            .with_exclude(vec![
+                Pattern::new("Series/Breaking Bad/**").unwrap(),
+                Pattern::new("**/Extras/**").unwrap(),
+            ]);
+
+        assert!(filter.allows(Path::new("Series/Other/Season 01/episode.mkv")));
+        assert!(!filter.allows(Path::new("Series/Breaking Bad/Season 01/episode.mkv")));
+        assert!(!filter.allows(Path::new("Series/Other/Extras/interview.mkv")));
+        assert!(!filter.allows(Path::new("Movies/movie.mkv")));
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::queue::JobQueue;
+
+/// Command to stop workers from claiming new jobs (running jobs finish)
+pub struct PauseCommand {
+    work_root: PathBuf,
+}
+
+impl PauseCommand {
+    pub fn new(work_root: PathBuf) -> Self {
+        Self { work_root }
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        let queue = JobQueue::new(self.work_root.clone(), self.work_root.clone());
+        queue.pause().await?;
+        info!(
+            "⏸️ Queue paused. Workers finish their current job, then wait until `plexify resume`."
+        );
+        Ok(())
+    }
+}
+
+/// Command to let workers claim jobs again after a pause
+pub struct ResumeCommand {
+    work_root: PathBuf,
+}
+
+impl ResumeCommand {
+    pub fn new(work_root: PathBuf) -> Self {
+        Self { work_root }
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        let queue = JobQueue::new(self.work_root.clone(), self.work_root.clone());
+        if queue.is_paused() {
+            queue.resume().await?;
+            info!("▶️ Queue resumed.");
+        } else {
+            info!("▶️ Queue was not paused.");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_pause_and_resume_toggle_marker() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_root = temp_dir.path().to_path_buf();
+        let queue = JobQueue::new(work_root.clone(), work_root.clone());
+
+        PauseCommand::new(work_root.clone())
+            .execute()
+            .await
+            .unwrap();
+        assert!(queue.is_paused());
+
+        ResumeCommand::new(work_root.clone())
+            .execute()
+            .await
+            .unwrap();
+        assert!(!queue.is_paused());
+
+        // Resuming an unpaused queue is fine
+        ResumeCommand::new(work_root).execute().await.unwrap();
+    }
+}
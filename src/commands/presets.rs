@@ -0,0 +1,125 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::job::{QualityPreset, VideoCodec};
+
+/// One quality preset and the encoder settings it stands for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresetInfo {
+    pub name: &'static str,
+    pub ffmpeg_preset: String,
+    pub ffmpeg_crf: String,
+    pub ffmpeg_audio_bitrate: String,
+}
+
+/// Command to list the quality presets accepted by `--preset`
+pub struct PresetsCommand {
+    json: bool,
+    codec: VideoCodec,
+}
+
+impl PresetsCommand {
+    pub fn new() -> Self {
+        Self {
+            json: false,
+            codec: VideoCodec::default(),
+        }
+    }
+
+    /// Show the settings the presets use for this codec
+    pub fn with_codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Print JSON instead of a table, for scripts
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// The built-in presets, in the order they are documented
+    pub fn presets(&self) -> Result<Vec<PresetInfo>> {
+        QualityPreset::all_names()
+            .into_iter()
+            .map(|name| {
+                let settings = QualityPreset::from_name(name)?.to_quality_settings(self.codec);
+                Ok(PresetInfo {
+                    name,
+                    ffmpeg_preset: settings.ffmpeg_preset,
+                    ffmpeg_crf: settings.ffmpeg_crf,
+                    ffmpeg_audio_bitrate: settings.ffmpeg_audio_bitrate,
+                })
+            })
+            .collect()
+    }
+
+    pub fn render(&self, presets: &[PresetInfo]) -> Result<String> {
+        if self.json {
+            return Ok(serde_json::to_string_pretty(presets)?);
+        }
+
+        let mut table = format!("{:<10} {:<10} {:<4} {}", "NAME", "PRESET", "CRF", "AUDIO");
+        for preset in presets {
+            table.push_str(&format!(
+                "\n{:<10} {:<10} {:<4} {}",
+                preset.name, preset.ffmpeg_preset, preset.ffmpeg_crf, preset.ffmpeg_audio_bitrate
+            ));
+        }
+        Ok(table)
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let presets = self.presets()?;
+        println!("{}", self.render(&presets)?);
+        Ok(())
+    }
+}
+
+impl Default for PresetsCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_lists_all_builtins() {
+        let command = PresetsCommand::new().with_json(true);
+        let presets = command.presets().unwrap();
+        let summary: Vec<_> = presets
+            .iter()
+            .map(|p| {
+                (
+                    p.name,
+                    p.ffmpeg_preset.as_str(),
+                    p.ffmpeg_crf.as_str(),
+                    p.ffmpeg_audio_bitrate.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("fast", "veryfast", "23", "128k"),
+                ("balanced", "medium", "20", "192k"),
+                ("quality", "slow", "18", "256k"),
+                ("ultrafast", "ultrafast", "28", "96k"),
+                ("archive", "veryslow", "15", "320k"),
+            ]
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&command.render(&presets).unwrap()).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 5);
+        assert_eq!(json[1]["name"], "balanced");
+        assert_eq!(json[1]["ffmpeg_crf"], "20");
+
+        let table = PresetsCommand::new().render(&presets).unwrap();
+        assert_eq!(table.lines().count(), 6);
+        assert!(table.contains("archive    veryslow   15   320k"));
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+use walkdir::WalkDir;
+
+use crate::queue::JobQueue;
+
+/// Remove directories under `root` that are empty, bottom-up, so parents left empty
+/// by their children's removal go too. The root itself, queue directories and
+/// anything under `protected` are never removed. Directories holding only hidden
+/// files (e.g. `.plexifyignore`) are not empty and are kept.
+pub fn prune_empty_dirs(root: &Path, protected: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+
+    // Collect directories top-down, then visit them in reverse so children are
+    // handled before their parents
+    let dirs: Vec<PathBuf> = WalkDir::new(root)
+        .follow_links(false)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| {
+            !(JobQueue::is_queue_dir(e.path()) || protected.iter().any(|p| e.path().starts_with(p)))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.into_path())
+        .collect();
+
+    for path in dirs.iter().rev() {
+        if fs::read_dir(path)?.next().is_none() {
+            fs::remove_dir(path)?;
+            debug!("Removed empty directory: {:?}", path);
+            removed.push(path.clone());
+        }
+    }
+
+    if !removed.is_empty() {
+        info!("🗑️ Removed {} empty directories", removed.len());
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_prune_nested_empty_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("WrongDir/Season 01")).unwrap();
+        fs::create_dir_all(root.join("Series/Show/Season 01")).unwrap();
+        fs::write(root.join("Series/Show/Season 01/episode.mkv"), "").unwrap();
+        // Simulate a file having been moved out of WrongDir
+        fs::write(root.join("WrongDir/Season 01/episode.mkv"), "").unwrap();
+        fs::remove_file(root.join("WrongDir/Season 01/episode.mkv")).unwrap();
+
+        let removed = prune_empty_dirs(root, &[]).unwrap();
+
+        assert_eq!(removed.len(), 2);
+        assert!(!root.join("WrongDir").exists());
+        assert!(root.join("Series/Show/Season 01/episode.mkv").exists());
+        assert!(root.exists());
+    }
+
+    #[test]
+    fn test_prune_keeps_hidden_files_and_protected_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("Downloads")).unwrap();
+        fs::write(root.join("Downloads/.plexifyignore"), "*").unwrap();
+        fs::create_dir_all(root.join("_queue")).unwrap();
+        fs::create_dir_all(root.join("_in_progress")).unwrap();
+        fs::create_dir_all(root.join("work/cache")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+
+        let removed = prune_empty_dirs(root, &[root.join("work")]).unwrap();
+
+        assert_eq!(removed, vec![root.join("empty")]);
+        assert!(root.join("Downloads/.plexifyignore").exists());
+        assert!(root.join("_queue").exists());
+        assert!(root.join("_in_progress").exists());
+        assert!(root.join("work/cache").exists());
+    }
+}
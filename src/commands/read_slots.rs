@@ -0,0 +1,252 @@
+use anyhow::Result;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How often a worker waiting for a read slot checks again
+const SLOT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Age after which a slot held by another host is reclaimed, as its pid can't be
+/// checked from here; longer than any encode holds a slot
+const STALE_REMOTE_SLOT_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Cross-process semaphore limiting how many workers read their input at once.
+/// Each slot is a `slot-N.lock` file in `_read_slots`, created with `O_EXCL` and
+/// holding the owner's hostname and pid so slots of crashed workers can be reclaimed.
+#[derive(Debug, Clone)]
+pub struct ReadSlots {
+    dir: PathBuf,
+    max: usize,
+}
+
+/// A held read slot; the lock file is removed when this is dropped
+#[derive(Debug)]
+pub struct ReadSlot {
+    path: PathBuf,
+}
+
+/// Who holds a slot, as written into its lock file
+#[derive(Debug, PartialEq)]
+struct SlotOwner {
+    host: String,
+    pid: u32,
+}
+
+impl SlotOwner {
+    fn current() -> Self {
+        Self {
+            host: gethostname::gethostname().to_string_lossy().into_owned(),
+            pid: std::process::id(),
+        }
+    }
+
+    fn read(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let (host, pid) = content.trim().rsplit_once(' ')?;
+        Some(Self {
+            host: host.to_string(),
+            pid: pid.parse().ok()?,
+        })
+    }
+}
+
+impl ReadSlots {
+    pub fn new(work_root: &Path, max: usize) -> Self {
+        Self {
+            dir: work_root.join("_read_slots"),
+            max: max.max(1),
+        }
+    }
+
+    /// Take a free slot if there is one
+    pub fn try_acquire(&self) -> Result<Option<ReadSlot>> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        for index in 0..self.max {
+            let path = self.dir.join(format!("slot-{index}.lock"));
+            if Self::create(&path)? {
+                debug!("Acquired read slot {:?}", path);
+                return Ok(Some(ReadSlot { path }));
+            }
+            if let Some(owner) = Self::stale_owner(&path) {
+                warn!(
+                    "Reclaiming read slot of a worker that is gone ({} pid {}): {:?}",
+                    owner.host, owner.pid, path
+                );
+                if Self::reclaim(&path, &owner) && Self::create(&path)? {
+                    return Ok(Some(ReadSlot { path }));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Wait until a slot frees up
+    pub async fn acquire(&self) -> Result<ReadSlot> {
+        let mut logged = false;
+        loop {
+            if let Some(slot) = self.try_acquire()? {
+                return Ok(slot);
+            }
+            if !logged {
+                info!(
+                    "⏳ All {} read slots are taken, waiting for another worker...",
+                    self.max
+                );
+                logged = true;
+            }
+            tokio::time::sleep(SLOT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Create the lock file, returning false if another worker holds it
+    fn create(path: &Path) -> Result<bool> {
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+        {
+            Ok(mut file) => {
+                let owner = SlotOwner::current();
+                file.write_all(format!("{} {}", owner.host, owner.pid).as_bytes())?;
+                Ok(true)
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The owner of a slot that is stale: on this host when its pid is no longer
+    /// running (never without `/proc`, as we cannot tell), on other hosts only by age
+    fn stale_owner(path: &Path) -> Option<SlotOwner> {
+        let owner = SlotOwner::read(path)?;
+        let stale = if owner.host == SlotOwner::current().host {
+            Path::new("/proc").is_dir() && !Path::new("/proc").join(owner.pid.to_string()).exists()
+        } else {
+            std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > STALE_REMOTE_SLOT_AGE)
+        };
+        stale.then_some(owner)
+    }
+
+    /// Move a stale slot out of the way. The rename is atomic, so of several
+    /// workers reclaiming it only one succeeds; if the slot was freshly taken
+    /// in the meantime, it is put back instead.
+    fn reclaim(path: &Path, stale: &SlotOwner) -> bool {
+        let mut reclaimed = path.as_os_str().to_owned();
+        reclaimed.push(format!(".reclaim-{}", std::process::id()));
+        let reclaimed = PathBuf::from(reclaimed);
+        if std::fs::rename(path, &reclaimed).is_err() {
+            return false;
+        }
+
+        let still_stale = SlotOwner::read(&reclaimed).as_ref() == Some(stale);
+        if !still_stale {
+            // Linking fails rather than overwrites if yet another slot appeared
+            let _ = std::fs::hard_link(&reclaimed, path);
+        }
+        let _ = std::fs::remove_file(&reclaimed);
+        still_stale
+    }
+}
+
+impl Drop for ReadSlot {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to release read slot {:?}: {}", self.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_slots_acquire_and_release() {
+        let temp_dir = TempDir::new().unwrap();
+        let slots = ReadSlots::new(temp_dir.path(), 2);
+
+        let first = slots.try_acquire().unwrap().unwrap();
+        let second = slots.try_acquire().unwrap().unwrap();
+        assert!(slots.try_acquire().unwrap().is_none());
+
+        // Another process sees the same slots through the work dir
+        let other = ReadSlots::new(temp_dir.path(), 2);
+        assert!(other.try_acquire().unwrap().is_none());
+
+        drop(first);
+        let third = other.try_acquire().unwrap().unwrap();
+        assert!(slots.try_acquire().unwrap().is_none());
+
+        drop(second);
+        drop(third);
+        assert_eq!(
+            std::fs::read_dir(temp_dir.path().join("_read_slots"))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_read_slots_reclaim_dead_owner() {
+        let temp_dir = TempDir::new().unwrap();
+        let slots = ReadSlots::new(temp_dir.path(), 1);
+
+        // A slot left behind by a pid that is not running on this host
+        std::fs::create_dir_all(temp_dir.path().join("_read_slots")).unwrap();
+        let host = SlotOwner::current().host;
+        std::fs::write(
+            temp_dir.path().join("_read_slots/slot-0.lock"),
+            format!("{host} {}", u32::MAX),
+        )
+        .unwrap();
+
+        let slot = slots.try_acquire().unwrap().unwrap();
+        assert_eq!(SlotOwner::read(&slot.path), Some(SlotOwner::current()));
+        assert_eq!(
+            std::fs::read_dir(temp_dir.path().join("_read_slots"))
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_read_slots_keep_recent_slot_of_other_host() {
+        let temp_dir = TempDir::new().unwrap();
+        let slots = ReadSlots::new(temp_dir.path(), 1);
+
+        // The pid means nothing here, only the slot's age does
+        std::fs::create_dir_all(temp_dir.path().join("_read_slots")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("_read_slots/slot-0.lock"),
+            format!("some-other-host {}", u32::MAX),
+        )
+        .unwrap();
+
+        assert!(slots.try_acquire().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reclaim_puts_back_a_fresh_slot() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("slot-0.lock");
+        assert!(ReadSlots::create(&path).unwrap());
+
+        // Another worker already reclaimed the stale slot and took it
+        let stale = SlotOwner {
+            host: SlotOwner::current().host,
+            pid: u32::MAX,
+        };
+        assert!(!ReadSlots::reclaim(&path, &stale));
+        assert_eq!(SlotOwner::read(&path), Some(SlotOwner::current()));
+    }
+}
@@ -0,0 +1,311 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Variables that can be used in a rename template
+const TEMPLATE_VARIABLES: &[&str] = &["show", "season", "episode", "title", "metadata", "ext"];
+
+/// Characters that only separate variables; dropped along with an empty variable
+const SEPARATORS: &[char] = &[' ', '-', '.', '_'];
+
+/// The parts of an episode filename like `Show.Name.S01E02E03.Title.[1080p].mkv`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpisodeInfo {
+    /// Show name before the episode code, if the filename has one
+    pub show: Option<String>,
+    pub season: u32,
+    pub episode: u32,
+    /// Last episode of a multi-episode file
+    pub last_episode: Option<u32>,
+    /// Episode title after the code, with dots and underscores turned into spaces
+    pub title: String,
+    /// Trailing bracketed tags such as `[1080p] [x265]`
+    pub metadata: String,
+    pub ext: String,
+}
+
+impl EpisodeInfo {
+    /// Parse a filename with an `SxxExx` code, or `None` if it has none
+    pub fn parse(filename: &str) -> Option<Self> {
+        let path = Path::new(filename);
+        let stem = path.file_stem()?.to_str()?;
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+
+        let re = Regex::new(r"(?i)^(.*?)[\s._-]*\bs(\d{1,2})e(\d{1,3})(?:-?e(\d{1,3}))?\b(.*)$")
+            .expect("valid regex");
+        let captures = re.captures(stem)?;
+        let show = tidy(&captures[1]);
+        let rest = &captures[5];
+
+        // Bracketed tags at the end are metadata, the rest is the title
+        let metadata_re = Regex::new(r"(?:\s*\[[^\]]*\])+\s*$").expect("valid regex");
+        let (title, metadata) = match metadata_re.find(rest) {
+            Some(m) => (&rest[..m.start()], m.as_str().trim()),
+            None => (rest, ""),
+        };
+
+        Some(Self {
+            show: (!show.is_empty()).then_some(show),
+            season: captures[2].parse().ok()?,
+            episode: captures[3].parse().ok()?,
+            last_episode: captures.get(4).and_then(|m| m.as_str().parse().ok()),
+            title: tidy(title),
+            metadata: metadata.to_string(),
+            ext: ext.to_string(),
+        })
+    }
+}
+
+/// Turn `.`/`_` word separators into spaces and trim leftover separators
+fn tidy(text: &str) -> String {
+    text.replace(['.', '_'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(SEPARATORS)
+        .to_string()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Variable(&'static str),
+}
+
+/// Episode filename template for suggested paths, such as
+/// `{show} - S{season}E{episode} - {title}.{ext}`
+///
+/// Available variables:
+/// - `{show}`: show name from the filename, or the show folder
+/// - `{season}`, `{episode}`: zero-padded to two digits, except for the season
+///   in the `{season}x{episode}` style (`1x02`). A multi-episode file repeats
+///   the letter before `{episode}` for the range (`S01E01-E02`, `s01e01-e02`),
+///   or just the number in the `x` style (`1x01-02`)
+/// - `{title}`: episode title after the code
+/// - `{metadata}`: trailing bracketed tags such as `[1080p]`
+/// - `{ext}`: file extension
+///
+/// An empty variable takes the separators before it along, so a missing title
+/// does not leave a dangling ` - `.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameTemplate {
+    template: String,
+    tokens: Vec<Token>,
+    /// Digits `{season}` is zero-padded to
+    season_width: usize,
+    /// Joins the first and last episode of a multi-episode file, e.g. `-E`
+    range_separator: String,
+}
+
+impl RenameTemplate {
+    /// Parse a template, rejecting unknown variables, unbalanced braces and directories
+    pub fn parse(template: &str) -> Result<Self> {
+        if template.contains('/') || template.contains('\\') {
+            return Err(anyhow!(
+                "Rename template '{template}' must be a file name, not a path"
+            ));
+        }
+
+        let mut tokens = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or_else(|| anyhow!("Unclosed '{{' in rename template '{template}'"))?;
+            let name = &rest[start + 1..end];
+            let variable = TEMPLATE_VARIABLES
+                .iter()
+                .find(|variable| **variable == name)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Unknown rename template variable '{{{name}}}'. Available: {}",
+                        TEMPLATE_VARIABLES
+                            .iter()
+                            .map(|v| format!("{{{v}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?;
+            if start > 0 {
+                tokens.push(Token::Literal(rest[..start].to_string()));
+            }
+            tokens.push(Token::Variable(variable));
+            rest = &rest[end + 1..];
+        }
+        if rest.contains('}') {
+            return Err(anyhow!("Unmatched '}}' in rename template '{template}'"));
+        }
+        if !rest.is_empty() {
+            tokens.push(Token::Literal(rest.to_string()));
+        }
+        if !tokens.contains(&Token::Variable("ext")) {
+            return Err(anyhow!(
+                "Rename template '{template}' must keep the extension with {{ext}}"
+            ));
+        }
+
+        // The text leading into `{episode}`, like the `E` of `S{season}E{episode}`
+        let episode_prefix = tokens
+            .iter()
+            .position(|token| *token == Token::Variable("episode"))
+            .and_then(|index| index.checked_sub(1))
+            .and_then(|index| match &tokens[index] {
+                Token::Literal(text) => Some(text.as_str()),
+                Token::Variable(_) => None,
+            })
+            .unwrap_or_default();
+        let season_by_episode = episode_prefix.eq_ignore_ascii_case("x")
+            && tokens.windows(3).any(|window| {
+                window[0] == Token::Variable("season") && window[2] == Token::Variable("episode")
+            });
+        let episode_letter: String = if season_by_episode {
+            String::new()
+        } else {
+            let letters = episode_prefix
+                .chars()
+                .rev()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .count();
+            episode_prefix[episode_prefix.len() - letters..].to_string()
+        };
+
+        Ok(Self {
+            template: template.to_string(),
+            tokens,
+            season_width: if season_by_episode { 1 } else { 2 },
+            range_separator: format!("-{episode_letter}"),
+        })
+    }
+
+    /// Render a filename. `show` is used when the filename itself has no show name.
+    pub fn render(&self, info: &EpisodeInfo, show: Option<&str>) -> String {
+        let mut rendered = String::new();
+        // Separators are held back until we know whether the next variable is empty
+        let mut pending = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(text) => pending.push_str(text),
+                Token::Variable(name) => {
+                    let value = match *name {
+                        "show" => info
+                            .show
+                            .as_deref()
+                            .or(show)
+                            .unwrap_or_default()
+                            .to_string(),
+                        "season" => format!("{:0width$}", info.season, width = self.season_width),
+                        "episode" => match info.last_episode {
+                            Some(last) => {
+                                format!("{:02}{}{:02}", info.episode, self.range_separator, last)
+                            }
+                            None => format!("{:02}", info.episode),
+                        },
+                        "title" => info.title.clone(),
+                        "metadata" => info.metadata.clone(),
+                        _ => info.ext.clone(),
+                    };
+                    if value.is_empty() && pending.chars().all(|c| SEPARATORS.contains(&c)) {
+                        pending.clear();
+                        continue;
+                    }
+                    rendered.push_str(&pending);
+                    pending.clear();
+                    rendered.push_str(&value);
+                }
+            }
+        }
+        rendered.push_str(&pending);
+        rendered.trim_start_matches(SEPARATORS).to_string()
+    }
+}
+
+impl FromStr for RenameTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for RenameTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_episode_info() {
+        let info =
+            EpisodeInfo::parse("Breaking.Bad.S01E02.Cats.in.the.Bag [1080p] [x265].mkv").unwrap();
+        assert_eq!(
+            info,
+            EpisodeInfo {
+                show: Some("Breaking Bad".to_string()),
+                season: 1,
+                episode: 2,
+                last_episode: None,
+                title: "Cats in the Bag".to_string(),
+                metadata: "[1080p] [x265]".to_string(),
+                ext: "mkv".to_string(),
+            }
+        );
+
+        let info = EpisodeInfo::parse("s03e04e05.mp4").unwrap();
+        assert_eq!(info.show, None);
+        assert_eq!((info.season, info.episode), (3, 4));
+        assert_eq!(info.last_episode, Some(5));
+        assert_eq!(info.title, "");
+
+        assert!(EpisodeInfo::parse("Heat (1995).mkv").is_none());
+    }
+
+    #[test]
+    fn test_render_custom_templates() {
+        let info = EpisodeInfo::parse("Breaking.Bad.S01E02.Cats.in.the.Bag [1080p].mkv").unwrap();
+
+        let standard =
+            RenameTemplate::parse("{show} - S{season}E{episode} - {title} {metadata}.{ext}")
+                .unwrap();
+        assert_eq!(
+            standard.render(&info, None),
+            "Breaking Bad - S01E02 - Cats in the Bag [1080p].mkv"
+        );
+
+        // Without the show prefix, in the `1x02` style
+        let short = RenameTemplate::parse("{season}x{episode} {title}.{ext}").unwrap();
+        assert_eq!(short.render(&info, None), "1x02 Cats in the Bag.mkv");
+
+        // Missing parts take their separators along; the show falls back to the folder
+        let bare = EpisodeInfo::parse("s01e01e02.mkv").unwrap();
+        assert_eq!(
+            standard.render(&bare, Some("Show")),
+            "Show - S01E01-E02.mkv"
+        );
+
+        // The episode range follows the template's own style
+        assert_eq!(short.render(&bare, None), "1x01-02.mkv");
+        let lower = RenameTemplate::parse("s{season}e{episode}.{ext}").unwrap();
+        assert_eq!(lower.render(&bare, None), "s01e01-e02.mkv");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_templates() {
+        assert!(RenameTemplate::parse("{show} - {year}.{ext}").is_err());
+        assert!(RenameTemplate::parse("{show - S{season}.{ext}").is_err());
+        assert!(RenameTemplate::parse("{show}/S{season}.{ext}").is_err());
+        assert!(RenameTemplate::parse("{show} - S{season}E{episode}.mkv").is_err());
+        assert!("S{season}E{episode}.{ext}"
+            .parse::<RenameTemplate>()
+            .is_ok());
+    }
+}
@@ -1,20 +1,77 @@
-use anyhow::{anyhow, Result};
-use indicatif::{ProgressBar, ProgressStyle};
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use indicatif::ProgressStyle;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
+use crate::config::Config;
+use crate::error::PlexifyError;
+use crate::ffmpeg::FFmpegProcessor;
 use crate::ignore::IgnoreFilter;
-use crate::job::MediaFileType;
+use crate::job::{
+    split_part, AudioMode, ChecksumAlgorithm, Job, MediaFileType, OutputTemplate, SubtitleFormat,
+    SubtitleMode, VideoCodec,
+};
+use crate::output;
 use crate::queue::JobQueue;
 
 use super::job_processor::{JobProcessResult, JobProcessor, JobProcessorConfig};
+use super::path_filter::PathFilter;
+use super::throttle::Throttle;
+
+/// Sibling suffixes left behind by downloaders while a file is incomplete
+const PARTIAL_DOWNLOAD_SUFFIXES: &[&str] = &[".part", ".!ut", ".crdownload", ".partial"];
+
+/// Bytes read from each end of a file when computing its quick hash
+const QUICK_HASH_SAMPLE: u64 = 1024 * 1024;
+
+/// Which input wins when several share a stem: MKV usually carries its own subtitles
+const DEFAULT_INPUT_EXT_PRIORITY: &[&str] = &["mkv", "webm"];
+
+/// Jobs computed by `scan --plan-out`, reviewed and then enqueued with `scan --plan-in`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanPlan {
+    /// Media directory the plan was computed for
+    pub media_root: PathBuf,
+    pub jobs: Vec<Job>,
+}
 
 /// Command to scan a directory for media files and create jobs
 pub struct ScanCommand {
     media_root: PathBuf,
     work_root: PathBuf,
     preset: Option<String>,
+    skip_growing: bool,
+    growth_check_interval: Duration,
+    throttle: Throttle,
+    path_filter: PathFilter,
+    dedupe: bool,
+    input_ext_priority: Vec<String>,
+    output_template: Option<OutputTemplate>,
+    audio_mode: AudioMode,
+    tonemap: bool,
+    auto_chapters: bool,
+    downmix_stereo: bool,
+    tag_metadata: bool,
+    ass_subtitles: SubtitleMode,
+    burn_subtitles: bool,
+    skip_if_codec: Vec<String>,
+    codec: VideoCodec,
+    checksum: ChecksumAlgorithm,
+    min_duration: Option<Duration>,
+    max_duration: Option<Duration>,
+    plan_out: Option<PathBuf>,
+    plan_in: Option<PathBuf>,
+    remux_mp4: bool,
+    convert_subs: Option<SubtitleFormat>,
+    skip_completed: bool,
+    keep_completed_days: Option<u64>,
+    combine_parts: bool,
 }
 
 impl ScanCommand {
@@ -23,19 +80,214 @@ impl ScanCommand {
             media_root,
             work_root,
             preset,
+            skip_growing: false,
+            growth_check_interval: Duration::from_secs(2),
+            throttle: Throttle::disabled(),
+            path_filter: PathFilter::new(),
+            dedupe: false,
+            input_ext_priority: DEFAULT_INPUT_EXT_PRIORITY
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            output_template: None,
+            audio_mode: AudioMode::default(),
+            tonemap: false,
+            auto_chapters: false,
+            downmix_stereo: false,
+            tag_metadata: false,
+            ass_subtitles: SubtitleMode::default(),
+            burn_subtitles: false,
+            skip_if_codec: Vec::new(),
+            codec: VideoCodec::default(),
+            checksum: ChecksumAlgorithm::default(),
+            min_duration: None,
+            max_duration: None,
+            plan_out: None,
+            plan_in: None,
+            remux_mp4: false,
+            convert_subs: None,
+            skip_completed: false,
+            keep_completed_days: None,
+            combine_parts: false,
         }
     }
 
+    /// Join MKV episodes split into `part1`, `part2`, ... files into one output each
+    pub fn with_combine_parts(mut self, combine_parts: bool) -> Self {
+        self.combine_parts = combine_parts;
+        self
+    }
+
+    /// Prune completed job records older than this many days before scanning
+    pub fn with_keep_completed_days(mut self, keep_completed_days: Option<u64>) -> Self {
+        self.keep_completed_days = keep_completed_days;
+        self
+    }
+
+    /// Treat every input recorded in `_completed` as done, even if its output is gone
+    pub fn with_skip_completed(mut self, skip_completed: bool) -> Self {
+        self.skip_completed = skip_completed;
+        self
+    }
+
+    /// Also queue stream-copy remuxes for MP4 files that lack faststart
+    pub fn with_remux_mp4(mut self, remux_mp4: bool) -> Self {
+        self.remux_mp4 = remux_mp4;
+        self
+    }
+
+    /// Queue conversions of `.srt`/`.vtt` sidecars into this format (video is left alone)
+    pub fn with_convert_subs(mut self, convert_subs: Option<SubtitleFormat>) -> Self {
+        self.convert_subs = convert_subs;
+        self
+    }
+
+    /// Write the jobs the scan would create to this file instead of enqueueing them
+    pub fn with_plan_out(mut self, plan_out: Option<PathBuf>) -> Self {
+        self.plan_out = plan_out;
+        self
+    }
+
+    /// Skip the scan and enqueue exactly the jobs in this plan file
+    pub fn with_plan_in(mut self, plan_in: Option<PathBuf>) -> Self {
+        self.plan_in = plan_in;
+        self
+    }
+
+    /// Encode new jobs in this codec, with preset CRFs scaled to match
+    pub fn with_codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Don't remux MP4 files whose video already uses one of these codecs (e.g. h264)
+    pub fn with_skip_if_codec(mut self, skip_if_codec: Vec<String>) -> Self {
+        self.skip_if_codec = skip_if_codec;
+        self
+    }
+
+    /// Record a checksum of each queued input so `verify --checksums` can detect bit rot
+    pub fn with_checksum(mut self, checksum: ChecksumAlgorithm) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Skip files whose ffprobe duration is outside this range (both ends inclusive)
+    pub fn with_duration_range(
+        mut self,
+        min_duration: Option<Duration>,
+        max_duration: Option<Duration>,
+    ) -> Self {
+        self.min_duration = min_duration;
+        self.max_duration = max_duration;
+        self
+    }
+
+    /// Tag outputs with a title parsed from the path and `encoder=plexify`
+    pub fn with_tag_metadata(mut self, tag_metadata: bool) -> Self {
+        self.tag_metadata = tag_metadata;
+        self
+    }
+
+    /// Burn subtitles into the video for players that cannot show soft subtitles
+    pub fn with_burn_subtitles(mut self, burn_subtitles: bool) -> Self {
+        self.burn_subtitles = burn_subtitles;
+        self
+    }
+
+    /// Mux `.ass` sidecar subtitles as mov_text (losing styling) or burn them in
+    pub fn with_ass_subtitles(mut self, ass_subtitles: SubtitleMode) -> Self {
+        self.ass_subtitles = ass_subtitles;
+        self
+    }
+
+    /// Downmix 5.1 and other multichannel audio to stereo
+    pub fn with_downmix_stereo(mut self, downmix_stereo: bool) -> Self {
+        self.downmix_stereo = downmix_stereo;
+        self
+    }
+
+    /// Tone-map HDR sources to SDR (detected with ffprobe when the job runs)
+    pub fn with_tonemap(mut self, tonemap: bool) -> Self {
+        self.tonemap = tonemap;
+        self
+    }
+
+    /// Add chapter markers at silent gaps, found with ffmpeg's silencedetect when a job runs
+    pub fn with_auto_chapters(mut self, auto_chapters: bool) -> Self {
+        self.auto_chapters = auto_chapters;
+        self
+    }
+
+    /// Write audio for new jobs in this mode
+    pub fn with_audio_mode(mut self, audio_mode: AudioMode) -> Self {
+        self.audio_mode = audio_mode;
+        self
+    }
+
+    /// Render job output paths from a template
+    pub fn with_output_template(mut self, output_template: Option<OutputTemplate>) -> Self {
+        self.output_template = output_template;
+        self
+    }
+
+    /// Skip inputs whose content is identical to another input found in this scan
+    pub fn with_dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// When several inputs share a stem (and so an output), only queue the one whose
+    /// extension comes first in this list
+    pub fn with_input_ext_priority(mut self, input_ext_priority: Vec<String>) -> Self {
+        self.input_ext_priority = input_ext_priority
+            .into_iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Only process files whose path relative to the media root matches this glob
+    pub fn with_match(mut self, pattern: Option<glob::Pattern>) -> Self {
+        self.path_filter = self.path_filter.with_include(pattern);
+        self
+    }
+
+    /// Skip files whose path relative to the media root matches any of these globs
+    pub fn with_exclude(mut self, patterns: Vec<glob::Pattern>) -> Self {
+        self.path_filter = self.path_filter.with_exclude(patterns);
+        self
+    }
+
+    /// Pause for `delay` every `batch_size` filesystem entries while walking
+    pub fn with_throttle(mut self, delay: Duration, batch_size: usize) -> Self {
+        self.throttle = Throttle::new(delay, batch_size);
+        self
+    }
+
+    /// Skip files that are still being written (size changing or a partial-download sibling exists)
+    pub fn with_skip_growing(mut self, skip_growing: bool) -> Self {
+        self.skip_growing = skip_growing;
+        self
+    }
+
+    /// The `--keep-completed-days` flag, falling back to `PLEXIFY_KEEP_COMPLETED_DAYS`
+    fn keep_completed_days(&self) -> Option<u64> {
+        self.keep_completed_days
+            .or_else(|| Config::from_env().keep_completed_days)
+    }
+
     pub async fn execute(&self) -> Result<()> {
         if !self.media_root.exists() {
-            return Err(anyhow!(
-                "Media directory does not exist: {:?}",
-                self.media_root
-            ));
+            return Err(PlexifyError::MediaRootMissing(self.media_root.clone()).into());
         }
 
         if !self.media_root.is_dir() {
-            return Err(anyhow!("Path is not a directory: {:?}", self.media_root));
+            return Err(PlexifyError::NotADirectory(self.media_root.clone()).into());
+        }
+
+        if let Some(plan_in) = &self.plan_in {
+            return self.apply_plan(plan_in).await;
         }
 
         info!("🔎 Scanning directory: {:?}", self.media_root);
@@ -52,15 +304,21 @@ impl ScanCommand {
 
         let queue = JobQueue::new(self.media_root.clone(), self.work_root.clone());
         queue.init().await?;
+        queue
+            .apply_completed_retention(self.keep_completed_days())
+            .await?;
 
         let mut webm_files = Vec::new();
         let mut mkv_files = Vec::new();
+        let mut mp4_files = Vec::new();
+        let mut subtitle_files = Vec::new();
         let mut directories_scanned = std::collections::HashSet::new();
         let mut ignored_count = 0;
         let mut files_processed = 0;
+        let mut throttle = self.throttle.clone();
 
         // Create a progress bar for scanning
-        let scan_pb = ProgressBar::new_spinner();
+        let scan_pb = output::spinner();
         scan_pb.set_style(
             ProgressStyle::with_template("{spinner:.green} {msg}")
                 .unwrap()
@@ -82,7 +340,7 @@ impl ScanCommand {
                 }
 
                 // Check if we should skip this directory and all its contents
-                if path.is_dir() {
+                if e.file_type().is_dir() {
                     if let Some(ref filter) = ignore_filter {
                         if filter.should_skip_dir(path) {
                             debug!("🚫 Skipping entire directory: {:?}", path);
@@ -95,11 +353,17 @@ impl ScanCommand {
             })
             .filter_map(|e| e.ok())
         {
+            throttle.tick().await;
+
             let path = entry.path();
+            // Use the walker's file type rather than stat'ing again
+            let is_dir = entry.file_type().is_dir();
+            let is_file =
+                entry.file_type().is_file() || (entry.path_is_symlink() && path.is_file());
 
             // Check if this individual path should be ignored
             if let Some(ref filter) = ignore_filter {
-                if filter.should_ignore(path) {
+                if filter.should_ignore(path, is_dir) {
                     debug!("🚫 Ignoring path: {:?}", path);
                     ignored_count += 1;
                     // Skip this entry completely
@@ -108,7 +372,7 @@ impl ScanCommand {
             }
 
             // Track directories being scanned for better user feedback
-            if path.is_dir() && path != self.media_root {
+            if is_dir && path != self.media_root {
                 if let Ok(relative_dir) = path.strip_prefix(&self.media_root) {
                     if !directories_scanned.contains(relative_dir) {
                         directories_scanned.insert(relative_dir.to_path_buf());
@@ -117,7 +381,13 @@ impl ScanCommand {
                 }
             }
 
-            if path.is_file() {
+            if is_file {
+                if let Ok(relative_path) = path.strip_prefix(&self.media_root) {
+                    if !self.path_filter.allows(relative_path) {
+                        continue;
+                    }
+                }
+
                 files_processed += 1;
 
                 // Update progress bar message periodically
@@ -138,6 +408,20 @@ impl ScanCommand {
                                 mkv_files.push(relative_path.to_path_buf());
                             }
                         }
+                        "mp4" if self.remux_mp4 => {
+                            if let Ok(relative_path) = path.strip_prefix(&self.media_root) {
+                                mp4_files.push(relative_path.to_path_buf());
+                            }
+                        }
+                        "srt" | "vtt"
+                            if self.convert_subs.is_some_and(|target| {
+                                SubtitleFormat::from_path(path) != Some(target)
+                            }) =>
+                        {
+                            if let Ok(relative_path) = path.strip_prefix(&self.media_root) {
+                                subtitle_files.push(relative_path.to_path_buf());
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -146,6 +430,71 @@ impl ScanCommand {
 
         scan_pb.finish_and_clear();
 
+        let shadowed = self.find_shadowed_inputs(&webm_files, &mkv_files);
+        if !shadowed.is_empty() {
+            Self::retain_all(&mut webm_files, &mut mkv_files, &mut mp4_files, &shadowed);
+        }
+
+        if self.skip_completed {
+            let completed_inputs = queue.completed_inputs().await?;
+            let completed: HashSet<PathBuf> = Self::candidates(&webm_files, &mkv_files, &mp4_files)
+                .into_iter()
+                .filter(|path| completed_inputs.contains(&self.media_root.join(path)))
+                .cloned()
+                .collect();
+            if !completed.is_empty() {
+                info!("✔️ Skipping {} files already completed", completed.len());
+                Self::retain_all(&mut webm_files, &mut mkv_files, &mut mp4_files, &completed);
+            }
+        }
+
+        if self.skip_growing {
+            let candidates = Self::candidates(&webm_files, &mkv_files, &mp4_files);
+            let growing = self.find_growing_files(&candidates).await;
+
+            if !growing.is_empty() {
+                for path in &growing {
+                    info!("⏳ Skipping file that is still being written: {:?}", path);
+                }
+                Self::retain_all(&mut webm_files, &mut mkv_files, &mut mp4_files, &growing);
+            }
+        }
+
+        if self.dedupe {
+            let candidates = Self::candidates(&webm_files, &mkv_files, &mp4_files);
+            let duplicates = self.find_duplicate_files(&candidates).await;
+            if !duplicates.is_empty() {
+                info!("🔁 Skipping {} duplicate files", duplicates.len());
+                Self::retain_all(&mut webm_files, &mut mkv_files, &mut mp4_files, &duplicates);
+            }
+        }
+
+        if self.min_duration.is_some() || self.max_duration.is_some() {
+            let candidates = Self::candidates(&webm_files, &mkv_files, &mp4_files);
+            let out_of_range = self.find_out_of_range_files(&candidates).await;
+            if !out_of_range.is_empty() {
+                info!(
+                    "⏱️ Skipping {} files outside the duration range",
+                    out_of_range.len()
+                );
+                Self::retain_all(
+                    &mut webm_files,
+                    &mut mkv_files,
+                    &mut mp4_files,
+                    &out_of_range,
+                );
+            }
+        }
+
+        let split_episodes = if self.combine_parts {
+            let split_episodes = split_episodes(&mkv_files);
+            let parts: HashSet<&PathBuf> = split_episodes.iter().flatten().collect();
+            mkv_files.retain(|path| !parts.contains(path));
+            split_episodes
+        } else {
+            Vec::new()
+        };
+
         info!(
             "📊 Scanned {} directories, processed {} files, and found {} .webm files and {} .mkv files",
             directories_scanned.len(),
@@ -153,6 +502,25 @@ impl ScanCommand {
             webm_files.len(),
             mkv_files.len()
         );
+        if !split_episodes.is_empty() {
+            info!(
+                "📊 Found {} .mkv episodes split into parts",
+                split_episodes.len()
+            );
+        }
+        if self.remux_mp4 {
+            info!(
+                "📊 Found {} .mp4 files to check for faststart",
+                mp4_files.len()
+            );
+        }
+        if let Some(target) = self.convert_subs {
+            info!(
+                "📊 Found {} subtitle files to convert to .{}",
+                subtitle_files.len(),
+                target.extension()
+            );
+        }
 
         if ignored_count > 0 {
             info!(
@@ -171,10 +539,14 @@ impl ScanCommand {
         info!("🔄 Now creating transcoding jobs...");
 
         let mut job_count = 0;
-        let total_files = webm_files.len() + mkv_files.len();
+        let total_files = webm_files.len()
+            + mkv_files.len()
+            + split_episodes.len()
+            + mp4_files.len()
+            + subtitle_files.len();
 
         let job_pb = if total_files > 0 {
-            let pb = ProgressBar::new(total_files as u64);
+            let pb = output::progress_bar(total_files as u64);
             pb.set_style(
                 ProgressStyle::with_template("Creating jobs {bar:30.cyan/blue} {pos}/{len} {msg}")
                     .unwrap()
@@ -186,8 +558,30 @@ impl ScanCommand {
         };
 
         // Get configuration settings for jobs
-        let config = JobProcessorConfig::from_preset(self.preset.as_deref())?;
-        let processor = JobProcessor::new(&queue, &config, &self.media_root);
+        let config = JobProcessorConfig::from_preset_for(self.preset.as_deref(), self.codec)?
+            .with_output_template(self.output_template.clone())
+            .with_audio_mode(self.audio_mode)
+            .with_tonemap(self.tonemap)
+            .with_auto_chapters(self.auto_chapters)
+            .with_downmix_stereo(self.downmix_stereo)
+            .with_tag_metadata(self.tag_metadata)
+            .with_ass_subtitles(self.ass_subtitles)
+            .with_burn_subtitles(self.burn_subtitles)
+            .with_skip_if_codec(self.skip_if_codec.clone())
+            .with_checksum(self.checksum);
+        if !self.skip_if_codec.is_empty() {
+            info!(
+                "Not remuxing MP4 files already encoded as: {}",
+                self.skip_if_codec.join(", ")
+            );
+        }
+        if self.checksum != ChecksumAlgorithm::default() {
+            info!("Recording {:?} checksums of queued inputs", self.checksum);
+        }
+        let processor = JobProcessor::new(&queue, &config, &self.media_root)
+            .with_queued_inputs()
+            .await?;
+        let mut planned = self.plan_out.as_ref().map(|_| Vec::new());
 
         // Process WebM files (require VTT subtitles)
         for webm_path in &webm_files {
@@ -198,11 +592,24 @@ impl ScanCommand {
                 ));
             }
 
-            let result = processor
-                .process_media_file(webm_path, MediaFileType::WebM)
-                .await?;
+            let result = match planned.as_mut() {
+                Some(plan) => {
+                    processor
+                        .plan_media_file(webm_path, MediaFileType::WebM, plan)
+                        .await?
+                }
+                None => {
+                    processor
+                        .process_media_file(webm_path, MediaFileType::WebM)
+                        .await?
+                }
+            };
 
             match result {
+                JobProcessResult::Created if planned.is_some() => {
+                    debug!("📝 Planned job for: {:?}", webm_path);
+                    job_count += 1;
+                }
                 JobProcessResult::Created => {
                     processor.log_result(webm_path, &MediaFileType::WebM, &result);
                     job_count += 1;
@@ -226,11 +633,24 @@ impl ScanCommand {
                 ));
             }
 
-            let result = processor
-                .process_media_file(mkv_path, MediaFileType::Mkv)
-                .await?;
+            let result = match planned.as_mut() {
+                Some(plan) => {
+                    processor
+                        .plan_media_file(mkv_path, MediaFileType::Mkv, plan)
+                        .await?
+                }
+                None => {
+                    processor
+                        .process_media_file(mkv_path, MediaFileType::Mkv)
+                        .await?
+                }
+            };
 
             match result {
+                JobProcessResult::Created if planned.is_some() => {
+                    debug!("📝 Planned job for: {:?}", mkv_path);
+                    job_count += 1;
+                }
                 JobProcessResult::Created => {
                     processor.log_result(mkv_path, &MediaFileType::Mkv, &result);
                     job_count += 1;
@@ -245,24 +665,466 @@ impl ScanCommand {
             }
         }
 
+        // Join split episodes into one output each
+        for parts in &split_episodes {
+            if let Some(ref pb) = job_pb {
+                pb.set_message(format!(
+                    "Parts: {:?}",
+                    parts[0].file_name().unwrap_or_default()
+                ));
+            }
+
+            let result = match planned.as_mut() {
+                Some(plan) => processor.plan_split_episode(parts, plan).await?,
+                None => processor.process_split_episode(parts).await?,
+            };
+
+            match result {
+                JobProcessResult::Created if planned.is_some() => {
+                    debug!("📝 Planned job for: {:?}", parts);
+                    job_count += 1;
+                }
+                JobProcessResult::Created => {
+                    info!("➕ Queueing job joining {} parts: {:?}", parts.len(), parts);
+                    job_count += 1;
+                }
+                _ => {
+                    processor.log_result(&parts[0], &MediaFileType::Mkv, &result);
+                }
+            }
+
+            if let Some(ref pb) = job_pb {
+                pb.inc(1);
+            }
+        }
+
+        // Remux MP4 files whose index is at the end (stream copy)
+        for mp4_path in &mp4_files {
+            if let Some(ref pb) = job_pb {
+                pb.set_message(format!(
+                    "MP4: {:?}",
+                    mp4_path.file_name().unwrap_or_default()
+                ));
+            }
+
+            let result = match planned.as_mut() {
+                Some(plan) => {
+                    processor
+                        .plan_media_file(mp4_path, MediaFileType::Mp4, plan)
+                        .await?
+                }
+                None => {
+                    processor
+                        .process_media_file(mp4_path, MediaFileType::Mp4)
+                        .await?
+                }
+            };
+
+            match result {
+                JobProcessResult::Created if planned.is_some() => {
+                    debug!("📝 Planned job for: {:?}", mp4_path);
+                    job_count += 1;
+                }
+                JobProcessResult::Created => {
+                    processor.log_result(mp4_path, &MediaFileType::Mp4, &result);
+                    job_count += 1;
+                }
+                _ => {
+                    processor.log_result(mp4_path, &MediaFileType::Mp4, &result);
+                }
+            }
+
+            if let Some(ref pb) = job_pb {
+                pb.inc(1);
+            }
+        }
+
+        // Convert subtitle sidecars to the requested format
+        for subtitle_path in &subtitle_files {
+            if let Some(ref pb) = job_pb {
+                pb.set_message(format!(
+                    "Subtitle: {:?}",
+                    subtitle_path.file_name().unwrap_or_default()
+                ));
+            }
+
+            let result = match planned.as_mut() {
+                Some(plan) => {
+                    processor
+                        .plan_media_file(subtitle_path, MediaFileType::Subtitle, plan)
+                        .await?
+                }
+                None => {
+                    processor
+                        .process_media_file(subtitle_path, MediaFileType::Subtitle)
+                        .await?
+                }
+            };
+
+            match result {
+                JobProcessResult::Created if planned.is_some() => {
+                    debug!("📝 Planned job for: {:?}", subtitle_path);
+                    job_count += 1;
+                }
+                JobProcessResult::Created => {
+                    processor.log_result(subtitle_path, &MediaFileType::Subtitle, &result);
+                    job_count += 1;
+                }
+                _ => {
+                    processor.log_result(subtitle_path, &MediaFileType::Subtitle, &result);
+                }
+            }
+
+            if let Some(ref pb) = job_pb {
+                pb.inc(1);
+            }
+        }
+
         if let Some(pb) = job_pb {
             pb.finish_and_clear();
         }
 
+        if let (Some(plan_out), Some(jobs)) = (&self.plan_out, planned) {
+            let plan = ScanPlan {
+                media_root: self.media_root.clone(),
+                jobs,
+            };
+            tokio::fs::write(plan_out, serde_json::to_string_pretty(&plan)?).await?;
+            info!(
+                "📝 Wrote a plan with {} jobs to {:?}. Review it, then enqueue it with --plan-in.",
+                job_count, plan_out
+            );
+            return Ok(());
+        }
+
         info!(
             "✅ Scan complete. Added {} new jobs to the queue.",
             job_count
         );
         Ok(())
     }
+
+    /// Enqueue the jobs from a plan file, skipping ones that are done or already queued
+    async fn apply_plan(&self, plan_path: &Path) -> Result<()> {
+        let content = tokio::fs::read_to_string(plan_path)
+            .await
+            .with_context(|| format!("Failed to read plan {plan_path:?}"))?;
+        let plan: ScanPlan = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse plan {plan_path:?}"))?;
+        if plan.media_root != self.media_root {
+            warn!(
+                "Plan was made for {:?}, applying it to {:?}",
+                plan.media_root, self.media_root
+            );
+        }
+
+        let queue = JobQueue::new(self.media_root.clone(), self.work_root.clone());
+        queue.init().await?;
+        queue
+            .apply_completed_retention(self.keep_completed_days())
+            .await?;
+        let config = JobProcessorConfig::from_preset(self.preset.as_deref())?;
+        let processor = JobProcessor::new(&queue, &config, &self.media_root)
+            .with_queued_inputs()
+            .await?;
+
+        let mut job_count = 0;
+        for job in &plan.jobs {
+            match processor.enqueue_checked(job).await? {
+                JobProcessResult::Created => {
+                    processor.log_result(
+                        &job.input_path,
+                        &job.file_type,
+                        &JobProcessResult::Created,
+                    );
+                    job_count += 1;
+                }
+                result => {
+                    processor.log_result(&job.input_path, &job.file_type, &result);
+                    debug!("Skipping planned job for: {:?}", job.input_path);
+                }
+            }
+        }
+
+        info!(
+            "✅ Plan applied. Added {} of {} planned jobs to the queue.",
+            job_count,
+            plan.jobs.len()
+        );
+        Ok(())
+    }
+
+    /// All candidate files across media types
+    fn candidates<'a>(
+        webm_files: &'a [PathBuf],
+        mkv_files: &'a [PathBuf],
+        mp4_files: &'a [PathBuf],
+    ) -> Vec<&'a PathBuf> {
+        webm_files
+            .iter()
+            .chain(mkv_files.iter())
+            .chain(mp4_files.iter())
+            .collect()
+    }
+
+    /// Drop the given paths from every candidate list
+    fn retain_all(
+        webm_files: &mut Vec<PathBuf>,
+        mkv_files: &mut Vec<PathBuf>,
+        mp4_files: &mut Vec<PathBuf>,
+        skipped: &HashSet<PathBuf>,
+    ) {
+        for files in [webm_files, mkv_files, mp4_files] {
+            files.retain(|path| !skipped.contains(path));
+        }
+    }
+
+    /// Find inputs that would produce the same output as another input with a
+    /// preferred extension, e.g. `episode.webm` next to `episode.mkv`
+    fn find_shadowed_inputs(
+        &self,
+        webm_files: &[PathBuf],
+        mkv_files: &[PathBuf],
+    ) -> HashSet<PathBuf> {
+        let rank = |path: &PathBuf| {
+            let ext = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            self.input_ext_priority
+                .iter()
+                .position(|preferred| *preferred == ext)
+                .unwrap_or(self.input_ext_priority.len())
+        };
+
+        let mut by_stem: HashMap<PathBuf, Vec<&PathBuf>> = HashMap::new();
+        for path in webm_files.iter().chain(mkv_files) {
+            by_stem
+                .entry(path.with_extension(""))
+                .or_default()
+                .push(path);
+        }
+
+        let mut shadowed = HashSet::new();
+        for mut inputs in by_stem.into_values().filter(|inputs| inputs.len() > 1) {
+            inputs.sort_by_key(|path| (rank(path), (*path).clone()));
+            let (preferred, rest) = inputs.split_first().unwrap();
+            for path in rest {
+                info!(
+                    "🎞️ Skipping {:?}: {:?} is preferred by --input-ext-priority",
+                    path, preferred
+                );
+                shadowed.insert((*path).clone());
+            }
+        }
+
+        shadowed
+    }
+
+    /// Find files (relative to the media root) that are still being written: either a
+    /// partial-download sibling exists, or the size changes across a short interval
+    async fn find_growing_files(&self, files: &[&PathBuf]) -> HashSet<PathBuf> {
+        let mut growing = HashSet::new();
+        let mut initial_sizes = Vec::new();
+
+        for relative_path in files {
+            let path = self.media_root.join(relative_path);
+            if Self::has_partial_sibling(&path) {
+                growing.insert((*relative_path).clone());
+                continue;
+            }
+            let size = tokio::fs::metadata(&path).await.ok().map(|m| m.len());
+            initial_sizes.push((*relative_path, size));
+        }
+
+        if initial_sizes.is_empty() {
+            return growing;
+        }
+
+        // Stat everything once, wait once, then compare
+        tokio::time::sleep(self.growth_check_interval).await;
+
+        for (relative_path, size) in initial_sizes {
+            let path = self.media_root.join(relative_path);
+            let new_size = tokio::fs::metadata(&path).await.ok().map(|m| m.len());
+            if new_size != size {
+                growing.insert(relative_path.clone());
+            }
+        }
+
+        growing
+    }
+
+    /// Find files (relative to the media root) whose quick hash matches an earlier
+    /// file. Files are compared in sorted order so the first path always wins.
+    async fn find_duplicate_files(&self, files: &[&PathBuf]) -> HashSet<PathBuf> {
+        let mut candidates = files.to_vec();
+        candidates.sort();
+
+        let mut seen: HashMap<(u64, u64), &PathBuf> = HashMap::new();
+        let mut duplicates = HashSet::new();
+
+        for relative_path in candidates {
+            let hash = match quick_hash(&self.media_root.join(relative_path)).await {
+                Ok(hash) => hash,
+                Err(e) => {
+                    warn!("Failed to hash {:?}: {}", relative_path, e);
+                    continue;
+                }
+            };
+
+            match seen.get(&hash) {
+                Some(original) => {
+                    info!(
+                        "🔁 Duplicate input {:?} is identical to {:?}",
+                        relative_path, original
+                    );
+                    duplicates.insert(relative_path.clone());
+                }
+                None => {
+                    seen.insert(hash, relative_path);
+                }
+            }
+        }
+
+        duplicates
+    }
+
+    /// Find files (relative to the media root) whose duration is outside the
+    /// configured range. Files that cannot be probed are kept, and if ffprobe
+    /// itself cannot be run the filter is skipped entirely.
+    async fn find_out_of_range_files(&self, files: &[&PathBuf]) -> HashSet<PathBuf> {
+        let processor = FFmpegProcessor::new(Config::from_env(), false);
+        let mut out_of_range = HashSet::new();
+
+        for relative_path in files {
+            match processor
+                .get_duration(&self.media_root.join(relative_path))
+                .await
+            {
+                Ok(seconds) => {
+                    // Broken or streaming inputs can report `inf` or `NaN`; keep those
+                    let Ok(duration) = Duration::try_from_secs_f64(seconds.max(0.0)) else {
+                        debug!("⏱️ {:?} has no usable duration: {}", relative_path, seconds);
+                        continue;
+                    };
+                    if !duration_in_range(duration, self.min_duration, self.max_duration) {
+                        debug!(
+                            "⏱️ {:?} is {} long, outside the duration range",
+                            relative_path,
+                            humantime::format_duration(Duration::from_secs(duration.as_secs()))
+                        );
+                        out_of_range.insert((*relative_path).clone());
+                    }
+                }
+                Err(e) if e.downcast_ref::<std::io::Error>().is_some() => {
+                    warn!(
+                        "Could not run ffprobe ({}), ignoring --min-duration/--max-duration",
+                        e
+                    );
+                    return HashSet::new();
+                }
+                Err(e) => warn!("Failed to probe duration of {:?}: {}", relative_path, e),
+            }
+        }
+
+        out_of_range
+    }
+
+    /// Check for a downloader's in-progress sibling such as `video.mkv.part`
+    fn has_partial_sibling(path: &Path) -> bool {
+        PARTIAL_DOWNLOAD_SUFFIXES.iter().any(|suffix| {
+            let mut sibling = path.as_os_str().to_owned();
+            sibling.push(suffix);
+            Path::new(&sibling).exists()
+        })
+    }
+}
+
+/// Files that are parts of the same episode (same folder and name apart from the part
+/// number), each group in part order. Groups with a part missing are left alone.
+fn split_episodes(files: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let mut groups: BTreeMap<(&Path, &str), Vec<(u32, &PathBuf)>> = BTreeMap::new();
+    for file in files {
+        let stem = file.file_stem().and_then(|stem| stem.to_str());
+        if let Some((base, part)) = stem.and_then(split_part) {
+            let parent = file.parent().unwrap_or(Path::new(""));
+            groups.entry((parent, base)).or_default().push((part, file));
+        }
+    }
+
+    groups
+        .into_values()
+        .filter(|parts| parts.len() > 1)
+        .filter_map(|mut parts| {
+            parts.sort_by_key(|(part, _)| *part);
+            if !parts
+                .iter()
+                .map(|(part, _)| *part)
+                .eq(1..=parts.len() as u32)
+            {
+                warn!(
+                    "⚠️ Not joining {:?}: its parts are not numbered 1 to {}",
+                    parts[0].1,
+                    parts.len()
+                );
+                return None;
+            }
+            Some(parts.into_iter().map(|(_, file)| file.clone()).collect())
+        })
+        .collect()
+}
+
+/// Check a duration against optional inclusive bounds
+fn duration_in_range(
+    duration: Duration,
+    min_duration: Option<Duration>,
+    max_duration: Option<Duration>,
+) -> bool {
+    min_duration.is_none_or(|min| duration >= min) && max_duration.is_none_or(|max| duration <= max)
+}
+
+/// Cheap content fingerprint: the file size plus a hash of its first and last
+/// megabyte. Good enough to spot copies without reading whole media files.
+pub async fn quick_hash(path: &Path) -> Result<(u64, u64)> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let size = file.metadata().await?.len();
+    let mut hasher = DefaultHasher::new();
+
+    let mut head = Vec::new();
+    (&mut file)
+        .take(QUICK_HASH_SAMPLE)
+        .read_to_end(&mut head)
+        .await?;
+    hasher.write(&head);
+
+    if size > QUICK_HASH_SAMPLE * 2 {
+        file.seek(std::io::SeekFrom::End(-(QUICK_HASH_SAMPLE as i64)))
+            .await?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail).await?;
+        hasher.write(&tail);
+    }
+
+    Ok((size, hasher.finish()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(unix)]
+    use crate::test_support::{self, EnvGuard};
+    use serial_test::serial;
     use std::fs;
     use tempfile::TempDir;
 
+    fn mp4_box(kind: &[u8; 4], payload: usize) -> Vec<u8> {
+        let mut data = ((8 + payload) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend(std::iter::repeat_n(0, payload));
+        data
+    }
+
     #[tokio::test]
     async fn test_scan_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -276,6 +1138,188 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_scan_plan_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("a.mkv"), "a").unwrap();
+        fs::write(media_root.join("b.mkv"), "b").unwrap();
+        // An in-place remux, whose output is its own input
+        let index_last = [
+            mp4_box(b"ftyp", 8),
+            mp4_box(b"mdat", 64),
+            mp4_box(b"moov", 16),
+        ];
+        fs::write(media_root.join("slow.mp4"), index_last.concat()).unwrap();
+        let plan_path = temp_dir.path().join("plan.json");
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_remux_mp4(true)
+            .with_plan_out(Some(plan_path.clone()))
+            .execute()
+            .await
+            .unwrap();
+
+        // Planning enqueues nothing
+        assert_eq!(queue.pending_count().await.unwrap(), 0);
+        let mut plan: ScanPlan =
+            serde_json::from_str(&fs::read_to_string(&plan_path).unwrap()).unwrap();
+        assert_eq!(plan.jobs.len(), 3);
+
+        // Edit the plan: drop a job and change the CRF of another
+        plan.jobs.retain(|job| !job.input_path.ends_with("b.mkv"));
+        plan.jobs
+            .iter_mut()
+            .find(|job| job.input_path.ends_with("a.mkv"))
+            .unwrap()
+            .quality_settings
+            .ffmpeg_crf = "30".to_string();
+        fs::write(&plan_path, serde_json::to_string(&plan).unwrap()).unwrap();
+
+        let apply = ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_plan_in(Some(plan_path));
+        apply.execute().await.unwrap();
+        // Applying again does not duplicate queued jobs
+        apply.execute().await.unwrap();
+
+        let mut queued = queue.pending_jobs().await.unwrap();
+        queued.sort_by(|a, b| a.input_path.cmp(&b.input_path));
+        plan.jobs.sort_by(|a, b| a.input_path.cmp(&b.input_path));
+        assert_eq!(queued, plan.jobs);
+    }
+
+    #[tokio::test]
+    async fn test_scan_remux_mp4() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        let index_last = [
+            mp4_box(b"ftyp", 8),
+            mp4_box(b"mdat", 64),
+            mp4_box(b"moov", 16),
+        ];
+        fs::write(media_root.join("slow.mp4"), index_last.concat()).unwrap();
+        let index_first = [
+            mp4_box(b"ftyp", 8),
+            mp4_box(b"moov", 16),
+            mp4_box(b"mdat", 64),
+        ];
+        fs::write(media_root.join("fast.mp4"), index_first.concat()).unwrap();
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+
+        // MP4s are left alone unless asked for
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(queue.pending_count().await.unwrap(), 0);
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_remux_mp4(true)
+            .execute()
+            .await
+            .unwrap();
+        let jobs = queue.pending_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].file_type, MediaFileType::Mp4);
+        assert_eq!(jobs[0].input_path, media_root.join("slow.mp4"));
+        assert!(jobs[0].replaces_input());
+    }
+
+    #[tokio::test]
+    async fn test_scan_skip_completed() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("done.mkv"), "a").unwrap();
+        fs::write(media_root.join("new.mkv"), "b").unwrap();
+        fs::write(media_root.join("sampled.mkv"), "c").unwrap();
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+
+        // done.mkv was converted before, and its output has since moved elsewhere;
+        // sampled.mkv only had a preview encoded
+        for job in [
+            Job::new(
+                PathBuf::from("done.mkv"),
+                MediaFileType::Mkv,
+                crate::job::QualitySettings::default(),
+                crate::job::PostProcessingSettings::default(),
+                &media_root,
+            ),
+            Job::new(
+                PathBuf::from("sampled.mkv"),
+                MediaFileType::Mkv,
+                crate::job::QualitySettings::default(),
+                crate::job::PostProcessingSettings::default(),
+                &media_root,
+            )
+            .with_sample(30),
+        ] {
+            queue.enqueue_job(&job).await.unwrap();
+            queue
+                .claim_job(None)
+                .await
+                .unwrap()
+                .unwrap()
+                .complete()
+                .await
+                .unwrap();
+        }
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_skip_completed(true)
+            .execute()
+            .await
+            .unwrap();
+
+        let mut inputs: Vec<PathBuf> = queue
+            .pending_jobs()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|job| job.input_path)
+            .collect();
+        inputs.sort();
+        assert_eq!(
+            inputs,
+            vec![media_root.join("new.mkv"), media_root.join("sampled.mkv")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_input_ext_priority() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("episode.webm"), "a").unwrap();
+        fs::write(media_root.join("episode.vtt"), "WEBVTT").unwrap();
+        fs::write(media_root.join("episode.mkv"), "b").unwrap();
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .execute()
+            .await
+            .unwrap();
+
+        let jobs = queue.pending_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].input_path, media_root.join("episode.mkv"));
+
+        queue.clean().await.unwrap();
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_input_ext_priority(vec!["webm".to_string(), "mkv".to_string()])
+            .execute()
+            .await
+            .unwrap();
+
+        let jobs = queue.pending_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].input_path, media_root.join("episode.webm"));
+    }
+
     #[tokio::test]
     async fn test_scan_nonexistent_directory() {
         let scan_cmd = ScanCommand::new(
@@ -285,7 +1329,10 @@ mod tests {
         );
 
         let result = scan_cmd.execute().await;
-        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<PlexifyError>(),
+            Some(PlexifyError::MediaRootMissing(_))
+        ));
     }
 
     #[tokio::test]
@@ -327,11 +1374,15 @@ mod tests {
 
         // Create media files in different subdirectories
         fs::write(media_root.join("show1/season1/episode1.webm"), "").unwrap();
-        fs::write(media_root.join("show1/season1/episode1.vtt"), "").unwrap();
+        fs::write(media_root.join("show1/season1/episode1.vtt"), "WEBVTT\n").unwrap();
         fs::write(media_root.join("show2/season2/episode2.mkv"), "").unwrap();
         fs::write(media_root.join("movies/movie1.mkv"), "").unwrap();
         fs::write(media_root.join("very/deep/nested/folder/deep.webm"), "").unwrap();
-        fs::write(media_root.join("very/deep/nested/folder/deep.vtt"), "").unwrap();
+        fs::write(
+            media_root.join("very/deep/nested/folder/deep.vtt"),
+            "WEBVTT\n",
+        )
+        .unwrap();
 
         let scan_cmd = ScanCommand::new(
             media_root.to_path_buf(),
@@ -411,7 +1462,7 @@ mod tests {
 
         // WebM files (need matching VTT)
         fs::write(media_root.join("webm_folder/video1.webm"), "").unwrap();
-        fs::write(media_root.join("webm_folder/video1.vtt"), "").unwrap();
+        fs::write(media_root.join("webm_folder/video1.vtt"), "WEBVTT\n").unwrap();
         fs::write(media_root.join("webm_folder/video2.webm"), "").unwrap(); // No VTT - should be skipped
 
         // MKV files
@@ -420,7 +1471,7 @@ mod tests {
 
         // Mixed folder
         fs::write(media_root.join("mixed_folder/mixed1.webm"), "").unwrap();
-        fs::write(media_root.join("mixed_folder/mixed1.vtt"), "").unwrap();
+        fs::write(media_root.join("mixed_folder/mixed1.vtt"), "WEBVTT\n").unwrap();
         fs::write(media_root.join("mixed_folder/mixed2.mkv"), "").unwrap();
 
         let scan_cmd = ScanCommand::new(
@@ -443,6 +1494,490 @@ mod tests {
         assert_eq!(job_count, 5);
     }
 
+    #[tokio::test]
+    async fn test_scan_skip_growing() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+
+        fs::write(media_root.join("stable.mkv"), "complete").unwrap();
+        fs::write(media_root.join("growing.mkv"), "partial").unwrap();
+        fs::write(media_root.join("downloading.mkv"), "partial").unwrap();
+        fs::write(media_root.join("downloading.mkv.part"), "").unwrap();
+
+        // Keep appending to growing.mkv while the scan checks sizes
+        let growing_path = media_root.join("growing.mkv");
+        let writer = tokio::spawn(async move {
+            for _ in 0..20 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                let mut content = fs::read(&growing_path).unwrap();
+                content.extend_from_slice(b"more");
+                fs::write(&growing_path, content).unwrap();
+            }
+        });
+
+        let mut scan_cmd =
+            ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+                .with_skip_growing(true);
+        scan_cmd.growth_check_interval = Duration::from_millis(200);
+
+        scan_cmd.execute().await.unwrap();
+        writer.await.unwrap();
+
+        let queue_dir = temp_dir.path().join("_queue");
+        let inputs: Vec<String> = fs::read_dir(&queue_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension() == Some("job".as_ref()))
+            .map(|path| {
+                let job: crate::job::Job =
+                    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+                job.input_path.to_string_lossy().to_string()
+            })
+            .collect();
+
+        assert_eq!(inputs.len(), 1);
+        assert!(inputs[0].ends_with("stable.mkv"));
+    }
+
+    #[tokio::test]
+    async fn test_quick_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.mkv");
+        let b = temp_dir.path().join("b.mkv");
+        let c = temp_dir.path().join("c.mkv");
+        fs::write(&a, "same content").unwrap();
+        fs::write(&b, "same content").unwrap();
+        fs::write(&c, "diff content").unwrap();
+
+        assert_eq!(quick_hash(&a).await.unwrap(), quick_hash(&b).await.unwrap());
+        assert_ne!(quick_hash(&a).await.unwrap(), quick_hash(&c).await.unwrap());
+
+        // Large files also hash their tail
+        let large = vec![7u8; (QUICK_HASH_SAMPLE * 3) as usize];
+        let mut changed_tail = large.clone();
+        *changed_tail.last_mut().unwrap() = 8;
+        fs::write(&a, &large).unwrap();
+        fs::write(&b, &changed_tail).unwrap();
+        assert_ne!(quick_hash(&a).await.unwrap(), quick_hash(&b).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_scan_dedupe() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(media_root.join("Movies/Heat (1995)")).unwrap();
+        fs::create_dir_all(media_root.join("Downloads")).unwrap();
+
+        fs::write(media_root.join("Movies/Heat (1995)/Heat.mkv"), "heat").unwrap();
+        fs::write(media_root.join("Downloads/Heat copy.mkv"), "heat").unwrap();
+        fs::write(media_root.join("Downloads/Other.mkv"), "other").unwrap();
+
+        let count_jobs = |queue_root: &Path| {
+            fs::read_dir(queue_root.join("_queue"))
+                .unwrap()
+                .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("job".as_ref()))
+                .count()
+        };
+
+        let work_root = temp_dir.path().join("work");
+        ScanCommand::new(media_root.clone(), work_root.clone(), None)
+            .with_dedupe(true)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(count_jobs(&work_root), 2);
+
+        // Without --dedupe every file gets a job
+        let work_root = temp_dir.path().join("work_all");
+        ScanCommand::new(media_root.clone(), work_root.clone(), None)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(count_jobs(&work_root), 3);
+    }
+
+    #[tokio::test]
+    async fn test_scan_output_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(media_root.join("Movies")).unwrap();
+        fs::write(media_root.join("Movies/done.mkv"), "").unwrap();
+        fs::write(media_root.join("Movies/todo.mkv"), "").unwrap();
+        // Already converted into the templated location
+        fs::create_dir_all(media_root.join("Transcoded/Movies")).unwrap();
+        fs::write(media_root.join("Transcoded/Movies/done.mp4"), "").unwrap();
+
+        let template =
+            OutputTemplate::parse("{root}/Transcoded/{relative_dir}/{stem}.mp4").unwrap();
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_output_template(Some(template))
+            .execute()
+            .await
+            .unwrap();
+
+        let jobs: Vec<crate::job::Job> = fs::read_dir(temp_dir.path().join("_queue"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension() == Some("job".as_ref()))
+            .map(|path| serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(
+            jobs[0].output_path,
+            media_root.join("Transcoded/Movies/todo.mp4")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_combine_parts() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        let show_dir = media_root.join("Show/Season 01");
+        fs::create_dir_all(&show_dir).unwrap();
+        for name in [
+            "Show S01E01 part10.mkv",
+            "Show S01E01 part2.mkv",
+            "Show S01E01 part1.mkv",
+            "Show S01E02 - Pt 1.mkv",
+            "Show S01E02 - Pt 3.mkv",
+            "Show S01E03.mkv",
+        ] {
+            fs::write(show_dir.join(name), "").unwrap();
+        }
+        for part in 3..=9 {
+            fs::write(show_dir.join(format!("Show S01E01 part{part}.mkv")), "").unwrap();
+        }
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_combine_parts(true)
+            .execute()
+            .await
+            .unwrap();
+
+        let mut jobs: Vec<Job> = fs::read_dir(temp_dir.path().join("_queue"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension() == Some("job".as_ref()))
+            .map(|path| serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+        jobs.sort_by(|a, b| a.input_path.cmp(&b.input_path));
+
+        // E01's ten parts become one job in numeric order; E02 is missing part 2, so
+        // its parts are converted separately like E03
+        assert_eq!(jobs.len(), 4);
+        let joined = jobs
+            .iter()
+            .find(|job| !job.extra_inputs.is_empty())
+            .unwrap();
+        assert_eq!(joined.input_path, show_dir.join("Show S01E01 part1.mkv"));
+        let parts: Vec<PathBuf> = (2..=10)
+            .map(|part| show_dir.join(format!("Show S01E01 part{part}.mkv")))
+            .collect();
+        assert_eq!(joined.extra_inputs, parts);
+        assert_eq!(joined.output_path, show_dir.join("Show S01E01.mp4"));
+        assert!(jobs
+            .iter()
+            .any(|job| job.output_path == show_dir.join("Show S01E02 - Pt 3.mp4")));
+    }
+
+    #[tokio::test]
+    async fn test_scan_match_glob() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        for path in [
+            "Series/Breaking Bad/Season 01/e1.mkv",
+            "Series/Breaking Bad/Season 01/e2.mkv",
+            "Series/Breaking Bad/Extras/ignored.mkv",
+            "Series/Better Call Saul/Season 01/e1.mkv",
+            "Movies/movie.mkv",
+        ] {
+            let path = media_root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        // .plexifyignore still applies inside the matched subset
+        fs::write(
+            media_root.join("Series/Breaking Bad/.plexifyignore"),
+            "Extras/",
+        )
+        .unwrap();
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_match(Some(glob::Pattern::new("Series/Breaking Bad/**").unwrap()))
+            .execute()
+            .await
+            .unwrap();
+
+        let mut inputs: Vec<PathBuf> = fs::read_dir(temp_dir.path().join("_queue"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension() == Some("job".as_ref()))
+            .map(|path| {
+                let job: crate::job::Job =
+                    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+                job.input_path
+            })
+            .collect();
+        inputs.sort();
+
+        assert_eq!(
+            inputs,
+            vec![
+                media_root.join("Series/Breaking Bad/Season 01/e1.mkv"),
+                media_root.join("Series/Breaking Bad/Season 01/e2.mkv"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duration_in_range() {
+        let min = Some(Duration::from_secs(60));
+        let max = Some(Duration::from_secs(3 * 60 * 60));
+
+        assert!(!duration_in_range(Duration::from_secs(10), min, max));
+        assert!(duration_in_range(
+            Duration::from_secs(2 * 60 * 60),
+            min,
+            max
+        ));
+        assert!(!duration_in_range(
+            Duration::from_secs(4 * 60 * 60),
+            min,
+            max
+        ));
+        // Bounds are inclusive and either end can be left open
+        assert!(duration_in_range(Duration::from_secs(60), min, max));
+        assert!(duration_in_range(
+            Duration::from_secs(3 * 60 * 60),
+            min,
+            max
+        ));
+        assert!(duration_in_range(Duration::from_secs(10), None, max));
+        assert!(duration_in_range(
+            Duration::from_secs(5 * 60 * 60),
+            min,
+            None
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_scan_duration_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("trailer.mkv"), "").unwrap();
+        fs::write(media_root.join("movie.mkv"), "").unwrap();
+        fs::write(media_root.join("stream.mkv"), "").unwrap();
+
+        // Trailers are 10 seconds, streams have no length, everything else is two hours
+        let _ffprobe = test_support::fake_tool(
+            "PLEXIFY_FFPROBE_PATH",
+            temp_dir.path(),
+            "ffprobe",
+            "case \"$*\" in *trailer*) echo 10.0;; *stream*) echo inf;; *) echo 7200.0;; esac\n",
+        );
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_duration_range(Some(Duration::from_secs(60)), None)
+            .execute()
+            .await
+            .unwrap();
+
+        let queued: Vec<String> = fs::read_dir(temp_dir.path().join("_queue"))
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        // A file without a usable duration is kept, like one that cannot be probed
+        assert_eq!(queued.len(), 2);
+        assert!(queued.iter().any(|job| job.contains("movie.mkv")));
+        assert!(queued.iter().any(|job| job.contains("stream.mkv")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_scan_skip_if_codec() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        // MP4s whose index is at the end, so they would be remuxed
+        let index_last = [
+            mp4_box(b"ftyp", 8),
+            mp4_box(b"mdat", 64),
+            mp4_box(b"moov", 16),
+        ]
+        .concat();
+        fs::write(media_root.join("already-h264.mp4"), &index_last).unwrap();
+        fs::write(media_root.join("needs-work.mp4"), &index_last).unwrap();
+        // Still needs converting to MP4
+        fs::write(media_root.join("also-h264.mkv"), "").unwrap();
+
+        let _ffprobe = test_support::fake_tool(
+            "PLEXIFY_FFPROBE_PATH",
+            temp_dir.path(),
+            "ffprobe",
+            "case \"$*\" in *h264*) echo h264;; *) echo hevc;; esac\n",
+        );
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_skip_if_codec(vec!["H264".to_string()])
+            .with_remux_mp4(true)
+            .execute()
+            .await
+            .unwrap();
+
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        let mut queued: Vec<PathBuf> = queue
+            .pending_jobs()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|job| job.input_path)
+            .collect();
+        queued.sort();
+        assert_eq!(
+            queued,
+            vec![
+                media_root.join("also-h264.mkv"),
+                media_root.join("needs-work.mp4")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_scan_convert_subs_leaves_video_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("movie.mp4"), "video").unwrap();
+        fs::write(media_root.join("movie.srt"), "subs").unwrap();
+        // Already converted
+        fs::write(media_root.join("other.srt"), "subs").unwrap();
+        fs::write(media_root.join("other.vtt"), "subs").unwrap();
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_convert_subs(Some(SubtitleFormat::Vtt))
+            .execute()
+            .await
+            .unwrap();
+
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        let jobs = queue.pending_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].file_type, MediaFileType::Subtitle);
+        assert_eq!(jobs[0].input_path, media_root.join("movie.srt"));
+        assert_eq!(jobs[0].output_path, media_root.join("movie.vtt"));
+        assert_eq!(
+            fs::read_to_string(media_root.join("movie.mp4")).unwrap(),
+            "video"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_records_input_checksum() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("movie.mkv"), "123456789").unwrap();
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_checksum(ChecksumAlgorithm::Crc32)
+            .execute()
+            .await
+            .unwrap();
+
+        let queue = JobQueue::new(media_root, temp_dir.path().to_path_buf());
+        let jobs = queue.pending_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        let checksum = jobs[0].input_checksum.as_ref().unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::Crc32);
+        assert_eq!(checksum.value, "cbf43926");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_scan_duration_filters_without_ffprobe() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("trailer.mkv"), "").unwrap();
+        let _ffprobe = EnvGuard::set("PLEXIFY_FFPROBE_PATH", "/nonexistent/ffprobe");
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_duration_range(Some(Duration::from_secs(60)), None)
+            .execute()
+            .await
+            .unwrap();
+
+        // The filter is skipped rather than dropping everything
+        assert_eq!(
+            fs::read_dir(temp_dir.path().join("_queue"))
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_exclude_globs() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        for path in ["keep/a.mkv", "keep/b.mkv", "skip/c.mkv", "samples/d.mkv"] {
+            let path = media_root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None)
+            .with_exclude(vec![
+                glob::Pattern::new("skip/**").unwrap(),
+                glob::Pattern::new("samples/*.mkv").unwrap(),
+            ])
+            .execute()
+            .await
+            .unwrap();
+
+        let job_count = fs::read_dir(temp_dir.path().join("_queue"))
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("job".as_ref()))
+            .count();
+        assert_eq!(job_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_scan_negation_inside_ignored_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        fs::create_dir_all(media_root.join("old")).unwrap();
+
+        fs::write(media_root.join(".plexifyignore"), "old/\n!old/keep.mkv").unwrap();
+        fs::write(media_root.join("old/keep.mkv"), "").unwrap();
+        fs::write(media_root.join("old/drop.mkv"), "").unwrap();
+
+        let scan_cmd = ScanCommand::new(media_root.clone(), temp_dir.path().to_path_buf(), None);
+        scan_cmd.execute().await.unwrap();
+
+        let queue_dir = temp_dir.path().join("_queue");
+        let inputs: Vec<PathBuf> = fs::read_dir(&queue_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension() == Some("job".as_ref()))
+            .map(|path| {
+                let job: crate::job::Job =
+                    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+                job.input_path
+            })
+            .collect();
+
+        assert_eq!(inputs, vec![media_root.join("old/keep.mkv")]);
+    }
+
     #[tokio::test]
     async fn test_scan_with_plexifyignore() {
         let temp_dir = TempDir::new().unwrap();
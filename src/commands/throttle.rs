@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+/// Default number of filesystem operations between throttle pauses
+pub const DEFAULT_THROTTLE_BATCH: usize = 100;
+
+/// Inserts a short pause every `batch_size` filesystem operations to keep IO
+/// pressure low on network shares. A zero delay disables throttling.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    delay: Duration,
+    batch_size: usize,
+    operations: usize,
+}
+
+impl Throttle {
+    pub fn new(delay: Duration, batch_size: usize) -> Self {
+        Self {
+            delay,
+            batch_size: batch_size.max(1),
+            operations: 0,
+        }
+    }
+
+    /// No throttling at all
+    pub fn disabled() -> Self {
+        Self::new(Duration::ZERO, DEFAULT_THROTTLE_BATCH)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.delay.is_zero()
+    }
+
+    /// Count one operation and report whether a pause is due
+    fn record(&mut self) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+        self.operations += 1;
+        self.operations.is_multiple_of(self.batch_size)
+    }
+
+    /// Count one operation, sleeping if this completes a batch
+    pub async fn tick(&mut self) {
+        if self.record() {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_triggers_at_batch_interval() {
+        let mut throttle = Throttle::new(Duration::from_millis(5), 3);
+        let pauses: Vec<bool> = (0..7).map(|_| throttle.record()).collect();
+        assert_eq!(pauses, vec![false, false, true, false, false, true, false]);
+    }
+
+    #[test]
+    fn test_throttle_disabled_never_pauses() {
+        let mut throttle = Throttle::disabled();
+        assert!(!throttle.is_enabled());
+        assert!((0..1000).all(|_| !throttle.record()));
+
+        // A zero batch size is treated as pausing after every operation
+        let mut throttle = Throttle::new(Duration::from_millis(1), 0);
+        assert!(throttle.record());
+        assert!(throttle.record());
+    }
+
+    #[tokio::test]
+    async fn test_throttle_tick_sleeps() {
+        let mut throttle = Throttle::new(Duration::from_millis(20), 2);
+        let start = std::time::Instant::now();
+        for _ in 0..4 {
+            throttle.tick().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Folder in the work directory that undo logs are written to
+const UNDO_DIR: &str = "_undo";
+
+/// One rename done by `validate --fix`, with what the target looked like right
+/// after it so later changes can be noticed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UndoMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl UndoMove {
+    /// Record a rename that has just happened
+    pub fn record(from: &Path, to: &Path) -> Self {
+        let metadata = std::fs::metadata(to).ok();
+        Self {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+            size: metadata.as_ref().map_or(0, |m| m.len()),
+            modified: metadata.and_then(|m| m.modified().ok()),
+        }
+    }
+
+    /// Whether the target is still the file the fix left behind
+    fn target_unchanged(&self) -> bool {
+        match std::fs::metadata(&self.to) {
+            Ok(metadata) => {
+                metadata.len() == self.size && metadata.modified().ok() == self.modified
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// The renames of one `validate --fix` run, in the order they were made
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UndoLog {
+    pub moves: Vec<UndoMove>,
+}
+
+impl UndoLog {
+    /// Write the log to `<work_root>/_undo/validate-<timestamp>.json`
+    pub fn write(&self, work_root: &Path) -> Result<PathBuf> {
+        let dir = work_root.join(UNDO_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = dir.join(format!("validate-{millis}.json"));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Could not read undo log {:?}: {}", path, e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Could not parse undo log {:?}: {}", path, e))
+    }
+}
+
+/// Outcome of replaying an undo log
+#[derive(Debug, Default, PartialEq)]
+pub struct UndoReport {
+    pub restored: usize,
+    /// Moves left alone because the file changed or its old path is taken
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Command to reverse the renames recorded by `validate --fix`
+pub struct UndoCommand {
+    log_path: PathBuf,
+}
+
+impl UndoCommand {
+    pub fn new(log_path: PathBuf) -> Self {
+        Self { log_path }
+    }
+
+    pub async fn execute(&self) -> Result<UndoReport> {
+        let log = UndoLog::load(&self.log_path)?;
+        let mut report = UndoReport::default();
+
+        // Newest first, so chained renames unwind in order
+        for undo in log.moves.iter().rev() {
+            match restore(undo) {
+                Ok(()) => {
+                    info!("↩️ Restored {:?} -> {:?}", undo.to, undo.from);
+                    report.restored += 1;
+                }
+                Err(e) => {
+                    warn!("⚠️ Skipping {:?}: {}", undo.to, e);
+                    report.skipped.push(undo.to.clone());
+                }
+            }
+        }
+
+        info!(
+            "↩️ Restored {} file(s), skipped {}",
+            report.restored,
+            report.skipped.len()
+        );
+        if report.skipped.is_empty() {
+            std::fs::remove_file(&self.log_path)?;
+        }
+        Ok(report)
+    }
+}
+
+/// Move one file back, unless it changed since the fix or its old path is in use
+fn restore(undo: &UndoMove) -> Result<()> {
+    if !undo.to.exists() {
+        return Err(anyhow!("it no longer exists"));
+    }
+    if !undo.target_unchanged() {
+        return Err(anyhow!("it was modified after the fix"));
+    }
+    // A case-only rename looks like an existing source on case-insensitive filesystems
+    let case_only =
+        undo.from.to_string_lossy().to_lowercase() == undo.to.to_string_lossy().to_lowercase();
+    if !case_only && undo.from.exists() {
+        return Err(anyhow!("{:?} exists again", undo.from));
+    }
+
+    if let Some(parent) = undo.from.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if case_only {
+        let mut temp_name = undo.to.as_os_str().to_owned();
+        temp_name.push(".plexify-rename");
+        let temp = PathBuf::from(temp_name);
+        std::fs::rename(&undo.to, &temp)?;
+        std::fs::rename(&temp, &undo.from)?;
+    } else {
+        std::fs::rename(&undo.to, &undo.from)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::validate::{ShowLayout, ValidateCommand};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_undo_restores_layout_after_fix() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        let work_root = temp_dir.path().join("work");
+        let show_dir = media_root.join("Series/Show");
+        fs::create_dir_all(show_dir.join("Season 03")).unwrap();
+        // Two loose episodes (one with a sidecar) and one with an uppercase extension
+        let episode = show_dir.join("Show S01E01 Pilot.mkv");
+        let subtitle = show_dir.join("Show S01E01 Pilot.en.srt");
+        let second = show_dir.join("Show S02E01 Return.mkv");
+        let uppercase = show_dir.join("Season 03/Show S03E01 Finale.MKV");
+        fs::write(&episode, "video").unwrap();
+        fs::write(&subtitle, "subs").unwrap();
+        fs::write(&second, "video").unwrap();
+        fs::write(&uppercase, "video").unwrap();
+
+        let report = ValidateCommand::new(media_root.clone())
+            .with_layout(Some(ShowLayout::Seasons))
+            .with_fix(true)
+            .with_work_root(Some(work_root.clone()))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(report.fixed_files.len(), 3);
+        assert!(!episode.exists());
+        assert!(!second.exists());
+        assert!(show_dir.join("Season 03/Show S03E01 Finale.mkv").exists());
+
+        let logs: Vec<PathBuf> = fs::read_dir(work_root.join(UNDO_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(logs.len(), 1);
+        let log = UndoLog::load(&logs[0]).unwrap();
+
+        // Someone edits one of the moved files before undoing
+        let edited = log
+            .moves
+            .iter()
+            .find(|undo| undo.from == second)
+            .unwrap()
+            .to
+            .clone();
+        fs::write(&edited, "re-encoded elsewhere").unwrap();
+
+        let report = UndoCommand::new(logs[0].clone()).execute().await.unwrap();
+        assert_eq!(report.restored, log.moves.len() - 1);
+        assert_eq!(report.skipped, vec![edited.clone()]);
+        assert_eq!(fs::read_to_string(&episode).unwrap(), "video");
+        assert_eq!(fs::read_to_string(&subtitle).unwrap(), "subs");
+        let season_03: Vec<_> = fs::read_dir(show_dir.join("Season 03"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(season_03, ["Show S03E01 Finale.MKV"]);
+        assert!(!second.exists());
+        assert!(edited.exists());
+        // The log is kept while something is left to sort out
+        assert!(logs[0].exists());
+    }
+}
@@ -1,16 +1,27 @@
 use anyhow::{anyhow, Result};
-use indicatif::{ProgressBar, ProgressStyle};
+use clap::ValueEnum;
+use console::{style, StyledObject};
+use indicatif::ProgressStyle;
 use rayon::prelude::*;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
+
+use super::path_filter::PathFilter;
+use super::prune::prune_empty_dirs;
+use super::rename_template::{EpisodeInfo, RenameTemplate};
+use super::throttle::Throttle;
+use super::undo::{UndoLog, UndoMove};
 use walkdir::WalkDir;
 
+use crate::error::PlexifyError;
 use crate::ignore::IgnoreFilter;
+use crate::output;
 
 /// Media file extensions that should be validated
 const MEDIA_EXTENSIONS: &[&str] = &["mkv", "mp4", "avi", "webm", "mov", "m4v"];
@@ -29,6 +40,18 @@ const DIRECTORY_MAPPING: &[(&str, ContentType)] = &[
     ("Movies", ContentType::Movie),
 ];
 
+/// The directory holding the nearest `Anime`, `Series` or `Movies` folder above a file
+fn infer_library_root(file: &Path) -> Option<PathBuf> {
+    file.ancestors().skip(1).find_map(|dir| {
+        let name = dir.file_name()?.to_str()?;
+        DIRECTORY_MAPPING
+            .iter()
+            .any(|(content_dir, _)| *content_dir == name)
+            .then(|| dir.parent().map(Path::to_path_buf))
+            .flatten()
+    })
+}
+
 /// Naming scheme patterns for different content types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NamingPatterns {
@@ -36,15 +59,13 @@ pub struct NamingPatterns {
     pub patterns: Vec<NamingPattern>,
 }
 
-/// Unified naming pattern with precompiled regex
+/// Unified naming pattern
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NamingPattern {
     pub description: String,
     pub pattern: String,
     pub example: String,
     pub content_type: ContentType,
-    #[serde(skip)]
-    pub compiled_regex: Option<Regex>,
 }
 
 /// Validation issue found in a media file
@@ -64,6 +85,77 @@ pub enum IssueType {
     DirectoryStructure,
     FileExtension,
     UnknownContentType,
+    /// A well-named episode in the other layout than the `--layout` target
+    ShowLayout,
+    /// An episode whose `SxxExx` code names another season than its folder
+    SeasonMismatch,
+}
+
+impl IssueType {
+    /// Human-readable name used in reports
+    pub fn label(&self) -> &'static str {
+        match self {
+            IssueType::ShowNaming => "Show Naming",
+            IssueType::MovieNaming => "Movie Naming",
+            IssueType::DirectoryStructure => "Directory Structure",
+            IssueType::FileExtension => "File Extension",
+            IssueType::UnknownContentType => "Unknown Content Type",
+            IssueType::ShowLayout => "Show Layout",
+            IssueType::SeasonMismatch => "Season Mismatch",
+        }
+    }
+
+    /// Color the issue marker by severity: misplaced files are errors, naming
+    /// problems are warnings, and cosmetic fixes are minor
+    fn styled_marker(&self) -> StyledObject<&'static str> {
+        match self {
+            IssueType::DirectoryStructure
+            | IssueType::UnknownContentType
+            | IssueType::SeasonMismatch => style("❌").red(),
+            IssueType::ShowNaming | IssueType::MovieNaming | IssueType::ShowLayout => {
+                style("⚠️").yellow()
+            }
+            IssueType::FileExtension => style("•").cyan(),
+        }
+    }
+}
+
+/// How to group issues in the validation report
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum GroupBy {
+    /// By top-level content directory (Movies, Series, Anime)
+    Type,
+    /// By content directory and show or movie folder
+    Show,
+    /// By the directory containing the file
+    Dir,
+}
+
+/// Folder layout that shows are normalized to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ShowLayout {
+    /// Episodes in `Show/Season XX/` folders (default)
+    #[default]
+    Seasons,
+    /// Episodes of single-season shows directly in `Show/`
+    Flat,
+}
+
+/// Where an episode belongs under the target layout
+enum LayoutCheck {
+    /// The file is where the layout wants it
+    Conforms,
+    /// The file should move to this path (relative to the library root)
+    Move(String, &'static str),
+    /// Not a well-named episode, so the naming checks decide
+    NotApplicable,
+}
+
+/// A named group of issues in the validation report
+#[derive(Debug)]
+pub struct IssueGroup<'a> {
+    pub name: String,
+    pub issues: Vec<&'a ValidationIssue>,
 }
 
 /// Validation report containing all issues found
@@ -74,6 +166,36 @@ pub struct ValidationReport {
     pub patterns_used: NamingPatterns,
     pub scan_path: PathBuf,
     pub validation_time: Duration,
+    /// Empty directories removed after validation (with `--prune-empty`)
+    #[serde(default)]
+    pub pruned_dirs: Vec<PathBuf>,
+    /// Files renamed to fix their extension case (with `--fix`)
+    #[serde(default)]
+    pub fixed_files: Vec<PathBuf>,
+}
+
+impl ValidationReport {
+    /// Issues still present once `--fix` renames are taken into account
+    pub fn remaining_issues(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| {
+                let fixed = match issue.issue_type {
+                    IssueType::FileExtension => {
+                        normalize_extension(&issue.file_path.to_string_lossy())
+                            .is_some_and(|target| self.fixed_files.contains(&PathBuf::from(target)))
+                    }
+                    IssueType::ShowLayout | IssueType::SeasonMismatch => {
+                        issue.suggested_path.as_ref().is_some_and(|target| {
+                            self.fixed_files.iter().any(|file| file.ends_with(target))
+                        })
+                    }
+                    _ => false,
+                };
+                !fixed
+            })
+            .count()
+    }
 }
 
 /// Command to validate Plex naming scheme conformity
@@ -81,6 +203,17 @@ pub struct ValidateCommand {
     media_root: PathBuf,
     patterns: NamingPatterns,
     compiled_patterns: Vec<CompiledPattern>,
+    prune_empty: bool,
+    fix: bool,
+    throttle: Throttle,
+    path_filter: PathFilter,
+    group_by: Option<GroupBy>,
+    summary_only: bool,
+    input_root: Option<PathBuf>,
+    keep_season_suffix: bool,
+    layout: Option<ShowLayout>,
+    rename_template: Option<RenameTemplate>,
+    work_root: Option<PathBuf>,
 }
 
 /// Internal structure for compiled regex patterns
@@ -96,39 +229,34 @@ impl Default for NamingPatterns {
                 // Anime patterns (shows)
                 NamingPattern {
                     description: "Standard Anime format".to_string(),
-                    pattern: r"^Anime/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ - s\d{2}e\d{2} - [^/]+\.\w+$".to_string(),
+                    pattern: r"^Anime/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ - s\d{2}e\d{2}(?:-e\d{2})? - [^/]+\.\w+$".to_string(),
                     example: "Anime/Attack on Titan/Season 01/Attack on Titan - s01e01 - To You, in 2000 Years.mkv".to_string(),
                     content_type: ContentType::Series,
-                    compiled_regex: None,
                 },
                 NamingPattern {
                     description: "Alternative Anime format".to_string(),
-                    pattern: r"^Anime/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ S\d{2}E\d{2} [^/]+\.\w+$".to_string(),
+                    pattern: r"^Anime/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ S\d{2}E\d{2}(?:-E\d{2})? [^/]+\.\w+$".to_string(),
                     example: "Anime/Attack on Titan/Season 01/Attack on Titan S01E01 To You, in 2000 Years.mkv".to_string(),
                     content_type: ContentType::Series,
-                    compiled_regex: None,
                 },
                 // Series patterns (shows)  
                 NamingPattern {
                     description: "Standard Series format".to_string(),
-                    pattern: r"^Series/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ - s\d{2}e\d{2} - [^/]+\.\w+$".to_string(),
+                    pattern: r"^Series/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ - s\d{2}e\d{2}(?:-e\d{2})? - [^/]+\.\w+$".to_string(),
                     example: "Series/Breaking Bad/Season 01/Breaking Bad - s01e01 - Pilot.mkv".to_string(),
                     content_type: ContentType::Series,
-                    compiled_regex: None,
                 },
                 NamingPattern {
                     description: "Alternative Series format".to_string(),
-                    pattern: r"^Series/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ S\d{2}E\d{2} [^/]+\.\w+$".to_string(),
+                    pattern: r"^Series/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/[^/]+ S\d{2}E\d{2}(?:-E\d{2})? [^/]+\.\w+$".to_string(),
                     example: "Series/Breaking Bad (2008) {tvdb-296861}/Season 01/Breaking Bad S01E01 Pilot.mkv".to_string(),
                     content_type: ContentType::Series,
-                    compiled_regex: None,
                 },
                 NamingPattern {
                     description: "Simple Series format".to_string(),
-                    pattern: r"^Series/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/S\d{2}E\d{2} - [^/]+\.\w+$".to_string(),
+                    pattern: r"^Series/[^/]+(?:\s*\{tvdb-\d+\})?/Season \d{2}(?:\s*-[^/]*)*/S\d{2}E\d{2}(?:-E\d{2})? - [^/]+\.\w+$".to_string(),
                     example: "Series/Breaking Bad/Season 01/S01E01 - Pilot.mkv".to_string(),
                     content_type: ContentType::Series,
-                    compiled_regex: None,
                 },
                 // Movie patterns
                 NamingPattern {
@@ -136,14 +264,12 @@ impl Default for NamingPatterns {
                     pattern: r"^Movies/[^/]+ \(\d{4}\)/[^/]+ \(\d{4}\)\.\w+$".to_string(),
                     example: "Movies/The Dark Knight (2008)/The Dark Knight (2008).mkv".to_string(),
                     content_type: ContentType::Movie,
-                    compiled_regex: None,
                 },
                 NamingPattern {
                     description: "Collection Movie format".to_string(),
                     pattern: r"^Movies/[^/]+ Collection/[^/]+ \(\d{4}\)\.\w+$".to_string(),
                     example: "Movies/Marvel Cinematic Universe Collection/Iron Man (2008).mkv".to_string(),
                     content_type: ContentType::Movie,
-                    compiled_regex: None,
                 },
             ],
         }
@@ -160,9 +286,97 @@ impl ValidateCommand {
             media_root,
             patterns,
             compiled_patterns,
+            prune_empty: false,
+            fix: false,
+            throttle: Throttle::disabled(),
+            path_filter: PathFilter::new(),
+            group_by: None,
+            summary_only: false,
+            input_root: None,
+            keep_season_suffix: false,
+            layout: None,
+            rename_template: None,
+            work_root: None,
         }
     }
 
+    /// Write an undo log of the `--fix` renames to `<work_root>/_undo/`
+    pub fn with_work_root(mut self, work_root: Option<PathBuf>) -> Self {
+        self.work_root = work_root;
+        self
+    }
+
+    /// Library root that paths are matched relative to, for validating a subtree
+    /// (e.g. one show) or a single file
+    pub fn with_input_root(mut self, input_root: Option<PathBuf>) -> Self {
+        self.input_root = input_root;
+        self
+    }
+
+    /// Group issues in the printed report instead of listing them in scan order
+    pub fn with_group_by(mut self, group_by: Option<GroupBy>) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Print only the counts and the per-type issue summary, not every file
+    pub fn with_summary_only(mut self, summary_only: bool) -> Self {
+        self.summary_only = summary_only;
+        self
+    }
+
+    /// Only process files whose path relative to the media root matches this glob
+    pub fn with_match(mut self, pattern: Option<glob::Pattern>) -> Self {
+        self.path_filter = self.path_filter.with_include(pattern);
+        self
+    }
+
+    /// Skip files whose path relative to the media root matches any of these globs
+    pub fn with_exclude(mut self, patterns: Vec<glob::Pattern>) -> Self {
+        self.path_filter = self.path_filter.with_exclude(patterns);
+        self
+    }
+
+    /// Pause for `delay` every `batch_size` filesystem entries while walking
+    pub fn with_throttle(mut self, delay: Duration, batch_size: usize) -> Self {
+        self.throttle = Throttle::new(delay, batch_size);
+        self
+    }
+
+    /// Keep a descriptive season folder suffix (`Season 11 - Arc Name`) in suggested
+    /// paths instead of shortening the folder to `Season 11`
+    pub fn with_keep_season_suffix(mut self, keep_season_suffix: bool) -> Self {
+        self.keep_season_suffix = keep_season_suffix;
+        self
+    }
+
+    /// Build suggested episode filenames from this template instead of keeping
+    /// the original name
+    pub fn with_rename_template(mut self, rename_template: Option<RenameTemplate>) -> Self {
+        self.rename_template = rename_template;
+        self
+    }
+
+    /// Flag well-named episodes that are not in this layout; with `--fix` they are moved
+    pub fn with_layout(mut self, layout: Option<ShowLayout>) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Apply safe fixes after validation: move episodes into the chosen layout and
+    /// their `SxxExx` season folder, and lowercase media extensions. Every rename
+    /// goes into an undo log.
+    pub fn with_fix(mut self, fix: bool) -> Self {
+        self.fix = fix;
+        self
+    }
+
+    /// Remove empty directories (bottom-up) once validation completes
+    pub fn with_prune_empty(mut self, prune_empty: bool) -> Self {
+        self.prune_empty = prune_empty;
+        self
+    }
+
     /// Compile all regex patterns once for better performance
     fn compile_patterns(patterns: &NamingPatterns) -> Vec<CompiledPattern> {
         patterns
@@ -186,14 +400,25 @@ impl ValidateCommand {
         let start_time = Instant::now();
 
         if !self.media_root.exists() {
-            return Err(anyhow!(
-                "Media directory does not exist: {:?}",
-                self.media_root
-            ));
+            return Err(PlexifyError::MediaRootMissing(self.media_root.clone()).into());
+        }
+
+        if self.media_root.is_file() {
+            return self.validate_single_file(&self.media_root, start_time);
         }
 
         if !self.media_root.is_dir() {
-            return Err(anyhow!("Path is not a directory: {:?}", self.media_root));
+            return Err(PlexifyError::NotADirectory(self.media_root.clone()).into());
+        }
+
+        // Patterns start at the library root (`Series/...`), which may be above the scanned path
+        let match_root = self.input_root.as_ref().unwrap_or(&self.media_root);
+        if !self.media_root.starts_with(match_root) {
+            return Err(anyhow!(
+                "{:?} is not inside the input root {:?}",
+                self.media_root,
+                match_root
+            ));
         }
 
         info!("🔍 Validating Plex naming scheme in: {:?}", self.media_root);
@@ -216,8 +441,9 @@ impl ValidateCommand {
         let mut media_files = Vec::new();
         let mut ignored_count = 0;
         let mut files_processed = 0;
+        let mut throttle = self.throttle.clone();
 
-        let scan_pb = ProgressBar::new_spinner();
+        let scan_pb = output::spinner();
         scan_pb.set_style(
             ProgressStyle::with_template("{spinner:.green} {msg}")
                 .unwrap()
@@ -238,7 +464,7 @@ impl ValidateCommand {
                 }
 
                 // Check if we should skip this directory and all its contents
-                if path.is_dir() {
+                if e.file_type().is_dir() {
                     if let Some(ref filter) = ignore_filter {
                         if filter.should_skip_dir(path) {
                             debug!("🚫 Skipping entire directory: {:?}", path);
@@ -251,22 +477,30 @@ impl ValidateCommand {
             })
             .filter_map(|e| e.ok())
         {
+            throttle.tick().await;
+
             let path = entry.path();
 
             // Skip directories and non-media files
-            if path.is_dir() {
+            if entry.file_type().is_dir() {
                 continue;
             }
 
             // Check if this individual file should be ignored
             if let Some(ref filter) = ignore_filter {
-                if filter.should_ignore(path) {
+                if filter.should_ignore(path, false) {
                     debug!("🚫 Ignoring path: {:?}", path);
                     ignored_count += 1;
                     continue;
                 }
             }
 
+            if let Ok(relative_path) = path.strip_prefix(&self.media_root) {
+                if !self.path_filter.allows(relative_path) {
+                    continue;
+                }
+            }
+
             files_processed += 1;
 
             // Update progress message periodically
@@ -297,7 +531,7 @@ impl ValidateCommand {
             );
         }
         // Create validation progress bar
-        let validate_pb = ProgressBar::new(media_files.len() as u64);
+        let validate_pb = output::progress_bar(media_files.len() as u64);
         validate_pb.set_style(
             ProgressStyle::with_template("Validating {bar:30.cyan/blue} {pos}/{len} {msg}")
                 .unwrap()
@@ -306,14 +540,14 @@ impl ValidateCommand {
         validate_pb.set_message("files");
 
         // Create shared reference to self for parallel processing
-        let media_root = Arc::new(&self.media_root);
+        let match_root = Arc::new(match_root);
         let pb = Arc::new(validate_pb);
 
         // Process files in parallel using rayon
-        let issues: Vec<ValidationIssue> = media_files
+        let mut issues: Vec<ValidationIssue> = media_files
             .par_iter()
             .filter_map(|path| {
-                let relative_path = match path.strip_prefix(media_root.as_ref()) {
+                let relative_path = match path.strip_prefix(match_root.as_ref()) {
                     Ok(rel_path) => rel_path,
                     Err(_) => return None,
                 };
@@ -326,15 +560,31 @@ impl ValidateCommand {
             .collect();
 
         pb.finish_and_clear();
+        // The walk order depends on the filesystem; sort so reports diff cleanly
+        issues.sort_by(|a, b| a.file_path.cmp(&b.file_path));
 
         let validation_time = start_time.elapsed();
 
+        let fixed_files = if self.fix {
+            self.apply_fixes(&issues, match_root.as_ref())?
+        } else {
+            Vec::new()
+        };
+
+        let pruned_dirs = if self.prune_empty {
+            prune_empty_dirs(&self.media_root, &[])?
+        } else {
+            Vec::new()
+        };
+
         let report = ValidationReport {
             scanned_files: media_files.len(),
             issues,
             patterns_used: self.patterns.clone(),
             scan_path: self.media_root.clone(),
             validation_time,
+            pruned_dirs,
+            fixed_files,
         };
 
         info!(
@@ -347,13 +597,49 @@ impl ValidateCommand {
         Ok(report)
     }
 
-    /// Validate a single file path against patterns (sequential version for testing)
-    fn validate_file_path(
-        &self,
-        relative_path: &Path,
-        full_path: &Path,
-    ) -> Option<ValidationIssue> {
-        self.validate_file_path_parallel(&self.compiled_patterns, relative_path, full_path)
+    /// Validate (and with `--fix`, fix) one file against the patterns, relative
+    /// to the library root
+    fn validate_single_file(&self, file: &Path, start_time: Instant) -> Result<ValidationReport> {
+        let library_root = match &self.input_root {
+            Some(root) => root.clone(),
+            None => infer_library_root(file).ok_or_else(|| {
+                anyhow!(
+                    "Cannot tell which library {:?} belongs to; pass --input-root",
+                    file
+                )
+            })?,
+        };
+        let relative_path = file.strip_prefix(&library_root).map_err(|_| {
+            anyhow!(
+                "{:?} is not inside the library root {:?}",
+                file,
+                library_root
+            )
+        })?;
+
+        info!(
+            "🔍 Validating {:?} against {:?}",
+            relative_path, library_root
+        );
+        let issues: Vec<ValidationIssue> = self
+            .validate_file_path_parallel(&self.compiled_patterns, relative_path, file)
+            .into_iter()
+            .collect();
+        let fixed_files = if self.fix {
+            self.apply_fixes(&issues, &library_root)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(ValidationReport {
+            scanned_files: 1,
+            issues,
+            patterns_used: self.patterns.clone(),
+            scan_path: file.to_path_buf(),
+            validation_time: start_time.elapsed(),
+            pruned_dirs: Vec::new(),
+            fixed_files,
+        })
     }
 
     /// Validate a single file path against patterns (parallel version)
@@ -365,10 +651,51 @@ impl ValidateCommand {
     ) -> Option<ValidationIssue> {
         let path_str = relative_path.to_string_lossy().replace("\\", "/");
 
+        let normalized = normalize_extension(&path_str);
+
+        if let Some(suggested) = self.season_mismatch(&path_str) {
+            return Some(ValidationIssue {
+                file_path: full_path.to_path_buf(),
+                issue_type: IssueType::SeasonMismatch,
+                description: "Episode code names another season than its folder".to_string(),
+                suggested_path: Some(PathBuf::from(
+                    normalize_extension(&suggested).unwrap_or(suggested),
+                )),
+            });
+        }
+
+        match self.check_layout(compiled_patterns, &path_str, full_path) {
+            LayoutCheck::Conforms => {
+                return normalized.map(|suggested| ValidationIssue {
+                    file_path: full_path.to_path_buf(),
+                    issue_type: IssueType::FileExtension,
+                    description: "File extension should be lowercase".to_string(),
+                    suggested_path: Some(PathBuf::from(suggested)),
+                });
+            }
+            LayoutCheck::Move(suggested, description) => {
+                return Some(ValidationIssue {
+                    file_path: full_path.to_path_buf(),
+                    issue_type: IssueType::ShowLayout,
+                    description: description.to_string(),
+                    suggested_path: Some(PathBuf::from(
+                        normalize_extension(&suggested).unwrap_or(suggested),
+                    )),
+                });
+            }
+            LayoutCheck::NotApplicable => {}
+        }
+
         // Try all compiled patterns (much faster than recompiling regex each time)
         for pattern in compiled_patterns.iter() {
             if pattern.regex.is_match(&path_str) {
-                return None; // Valid
+                // The name is valid, but the extension may still need its case fixed
+                return normalized.map(|suggested| ValidationIssue {
+                    file_path: full_path.to_path_buf(),
+                    issue_type: IssueType::FileExtension,
+                    description: "File extension should be lowercase".to_string(),
+                    suggested_path: Some(PathBuf::from(suggested)),
+                });
             }
         }
 
@@ -398,10 +725,92 @@ impl ValidateCommand {
             file_path: full_path.to_path_buf(),
             issue_type: issue_type.clone(),
             description,
-            suggested_path: self.suggest_path(&path_str, &issue_type),
+            suggested_path: self
+                .suggest_path(&path_str, &issue_type)
+                .map(|suggested| {
+                    let suggested = suggested.to_string_lossy().into_owned();
+                    PathBuf::from(normalize_extension(&suggested).unwrap_or(suggested))
+                })
+                .or(normalized.map(PathBuf::from)),
         })
     }
 
+    /// With a `--layout` target, work out whether a well-named episode sits in the
+    /// right layout. A flat episode is well-named if it would match once moved into
+    /// its season folder; only shows with a single season folder are flattened.
+    fn check_layout(
+        &self,
+        compiled_patterns: &[CompiledPattern],
+        path_str: &str,
+        full_path: &Path,
+    ) -> LayoutCheck {
+        let Some(layout) = self.layout else {
+            return LayoutCheck::NotApplicable;
+        };
+        let matches = |path: &str| {
+            compiled_patterns
+                .iter()
+                .any(|pattern| pattern.regex.is_match(path))
+        };
+
+        match path_str.split('/').collect::<Vec<_>>().as_slice() {
+            [content_dir, show, filename] if self.is_show_dir(content_dir) => {
+                let Some((_, season)) = parse_episode_code(filename) else {
+                    return LayoutCheck::NotApplicable;
+                };
+                let seasoned = format!("{content_dir}/{show}/Season {season:02}/{filename}");
+                if !matches(&seasoned) {
+                    return LayoutCheck::NotApplicable;
+                }
+                match layout {
+                    ShowLayout::Flat => LayoutCheck::Conforms,
+                    ShowLayout::Seasons => {
+                        LayoutCheck::Move(seasoned, "Episode should be in a Season folder")
+                    }
+                }
+            }
+            [content_dir, show, _, filename]
+                if layout == ShowLayout::Flat
+                    && self.is_show_dir(content_dir)
+                    && matches(path_str)
+                    && is_single_season_show(full_path) =>
+            {
+                LayoutCheck::Move(
+                    format!("{content_dir}/{show}/{filename}"),
+                    "Single-season show should be flat",
+                )
+            }
+            _ => LayoutCheck::NotApplicable,
+        }
+    }
+
+    /// The season folder an episode belongs in, if its `SxxExx` code disagrees with
+    /// the `Season XX` (or `Specials`) folder it is in. Plex catalogs by folder, so
+    /// such episodes end up under the wrong season.
+    fn season_mismatch(&self, path_str: &str) -> Option<String> {
+        let [content_dir, show, season_dir, filename] = path_str.split('/').collect::<Vec<_>>()[..]
+        else {
+            return None;
+        };
+        if !self.is_show_dir(content_dir) {
+            return None;
+        }
+        let folder_season = match season_dir {
+            "Specials" => 0,
+            _ => SEASON_DIR_RE.captures(season_dir)?[1].parse().ok()?,
+        };
+        let (_, season) = parse_episode_code(filename)?;
+        (season != folder_season)
+            .then(|| format!("{content_dir}/{show}/Season {season:02}/{filename}"))
+    }
+
+    /// Whether a top-level directory holds shows (`Series`, `Anime`)
+    fn is_show_dir(&self, dir: &str) -> bool {
+        DIRECTORY_MAPPING
+            .iter()
+            .any(|(name, content_type)| *name == dir && *content_type == ContentType::Series)
+    }
+
     /// Determine issue type based on directory structure
     fn determine_issue_type(&self, path_str: &str) -> IssueType {
         for (dir_name, content_type) in DIRECTORY_MAPPING {
@@ -419,6 +828,15 @@ impl ValidateCommand {
     fn suggest_path(&self, path_str: &str, issue_type: &IssueType) -> Option<PathBuf> {
         // This is a simplified suggestion system
         // In a full implementation, this would be more sophisticated
+        if let Some(suggested) = Self::suggest_season_path(
+            path_str,
+            issue_type,
+            self.keep_season_suffix,
+            self.rename_template.as_ref(),
+        ) {
+            return Some(suggested);
+        }
+
         if let IssueType::DirectoryStructure = issue_type {
             // If it's not in Movies/ or TV Shows/, suggest moving to Movies/
             if let Some(filename) = Path::new(path_str).file_name() {
@@ -449,55 +867,321 @@ impl ValidateCommand {
         None
     }
 
+    /// Suggest a `Season XX` directory for files whose name carries an episode code,
+    /// using the season from the code rather than assuming season 1. With
+    /// `keep_season_suffix`, a `Season XX - Description` folder for the same season
+    /// keeps its description. With a rename template the filename is rebuilt from
+    /// its parts, otherwise it is kept apart from the episode range.
+    fn suggest_season_path(
+        path_str: &str,
+        issue_type: &IssueType,
+        keep_season_suffix: bool,
+        rename_template: Option<&RenameTemplate>,
+    ) -> Option<PathBuf> {
+        let path = Path::new(path_str);
+        let original_filename = path.file_name()?.to_string_lossy();
+        let (show_from_name, season) = parse_episode_code(&original_filename)?;
+        let show_dir = path
+            .components()
+            .nth(1)
+            .filter(|_| matches!(issue_type, IssueType::ShowNaming))
+            .map(|dir| strip_id_tags(&dir.as_os_str().to_string_lossy()));
+        let filename = match (rename_template, EpisodeInfo::parse(&original_filename)) {
+            (Some(template), Some(info)) => template.render(&info, show_dir.as_deref()),
+            _ => canonical_episode_range(&original_filename)
+                .unwrap_or_else(|| original_filename.to_string()),
+        };
+
+        match issue_type {
+            // Loose file: derive the show from the filename
+            IssueType::DirectoryStructure => {
+                let show = show_from_name?;
+                Some(PathBuf::from(format!(
+                    "Series/{show}/Season {season:02}/{filename}"
+                )))
+            }
+            // Already under Series/ or Anime/: keep the show folder, fix the season folder
+            IssueType::ShowNaming => {
+                let mut components = path.components();
+                let content_dir = components.next()?.as_os_str().to_string_lossy();
+                let show = components.next()?.as_os_str().to_string_lossy();
+                // The file must be inside a folder below the show
+                let current_dir = components.next()?.as_os_str().to_string_lossy();
+                let suffix = match season_folder_suffix(&current_dir) {
+                    Some((number, suffix)) if keep_season_suffix && number == season => suffix,
+                    _ => "",
+                };
+                let suggested = PathBuf::from(format!(
+                    "{content_dir}/{show}/Season {season:02}{suffix}/{filename}"
+                ));
+                (suggested != path).then_some(suggested)
+            }
+            _ => None,
+        }
+    }
+
+    /// Move episodes into the target layout or their own season folder, then fix
+    /// extension case on the rest, logging every rename for `plexify undo`.
+    /// Returns the new paths, sorted.
+    fn apply_fixes(&self, issues: &[ValidationIssue], library_root: &Path) -> Result<Vec<PathBuf>> {
+        let (layout_issues, other_issues): (Vec<_>, Vec<_>) =
+            issues.iter().cloned().partition(|issue| {
+                matches!(
+                    issue.issue_type,
+                    IssueType::ShowLayout | IssueType::SeasonMismatch
+                )
+            });
+
+        let mut undo_log = UndoLog::default();
+        let renames = self.fix_extensions(&other_issues)?;
+        let mut fixed = Vec::new();
+        for (source, target) in renames {
+            undo_log.moves.push(UndoMove::record(&source, &target));
+            fixed.push(target);
+        }
+        for issue in layout_issues {
+            let Some(suggested) = &issue.suggested_path else {
+                continue;
+            };
+            let target = library_root.join(suggested);
+            match move_with_sidecars(&issue.file_path, &target) {
+                Ok(moves) => {
+                    info!("📂 Moved {:?} -> {:?}", issue.file_path, target);
+                    for (source, target) in moves {
+                        undo_log.moves.push(UndoMove::record(&source, &target));
+                    }
+                    fixed.push(target);
+                }
+                Err(e) => warn!("Could not move {:?}: {}", issue.file_path, e),
+            }
+        }
+        fixed.sort();
+
+        if let Some(work_root) = &self.work_root {
+            if !undo_log.moves.is_empty() {
+                let log_path = undo_log.write(work_root)?;
+                info!(
+                    "↩️ Undo log written, reverse these fixes with `plexify undo {}`",
+                    log_path.display()
+                );
+            }
+        }
+
+        Ok(fixed)
+    }
+
+    /// Lowercase the extension of every flagged file whose extension isn't already
+    /// lowercase. Returns the renames done, sorted by new path.
+    fn fix_extensions(&self, issues: &[ValidationIssue]) -> Result<Vec<(PathBuf, PathBuf)>> {
+        // Work out every rename in parallel
+        let mut renames: Vec<(&PathBuf, PathBuf)> = issues
+            .par_iter()
+            .filter_map(|issue| {
+                let path_str = issue.file_path.to_string_lossy();
+                normalize_extension(&path_str).map(|target| (&issue.file_path, target.into()))
+            })
+            .collect();
+
+        // Two sources with the same target (`a.MKV` and `a.Mkv`) would race for it,
+        // so only the first by path gets renamed. After this every target is unique.
+        renames.sort();
+        let mut targets = HashSet::new();
+        renames.retain(|(source, target)| {
+            let unique = targets.insert(target.clone());
+            if !unique {
+                warn!(
+                    "Could not fix extension of {:?}: another file is being renamed to {:?}",
+                    source, target
+                );
+            }
+            unique
+        });
+
+        // With unique targets the renames cannot interfere with each other
+        let mut fixed: Vec<(PathBuf, PathBuf)> = renames
+            .into_par_iter()
+            .filter_map(|(source, target)| match rename_case_only(source, &target) {
+                Ok(()) => {
+                    info!("✏️ Renamed {:?} -> {:?}", source, target);
+                    Some((source.clone(), target))
+                }
+                Err(e) => {
+                    warn!("Could not fix extension of {:?}: {}", source, e);
+                    None
+                }
+            })
+            .collect();
+        fixed.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        Ok(fixed)
+    }
+
+    /// Group report issues, sorted by group name and then by path
+    pub fn group_issues(report: &ValidationReport, group_by: GroupBy) -> Vec<IssueGroup<'_>> {
+        let mut groups: std::collections::BTreeMap<String, Vec<&ValidationIssue>> =
+            std::collections::BTreeMap::new();
+
+        for issue in &report.issues {
+            let relative = issue
+                .file_path
+                .strip_prefix(&report.scan_path)
+                .unwrap_or(&issue.file_path);
+            let components: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            let content_dir = components
+                .first()
+                .filter(|dir| {
+                    components.len() > 1 && DIRECTORY_MAPPING.iter().any(|(name, _)| name == dir)
+                })
+                .cloned();
+
+            let name = match group_by {
+                GroupBy::Type => content_dir.unwrap_or_else(|| "Other".to_string()),
+                GroupBy::Show => match content_dir {
+                    Some(dir) if components.len() > 2 => format!("{}/{}", dir, components[1]),
+                    Some(dir) => dir,
+                    None => "Other".to_string(),
+                },
+                GroupBy::Dir => match relative.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => {
+                        parent.to_string_lossy().replace('\\', "/")
+                    }
+                    _ => ".".to_string(),
+                },
+            };
+            groups.entry(name).or_default().push(issue);
+        }
+
+        groups
+            .into_iter()
+            .map(|(name, mut issues)| {
+                issues.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+                IssueGroup { name, issues }
+            })
+            .collect()
+    }
+
+    fn render_issue(out: &mut String, issue: &ValidationIssue) {
+        writeln!(
+            out,
+            "\n{} {}",
+            issue.issue_type.styled_marker(),
+            issue.file_path.display()
+        )
+        .unwrap();
+        writeln!(out, "   Issue: {}", issue.description).unwrap();
+
+        if let Some(suggested) = &issue.suggested_path {
+            writeln!(out, "   Suggested: {}", suggested.display()).unwrap();
+        }
+    }
+
     /// Print the validation report to stdout
     pub fn print_report(&self, report: &ValidationReport) {
-        println!("\n📊 Plex Naming Scheme Validation Report");
-        println!("═══════════════════════════════════════");
-        println!("📂 Scanned directory: {}", report.scan_path.display());
-        println!("📁 Files scanned: {}", report.scanned_files);
-        println!("⚠️  Issues found: {}", report.issues.len());
-        println!(
+        print!("{}", output::for_terminal(&self.render_report(report)));
+    }
+
+    /// Render the validation report as it is printed
+    pub fn render_report(&self, report: &ValidationReport) -> String {
+        let mut out = String::new();
+        writeln!(out, "\n📊 Plex Naming Scheme Validation Report").unwrap();
+        writeln!(out, "═══════════════════════════════════════").unwrap();
+        writeln!(out, "📂 Scanned directory: {}", report.scan_path.display()).unwrap();
+        writeln!(out, "📁 Files scanned: {}", report.scanned_files).unwrap();
+        writeln!(out, "⚠️  Issues found: {}", report.issues.len()).unwrap();
+        writeln!(
+            out,
             "⏱️  Validation time: {:.2}s",
             report.validation_time.as_secs_f64()
-        );
+        )
+        .unwrap();
 
-        if report.issues.is_empty() {
-            println!("\n✅ All files conform to Plex naming conventions!");
-            return;
+        if !report.pruned_dirs.is_empty() {
+            writeln!(
+                out,
+                "🗑️  Empty directories removed: {}",
+                report.pruned_dirs.len()
+            )
+            .unwrap();
+            if !self.summary_only {
+                for dir in &report.pruned_dirs {
+                    writeln!(out, "   {}", dir.display()).unwrap();
+                }
+            }
         }
 
-        println!("\n🔍 Issues Found:");
-        println!("─────────────────");
+        if !report.fixed_files.is_empty() {
+            writeln!(out, "✏️  Files renamed: {}", report.fixed_files.len()).unwrap();
+            if !self.summary_only {
+                for file in &report.fixed_files {
+                    writeln!(out, "   {}", file.display()).unwrap();
+                }
+            }
+        }
 
-        let mut issue_counts: HashMap<String, usize> = HashMap::new();
+        if report.issues.is_empty() {
+            writeln!(out, "\n✅ All files conform to Plex naming conventions!").unwrap();
+            return out;
+        }
 
+        let mut issue_counts: BTreeMap<String, usize> = BTreeMap::new();
         for issue in &report.issues {
-            let issue_type_str = match issue.issue_type {
-                IssueType::ShowNaming => "Show Naming",
-                IssueType::MovieNaming => "Movie Naming",
-                IssueType::DirectoryStructure => "Directory Structure",
-                IssueType::FileExtension => "File Extension",
-                IssueType::UnknownContentType => "Unknown Content Type",
-            };
+            *issue_counts
+                .entry(issue.issue_type.label().to_string())
+                .or_insert(0) += 1;
+        }
 
-            *issue_counts.entry(issue_type_str.to_string()).or_insert(0) += 1;
+        if !self.summary_only {
+            self.render_issues(&mut out, report);
+        }
 
-            println!("\n❌ {}", issue.file_path.display());
-            println!("   Issue: {}", issue.description);
+        writeln!(out, "\n📈 Issue Summary:").unwrap();
+        writeln!(out, "─────────────────").unwrap();
+        for (issue_type, count) in issue_counts {
+            writeln!(out, "• {}: {} files", issue_type, count).unwrap();
+        }
 
-            if let Some(suggested) = &issue.suggested_path {
-                println!("   Suggested: {}", suggested.display());
-            }
+        if !self.summary_only {
+            Self::render_patterns(&mut out, report);
         }
 
-        println!("\n📈 Issue Summary:");
-        println!("─────────────────");
-        for (issue_type, count) in issue_counts {
-            println!("• {}: {} files", issue_type, count);
+        out
+    }
+
+    /// The detailed list of issues, grouped if asked to
+    fn render_issues(&self, out: &mut String, report: &ValidationReport) {
+        writeln!(out, "\n🔍 Issues Found:").unwrap();
+        writeln!(out, "─────────────────").unwrap();
+
+        match self.group_by {
+            Some(group_by) => {
+                for group in Self::group_issues(report, group_by) {
+                    writeln!(
+                        out,
+                        "\n{} {}",
+                        style(&group.name).bold().underlined(),
+                        style(format!("({} issues)", group.issues.len())).dim()
+                    )
+                    .unwrap();
+                    for issue in group.issues {
+                        Self::render_issue(out, issue);
+                    }
+                }
+            }
+            None => {
+                for issue in &report.issues {
+                    Self::render_issue(out, issue);
+                }
+            }
         }
+    }
 
-        println!("\n💡 Supported Patterns:");
-        println!("─────────────────────");
+    /// Examples of the naming patterns validated against
+    fn render_patterns(out: &mut String, report: &ValidationReport) {
+        writeln!(out, "\n💡 Supported Patterns:").unwrap();
+        writeln!(out, "─────────────────────").unwrap();
 
         let show_patterns: Vec<_> = report
             .patterns_used
@@ -513,21 +1197,180 @@ impl ValidateCommand {
             .collect();
 
         if !show_patterns.is_empty() {
-            println!("📺 Shows:");
+            writeln!(out, "📺 Shows:").unwrap();
             for pattern in show_patterns {
-                println!("   • {}", pattern.example);
+                writeln!(out, "   • {}", pattern.example).unwrap();
             }
         }
 
         if !movie_patterns.is_empty() {
-            println!("\n🎬 Movies:");
+            writeln!(out, "\n🎬 Movies:").unwrap();
             for pattern in movie_patterns {
-                println!("   • {}", pattern.example);
+                writeln!(out, "   • {}", pattern.example).unwrap();
             }
         }
     }
 }
 
+/// Compiled once, as validation matches these against every media file
+static SEASON_DIR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^Season (\d+)\b").expect("valid regex"));
+static EPISODE_CODE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^(.*?)[\s._-]*\bs(\d{1,2})e\d{1,3}").expect("valid regex"));
+
+/// Parse an `SxxExx` episode code from a filename, returning the show name before
+/// it (if any) and the season number
+fn parse_episode_code(filename: &str) -> Option<(Option<String>, u32)> {
+    let captures = EPISODE_CODE_RE.captures(filename)?;
+    let season = captures.get(2)?.as_str().parse().ok()?;
+    let show = captures
+        .get(1)
+        .map(|m| {
+            m.as_str()
+                .replace(['.', '_'], " ")
+                .trim()
+                .trim_end_matches('-')
+                .trim()
+                .to_string()
+        })
+        .map(|show| disambiguate_show_year(&show))
+        .filter(|show| !show.is_empty());
+    Some((show, season))
+}
+
+/// Keep a trailing release year as Plex's `Show (YYYY)` form so same-named shows
+/// from different years stay apart (`Doctor Who 2005` -> `Doctor Who (2005)`)
+fn disambiguate_show_year(show: &str) -> String {
+    let re = Regex::new(r"^(.+?)\s+\(?((?:19|20)\d{2})\)?$").expect("valid regex");
+    match re.captures(show) {
+        Some(captures) => format!("{} ({})", &captures[1], &captures[2]),
+        None => show.to_string(),
+    }
+}
+
+/// Drop `{tvdb-81189}`-style id tags from a show folder name
+fn strip_id_tags(dir: &str) -> String {
+    let re = Regex::new(r"\s*\{[^}]*\}").expect("valid regex");
+    re.replace_all(dir, "").trim().to_string()
+}
+
+/// Split a `Season 11 - Description` folder name into its season number and the
+/// ` - Description` suffix. Returns `None` for plain `Season NN` and other folders.
+fn season_folder_suffix(dir: &str) -> Option<(u32, &str)> {
+    let re = Regex::new(r"^Season (\d+)(\s*-.*)$").expect("valid regex");
+    let captures = re.captures(dir)?;
+    let number = captures.get(1)?.as_str().parse().ok()?;
+    Some((number, captures.get(2)?.as_str()))
+}
+
+/// Rewrite a multi-episode code like `S01E01E02` to Plex's `S01E01-E02` form.
+/// Returns `None` if the filename has no such code.
+fn canonical_episode_range(filename: &str) -> Option<String> {
+    let re = Regex::new(r"(?i)\b(s\d{2}e\d{2})(e\d{2})\b").ok()?;
+    re.is_match(filename)
+        .then(|| re.replace(filename, "$1-$2").into_owned())
+}
+
+/// Return the path with its media extension lowercased, or `None` if it already is
+fn normalize_extension(path_str: &str) -> Option<String> {
+    let ext = Path::new(path_str).extension()?.to_str()?;
+    let lower = ext.to_lowercase();
+    if ext == lower || !MEDIA_EXTENSIONS.contains(&lower.as_str()) {
+        return None;
+    }
+    Some(format!(
+        "{}{}",
+        &path_str[..path_str.len() - ext.len()],
+        lower
+    ))
+}
+
+/// Whether the season folder holding a file is the only `Season XX` folder of its show
+fn is_single_season_show(file: &Path) -> bool {
+    let Some(show_dir) = file.parent().and_then(Path::parent) else {
+        return false;
+    };
+    let Ok(entries) = std::fs::read_dir(show_dir) else {
+        return false;
+    };
+    let re = Regex::new(r"^Season \d+").expect("valid regex");
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir() && re.is_match(&e.file_name().to_string_lossy()))
+        .count()
+        == 1
+}
+
+/// Move a media file to another folder, taking sidecars that share its stem
+/// (`Show S01E01.en.srt`) along. Refuses to overwrite anything. Returns every
+/// move made, the media file first.
+fn move_with_sidecars(from: &Path, to: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let (Some(from_dir), Some(to_dir)) = (from.parent(), to.parent()) else {
+        return Err(anyhow!("Invalid path: {:?}", to));
+    };
+    let stem = from
+        .file_stem()
+        .ok_or_else(|| anyhow!("Invalid path: {:?}", from))?
+        .to_string_lossy()
+        .into_owned();
+    let sidecars: Vec<PathBuf> = std::fs::read_dir(from_dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            path != from
+                && path.is_file()
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with(&format!("{stem}.")))
+        })
+        .collect();
+
+    let mut moves = vec![(from.to_path_buf(), to.to_path_buf())];
+    for sidecar in sidecars {
+        let name = sidecar.file_name().unwrap_or_default().to_owned();
+        moves.push((sidecar, to_dir.join(name)));
+    }
+    if let Some((_, target)) = moves.iter().find(|(_, target)| target.exists()) {
+        return Err(PlexifyError::DestinationExists(target.clone()).into());
+    }
+
+    std::fs::create_dir_all(to_dir)?;
+    for (source, target) in &moves {
+        std::fs::rename(source, target)?;
+    }
+    Ok(moves)
+}
+
+/// Rename a file whose new name differs only in case. On case-insensitive
+/// filesystems a direct rename can be a no-op, so go through a temporary name.
+fn rename_case_only(from: &Path, to: &Path) -> Result<()> {
+    // On a case-sensitive filesystem both names can exist side by side
+    let target_name = to
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid path: {:?}", to))?;
+    let parent = to
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid path: {:?}", to))?;
+    if std::fs::read_dir(parent)?
+        .filter_map(|e| e.ok())
+        .any(|e| e.file_name() == target_name)
+    {
+        return Err(PlexifyError::DestinationExists(to.to_path_buf()).into());
+    }
+
+    let mut temp_name = from.as_os_str().to_owned();
+    temp_name.push(".plexify-rename");
+    let temp = PathBuf::from(temp_name);
+
+    std::fs::rename(from, &temp)?;
+    if let Err(e) = std::fs::rename(&temp, to) {
+        // Put the file back where it was
+        let _ = std::fs::rename(&temp, from);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -630,6 +1473,709 @@ mod tests {
         assert_eq!(report.issues.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_validate_prune_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+
+        fs::create_dir_all(media_root.join("Series/Show/Season 01")).unwrap();
+        fs::write(
+            media_root.join("Series/Show/Season 01/Show - s01e01 - Pilot.mkv"),
+            "",
+        )
+        .unwrap();
+        // Left behind after its contents were moved elsewhere
+        fs::create_dir_all(media_root.join("WrongDir/Season 01")).unwrap();
+        fs::create_dir_all(media_root.join("Kept")).unwrap();
+        fs::write(media_root.join("Kept/.plexifyignore"), "").unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_prune_empty(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(report.pruned_dirs.len(), 2);
+        assert!(!media_root.join("WrongDir").exists());
+        assert!(media_root.join("Kept").exists());
+        assert!(media_root.join("Series/Show/Season 01").exists());
+    }
+
+    #[test]
+    fn test_parse_episode_code() {
+        assert_eq!(
+            parse_episode_code("Show S03E05.mkv"),
+            Some((Some("Show".to_string()), 3))
+        );
+        assert_eq!(
+            parse_episode_code("The.Wire.s02e11.720p.mkv"),
+            Some((Some("The Wire".to_string()), 2))
+        );
+        assert_eq!(parse_episode_code("S10E01 - Finale.mkv"), Some((None, 10)));
+        assert_eq!(
+            parse_episode_code("Doctor.Who.2005.S01E01.mkv"),
+            Some((Some("Doctor Who (2005)".to_string()), 1))
+        );
+        assert_eq!(
+            parse_episode_code("Doctor Who (1963) - S01E01.mkv"),
+            Some((Some("Doctor Who (1963)".to_string()), 1))
+        );
+        assert_eq!(parse_episode_code("Heat (1995).mkv"), None);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_season_from_episode_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+
+        fs::create_dir_all(media_root.join("Downloads")).unwrap();
+        fs::write(media_root.join("Downloads/Show S03E05.mkv"), "").unwrap();
+        fs::create_dir_all(media_root.join("Series/Other Show/Season 01")).unwrap();
+        fs::write(
+            media_root.join("Series/Other Show/Season 01/Other Show S02E01.mkv"),
+            "",
+        )
+        .unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(report.issues.len(), 2);
+
+        let suggestion = |name: &str| {
+            report
+                .issues
+                .iter()
+                .find(|issue| issue.file_path.ends_with(name))
+                .and_then(|issue| issue.suggested_path.clone())
+        };
+
+        assert_eq!(
+            suggestion("Show S03E05.mkv"),
+            Some(PathBuf::from("Series/Show/Season 03/Show S03E05.mkv"))
+        );
+        assert_eq!(
+            suggestion("Other Show S02E01.mkv"),
+            Some(PathBuf::from(
+                "Series/Other Show/Season 02/Other Show S02E01.mkv"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suggest_path_keeps_show_year() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        fs::create_dir_all(media_root.join("Downloads")).unwrap();
+        fs::write(media_root.join("Downloads/Doctor.Who.2005.S02E03.mkv"), "").unwrap();
+        fs::write(media_root.join("Downloads/Sherlock S02E03.mkv"), "").unwrap();
+        fs::create_dir_all(media_root.join("Series/Doctor Who (1963)/Specials")).unwrap();
+        fs::write(
+            media_root.join("Series/Doctor Who (1963)/Specials/Doctor Who S04E01.mkv"),
+            "",
+        )
+        .unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .execute()
+            .await
+            .unwrap();
+
+        let suggestion = |name: &str| {
+            report
+                .issues
+                .iter()
+                .find(|issue| issue.file_path.ends_with(name))
+                .and_then(|issue| issue.suggested_path.clone())
+        };
+
+        assert_eq!(
+            suggestion("Doctor.Who.2005.S02E03.mkv"),
+            Some(PathBuf::from(
+                "Series/Doctor Who (2005)/Season 02/Doctor.Who.2005.S02E03.mkv"
+            ))
+        );
+        assert_eq!(
+            suggestion("Sherlock S02E03.mkv"),
+            Some(PathBuf::from(
+                "Series/Sherlock/Season 02/Sherlock S02E03.mkv"
+            ))
+        );
+        // The show folder's year is kept as-is
+        assert_eq!(
+            suggestion("Doctor Who S04E01.mkv"),
+            Some(PathBuf::from(
+                "Series/Doctor Who (1963)/Season 04/Doctor Who S04E01.mkv"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_match_glob() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        for path in [
+            "Series/Breaking Bad/Season 01/bad name.mkv",
+            "Series/Breaking Bad/Season 01/Breaking Bad - s01e01 - Pilot.mkv",
+            "Series/Other/Season 01/bad name.mkv",
+            "loose.mkv",
+        ] {
+            let path = media_root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_match(Some(glob::Pattern::new("Series/Breaking Bad/**").unwrap()))
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(report.scanned_files, 2);
+        assert_eq!(report.issues.len(), 1);
+        assert!(report.issues[0]
+            .file_path
+            .starts_with(media_root.join("Series/Breaking Bad")));
+    }
+
+    #[tokio::test]
+    async fn test_validate_exclude_globs() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        for path in [
+            "Series/Fine/Season 01/bad name.mkv",
+            "Series/Other/Season 01/bad name.mkv",
+            "Downloads/a.mkv",
+            "Downloads/b.mkv",
+            "loose.mkv",
+        ] {
+            let path = media_root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_exclude(vec![
+                glob::Pattern::new("Series/Fine/**").unwrap(),
+                glob::Pattern::new("Downloads/*").unwrap(),
+            ])
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(report.scanned_files, 2);
+        assert_eq!(report.issues.len(), 2);
+        assert!(report.issues.iter().all(|issue| !issue
+            .file_path
+            .starts_with(media_root.join("Series/Fine"))
+            && !issue.file_path.starts_with(media_root.join("Downloads"))));
+    }
+
+    #[tokio::test]
+    async fn test_validate_two_part_episodes() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let season_dir = media_root.join("Series/Show/Season 01");
+        fs::create_dir_all(&season_dir).unwrap();
+
+        // Canonical multi-episode forms are valid
+        fs::write(season_dir.join("Show - s01e01-e02 - Pilot.mkv"), "").unwrap();
+        fs::write(season_dir.join("Show S01E03-E04 Finale.mkv"), "").unwrap();
+        fs::write(season_dir.join("S01E05-E06 - Special.mkv"), "").unwrap();
+        // Concatenated codes are flagged and normalized
+        fs::write(season_dir.join("Show S01E07E08 Double.MKV"), "").unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_fix(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(report.scanned_files, 4);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(
+            report.issues[0].suggested_path,
+            Some(PathBuf::from(
+                "Series/Show/Season 01/Show S01E07-E08 Double.mkv"
+            ))
+        );
+        // The extension fix keeps the episode range intact
+        assert_eq!(
+            report.fixed_files,
+            vec![season_dir.join("Show S01E07E08 Double.mkv")]
+        );
+    }
+
+    #[test]
+    fn test_suggest_season_path_season_suffix() {
+        let arc = "Anime/One Piece/Season 11 - Sabaody Archipelago (382-407)/One Piece S11E01E02 Title.mkv";
+        let suggest = |path: &str, keep: bool| {
+            ValidateCommand::suggest_season_path(path, &IssueType::ShowNaming, keep, None)
+        };
+
+        // Stripped by default
+        assert_eq!(
+            suggest(arc, false),
+            Some(PathBuf::from(
+                "Anime/One Piece/Season 11/One Piece S11E01-E02 Title.mkv"
+            ))
+        );
+        // Kept on request
+        assert_eq!(
+            suggest(arc, true),
+            Some(PathBuf::from(
+                "Anime/One Piece/Season 11 - Sabaody Archipelago (382-407)/One Piece S11E01-E02 Title.mkv"
+            ))
+        );
+        // A description for another season does not carry over
+        assert_eq!(
+            suggest("Anime/Show/Season 1 - Arc/Show S02E01 Title.mkv", true),
+            Some(PathBuf::from("Anime/Show/Season 02/Show S02E01 Title.mkv"))
+        );
+
+        assert_eq!(
+            season_folder_suffix("Season 11 - Arc"),
+            Some((11, " - Arc"))
+        );
+        assert_eq!(season_folder_suffix("Season 11"), None);
+        assert_eq!(season_folder_suffix("Extras"), None);
+    }
+
+    #[test]
+    fn test_suggest_season_path_rename_template() {
+        let template =
+            RenameTemplate::parse("{show} - s{season}e{episode} - {title}.{ext}").unwrap();
+        let suggest = |path: &str, issue_type: &IssueType| {
+            ValidateCommand::suggest_season_path(path, issue_type, false, Some(&template))
+        };
+
+        // The show folder (without its id tag) names episodes that lack a show name
+        assert_eq!(
+            suggest(
+                "Series/Breaking Bad {tvdb-81189}/Season 1/S01E02.Cats.in.the.Bag.mkv",
+                &IssueType::ShowNaming
+            ),
+            Some(PathBuf::from(
+                "Series/Breaking Bad {tvdb-81189}/Season 01/Breaking Bad - s01e02 - Cats in the Bag.mkv"
+            ))
+        );
+        assert_eq!(
+            suggest(
+                "Downloads/Some.Show.S02E01E02.mkv",
+                &IssueType::DirectoryStructure
+            ),
+            Some(PathBuf::from(
+                "Series/Some Show/Season 02/Some Show - s02e01-e02.mkv"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_canonical_episode_range() {
+        assert_eq!(
+            canonical_episode_range("Show S01E01E02 Title.mkv").as_deref(),
+            Some("Show S01E01-E02 Title.mkv")
+        );
+        assert_eq!(
+            canonical_episode_range("show - s02e17e18 - title.mkv").as_deref(),
+            Some("show - s02e17-e18 - title.mkv")
+        );
+        assert_eq!(canonical_episode_range("Show S01E01-E02 Title.mkv"), None);
+        assert_eq!(canonical_episode_range("Show S01E01 Title.mkv"), None);
+    }
+
+    #[test]
+    fn test_normalize_extension() {
+        assert_eq!(
+            normalize_extension("Movies/Heat (1995)/Heat (1995).MKV").as_deref(),
+            Some("Movies/Heat (1995)/Heat (1995).mkv")
+        );
+        assert_eq!(
+            normalize_extension("Random/video.Mp4").as_deref(),
+            Some("Random/video.mp4")
+        );
+        assert_eq!(normalize_extension("Random/video.mkv"), None);
+        assert_eq!(normalize_extension("Random/notes.TXT"), None);
+        assert_eq!(normalize_extension("Random/README"), None);
+    }
+
+    #[tokio::test]
+    async fn test_validate_uppercase_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+
+        let movie_dir = media_root.join("Movies/Heat (1995)");
+        fs::create_dir_all(&movie_dir).unwrap();
+        fs::write(movie_dir.join("Heat (1995).MKV"), "").unwrap();
+        fs::create_dir_all(media_root.join("Series/Show")).unwrap();
+        fs::write(media_root.join("Series/Show/episode.Mkv"), "").unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(report.scanned_files, 2);
+        assert_eq!(report.issues.len(), 2);
+
+        let movie_issue = report
+            .issues
+            .iter()
+            .find(|i| matches!(i.issue_type, IssueType::FileExtension))
+            .unwrap();
+        assert_eq!(
+            movie_issue.suggested_path,
+            Some(PathBuf::from("Movies/Heat (1995)/Heat (1995).mkv"))
+        );
+
+        // Naming issues still suggest the extension fix
+        let show_issue = report
+            .issues
+            .iter()
+            .find(|i| matches!(i.issue_type, IssueType::ShowNaming))
+            .unwrap();
+        assert_eq!(
+            show_issue.suggested_path,
+            Some(PathBuf::from("Series/Show/episode.mkv"))
+        );
+
+        // Nothing was renamed without --fix
+        assert!(report.fixed_files.is_empty());
+        assert!(movie_dir.join("Heat (1995).MKV").exists());
+    }
+
+    #[tokio::test]
+    async fn test_validate_fix_lowercases_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+
+        let movie_dir = media_root.join("Movies/Heat (1995)");
+        fs::create_dir_all(&movie_dir).unwrap();
+        fs::write(movie_dir.join("Heat (1995).MKV"), "movie").unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_fix(true)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(report.fixed_files, vec![movie_dir.join("Heat (1995).mkv")]);
+        let names: Vec<_> = fs::read_dir(&movie_dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("Heat (1995).mkv")]);
+
+        assert_eq!(report.remaining_issues(), 0);
+
+        // A second run finds nothing to fix
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_fix(true)
+            .execute()
+            .await
+            .unwrap();
+        assert!(report.issues.is_empty());
+        assert!(report.fixed_files.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_fix_flattens_single_season_shows() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let mini = media_root.join("Series/Mini");
+        fs::create_dir_all(mini.join("Season 01")).unwrap();
+        fs::write(mini.join("Season 01/Mini S01E01 Pilot.mkv"), "").unwrap();
+        fs::write(mini.join("Season 01/Mini S01E01 Pilot.en.srt"), "").unwrap();
+        let long = media_root.join("Series/Long");
+        for season in ["Season 01", "Season 02"] {
+            fs::create_dir_all(long.join(season)).unwrap();
+        }
+        fs::write(long.join("Season 01/Long S01E01 Start.mkv"), "").unwrap();
+        fs::write(long.join("Season 02/Long S02E01 More.mkv"), "").unwrap();
+
+        let validate = || {
+            ValidateCommand::new(media_root.to_path_buf())
+                .with_layout(Some(ShowLayout::Flat))
+                .with_fix(true)
+        };
+        let report = validate().execute().await.unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert!(matches!(report.issues[0].issue_type, IssueType::ShowLayout));
+        assert_eq!(report.fixed_files, vec![mini.join("Mini S01E01 Pilot.mkv")]);
+        assert_eq!(report.remaining_issues(), 0);
+        assert!(mini.join("Mini S01E01 Pilot.en.srt").exists());
+        assert!(!mini.join("Season 01/Mini S01E01 Pilot.mkv").exists());
+        // Shows with several seasons keep their folders
+        assert!(long.join("Season 02/Long S02E01 More.mkv").exists());
+
+        // Flat episodes conform to the flat layout
+        let report = validate().execute().await.unwrap();
+        assert!(report.issues.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_fix_moves_flat_episodes_into_seasons() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let show = media_root.join("Anime/Flat Show");
+        fs::create_dir_all(&show).unwrap();
+        fs::write(show.join("Flat Show S02E03 Title.mkv"), "").unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_layout(Some(ShowLayout::Seasons))
+            .with_fix(true)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert!(matches!(report.issues[0].issue_type, IssueType::ShowLayout));
+        let target = show.join("Season 02/Flat Show S02E03 Title.mkv");
+        assert_eq!(report.fixed_files, vec![target.clone()]);
+        assert!(target.exists());
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_layout(Some(ShowLayout::Seasons))
+            .execute()
+            .await
+            .unwrap();
+        assert!(report.issues.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_flags_season_mismatch() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let season_dir = media_root.join("Series/Show/Season 01");
+        fs::create_dir_all(&season_dir).unwrap();
+        fs::write(season_dir.join("Show S01E04 Fine.mkv"), "").unwrap();
+        fs::write(season_dir.join("Show S02E05 Misfiled.mkv"), "").unwrap();
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(report.issues.len(), 1);
+        let issue = &report.issues[0];
+        assert!(matches!(issue.issue_type, IssueType::SeasonMismatch));
+        assert_eq!(issue.file_path, season_dir.join("Show S02E05 Misfiled.mkv"));
+        assert_eq!(
+            issue.suggested_path,
+            Some(PathBuf::from(
+                "Series/Show/Season 02/Show S02E05 Misfiled.mkv"
+            ))
+        );
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_fix(true)
+            .execute()
+            .await
+            .unwrap();
+        let target = media_root.join("Series/Show/Season 02/Show S02E05 Misfiled.mkv");
+        assert_eq!(report.fixed_files, vec![target.clone()]);
+        assert_eq!(report.remaining_issues(), 0);
+        assert!(target.exists());
+    }
+
+    #[tokio::test]
+    async fn test_validate_fix_many_files_concurrently() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+
+        let mut expected = Vec::new();
+        for i in 0..200 {
+            let movie_dir = media_root.join(format!("Movies/Movie {i} (2000)"));
+            fs::create_dir_all(&movie_dir).unwrap();
+            fs::write(movie_dir.join(format!("Movie {i} (2000).MKV")), "movie").unwrap();
+            expected.push(movie_dir.join(format!("Movie {i} (2000).mkv")));
+        }
+        expected.sort();
+
+        // Both of these want to become `Clash (2001).mkv`
+        let clash_dir = media_root.join("Movies/Clash (2001)");
+        fs::create_dir_all(&clash_dir).unwrap();
+        fs::write(clash_dir.join("Clash (2001).MKV"), "upper").unwrap();
+        fs::write(clash_dir.join("Clash (2001).Mkv"), "mixed").unwrap();
+        let both_exist = fs::read_dir(&clash_dir).unwrap().count() == 2;
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .with_fix(true)
+            .execute()
+            .await
+            .unwrap();
+
+        let (clash, fixed): (Vec<_>, Vec<_>) = report
+            .fixed_files
+            .into_iter()
+            .partition(|path| path.starts_with(&clash_dir));
+        assert_eq!(fixed, expected);
+        for path in &expected {
+            assert!(path.exists());
+        }
+
+        // Only meaningful on case-sensitive filesystems, where both files coexist
+        if both_exist {
+            assert_eq!(clash, vec![clash_dir.join("Clash (2001).mkv")]);
+            assert_eq!(fs::read_dir(&clash_dir).unwrap().count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_rename_case_only_refuses_existing_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let upper = temp_dir.path().join("video.MKV");
+        let lower = temp_dir.path().join("video.mkv");
+        fs::write(&upper, "upper").unwrap();
+        fs::write(&lower, "lower").unwrap();
+
+        // Only meaningful on case-sensitive filesystems, where both files coexist
+        if fs::read_dir(temp_dir.path()).unwrap().count() == 2 {
+            assert!(rename_case_only(&upper, &lower).is_err());
+            assert_eq!(fs::read_to_string(&lower).unwrap(), "lower");
+            assert_eq!(fs::read_to_string(&upper).unwrap(), "upper");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_group_issues() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+
+        for path in [
+            "Series/Show A/episode1.mkv",
+            "Series/Show A/episode2.mkv",
+            "Series/Show B/Season 1/episode.mkv",
+            "Anime/Show C/episode.mkv",
+            "Movies/movie.mkv",
+            "Downloads/random.mkv",
+            "loose.mkv",
+        ] {
+            let path = media_root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let report = ValidateCommand::new(media_root.to_path_buf())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(report.issues.len(), 7);
+
+        let counts = |group_by| {
+            ValidateCommand::group_issues(&report, group_by)
+                .into_iter()
+                .map(|group| (group.name, group.issues.len()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            counts(GroupBy::Type),
+            vec![
+                ("Anime".to_string(), 1),
+                ("Movies".to_string(), 1),
+                ("Other".to_string(), 2),
+                ("Series".to_string(), 3),
+            ]
+        );
+        assert_eq!(
+            counts(GroupBy::Show),
+            vec![
+                ("Anime/Show C".to_string(), 1),
+                ("Movies".to_string(), 1),
+                ("Other".to_string(), 2),
+                ("Series/Show A".to_string(), 2),
+                ("Series/Show B".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            counts(GroupBy::Dir),
+            vec![
+                (".".to_string(), 1),
+                ("Anime/Show C".to_string(), 1),
+                ("Downloads".to_string(), 1),
+                ("Movies".to_string(), 1),
+                ("Series/Show A".to_string(), 2),
+                ("Series/Show B/Season 1".to_string(), 1),
+            ]
+        );
+
+        // Issues within a group are sorted by path
+        let groups = ValidateCommand::group_issues(&report, GroupBy::Show);
+        let show_a = groups.iter().find(|g| g.name == "Series/Show A").unwrap();
+        assert!(show_a.issues[0].file_path.ends_with("episode1.mkv"));
+        assert!(show_a.issues[1].file_path.ends_with("episode2.mkv"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_single_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let season = media_root.join("Series/Breaking Bad/Season 01");
+        fs::create_dir_all(&season).unwrap();
+        let good = season.join("Breaking Bad - s01e01 - Pilot.mkv");
+        let bad = season.join("episode.MKV");
+        fs::write(&good, "").unwrap();
+        fs::write(&bad, "").unwrap();
+
+        // The library root is inferred from the Series folder
+        let report = ValidateCommand::new(good.clone()).execute().await.unwrap();
+        assert_eq!(report.scanned_files, 1);
+        assert!(report.issues.is_empty());
+
+        let report = ValidateCommand::new(bad.clone())
+            .with_input_root(Some(media_root.to_path_buf()))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(report.scanned_files, 1);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].file_path, bad);
+        assert!(matches!(report.issues[0].issue_type, IssueType::ShowNaming));
+
+        // A file outside any library needs an explicit root
+        let loose = media_root.join("loose.mkv");
+        fs::write(&loose, "").unwrap();
+        assert!(ValidateCommand::new(loose.clone()).execute().await.is_err());
+        assert!(ValidateCommand::new(loose)
+            .with_input_root(Some(season))
+            .execute()
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_subtree_with_input_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let show = media_root.join("Series/Breaking Bad");
+        fs::create_dir_all(show.join("Season 01")).unwrap();
+        fs::write(show.join("Season 01/Breaking Bad - s01e01 - Pilot.mkv"), "").unwrap();
+        fs::write(
+            show.join("Season 01/Breaking Bad S01E02 Cat's in the Bag.mkv"),
+            "",
+        )
+        .unwrap();
+
+        // Relative to the show folder nothing looks like Series/...
+        let report = ValidateCommand::new(show.clone()).execute().await.unwrap();
+        assert_eq!(report.issues.len(), 2);
+
+        let report = ValidateCommand::new(show.clone())
+            .with_input_root(Some(media_root.to_path_buf()))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(report.scanned_files, 2);
+        assert!(report.issues.is_empty());
+
+        // The scanned path has to be inside the input root
+        assert!(ValidateCommand::new(media_root.to_path_buf())
+            .with_input_root(Some(show))
+            .execute()
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_validate_nonexistent_directory() {
         let validate_cmd = ValidateCommand::new(PathBuf::from("/nonexistent/path"));
@@ -853,4 +2399,87 @@ mod tests {
         assert_eq!(report.scanned_files, 1);
         assert_eq!(report.issues.len(), 0); // The movie is correctly named
     }
+
+    #[tokio::test]
+    async fn test_plain_report_has_no_ansi_or_emoji() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        fs::create_dir_all(media_root.join("Random")).unwrap();
+        fs::write(media_root.join("Random/some_movie.mkv"), "").unwrap();
+
+        let validate_cmd =
+            ValidateCommand::new(media_root.to_path_buf()).with_group_by(Some(GroupBy::Type));
+        let report = validate_cmd.execute().await.unwrap();
+        assert!(!report.issues.is_empty());
+
+        // What `--color never` prints, without changing the process-wide choice
+        let text = crate::output::plain_text(&validate_cmd.render_report(&report));
+
+        assert!(text.contains("Issue Summary"));
+        assert!(!text.contains('\x1b'));
+        assert!(!text
+            .chars()
+            .any(|c| matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF)));
+    }
+
+    #[tokio::test]
+    async fn test_summary_only_report_omits_issue_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        fs::create_dir_all(media_root.join("Random")).unwrap();
+        fs::write(media_root.join("Random/some_movie.mkv"), "").unwrap();
+        fs::write(media_root.join("Random/other_movie.mkv"), "").unwrap();
+
+        let validate_cmd = ValidateCommand::new(media_root.to_path_buf());
+        let report = validate_cmd.execute().await.unwrap();
+        assert!(!report.issues.is_empty());
+        let full = validate_cmd.render_report(&report);
+        assert!(full.contains("Issues Found"));
+        assert!(full.contains("some_movie.mkv"));
+
+        let summary = validate_cmd.with_summary_only(true).render_report(&report);
+        assert!(summary.contains(&format!("Files scanned: {}", report.scanned_files)));
+        assert!(summary.contains(&format!("Issues found: {}", report.issues.len())));
+        assert!(summary.contains("Issue Summary"));
+        assert!(!summary.contains("Issues Found"));
+        assert!(!summary.contains("Supported Patterns"));
+        assert!(!summary.contains("some_movie.mkv"));
+    }
+
+    #[tokio::test]
+    async fn test_issues_are_sorted_by_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        // Created out of order, in several folders
+        for path in [
+            "Random/zeta.mkv",
+            "Movies/movie without year.mkv",
+            "Random/alpha.mkv",
+            "Random/Sub/mid.mkv",
+            "Another/beta.mkv",
+        ] {
+            let path = media_root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let validate_cmd = ValidateCommand::new(media_root.to_path_buf());
+        let report = validate_cmd.execute().await.unwrap();
+        assert_eq!(report.issues.len(), 5);
+        let paths: Vec<&PathBuf> = report.issues.iter().map(|issue| &issue.file_path).collect();
+        let mut sorted = paths.clone();
+        sorted.sort();
+        assert_eq!(paths, sorted);
+
+        // The per-type summary comes out in a fixed order too
+        let rendered = validate_cmd.render_report(&report);
+        let summary = rendered.split("Issue Summary").nth(1).unwrap();
+        let labels: Vec<&str> = summary
+            .lines()
+            .filter_map(|line| line.strip_prefix("• "))
+            .collect();
+        let mut sorted_labels = labels.clone();
+        sorted_labels.sort();
+        assert_eq!(labels, sorted_labels);
+    }
 }
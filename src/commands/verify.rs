@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info};
+
+use crate::job::{Checksum, Job};
+use crate::queue::JobQueue;
+
+/// Outcome of checking the recorded checksums against the files on disk
+#[derive(Debug, Default, PartialEq)]
+pub struct VerifyReport {
+    pub verified: usize,
+    /// Files whose contents no longer match their recorded checksum
+    pub mismatched: Vec<PathBuf>,
+    /// Files with a checksum that are gone (moved, deleted or replaced by a conversion)
+    pub missing: usize,
+}
+
+/// Command to check files against what the queue recorded about them
+pub struct VerifyCommand {
+    work_root: PathBuf,
+    checksums: bool,
+}
+
+impl VerifyCommand {
+    pub fn new(work_root: PathBuf) -> Self {
+        Self {
+            work_root,
+            checksums: false,
+        }
+    }
+
+    /// Recompute the checksums recorded by `scan --checksum` and compare
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.checksums = checksums;
+        self
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        if !self.checksums {
+            return Err(anyhow!("Nothing to verify, pass --checksums"));
+        }
+
+        let report = self.verify_checksums().await?;
+        info!(
+            "🔍 Verified {} file(s), {} mismatched, {} missing",
+            report.verified,
+            report.mismatched.len(),
+            report.missing
+        );
+        if !report.mismatched.is_empty() {
+            return Err(anyhow!(
+                "{} file(s) no longer match their recorded checksum",
+                report.mismatched.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check the inputs of queued and completed jobs, and the outputs of completed jobs
+    pub async fn verify_checksums(&self) -> Result<VerifyReport> {
+        let queue = JobQueue::new(self.work_root.clone(), self.work_root.clone());
+        let mut report = VerifyReport::default();
+
+        for job in queue.pending_jobs().await? {
+            if let Some(checksum) = &job.input_checksum {
+                check_file(&job.input_path, checksum, &mut report).await?;
+            }
+        }
+
+        for (job, result) in queue.completed_jobs().await? {
+            // A converted source is usually renamed to `.disabled`, and an in-place
+            // remux replaces it outright
+            if let Some(checksum) = &job.input_checksum {
+                if !job.replaces_input() {
+                    let disabled = Job::disabled_path(&job.input_path);
+                    let input_path = if job.input_path.exists() || !disabled.exists() {
+                        &job.input_path
+                    } else {
+                        &disabled
+                    };
+                    check_file(input_path, checksum, &mut report).await?;
+                }
+            }
+            if let Some(checksum) = result.and_then(|result| result.output_checksum) {
+                check_file(&job.output_path, &checksum, &mut report).await?;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Compare one file with its recorded checksum and add the outcome to the report
+async fn check_file(path: &Path, checksum: &Checksum, report: &mut VerifyReport) -> Result<()> {
+    if !path.exists() {
+        debug!("Skipping missing file {:?}", path);
+        report.missing += 1;
+        return Ok(());
+    }
+    if checksum.matches(path).await? {
+        report.verified += 1;
+    } else {
+        error!("❌ Checksum mismatch: {:?}", path);
+        report.mismatched.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::{ChecksumAlgorithm, MediaFileType, PostProcessingSettings, QualitySettings};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_verify_checksums_detects_modified_input() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_root = temp_dir.path().to_path_buf();
+        let queue = JobQueue::new(work_root.clone(), work_root.clone());
+        queue.init().await.unwrap();
+
+        let mut inputs = Vec::new();
+        for name in ["intact.mkv", "rotten.mkv", "gone.mkv"] {
+            let input = work_root.join(name);
+            std::fs::write(&input, name).unwrap();
+            let checksum = Checksum::compute(&input, ChecksumAlgorithm::Crc32)
+                .await
+                .unwrap();
+            let job = Job::new(
+                input.clone(),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                &work_root,
+            )
+            .with_input_checksum(checksum);
+            queue.enqueue_job(&job).await.unwrap();
+            inputs.push(input);
+        }
+        std::fs::write(&inputs[1], "flipped bits").unwrap();
+        std::fs::remove_file(&inputs[2]).unwrap();
+
+        let command = VerifyCommand::new(work_root).with_checksums(true);
+        let report = command.verify_checksums().await.unwrap();
+        assert_eq!(
+            report,
+            VerifyReport {
+                verified: 1,
+                mismatched: vec![inputs[1].clone()],
+                missing: 1,
+            }
+        );
+        assert!(command.execute().await.is_err());
+    }
+}
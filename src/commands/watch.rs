@@ -0,0 +1,502 @@
+use anyhow::{anyhow, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify};
+use tracing::{debug, info, warn};
+
+use crate::ignore::IgnoreFilter;
+use crate::queue::JobQueue;
+use crate::JobPriority;
+
+use super::job_processor::{JobProcessResult, JobProcessor, JobProcessorConfig};
+use super::scan::ScanCommand;
+use super::work::WorkCommand;
+
+/// How often pending files are re-checked for size changes
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A file seen by the watcher that hasn't settled yet
+#[derive(Debug)]
+struct PendingFile {
+    /// Last observed size, None until the file has been stat'ed
+    size: Option<u64>,
+    /// When the file last changed (event received or size changed)
+    changed_at: Instant,
+}
+
+/// Debounces filesystem events and tracks files until they stop growing
+#[derive(Debug)]
+pub struct SettleTracker {
+    settle_time: Duration,
+    pending: HashMap<PathBuf, PendingFile>,
+}
+
+impl SettleTracker {
+    pub fn new(settle_time: Duration) -> Self {
+        Self {
+            settle_time,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record a filesystem event for a path, restarting its settle timer
+    pub fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.pending
+            .entry(path)
+            .and_modify(|file| file.changed_at = now)
+            .or_insert(PendingFile {
+                size: None,
+                changed_at: now,
+            });
+    }
+
+    /// Record the current size of a pending file. A changed size restarts the
+    /// settle timer, and a missing file (None) is dropped from tracking.
+    pub fn observe_size(&mut self, path: &Path, size: Option<u64>, now: Instant) {
+        let Some(size) = size else {
+            self.pending.remove(path);
+            return;
+        };
+
+        if let Some(file) = self.pending.get_mut(path) {
+            if file.size != Some(size) {
+                file.size = Some(size);
+                file.changed_at = now;
+            }
+        }
+    }
+
+    /// Remove and return every file whose size hasn't changed for the settle time
+    pub fn take_settled(&mut self, now: Instant) -> Vec<PathBuf> {
+        let settled: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, file)| {
+                file.size.is_some() && now.duration_since(file.changed_at) >= self.settle_time
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in &settled {
+            self.pending.remove(path);
+        }
+
+        settled
+    }
+
+    /// Paths currently waiting to settle
+    pub fn pending_paths(&self) -> Vec<PathBuf> {
+        self.pending.keys().cloned().collect()
+    }
+}
+
+/// Command that watches the media directory and converts new files as they arrive
+pub struct WatchCommand {
+    media_root: PathBuf,
+    work_root: PathBuf,
+    preset: Option<String>,
+    background_mode: bool,
+    priority_mode: JobPriority,
+    settle_time: Duration,
+}
+
+impl WatchCommand {
+    pub fn new(
+        media_root: PathBuf,
+        work_root: PathBuf,
+        preset: Option<String>,
+        background_mode: bool,
+        priority_mode: JobPriority,
+        settle_time: Duration,
+    ) -> Self {
+        Self {
+            media_root,
+            work_root,
+            preset,
+            background_mode,
+            priority_mode,
+            settle_time,
+        }
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        if !self.media_root.exists() {
+            return Err(anyhow!(
+                "Media directory does not exist: {:?}",
+                self.media_root
+            ));
+        }
+
+        if !self.media_root.is_dir() {
+            return Err(anyhow!("Path is not a directory: {:?}", self.media_root));
+        }
+
+        // Pick up anything that arrived while we weren't watching
+        ScanCommand::new(
+            self.media_root.clone(),
+            self.work_root.clone(),
+            self.preset.clone(),
+        )
+        .execute()
+        .await?;
+
+        // The watcher reports absolute paths, so match against absolute roots
+        let watch_cmd = Self {
+            media_root: self.media_root.canonicalize()?,
+            work_root: std::path::absolute(&self.work_root)?,
+            preset: self.preset.clone(),
+            priority_mode: self.priority_mode.clone(),
+            ..*self
+        };
+
+        let wakeup = Arc::new(Notify::new());
+        let worker = WorkCommand::new(
+            self.media_root.clone(),
+            self.work_root.clone(),
+            self.background_mode,
+            self.priority_mode.clone(),
+        )
+        .with_wakeup(Arc::clone(&wakeup));
+
+        // The worker owns shutdown handling; once it exits the watcher is dropped too
+        tokio::select! {
+            result = worker.execute() => result,
+            result = watch_cmd.watch(wakeup) => result,
+        }
+    }
+
+    /// Watch the media directory and enqueue files once they have settled
+    async fn watch(&self, wakeup: Arc<Notify>) -> Result<()> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })?;
+        watcher.watch(&self.media_root, RecursiveMode::Recursive)?;
+
+        info!(
+            "👀 Watching {:?} for new media (settle time: {:?})",
+            self.media_root, self.settle_time
+        );
+
+        let queue = JobQueue::new(self.media_root.clone(), self.work_root.clone());
+        queue.init().await?;
+        let config = JobProcessorConfig::from_preset(self.preset.as_deref())?;
+        let processor = JobProcessor::new(&queue, &config, &self.media_root);
+
+        let mut ignore_filter = self.load_ignore_filter();
+        let mut tracker = SettleTracker::new(self.settle_time);
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        // Files enqueued this session, so later modify events don't create duplicate jobs
+        let mut enqueued = HashSet::new();
+
+        loop {
+            tokio::select! {
+                Some(res) = rx.recv() => match res {
+                    Ok(event) => {
+                        if Self::is_ignore_file_event(&event) {
+                            debug!("Reloading .plexifyignore patterns");
+                            ignore_filter = self.load_ignore_filter();
+                        }
+                        self.handle_event(event, ignore_filter.as_ref(), &mut tracker);
+                    }
+                    Err(e) => warn!("Filesystem watch error: {}", e),
+                },
+                _ = ticker.tick() => {
+                    let now = Instant::now();
+                    for path in tracker.pending_paths() {
+                        let size = tokio::fs::metadata(&path).await.ok().map(|m| m.len());
+                        tracker.observe_size(&path, size, now);
+                    }
+
+                    let mut created = 0;
+                    for path in tracker.take_settled(now) {
+                        if enqueued.contains(&path) {
+                            continue;
+                        }
+                        // One file failing to queue must not stop the watch; it is
+                        // tried again on its next change
+                        match self.enqueue(&processor, &path).await {
+                            Ok(true) => {
+                                enqueued.insert(path);
+                                created += 1;
+                            }
+                            Ok(false) => {}
+                            Err(e) => warn!("Could not queue {:?}: {}", path, e),
+                        }
+                    }
+
+                    if created > 0 {
+                        wakeup.notify_one();
+                    }
+                }
+            }
+        }
+    }
+
+    fn load_ignore_filter(&self) -> Option<IgnoreFilter> {
+        match IgnoreFilter::new(self.media_root.clone()) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                warn!("Failed to load .plexifyignore patterns: {}", e);
+                None
+            }
+        }
+    }
+
+    fn is_ignore_file_event(event: &Event) -> bool {
+        event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == Some(".plexifyignore".as_ref()))
+    }
+
+    /// Start tracking any media files touched by a filesystem event
+    fn handle_event(
+        &self,
+        event: Event,
+        ignore_filter: Option<&IgnoreFilter>,
+        tracker: &mut SettleTracker,
+    ) {
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+
+        let now = Instant::now();
+        for path in event.paths {
+            // Never react to our own work files
+            if path.starts_with(&self.work_root) && !self.work_root.starts_with(&path) {
+                continue;
+            }
+
+            let Some(media_path) = Self::candidate_media_path(&path) else {
+                continue;
+            };
+
+            if let Some(filter) = ignore_filter {
+                if filter.should_ignore(&media_path, false) {
+                    debug!("🚫 Ignoring path: {:?}", media_path);
+                    continue;
+                }
+            }
+
+            debug!("Change detected: {:?}", media_path);
+            tracker.touch(media_path, now);
+        }
+    }
+
+    /// Map a changed path to the media file that may now be ready for a job.
+    /// A new .vtt or .ass subtitle makes its .webm sibling eligible.
+    fn candidate_media_path(path: &Path) -> Option<PathBuf> {
+        if Self::is_temporary_file(path) {
+            return None;
+        }
+
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        match extension.as_str() {
+            "webm" | "mkv" => Some(path.to_path_buf()),
+            "vtt" | "ass" => {
+                let webm = path.with_extension("webm");
+                webm.exists().then_some(webm)
+            }
+            _ => None,
+        }
+    }
+
+    /// Editor swap files, hidden download temp files and backups
+    fn is_temporary_file(path: &Path) -> bool {
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy()) else {
+            return true;
+        };
+
+        name.starts_with('.') || name.starts_with("~$") || name.ends_with('~')
+    }
+
+    /// Create a job for a settled file. Returns true if a job was created.
+    async fn enqueue(&self, processor: &JobProcessor<'_>, path: &Path) -> Result<bool> {
+        let Ok(relative_path) = path.strip_prefix(&self.media_root) else {
+            return Ok(false);
+        };
+        let Ok(file_type) = JobProcessor::determine_file_type(path) else {
+            return Ok(false);
+        };
+
+        let result = processor
+            .process_media_file(relative_path, file_type.clone())
+            .await?;
+        processor.log_result(relative_path, &file_type, &result);
+
+        Ok(matches!(result, JobProcessResult::Created))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_file_settles_after_quiet_period() {
+        let start = Instant::now();
+        let mut tracker = SettleTracker::new(Duration::from_secs(5));
+        let path = PathBuf::from("/media/show.mkv");
+
+        tracker.touch(path.clone(), start);
+        tracker.observe_size(&path, Some(100), start);
+        assert!(tracker
+            .take_settled(start + Duration::from_secs(4))
+            .is_empty());
+
+        let settled = tracker.take_settled(start + Duration::from_secs(5));
+        assert_eq!(settled, vec![path]);
+
+        // Settled files are only reported once
+        assert!(tracker
+            .take_settled(start + Duration::from_secs(10))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_repeated_events_are_debounced() {
+        let start = Instant::now();
+        let mut tracker = SettleTracker::new(Duration::from_secs(5));
+        let path = PathBuf::from("/media/show.mkv");
+
+        tracker.touch(path.clone(), start);
+        tracker.observe_size(&path, Some(100), start);
+        tracker.touch(path.clone(), start + Duration::from_secs(3));
+        tracker.touch(path.clone(), start + Duration::from_secs(4));
+
+        assert_eq!(tracker.pending_paths().len(), 1);
+        assert!(tracker
+            .take_settled(start + Duration::from_secs(8))
+            .is_empty());
+        assert_eq!(
+            tracker.take_settled(start + Duration::from_secs(9)),
+            vec![path]
+        );
+    }
+
+    #[test]
+    fn test_growing_file_does_not_settle() {
+        let start = Instant::now();
+        let mut tracker = SettleTracker::new(Duration::from_secs(5));
+        let path = PathBuf::from("/media/download.mkv");
+
+        tracker.touch(path.clone(), start);
+        for i in 0..10 {
+            let now = start + Duration::from_secs(i * 2);
+            tracker.observe_size(&path, Some(i * 1000), now);
+            assert!(tracker.take_settled(now).is_empty());
+        }
+
+        // Size stops changing
+        let last_change = start + Duration::from_secs(18);
+        tracker.observe_size(&path, Some(9000), last_change + Duration::from_secs(5));
+        assert_eq!(
+            tracker.take_settled(last_change + Duration::from_secs(5)),
+            vec![path]
+        );
+    }
+
+    #[test]
+    fn test_unstatted_and_removed_files_do_not_settle() {
+        let start = Instant::now();
+        let mut tracker = SettleTracker::new(Duration::from_secs(1));
+        let path = PathBuf::from("/media/gone.mkv");
+
+        // Never stat'ed yet
+        tracker.touch(path.clone(), start);
+        assert!(tracker
+            .take_settled(start + Duration::from_secs(5))
+            .is_empty());
+
+        // Removed before settling
+        tracker.observe_size(&path, None, start + Duration::from_secs(5));
+        assert!(tracker.pending_paths().is_empty());
+    }
+
+    #[test]
+    fn test_candidate_media_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let webm = temp_dir.path().join("video.webm");
+        fs::write(&webm, "").unwrap();
+
+        assert_eq!(
+            WatchCommand::candidate_media_path(Path::new("/media/show.mkv")),
+            Some(PathBuf::from("/media/show.mkv"))
+        );
+        assert_eq!(
+            WatchCommand::candidate_media_path(&temp_dir.path().join("video.vtt")),
+            Some(webm)
+        );
+        assert_eq!(
+            WatchCommand::candidate_media_path(&temp_dir.path().join("orphan.vtt")),
+            None
+        );
+        assert_eq!(
+            WatchCommand::candidate_media_path(Path::new("/media/show.mkv.part")),
+            None
+        );
+        assert_eq!(
+            WatchCommand::candidate_media_path(Path::new("/media/.show.mkv")),
+            None
+        );
+        assert_eq!(
+            WatchCommand::candidate_media_path(Path::new("/media/show.mkv~")),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_settled_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        let work_root = temp_dir.path().join("work");
+        fs::create_dir_all(&media_root).unwrap();
+        fs::write(media_root.join("show.mkv"), "data").unwrap();
+        fs::write(media_root.join("notes.txt"), "data").unwrap();
+
+        let watch_cmd = WatchCommand::new(
+            media_root.clone(),
+            work_root.clone(),
+            None,
+            false,
+            JobPriority::None,
+            Duration::from_secs(1),
+        );
+
+        let queue = JobQueue::new(media_root.clone(), work_root.clone());
+        queue.init().await.unwrap();
+        let config = JobProcessorConfig::from_preset(None).unwrap();
+        let processor = JobProcessor::new(&queue, &config, &media_root);
+
+        assert!(watch_cmd
+            .enqueue(&processor, &media_root.join("show.mkv"))
+            .await
+            .unwrap());
+        assert!(!watch_cmd
+            .enqueue(&processor, &media_root.join("notes.txt"))
+            .await
+            .unwrap());
+
+        assert_eq!(queue.pending_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_watch_nonexistent_directory() {
+        let watch_cmd = WatchCommand::new(
+            PathBuf::from("/nonexistent/path"),
+            PathBuf::from("/tmp"),
+            None,
+            false,
+            JobPriority::None,
+            Duration::from_secs(30),
+        );
+
+        assert!(watch_cmd.execute().await.is_err());
+    }
+}
@@ -1,21 +1,143 @@
 use anyhow::{anyhow, Result};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::PathBuf;
-use std::time::Duration;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::signal;
-use tracing::{error, info, warn};
+use tokio::sync::{watch, Notify};
+use tracing::{debug, error, info, warn};
 
 use crate::config::Config;
-use crate::ffmpeg::FFmpegProcessor;
-use crate::queue::JobQueue;
+use crate::error::{self, PlexifyError};
+use crate::ffmpeg::permissions::OutputPermissions;
+use crate::ffmpeg::post_process::PostProcessStep;
+use crate::ffmpeg::{
+    render_command, FFmpegProcessor, FFmpegProgress, OversizePolicy, ProgressCallback,
+};
+use crate::job::{Checksum, Job, SpecialsOrder};
+use crate::metrics::{self, WorkerMetrics};
+use crate::notification::{NotificationEvent, NotificationPayload, Notifier};
+use crate::output;
+use crate::queue::{DiskLimits, JobQueue, JobResult, QueueFs};
 use crate::JobPriority;
 
+use super::dashboard::Dashboard;
+use super::hooks::Hook;
+use super::job_processor::{JobProcessor, JobProcessorConfig};
+use super::load_governor::LoadGovernor;
+use super::read_slots::ReadSlots;
+
+/// Running totals for a worker session
+#[derive(Debug)]
+pub struct WorkStats {
+    pub jobs_completed: u64,
+    pub jobs_failed: u64,
+    pub bytes_saved: i64,
+    /// Names of the jobs that failed this session
+    pub failed_jobs: Vec<String>,
+    /// Pending count last logged, so it is only logged again when it changes
+    last_remaining: Option<usize>,
+    started: Instant,
+}
+
+impl WorkStats {
+    pub fn new() -> Self {
+        Self {
+            jobs_completed: 0,
+            jobs_failed: 0,
+            bytes_saved: 0,
+            failed_jobs: Vec::new(),
+            last_remaining: None,
+            started: Instant::now(),
+        }
+    }
+
+    fn record_failure(&mut self, job_name: &str) {
+        self.jobs_failed += 1;
+        self.failed_jobs.push(job_name.to_string());
+    }
+
+    /// Remember `pending` and say whether it differs from the last count logged
+    fn remaining_changed(&mut self, pending: usize) -> bool {
+        let changed = self.last_remaining != Some(pending);
+        self.last_remaining = Some(pending);
+        changed
+    }
+
+    /// Build a notification payload summarizing this session
+    pub fn to_payload(&self, event: NotificationEvent) -> NotificationPayload {
+        NotificationPayload {
+            event,
+            jobs_completed: self.jobs_completed,
+            jobs_failed: self.jobs_failed,
+            bytes_saved: self.bytes_saved,
+            elapsed_secs: self.started.elapsed().as_secs(),
+        }
+    }
+}
+
+impl Default for WorkStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How far along a worker is in shutting down after Ctrl+C
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownState {
+    Running,
+    /// Finish the current job, then exit
+    Draining,
+    /// Kill the running ffmpeg and exit now
+    Terminate,
+}
+
+impl ShutdownState {
+    /// State after another shutdown signal
+    pub fn on_signal(self) -> Self {
+        match self {
+            ShutdownState::Running => ShutdownState::Draining,
+            ShutdownState::Draining | ShutdownState::Terminate => ShutdownState::Terminate,
+        }
+    }
+}
+
 /// Command to process jobs from the queue
 pub struct WorkCommand {
     media_root: PathBuf,
     work_root: PathBuf,
     background_mode: bool,
     priority_mode: JobPriority,
+    shuffle_seed: Option<u64>,
+    specials_order: SpecialsOrder,
+    max_per_series: Option<usize>,
+    disk_limits: DiskLimits,
+    queue_fs: QueueFs,
+    keep_completed_days: Option<u64>,
+    read_slots: Option<ReadSlots>,
+    load_governor: Option<LoadGovernor>,
+    job_timeout: Option<Duration>,
+    stall_timeout: Option<Duration>,
+    oversize_policy: OversizePolicy,
+    min_free_space: u64,
+    once: bool,
+    continue_on_error: bool,
+    done_marker: bool,
+    on_complete: Option<Hook>,
+    on_failure: Option<Hook>,
+    print_command: bool,
+    file: Option<PathBuf>,
+    notifier: Option<Notifier>,
+    metrics_addr: Option<SocketAddr>,
+    metrics: Option<Arc<WorkerMetrics>>,
+    wakeup: Option<Arc<Notify>>,
+    dashboard: Option<Dashboard>,
+    no_move: bool,
+    spill_dir: Option<PathBuf>,
+    output_permissions: OutputPermissions,
+    preserve_mtime: bool,
+    post_processors: Vec<PostProcessStep>,
 }
 
 impl WorkCommand {
@@ -30,22 +152,230 @@ impl WorkCommand {
             work_root,
             background_mode,
             priority_mode,
+            shuffle_seed: None,
+            specials_order: SpecialsOrder::default(),
+            max_per_series: None,
+            disk_limits: DiskLimits::default(),
+            queue_fs: QueueFs::Local,
+            keep_completed_days: None,
+            read_slots: None,
+            load_governor: None,
+            job_timeout: None,
+            stall_timeout: None,
+            oversize_policy: OversizePolicy::default(),
+            min_free_space: 0,
+            once: false,
+            continue_on_error: true,
+            done_marker: false,
+            on_complete: None,
+            on_failure: None,
+            print_command: false,
+            file: None,
+            notifier: None,
+            metrics_addr: None,
+            metrics: None,
+            wakeup: None,
+            dashboard: None,
+            no_move: false,
+            spill_dir: None,
+            output_permissions: OutputPermissions::default(),
+            preserve_mtime: false,
+            post_processors: Vec::new(),
         }
     }
 
+    /// Run these external tools, in order, on each output before it is moved into place
+    pub fn with_post_processors(mut self, post_processors: Vec<PostProcessStep>) -> Self {
+        self.post_processors = post_processors;
+        self
+    }
+
+    /// Copy each source's modification time onto its output
+    pub fn with_preserve_mtime(mut self, preserve_mtime: bool) -> Self {
+        self.preserve_mtime = preserve_mtime;
+        self
+    }
+
+    /// Set the mode and ownership of outputs after they are moved into place, so a
+    /// media server running as another user can read them
+    pub fn with_output_permissions(mut self, output_permissions: OutputPermissions) -> Self {
+        self.output_permissions = output_permissions;
+        self
+    }
+
+    /// Encode in this directory instead of the work folder when an output would not
+    /// fit there, e.g. when the work dir is a tmpfs
+    pub fn with_spill_dir(mut self, spill_dir: Option<PathBuf>) -> Self {
+        self.spill_dir = spill_dir;
+        self
+    }
+
+    /// Kill FFmpeg and fail the job if its output position stops advancing for this long
+    pub fn with_stall_timeout(mut self, stall_timeout: Option<Duration>) -> Self {
+        self.stall_timeout = stall_timeout;
+        self
+    }
+
+    /// Process the jobs queued at startup and exit instead of waiting for more
+    pub fn with_once(mut self, once: bool) -> Self {
+        self.once = once;
+        self
+    }
+
+    /// In `--once` runs, keep going after a failed job instead of stopping
+    pub fn with_continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// Write a `<output>.plexify-done` marker next to each output once it is in place
+    pub fn with_done_marker(mut self, done_marker: bool) -> Self {
+        self.done_marker = done_marker;
+        self
+    }
+
+    /// Run this command after each job whose output was moved into place
+    pub fn with_on_complete(mut self, on_complete: Option<Hook>) -> Self {
+        self.on_complete = on_complete;
+        self
+    }
+
+    /// Run this command when a job is moved to `_failed` because retrying cannot help
+    pub fn with_on_failure(mut self, on_failure: Option<Hook>) -> Self {
+        self.on_failure = on_failure;
+        self
+    }
+
+    /// Transcode just this file right away, without touching the queue
+    pub fn with_file(mut self, file: Option<PathBuf>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Print the ffmpeg command for each queued job instead of running anything
+    pub fn with_print_command(mut self, print_command: bool) -> Self {
+        self.print_command = print_command;
+        self
+    }
+
+    /// Decide what happens when an encode comes out larger than its source
+    pub fn with_oversize_policy(mut self, oversize_policy: OversizePolicy) -> Self {
+        self.oversize_policy = oversize_policy;
+        self
+    }
+
+    /// Fail jobs up front unless their output fits with this many bytes to spare
+    pub fn with_min_free_space(mut self, min_free_space: u64) -> Self {
+        self.min_free_space = min_free_space;
+        self
+    }
+
+    /// Seed for `--priority shuffle` so the processing order can be reproduced
+    pub fn with_shuffle_seed(mut self, shuffle_seed: Option<u64>) -> Self {
+        self.shuffle_seed = shuffle_seed;
+        self
+    }
+
+    /// Prune completed job records older than this many days at startup
+    pub fn with_keep_completed_days(mut self, keep_completed_days: Option<u64>) -> Self {
+        self.keep_completed_days = keep_completed_days;
+        self
+    }
+
+    /// With `--priority episode`, claim specials before or after the regular seasons
+    pub fn with_specials_order(mut self, specials_order: SpecialsOrder) -> Self {
+        self.specials_order = specials_order;
+        self
+    }
+
+    /// Don't claim a job while this many jobs of its series are already in progress
+    pub fn with_max_per_series(mut self, max_per_series: Option<usize>) -> Self {
+        self.max_per_series = max_per_series;
+        self
+    }
+
+    /// Don't claim a job while its input's disk already has its limit of jobs in progress
+    pub fn with_disk_limits(mut self, disk_limits: DiskLimits) -> Self {
+        self.disk_limits = disk_limits;
+        self
+    }
+
+    /// Claim jobs with lockfiles when the queue is shared over NFS
+    pub fn with_queue_fs(mut self, queue_fs: QueueFs) -> Self {
+        self.queue_fs = queue_fs;
+        self
+    }
+
+    /// Limit how many workers sharing this work dir read their input at once
+    pub fn with_max_concurrent_reads(mut self, max_concurrent_reads: Option<usize>) -> Self {
+        self.read_slots = max_concurrent_reads.map(|max| ReadSlots::new(&self.work_root, max));
+        self
+    }
+
+    /// Stop claiming new jobs while the system load average is above this
+    pub fn with_max_load(mut self, max_load: Option<f64>) -> Self {
+        self.load_governor = max_load.map(LoadGovernor::new);
+        self
+    }
+
+    /// Kill FFmpeg and fail the job if a single conversion runs longer than this
+    pub fn with_job_timeout(mut self, job_timeout: Option<Duration>) -> Self {
+        self.job_timeout = job_timeout;
+        self
+    }
+
+    /// Send a summary notification when the queue drains and when the worker exits
+    pub fn with_notifier(mut self, notifier: Option<Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Serve Prometheus metrics on this address while the worker runs
+    pub fn with_metrics_addr(mut self, metrics_addr: Option<SocketAddr>) -> Self {
+        self.metrics = metrics_addr.map(|_| Arc::new(WorkerMetrics::new()));
+        self.metrics_addr = metrics_addr;
+        self
+    }
+
+    /// Cut the idle wait short whenever this is notified (e.g. a new job was enqueued)
+    pub fn with_wakeup(mut self, wakeup: Arc<Notify>) -> Self {
+        self.wakeup = Some(wakeup);
+        self
+    }
+
+    /// Encode next to the destination instead of in the work folder, saving the
+    /// copy out of the work folder on single-machine setups
+    pub fn with_no_move(mut self, no_move: bool) -> Self {
+        self.no_move = no_move;
+        self
+    }
+
+    /// Show a live dashboard instead of a spinner while the worker runs
+    pub fn with_dashboard(mut self, dashboard: Option<Dashboard>) -> Self {
+        self.dashboard = dashboard;
+        self
+    }
+
     pub async fn execute(&self) -> Result<()> {
         if !self.media_root.exists() {
-            return Err(anyhow!(
-                "Media directory does not exist: {:?}",
-                self.media_root
-            ));
+            return Err(PlexifyError::MediaRootMissing(self.media_root.clone()).into());
         }
 
         if !self.media_root.is_dir() {
-            return Err(anyhow!("Path is not a directory: {:?}", self.media_root));
+            return Err(PlexifyError::NotADirectory(self.media_root.clone()).into());
+        }
+
+        for step in &self.post_processors {
+            let program = step.resolve_program()?;
+            debug!("Post-processing step '{}' runs {:?}", step, program);
         }
 
         let config = Config::from_env();
+        if let Some(file) = &self.file {
+            let processor = self.build_processor(config, None);
+            return self.process_single_file(file, &processor).await;
+        }
+
         let mode = if self.background_mode {
             "Low Priority Worker"
         } else {
@@ -54,77 +384,390 @@ impl WorkCommand {
 
         info!("✅ Starting worker in {} mode.", mode);
         info!("Watching for jobs in: {:?}", self.work_root.join("_queue"));
+        if let Some(governor) = &self.load_governor {
+            governor.warn_if_unsupported();
+        }
 
-        let queue = JobQueue::new(self.media_root.clone(), self.work_root.clone());
+        let queue = JobQueue::new(self.media_root.clone(), self.work_root.clone())
+            .with_shuffle_seed(self.shuffle_seed)
+            .with_specials_order(self.specials_order)
+            .with_max_per_series(self.max_per_series)
+            .with_disk_limits(self.disk_limits.clone())
+            .with_queue_fs(self.queue_fs);
         queue.init().await?;
+        queue
+            .apply_completed_retention(self.keep_completed_days.or(config.keep_completed_days))
+            .await?;
+
+        let progress_callback = (self.metrics.is_some() || self.dashboard.is_some()).then(|| {
+            let metrics = self.metrics.clone();
+            let dashboard = self.dashboard.clone();
+            Arc::new(move |progress: &FFmpegProgress| {
+                if let Some(metrics) = &metrics {
+                    if let Some(fraction) = progress.fraction() {
+                        metrics.set_progress(fraction);
+                    }
+                    if let Some(speed) = progress.speed {
+                        metrics.set_encode_speed(speed);
+                    }
+                }
+                if let Some(dashboard) = &dashboard {
+                    dashboard.state().update_progress(progress);
+                }
+            }) as ProgressCallback
+        });
 
-        let processor = FFmpegProcessor::new(config.clone(), self.background_mode);
+        let processor = self.build_processor(config.clone(), progress_callback);
 
-        // Set up signal handling for graceful shutdown
-        tokio::pin! {
-            let shutdown_signal = signal::ctrl_c();
+        if self.print_command {
+            return self.print_commands(&queue, &processor).await;
         }
 
-        loop {
-            tokio::select! {
-                // Check for shutdown signal
-                _ = &mut shutdown_signal => {
-                    info!("🛑 Shutdown signal received. Exiting gracefully.");
-                    break;
+        let metrics_server = match (self.metrics_addr, &self.metrics) {
+            (Some(addr), Some(metrics)) => {
+                let listener = metrics::bind(addr).await?;
+                Some(tokio::spawn(metrics::serve(listener, Arc::clone(metrics))))
+            }
+            _ => None,
+        };
+
+        // First Ctrl+C finishes the current job, a second one stops it right away
+        let (shutdown_tx, mut shutdown) = watch::channel(ShutdownState::Running);
+        let signal_task = tokio::spawn(async move {
+            while signal::ctrl_c().await.is_ok() {
+                let state = shutdown_tx.borrow().on_signal();
+                shutdown_tx.send_replace(state);
+                match state {
+                    ShutdownState::Draining => info!(
+                        "🛑 Shutdown signal received. Finishing the current job, then exiting (Ctrl+C again to stop now)."
+                    ),
+                    ShutdownState::Terminate => {
+                        warn!("🛑 Second shutdown signal received. Stopping now.");
+                        break;
+                    }
+                    ShutdownState::Running => {}
                 }
+            }
+        });
+
+        let renderer = self.dashboard.as_ref().map(|dashboard| {
+            dashboard.start(JobQueue::new(
+                self.media_root.clone(),
+                self.work_root.clone(),
+            ))
+        });
 
-                // Try to claim and process a job
-                job_result = self.process_next_job(&queue, &processor) => {
-                    match job_result {
-                        Ok(true) => {
-                            // Job was processed, continue immediately to check for more
-                            continue;
+        let mut stats = WorkStats::new();
+        // Only notify about a drained queue after we've actually processed something
+        let mut drain_notified = true;
+        let mut pause_logged = false;
+
+        // A batch run handles the jobs queued now; retried jobs are not picked up again
+        let batch_size = if self.once {
+            let pending = queue.pending_count().await?;
+            info!("📦 Processing {} queued jobs, then exiting.", pending);
+            Some(pending as u64)
+        } else {
+            None
+        };
+
+        while *shutdown.borrow_and_update() == ShutdownState::Running {
+            // Try to claim and process a job; a running job is only interrupted
+            // by a second signal
+            match self
+                .process_next_job(&queue, &processor, &mut stats, &shutdown)
+                .await
+            {
+                Ok(true) => {
+                    if let Some(batch_size) = batch_size {
+                        if !self.continue_on_error && stats.jobs_failed > 0 {
+                            warn!("Stopping batch after a failed job.");
+                            break;
                         }
-                        Ok(false) => {
-                            // No job available, sleep with progress bar
-                            let sleep_duration = config.sleep_interval;
-
-                            if sleep_duration > 5 {
-                                // Show progress bar for sleep intervals longer than 5 seconds
-                                let pb = ProgressBar::new(sleep_duration);
-                                pb.set_style(
-                                    ProgressStyle::with_template(
-                                        "💤 Waiting for jobs {bar:30.cyan/blue} {pos}/{len}s {msg}"
-                                    ).unwrap()
-                                    .progress_chars("█▉▊▋▌▍▎▏ ")
-                                );
-                                pb.set_message("Watching queue...");
-
-                                for _i in 0..sleep_duration {
-                                    tokio::time::sleep(Duration::from_secs(1)).await;
-                                    pb.inc(1);
-                                }
-
-                                pb.finish_and_clear();
-                            } else {
-                                tokio::time::sleep(Duration::from_secs(sleep_duration)).await;
-                            }
+                        if stats.jobs_completed + stats.jobs_failed >= batch_size {
+                            break;
                         }
-                        Err(e) => {
-                            error!("Error processing job: {}", e);
-                            // Sleep a bit before retrying
-                            tokio::time::sleep(Duration::from_secs(10)).await;
+                    }
+
+                    // Job was processed, continue immediately to check for more
+                    drain_notified = false;
+                    self.report_remaining(&queue, &mut stats).await;
+                }
+                Ok(false)
+                    if batch_size.is_some() && !queue.is_paused() && !self.load_throttled() =>
+                {
+                    break
+                }
+                Ok(false) => {
+                    if queue.is_paused() {
+                        if !pause_logged {
+                            info!("⏸️ Queue is paused. Run `plexify resume` to continue.");
+                            pause_logged = true;
+                        }
+                    } else if self.load_throttled() {
+                        // The governor logs when it starts and stops throttling
+                    } else {
+                        pause_logged = false;
+                        if !drain_notified {
+                            info!("📭 Queue is empty.");
+                            self.notify(&stats, NotificationEvent::QueueDrained).await;
+                            drain_notified = true;
                         }
                     }
+
+                    // No job available, sleep until the next poll, a wakeup or a signal
+                    let wakeup = async {
+                        match &self.wakeup {
+                            Some(wakeup) => wakeup.notified().await,
+                            None => std::future::pending().await,
+                        }
+                    };
+                    tokio::select! {
+                        _ = Self::wait_for_jobs(config.sleep_interval) => {}
+                        _ = wakeup => {}
+                        _ = shutdown.changed() => {}
+                    }
+                }
+                Err(e) => {
+                    error!("Error processing job: {}", e);
+                    // Sleep a bit before retrying
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs(10)) => {}
+                        _ = shutdown.changed() => {}
+                    }
                 }
             }
         }
 
+        signal_task.abort();
+        if let Some(renderer) = renderer {
+            renderer.stop();
+        }
+        self.notify(&stats, NotificationEvent::WorkerStopped).await;
+
+        if let Some(server) = metrics_server {
+            server.abort();
+        }
+
+        if batch_size.is_some() {
+            info!(
+                "📦 Batch finished: {} completed, {} failed.",
+                stats.jobs_completed, stats.jobs_failed
+            );
+            for job_name in &stats.failed_jobs {
+                warn!("❌ Failed: {}", job_name);
+            }
+            if !self.continue_on_error && stats.jobs_failed > 0 {
+                return Err(anyhow!(
+                    "Batch stopped after {} failed",
+                    stats.failed_jobs[0]
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the ffmpeg processor with every worker option applied, so `--file`
+    /// runs exactly what the queue would
+    fn build_processor(
+        &self,
+        config: Config,
+        progress_callback: Option<ProgressCallback>,
+    ) -> FFmpegProcessor {
+        FFmpegProcessor::new(config, self.background_mode)
+            .with_job_timeout(self.job_timeout)
+            .with_stall_timeout(self.stall_timeout)
+            .with_oversize_policy(self.oversize_policy.clone())
+            .with_min_free_space(self.min_free_space)
+            .with_progress_callback(progress_callback)
+            .with_no_move(self.no_move)
+            .with_spill_dir(self.spill_dir.clone())
+            .with_output_permissions(self.output_permissions.clone())
+            .with_preserve_mtime(self.preserve_mtime)
+            .with_post_processors(self.post_processors.clone())
+    }
+
+    /// Run the encode for one file outside the queue and print what happened.
+    /// The source is left in place so the run can be repeated.
+    async fn process_single_file(&self, file: &Path, processor: &FFmpegProcessor) -> Result<()> {
+        let file_type = JobProcessor::determine_file_type(file).map_err(|e| anyhow!(e))?;
+        let relative_path = file.strip_prefix(&self.media_root).unwrap_or(file);
+
+        // The queue is only needed to build the job, nothing is written to it
+        let queue = JobQueue::new(self.media_root.clone(), self.work_root.clone());
+        let config = JobProcessorConfig::from_preset(None)?;
+        let job = JobProcessor::new(&queue, &config, &self.media_root)
+            .build_job(relative_path, file_type)?;
+
+        info!("🎯 Processing single file: {}", job.display_name());
+        let input_path = job.full_input_path(Some(&self.media_root));
+        let input_size = tokio::fs::metadata(&input_path).await.map(|m| m.len()).ok();
+        let started = Instant::now();
+
+        let outcome = processor
+            .process_job(&job, Some(&self.media_root), None)
+            .await?;
+        let output_size = if outcome.kept_original {
+            None
+        } else {
+            tokio::fs::metadata(job.full_output_path(Some(&self.media_root)))
+                .await
+                .map(|m| m.len())
+                .ok()
+        };
+
+        let result = JobResult {
+            quality_settings: outcome.quality_settings,
+            ffmpeg_args: outcome.ffmpeg_args,
+            input_size,
+            output_size,
+            elapsed_secs: started.elapsed().as_secs_f64(),
+            output_checksum: None,
+        };
+        println!("{}", serde_json::to_string_pretty(&result)?);
         Ok(())
     }
 
+    /// Print the ffmpeg command each queued job would run, leaving the queue untouched
+    async fn print_commands(&self, queue: &JobQueue, processor: &FFmpegProcessor) -> Result<()> {
+        for job in queue.pending_jobs().await? {
+            let media_root = if job.input_path.is_absolute() {
+                None
+            } else {
+                Some(self.media_root.as_path())
+            };
+            let cmd = match processor
+                .job_command(&job, media_root, Some(&queue.in_progress_dir))
+                .await
+            {
+                Ok(cmd) => cmd,
+                Err(e) => {
+                    warn!("Skipping {}: {}", job.display_name(), e);
+                    continue;
+                }
+            };
+            println!("# {}", job.display_name());
+            for line in job.notes.iter().flat_map(|notes| notes.lines()) {
+                println!("# {}", line);
+            }
+            for (key, value) in &job.env_snapshot {
+                println!("# queued with {}={}", key, value);
+            }
+            println!("{}", render_command(&cmd));
+        }
+        Ok(())
+    }
+
+    /// Resolve once a second shutdown signal asks to stop immediately
+    async fn wait_for_terminate(mut shutdown: watch::Receiver<ShutdownState>) {
+        loop {
+            if *shutdown.borrow_and_update() == ShutdownState::Terminate {
+                return;
+            }
+            if shutdown.changed().await.is_err() {
+                // The signal handler is gone, so no stop can arrive anymore
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Sleep between queue polls, with a progress bar for longer intervals
+    async fn wait_for_jobs(sleep_duration: u64) {
+        if sleep_duration > 5 {
+            // Show progress bar for sleep intervals longer than 5 seconds
+            let pb = output::progress_bar(sleep_duration);
+            pb.set_style(
+                ProgressStyle::with_template(
+                    "💤 Waiting for jobs {bar:30.cyan/blue} {pos}/{len}s {msg}",
+                )
+                .unwrap()
+                .progress_chars("█▉▊▋▌▍▎▏ "),
+            );
+            pb.set_message("Watching queue...");
+
+            for _i in 0..sleep_duration {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                pb.inc(1);
+            }
+
+            pb.finish_and_clear();
+        } else {
+            tokio::time::sleep(Duration::from_secs(sleep_duration)).await;
+        }
+    }
+
+    /// Send a notification if a notifier is configured
+    async fn notify(&self, stats: &WorkStats, event: NotificationEvent) {
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(stats.to_payload(event)).await;
+        }
+    }
+
+    /// Update the metrics counters and the dashboard, and clear the per-job gauges
+    /// after a job finishes
+    fn record_metrics(&self, succeeded: bool) {
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.state().finish_job(succeeded);
+        }
+        if let Some(metrics) = &self.metrics {
+            if succeeded {
+                metrics.record_completed();
+            } else {
+                metrics.record_failed();
+            }
+            metrics.reset_job_gauges();
+        }
+    }
+
+    /// Log how many jobs are left after a job, when the count has changed. An
+    /// empty queue is announced separately. Returns the count if it was logged.
+    async fn report_remaining(&self, queue: &JobQueue, stats: &mut WorkStats) -> Option<usize> {
+        let pending = match queue.pending_count().await {
+            Ok(pending) => pending,
+            Err(e) => {
+                debug!("Could not count pending jobs: {}", e);
+                return None;
+            }
+        };
+        if !stats.remaining_changed(pending) || pending == 0 {
+            return None;
+        }
+        info!(
+            "📋 {} job{} remaining",
+            pending,
+            if pending == 1 { "" } else { "s" }
+        );
+        Some(pending)
+    }
+
+    /// Whether the load governor held off the last claim
+    fn load_throttled(&self) -> bool {
+        self.load_governor
+            .as_ref()
+            .is_some_and(|governor| governor.is_throttled())
+    }
+
     /// Try to claim and process the next job from the queue
     /// Returns Ok(true) if a job was processed, Ok(false) if no job was available
     async fn process_next_job(
         &self,
         queue: &JobQueue,
         processor: &FFmpegProcessor,
+        stats: &mut WorkStats,
+        shutdown: &watch::Receiver<ShutdownState>,
     ) -> Result<bool> {
+        if queue.is_paused() {
+            return Ok(false);
+        }
+        if self
+            .load_governor
+            .as_ref()
+            .is_some_and(|governor| governor.check())
+        {
+            return Ok(false);
+        }
+
         let priority = if self.priority_mode == JobPriority::None {
             None
         } else {
@@ -132,10 +775,17 @@ impl WorkCommand {
         };
 
         if let Some(claimed_job) = queue.claim_job(priority).await? {
-            info!("➡️ Claimed job: {}", claimed_job.job_name());
-
             // Get the job details directly from the job file
             let job = &claimed_job.job;
+            info!("➡️ Claimed job: {}", job.display_name());
+            if let Some(notes) = &job.notes {
+                info!("📝 Notes: {}", notes);
+            }
+            debug!(
+                "Job file: {}, input: {:?}",
+                claimed_job.job_name(),
+                job.input_path
+            );
 
             // Process the job with FFmpeg using the job's own media_root (for absolute paths) or self.media_root (for relative paths)
             let media_root = if job.input_path.is_absolute() {
@@ -144,11 +794,25 @@ impl WorkCommand {
                 Some(self.media_root.as_path())
             };
 
-            let job_name = claimed_job.job_name().to_string();
-            let work_folder = &queue.in_progress_dir;
+            let job_name = job.display_name();
+            let work_folder = &processor
+                .work_folder_for(job, media_root, &queue.in_progress_dir)
+                .await;
 
-            // Create a progress bar for job processing
-            let job_pb = ProgressBar::new_spinner();
+            // Record the input size before the source gets disabled
+            let input_size = tokio::fs::metadata(job.full_input_path(media_root))
+                .await
+                .map(|m| m.len())
+                .ok();
+
+            // Create a progress bar for job processing; the dashboard shows progress itself
+            let job_pb = match &self.dashboard {
+                Some(dashboard) => {
+                    dashboard.state().start_job(&job_name);
+                    ProgressBar::hidden()
+                }
+                None => output::spinner(),
+            };
             job_pb.set_style(
                 ProgressStyle::with_template("{spinner:.green} {msg}")
                     .unwrap()
@@ -157,26 +821,87 @@ impl WorkCommand {
             job_pb.set_message(format!("Processing: {}", job_name));
             job_pb.enable_steady_tick(Duration::from_millis(120));
 
-            match processor
-                .process_job(job, media_root, Some(work_folder))
-                .await
-            {
-                Ok(_) => {
+            let started = Instant::now();
+            // Hold a read slot (if limited) for as long as ffmpeg reads the input
+            let encode = async {
+                let _slot = match &self.read_slots {
+                    Some(read_slots) => Some(read_slots.acquire().await?),
+                    None => None,
+                };
+                processor
+                    .process_job(job, media_root, Some(work_folder))
+                    .await
+            };
+            let result = tokio::select! {
+                result = encode => result,
+                _ = Self::wait_for_terminate(shutdown.clone()) => {
+                    // Dropping the encode kills ffmpeg; hand the job back for next time
+                    job_pb.finish_with_message(format!("🛑 Stopped: {}", job_name));
+                    if let Some(dashboard) = &self.dashboard {
+                        dashboard.state().clear_job();
+                    }
+                    // A dropped --no-move encode leaves its hidden staging file behind
+                    if self.no_move {
+                        let staging_path = job.staging_output_path(media_root);
+                        if let Err(e) = tokio::fs::remove_file(&staging_path).await {
+                            debug!("No staged output to remove at {:?}: {}", staging_path, e);
+                        }
+                    }
+                    claimed_job.return_to_queue().await?;
+                    return Ok(true);
+                }
+            };
+            match result {
+                Ok(outcome) if outcome.kept_original => {
+                    job_pb.finish_with_message(format!("📦 Kept original: {}", job_name));
+                    stats.jobs_completed += 1;
+                    self.record_metrics(true);
+                    let result = JobResult {
+                        quality_settings: outcome.quality_settings,
+                        ffmpeg_args: outcome.ffmpeg_args,
+                        input_size,
+                        output_size: None,
+                        elapsed_secs: started.elapsed().as_secs_f64(),
+                        output_checksum: None,
+                    };
+                    claimed_job.complete_with_result(&result).await?;
+                }
+                Ok(outcome) => {
+                    // Move file from work folder to media folder (already in place with --no-move)
                     job_pb.set_message("Moving output file...");
-
-                    // Move file from work folder to media folder
-                    if let Err(e) = processor
-                        .move_to_destination(job, media_root, work_folder)
-                        .await
-                    {
+                    let moved = if self.no_move {
+                        Ok(())
+                    } else {
+                        processor
+                            .move_to_destination(
+                                job,
+                                media_root,
+                                work_folder,
+                                outcome.source_modified,
+                            )
+                            .await
+                    };
+                    if let Err(e) = moved {
                         error!("Failed to move file from work folder: {}", e);
                         job_pb.finish_and_clear();
+                        stats.record_failure(&job_name);
+                        self.record_metrics(false);
                         claimed_job.return_to_queue().await?;
                         return Ok(true);
                     }
 
+                    let output_path = job.full_output_path(media_root);
+                    let output_size = tokio::fs::metadata(&output_path)
+                        .await
+                        .map(|m| m.len())
+                        .ok();
+                    if let (Some(input_size), Some(output_size)) = (input_size, output_size) {
+                        stats.bytes_saved += input_size as i64 - output_size as i64;
+                    }
+
                     // Disable source files if configured
-                    if job.post_processing.disable_source_files {
+                    // An in-place remux has no separate source left to disable
+                    if job.post_processing.disable_source_files && !job.replaces_input() {
                         job_pb.set_message("Cleaning up source files...");
                         if let Err(e) = processor.disable_source_files(job, media_root).await {
                             warn!("Failed to disable source files: {}", e);
@@ -184,16 +909,62 @@ impl WorkCommand {
                         }
                     }
 
+                    if self.done_marker {
+                        let marker = Job::done_marker_path(&output_path);
+                        if let Err(e) = tokio::fs::write(&marker, format!("{}\n", job.id)).await {
+                            warn!("Failed to write done marker {:?}: {}", marker, e);
+                        }
+                    }
+
+                    // Jobs queued with a checksum get one for their output as well
+                    let output_checksum = match &job.input_checksum {
+                        Some(input_checksum) => {
+                            Checksum::compute(&output_path, input_checksum.algorithm)
+                                .await
+                                .unwrap_or_else(|e| {
+                                    warn!("Failed to checksum {:?}: {}", output_path, e);
+                                    None
+                                })
+                        }
+                        None => None,
+                    };
+
                     job_pb.finish_with_message(format!("✅ Completed: {}", job_name));
-                    claimed_job.complete().await?;
+                    if let Some(hook) = &self.on_complete {
+                        hook.run("on-complete", job, media_root, None).await;
+                    }
+                    stats.jobs_completed += 1;
+                    self.record_metrics(true);
+                    let result = JobResult {
+                        quality_settings: outcome.quality_settings,
+                        ffmpeg_args: outcome.ffmpeg_args,
+                        input_size,
+                        output_size,
+                        elapsed_secs: started.elapsed().as_secs_f64(),
+                        output_checksum,
+                    };
+                    claimed_job.complete_with_result(&result).await?;
                 }
                 Err(e) => {
                     job_pb.finish_with_message(format!("❌ Failed: {}", job_name));
                     error!("❌ Conversion FAILED: {}", e);
-                    claimed_job.return_to_queue().await?;
+                    stats.record_failure(&job_name);
+                    self.record_metrics(false);
 
-                    // Sleep a bit to avoid rapid retries of problematic jobs
-                    tokio::time::sleep(Duration::from_secs(10)).await;
+                    if error::is_retryable(&e) && !job.retries_exhausted() {
+                        claimed_job.retry().await?;
+
+                        // Sleep a bit to avoid rapid retries of problematic jobs
+                        tokio::time::sleep(Duration::from_secs(10)).await;
+                    } else {
+                        // Retrying cannot help (e.g. the input is gone) or the retries ran out
+                        let failed_job = job.clone();
+                        claimed_job.fail().await?;
+                        if let Some(hook) = &self.on_failure {
+                            hook.run("on-failure", &failed_job, media_root, Some(&e.to_string()))
+                                .await;
+                        }
+                    }
                 }
             }
 
@@ -207,6 +978,8 @@ impl WorkCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(unix)]
+    use crate::test_support;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -222,6 +995,56 @@ mod tests {
         assert_eq!(work_cmd.media_root, temp_dir.path());
         assert!(!work_cmd.background_mode);
         assert_eq!(work_cmd.priority_mode, JobPriority::None);
+        assert_eq!(work_cmd.job_timeout, None);
+        assert!(work_cmd.metrics.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_work_command_with_metrics_addr() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_cmd = WorkCommand::new(
+            temp_dir.path().to_path_buf(),
+            temp_dir.path().to_path_buf(),
+            false,
+            JobPriority::None,
+        )
+        .with_metrics_addr(Some("127.0.0.1:9184".parse().unwrap()));
+
+        assert!(work_cmd.metrics.is_some());
+
+        work_cmd.record_metrics(true);
+        work_cmd.record_metrics(false);
+        let text = work_cmd.metrics.as_ref().unwrap().render();
+        assert!(text.contains("plexify_jobs_completed_total 1\n"));
+        assert!(text.contains("plexify_jobs_failed_total 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_work_command_with_job_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_cmd = WorkCommand::new(
+            temp_dir.path().to_path_buf(),
+            temp_dir.path().to_path_buf(),
+            false,
+            JobPriority::None,
+        )
+        .with_job_timeout(Some(Duration::from_secs(3600)));
+
+        assert_eq!(work_cmd.job_timeout, Some(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_work_stats_payload() {
+        let mut stats = WorkStats::new();
+        stats.jobs_completed = 5;
+        stats.jobs_failed = 2;
+        stats.bytes_saved = 4096;
+
+        let payload = stats.to_payload(NotificationEvent::QueueDrained);
+        assert_eq!(payload.event, NotificationEvent::QueueDrained);
+        assert_eq!(payload.jobs_completed, 5);
+        assert_eq!(payload.jobs_failed, 2);
+        assert_eq!(payload.bytes_saved, 4096);
     }
 
     #[tokio::test]
@@ -236,4 +1059,608 @@ mod tests {
         let result = work_cmd.execute().await;
         assert!(result.is_err());
     }
+
+    /// A shutdown channel that stays in the running state while the sender is kept
+    fn running() -> (watch::Sender<ShutdownState>, watch::Receiver<ShutdownState>) {
+        watch::channel(ShutdownState::Running)
+    }
+
+    #[test]
+    fn test_shutdown_state_transitions() {
+        let state = ShutdownState::Running;
+        let state = state.on_signal();
+        assert_eq!(state, ShutdownState::Draining);
+        let state = state.on_signal();
+        assert_eq!(state, ShutdownState::Terminate);
+        assert_eq!(state.on_signal(), ShutdownState::Terminate);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_second_signal_stops_running_job() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().to_path_buf();
+        std::fs::write(media_root.join("slow.mkv"), "source").unwrap();
+
+        // Fake ffmpeg that never finishes
+        let script = test_support::script(temp_dir.path(), "ffmpeg", "sleep 30\n");
+        let processor = FFmpegProcessor::new(
+            Config {
+                ffmpeg_path: script.to_string_lossy().into_owned(),
+                ..Config::default()
+            },
+            false,
+        );
+
+        let work_cmd = WorkCommand::new(
+            media_root.clone(),
+            media_root.clone(),
+            false,
+            JobPriority::None,
+        );
+        let queue = JobQueue::new(media_root.clone(), media_root.clone());
+        queue.init().await.unwrap();
+        let job = crate::job::Job::new(
+            PathBuf::from("slow.mkv"),
+            crate::job::MediaFileType::Mkv,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        queue.enqueue_job(&job).await.unwrap();
+
+        let (shutdown_tx, shutdown) = watch::channel(ShutdownState::Running);
+        let signals = tokio::spawn(async move {
+            // The first signal lets the job keep running...
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            shutdown_tx.send_replace(ShutdownState::Draining);
+            // ...the second one stops it
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            shutdown_tx.send_replace(ShutdownState::Terminate);
+            // Handed back so the channel stays open until the test is done
+            shutdown_tx
+        });
+
+        let start = Instant::now();
+        let mut stats = WorkStats::new();
+        assert!(work_cmd
+            .process_next_job(&queue, &processor, &mut stats, &shutdown)
+            .await
+            .unwrap());
+
+        assert!(start.elapsed() >= Duration::from_millis(400));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(stats.jobs_failed, 0);
+        // The job is back in the queue for the next run
+        assert_eq!(queue.pending_count().await.unwrap(), 1);
+        drop(signals.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_remaining_count_reported_after_each_job() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().to_path_buf();
+        let work_cmd = WorkCommand::new(
+            media_root.clone(),
+            media_root.clone(),
+            false,
+            JobPriority::None,
+        );
+        let queue = JobQueue::new(media_root.clone(), media_root.clone());
+        queue.init().await.unwrap();
+
+        // Missing inputs fail right away, which is enough to move through the queue
+        for name in ["a.mkv", "b.mkv", "c.mkv"] {
+            let job = crate::job::Job::new(
+                PathBuf::from(name),
+                crate::job::MediaFileType::Mkv,
+                crate::job::QualitySettings::default(),
+                crate::job::PostProcessingSettings::default(),
+                &media_root,
+            );
+            queue.enqueue_job(&job).await.unwrap();
+        }
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let mut stats = WorkStats::new();
+        let (_shutdown_tx, shutdown) = running();
+        let mut reported = Vec::new();
+        for _ in 0..3 {
+            assert!(work_cmd
+                .process_next_job(&queue, &processor, &mut stats, &shutdown)
+                .await
+                .unwrap());
+            reported.push(work_cmd.report_remaining(&queue, &mut stats).await);
+        }
+        // The empty queue has its own message
+        assert_eq!(reported, vec![Some(2), Some(1), None]);
+
+        // An unchanged count is not logged again
+        assert!(stats.remaining_changed(5));
+        assert!(!stats.remaining_changed(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_on_failure_hook_gets_job_and_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().to_path_buf();
+        let record = temp_dir.path().join("hook.log");
+        let hook_script = test_support::script(
+            temp_dir.path(),
+            "hook",
+            &format!("printf '%s\\n' \"$@\" > '{}'\n", record.display()),
+        );
+
+        let work_cmd = WorkCommand::new(
+            media_root.clone(),
+            media_root.clone(),
+            false,
+            JobPriority::None,
+        )
+        .with_on_failure(Some(
+            Hook::parse(&format!(
+                "{} {{id}} {{input}} {{error}}",
+                hook_script.display()
+            ))
+            .unwrap(),
+        ));
+        let queue = JobQueue::new(media_root.clone(), media_root.clone());
+        queue.init().await.unwrap();
+        let job = crate::job::Job::new(
+            PathBuf::from("gone.mkv"),
+            crate::job::MediaFileType::Mkv,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        queue.enqueue_job(&job).await.unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let mut stats = WorkStats::new();
+        let (_shutdown_tx, shutdown) = running();
+        assert!(work_cmd
+            .process_next_job(&queue, &processor, &mut stats, &shutdown)
+            .await
+            .unwrap());
+
+        let input = media_root.join("gone.mkv");
+        let recorded = std::fs::read_to_string(&record).unwrap();
+        assert_eq!(
+            recorded.lines().collect::<Vec<_>>(),
+            vec![
+                job.id.to_string(),
+                input.to_string_lossy().into_owned(),
+                format!("Input file does not exist: {input:?}"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_on_failure_hook_runs_when_retries_run_out() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        std::fs::create_dir_all(&media_root).unwrap();
+        std::fs::write(media_root.join("episode.mkv"), "source").unwrap();
+        let record = temp_dir.path().join("hook.log");
+        let hook_script = test_support::script(
+            temp_dir.path(),
+            "hook",
+            &format!("printf '%s\\n' \"$@\" > '{}'\n", record.display()),
+        );
+        let ffmpeg = test_support::script(temp_dir.path(), "ffmpeg", "exit 1\n");
+
+        let work_cmd = WorkCommand::new(
+            media_root.clone(),
+            temp_dir.path().to_path_buf(),
+            false,
+            JobPriority::None,
+        )
+        .with_on_failure(Some(
+            Hook::parse(&format!("{} {{id}}", hook_script.display())).unwrap(),
+        ));
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+        let mut job = crate::job::Job::new(
+            PathBuf::from("episode.mkv"),
+            crate::job::MediaFileType::Mkv,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        job.max_retries = 0;
+        queue.enqueue_job(&job).await.unwrap();
+
+        let config = Config {
+            ffmpeg_path: ffmpeg.to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        let processor = FFmpegProcessor::new(config, false);
+        let mut stats = WorkStats::new();
+        let (_shutdown_tx, shutdown) = running();
+        assert!(work_cmd
+            .process_next_job(&queue, &processor, &mut stats, &shutdown)
+            .await
+            .unwrap());
+
+        assert!(queue.failed_dir.join(job.job_filename()).exists());
+        assert_eq!(queue.pending_count().await.unwrap(), 0);
+        assert_eq!(std::fs::read_to_string(&record).unwrap().trim(), job.id);
+    }
+
+    #[tokio::test]
+    async fn test_job_with_missing_input_is_not_retried() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().to_path_buf();
+        let work_cmd = WorkCommand::new(
+            media_root.clone(),
+            media_root.clone(),
+            false,
+            JobPriority::None,
+        );
+        let queue = JobQueue::new(media_root.clone(), media_root.clone());
+        queue.init().await.unwrap();
+
+        let job = crate::job::Job::new(
+            PathBuf::from("gone.mkv"),
+            crate::job::MediaFileType::Mkv,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        queue.enqueue_job(&job).await.unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let mut stats = WorkStats::new();
+        let (_shutdown_tx, shutdown) = running();
+        assert!(work_cmd
+            .process_next_job(&queue, &processor, &mut stats, &shutdown)
+            .await
+            .unwrap());
+
+        assert_eq!(stats.jobs_failed, 1);
+        assert!(queue.failed_dir.join(job.job_filename()).exists());
+        assert_eq!(queue.pending_count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_print_commands_skips_jobs_that_cannot_be_built() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().to_path_buf();
+        let work_cmd = WorkCommand::new(
+            media_root.clone(),
+            media_root.clone(),
+            false,
+            JobPriority::None,
+        );
+        let queue = JobQueue::new(media_root.clone(), media_root.clone());
+        queue.init().await.unwrap();
+
+        // A WebM job needs its subtitle to build the command
+        std::fs::write(media_root.join("clip.webm"), "").unwrap();
+        let broken = crate::job::Job::new(
+            PathBuf::from("clip.webm"),
+            crate::job::MediaFileType::WebM,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        let fine = crate::job::Job::new(
+            PathBuf::from("movie.mkv"),
+            crate::job::MediaFileType::Mkv,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        queue.enqueue_job(&broken).await.unwrap();
+        queue.enqueue_job(&fine).await.unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        work_cmd.print_commands(&queue, &processor).await.unwrap();
+        assert_eq!(queue.pending_count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_paused_queue_is_not_claimed() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().to_path_buf();
+        let work_cmd = WorkCommand::new(
+            media_root.clone(),
+            media_root.clone(),
+            false,
+            JobPriority::None,
+        );
+        let queue = JobQueue::new(media_root.clone(), media_root.clone());
+        queue.init().await.unwrap();
+
+        let job = crate::job::Job::new(
+            PathBuf::from("gone.mkv"),
+            crate::job::MediaFileType::Mkv,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        queue.enqueue_job(&job).await.unwrap();
+        queue.pause().await.unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let mut stats = WorkStats::new();
+        let (_shutdown_tx, shutdown) = running();
+        assert!(!work_cmd
+            .process_next_job(&queue, &processor, &mut stats, &shutdown)
+            .await
+            .unwrap());
+        assert_eq!(queue.pending_count().await.unwrap(), 1);
+
+        queue.resume().await.unwrap();
+        assert!(work_cmd
+            .process_next_job(&queue, &processor, &mut stats, &shutdown)
+            .await
+            .unwrap());
+        assert_eq!(queue.pending_count().await.unwrap(), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_once_continues_past_failed_job() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        std::fs::create_dir_all(&media_root).unwrap();
+        let _ffmpeg = test_support::fake_tool(
+            "PLEXIFY_FFMPEG_PATH",
+            temp_dir.path(),
+            "ffmpeg",
+            test_support::CONVERTING_FFMPEG,
+        );
+
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+        for name in ["a.mkv", "missing.mkv", "b.mkv"] {
+            if name != "missing.mkv" {
+                std::fs::write(media_root.join(name), "source").unwrap();
+            }
+            let job = crate::job::Job::new(
+                PathBuf::from(name),
+                crate::job::MediaFileType::Mkv,
+                crate::job::QualitySettings::default(),
+                crate::job::PostProcessingSettings::default(),
+                &media_root,
+            );
+            queue.enqueue_job(&job).await.unwrap();
+        }
+
+        WorkCommand::new(
+            media_root.clone(),
+            temp_dir.path().to_path_buf(),
+            false,
+            JobPriority::None,
+        )
+        .with_once(true)
+        .execute()
+        .await
+        .unwrap();
+
+        assert!(media_root.join("a.mp4").exists());
+        assert!(media_root.join("b.mp4").exists());
+        assert_eq!(std::fs::read_dir(&queue.failed_dir).unwrap().count(), 1);
+        assert_eq!(queue.pending_count().await.unwrap(), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_done_marker_only_on_success() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        std::fs::create_dir_all(&media_root).unwrap();
+        let _ffmpeg = test_support::fake_tool(
+            "PLEXIFY_FFMPEG_PATH",
+            temp_dir.path(),
+            "ffmpeg",
+            test_support::CONVERTING_FFMPEG,
+        );
+
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+        std::fs::write(media_root.join("a.mkv"), "source").unwrap();
+        for name in ["a.mkv", "missing.mkv"] {
+            let job = Job::new(
+                PathBuf::from(name),
+                crate::job::MediaFileType::Mkv,
+                crate::job::QualitySettings::default(),
+                crate::job::PostProcessingSettings::default(),
+                &media_root,
+            );
+            queue.enqueue_job(&job).await.unwrap();
+        }
+
+        WorkCommand::new(
+            media_root.clone(),
+            temp_dir.path().to_path_buf(),
+            false,
+            JobPriority::None,
+        )
+        .with_once(true)
+        .with_done_marker(true)
+        .execute()
+        .await
+        .unwrap();
+
+        assert!(media_root.join("a.mp4.plexify-done").exists());
+        assert!(!media_root.join("missing.mp4.plexify-done").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_on_complete_hook_gets_substituted_arguments() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        let season = media_root.join("Series/Show/Season 01");
+        std::fs::create_dir_all(&season).unwrap();
+        std::fs::write(season.join("Show S01E02.mkv"), "source").unwrap();
+
+        let _ffmpeg = test_support::fake_tool(
+            "PLEXIFY_FFMPEG_PATH",
+            temp_dir.path(),
+            "ffmpeg",
+            test_support::CONVERTING_FFMPEG,
+        );
+        // The hook records one argument per line
+        let record = temp_dir.path().join("hook.log");
+        let hook_script = test_support::script(
+            temp_dir.path(),
+            "hook",
+            &format!("printf '%s\\n' \"$@\" > '{}'\n", record.display()),
+        );
+
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+        for name in ["Series/Show/Season 01/Show S01E02.mkv", "missing.mkv"] {
+            let job = Job::new(
+                PathBuf::from(name),
+                crate::job::MediaFileType::Mkv,
+                crate::job::QualitySettings::default(),
+                crate::job::PostProcessingSettings::default(),
+                &media_root,
+            );
+            queue.enqueue_job(&job).await.unwrap();
+        }
+
+        let hook = Hook::parse(&format!(
+            "{} {{output}} {{series}} S{{season}}E{{episode}}",
+            hook_script.display()
+        ))
+        .unwrap();
+        WorkCommand::new(
+            media_root.clone(),
+            temp_dir.path().to_path_buf(),
+            false,
+            JobPriority::None,
+        )
+        .with_once(true)
+        .with_on_complete(Some(hook))
+        .execute()
+        .await
+        .unwrap();
+
+        // Only the successful job ran the hook
+        let recorded = std::fs::read_to_string(&record).unwrap();
+        assert_eq!(
+            recorded.lines().collect::<Vec<_>>(),
+            vec![
+                season.join("Show S01E02.mp4").to_string_lossy().as_ref(),
+                "Show",
+                "S01E02",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_no_move_encodes_next_to_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        let season = media_root.join("Series/Show/Season 01");
+        std::fs::create_dir_all(&season).unwrap();
+        std::fs::write(season.join("Show S01E01.mkv"), "source").unwrap();
+
+        // Fake ffmpeg that records where it was asked to write
+        let record = temp_dir.path().join("outputs.log");
+        let _ffmpeg = test_support::fake_tool(
+            "PLEXIFY_FFMPEG_PATH",
+            temp_dir.path(),
+            "ffmpeg",
+            &format!(
+                "for last; do :; done\necho \"$last\" >> '{}'\necho converted > \"$last\"\n",
+                record.display()
+            ),
+        );
+
+        let queue = JobQueue::new(media_root.clone(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+        let job = Job::new(
+            PathBuf::from("Series/Show/Season 01/Show S01E01.mkv"),
+            crate::job::MediaFileType::Mkv,
+            crate::job::QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            &media_root,
+        );
+        queue.enqueue_job(&job).await.unwrap();
+
+        WorkCommand::new(
+            media_root.clone(),
+            temp_dir.path().to_path_buf(),
+            false,
+            JobPriority::None,
+        )
+        .with_once(true)
+        .with_no_move(true)
+        .execute()
+        .await
+        .unwrap();
+
+        // ffmpeg wrote into the season folder, not the work folder
+        let written = std::fs::read_to_string(&record).unwrap();
+        assert_eq!(
+            written.trim(),
+            job.staging_output_path(None).to_string_lossy()
+        );
+        assert!(job.staging_output_path(None).starts_with(&season));
+        assert_eq!(
+            std::fs::read_to_string(season.join("Show S01E01.mp4")).unwrap(),
+            "converted\n"
+        );
+        assert!(!job.staging_output_path(None).exists());
+        assert_eq!(
+            std::fs::read_dir(&queue.in_progress_dir).unwrap().count(),
+            0
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_single_file_bypasses_queue() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("media");
+        let work_root = temp_dir.path().join("work");
+        std::fs::create_dir_all(&media_root).unwrap();
+        std::fs::write(media_root.join("episode.mkv"), "source").unwrap();
+        let added = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(media_root.join("episode.mkv"))
+            .unwrap()
+            .set_modified(added)
+            .unwrap();
+        let _ffmpeg = test_support::fake_tool(
+            "PLEXIFY_FFMPEG_PATH",
+            temp_dir.path(),
+            "ffmpeg",
+            test_support::CONVERTING_FFMPEG,
+        );
+
+        WorkCommand::new(
+            media_root.clone(),
+            work_root.clone(),
+            false,
+            JobPriority::None,
+        )
+        .with_file(Some(media_root.join("episode.mkv")))
+        .with_preserve_mtime(true)
+        .execute()
+        .await
+        .unwrap();
+
+        // Worker options apply to --file just like to queued jobs
+        let output = std::fs::metadata(media_root.join("episode.mp4")).unwrap();
+        assert_eq!(output.modified().unwrap(), added);
+        // The source stays for another run, and no queue was created
+        assert!(media_root.join("episode.mkv").exists());
+        assert!(!work_root.join("_queue").exists());
+    }
 }
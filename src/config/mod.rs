@@ -1,4 +1,5 @@
 use std::env;
+use std::path::{Path, PathBuf};
 
 /// Configuration for FFmpeg and application behavior
 #[derive(Debug, Clone)]
@@ -8,6 +9,10 @@ pub struct Config {
     pub ffmpeg_crf: String,
     pub ffmpeg_audio_bitrate: String,
     pub sleep_interval: u64,
+    pub ffmpeg_path: String,
+    pub ffprobe_path: String,
+    /// Prune completed job records older than this many days; keep them forever when unset
+    pub keep_completed_days: Option<u64>,
 }
 
 impl Config {
@@ -22,6 +27,12 @@ impl Config {
                 .unwrap_or_else(|_| "60".to_string())
                 .parse()
                 .unwrap_or(60),
+            ffmpeg_path: env::var("PLEXIFY_FFMPEG_PATH").unwrap_or_else(|_| "ffmpeg".to_string()),
+            ffprobe_path: env::var("PLEXIFY_FFPROBE_PATH")
+                .unwrap_or_else(|_| "ffprobe".to_string()),
+            keep_completed_days: env::var("PLEXIFY_KEEP_COMPLETED_DAYS")
+                .ok()
+                .and_then(|days| days.parse().ok()),
         }
     }
 }
@@ -33,6 +44,108 @@ impl Default for Config {
             ffmpeg_crf: "23".to_string(),
             ffmpeg_audio_bitrate: "128k".to_string(),
             sleep_interval: 60,
+            ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: "ffprobe".to_string(),
+            keep_completed_days: None,
         }
     }
 }
+
+/// Load `.env` files from the given directories, in order. Variables that are
+/// already set (in the real environment or by an earlier file) are never
+/// overridden. Missing files are skipped; returns the files that were loaded.
+pub fn load_env_files(dirs: &[&Path]) -> Vec<PathBuf> {
+    let mut loaded = Vec::new();
+    for dir in dirs {
+        let path = dir.join(".env");
+        if !path.is_file() || loaded.contains(&path) {
+            continue;
+        }
+        match dotenvy::from_path(&path) {
+            Ok(()) => loaded.push(path),
+            Err(e) => eprintln!("Warning: failed to load {}: {}", path.display(), e),
+        }
+    }
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_binary_paths_default() {
+        std::env::remove_var("PLEXIFY_FFMPEG_PATH");
+        std::env::remove_var("PLEXIFY_FFPROBE_PATH");
+
+        let config = Config::from_env();
+        assert_eq!(config.ffmpeg_path, "ffmpeg");
+        assert_eq!(config.ffprobe_path, "ffprobe");
+    }
+
+    #[test]
+    #[serial]
+    fn test_binary_paths_from_env() {
+        std::env::set_var("PLEXIFY_FFMPEG_PATH", "/opt/ffmpeg/bin/ffmpeg");
+        std::env::set_var("PLEXIFY_FFPROBE_PATH", "/opt/ffmpeg/bin/ffprobe");
+
+        let config = Config::from_env();
+        assert_eq!(config.ffmpeg_path, "/opt/ffmpeg/bin/ffmpeg");
+        assert_eq!(config.ffprobe_path, "/opt/ffmpeg/bin/ffprobe");
+
+        // Clean up
+        std::env::remove_var("PLEXIFY_FFMPEG_PATH");
+        std::env::remove_var("PLEXIFY_FFPROBE_PATH");
+    }
+
+    #[test]
+    #[serial]
+    fn test_keep_completed_days_from_env() {
+        std::env::remove_var("PLEXIFY_KEEP_COMPLETED_DAYS");
+        assert_eq!(Config::from_env().keep_completed_days, None);
+
+        std::env::set_var("PLEXIFY_KEEP_COMPLETED_DAYS", "30");
+        assert_eq!(Config::from_env().keep_completed_days, Some(30));
+
+        std::env::remove_var("PLEXIFY_KEEP_COMPLETED_DAYS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_env_files() {
+        let cwd = tempfile::TempDir::new().unwrap();
+        let media_root = tempfile::TempDir::new().unwrap();
+        let empty = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            cwd.path().join(".env"),
+            "FFMPEG_CRF=19\nFFMPEG_PRESET=slow\n",
+        )
+        .unwrap();
+        std::fs::write(
+            media_root.path().join(".env"),
+            "FFMPEG_CRF=30\nFFMPEG_AUDIO_BITRATE=192k\n",
+        )
+        .unwrap();
+        std::env::remove_var("FFMPEG_CRF");
+        std::env::remove_var("FFMPEG_AUDIO_BITRATE");
+        // Already set in the real environment, so .env must not override it
+        std::env::set_var("FFMPEG_PRESET", "medium");
+
+        let loaded = load_env_files(&[cwd.path(), media_root.path(), empty.path()]);
+        let config = Config::from_env();
+
+        assert_eq!(
+            loaded,
+            vec![cwd.path().join(".env"), media_root.path().join(".env")]
+        );
+        assert_eq!(config.ffmpeg_crf, "19");
+        assert_eq!(config.ffmpeg_preset, "medium");
+        assert_eq!(config.ffmpeg_audio_bitrate, "192k");
+
+        std::env::remove_var("FFMPEG_CRF");
+        std::env::remove_var("FFMPEG_PRESET");
+        std::env::remove_var("FFMPEG_AUDIO_BITRATE");
+    }
+}
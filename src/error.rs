@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Failure reasons callers may want to match on. Functions still return
+/// `anyhow::Result`; use `error.downcast_ref::<PlexifyError>()` to inspect them.
+#[derive(Debug, Error)]
+pub enum PlexifyError {
+    #[error("Input file does not exist: {0:?}")]
+    InputMissing(PathBuf),
+
+    #[error("Required subtitle file not found: {0:?}")]
+    SubtitleMissing(PathBuf),
+
+    #[error("FFmpeg conversion failed: {stderr}")]
+    FfmpegFailed { stderr: String },
+
+    #[error("FFmpeg timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("FFmpeg stalled: no progress for {0:?}")]
+    Stalled(Duration),
+
+    #[error("ffprobe failed for {path:?}: {stderr}")]
+    ProbeFailed { path: PathBuf, stderr: String },
+
+    #[error("Post-processing step '{step}' failed: {reason}")]
+    PostProcessFailed { step: String, reason: String },
+
+    #[error("Work folder output file does not exist: {0:?}")]
+    WorkOutputMissing(PathBuf),
+
+    #[error("{0:?} already exists")]
+    DestinationExists(PathBuf),
+
+    #[error("Could not move {from:?} to {to:?} on another filesystem")]
+    CrossDevice {
+        from: PathBuf,
+        to: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Media directory does not exist: {0:?}")]
+    MediaRootMissing(PathBuf),
+
+    #[error("Path is not a directory: {0:?}")]
+    NotADirectory(PathBuf),
+
+    #[error(
+        "Not enough free space for {path:?}: {available} bytes available, {required} bytes needed"
+    )]
+    InsufficientSpace {
+        path: PathBuf,
+        required: u64,
+        available: u64,
+    },
+
+    #[error("Path is not valid UTF-8, so it cannot be passed to ffmpeg: {0:?}")]
+    NonUtf8Path(PathBuf),
+
+    #[error("Failed to create job file {path:?}")]
+    JobFileCreate {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl PlexifyError {
+    /// Whether trying the same job again could succeed. Missing inputs stay
+    /// missing, while encoder failures may be transient. A failed post-processing
+    /// step would need the whole encode redone, and usually fails the same way.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self,
+            PlexifyError::InputMissing(_)
+                | PlexifyError::SubtitleMissing(_)
+                | PlexifyError::NonUtf8Path(_)
+                | PlexifyError::PostProcessFailed { .. }
+        )
+    }
+}
+
+/// Whether an error from a job run is worth retrying; errors that are not a
+/// `PlexifyError` are assumed to be transient
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<PlexifyError>()
+        .is_none_or(PlexifyError::is_retryable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_retryable_by_variant() {
+        let missing: anyhow::Error = PlexifyError::InputMissing(PathBuf::from("a.mkv")).into();
+        assert!(!is_retryable(&missing));
+
+        let failed: anyhow::Error = PlexifyError::FfmpegFailed {
+            stderr: "boom".to_string(),
+        }
+        .into();
+        assert!(is_retryable(&failed));
+        assert!(is_retryable(&anyhow!("something else")));
+
+        // A full destination disk may have room again later
+        let cross_device: anyhow::Error = PlexifyError::CrossDevice {
+            from: PathBuf::from("/work/a.mp4"),
+            to: PathBuf::from("/media/a.mp4"),
+            source: std::io::Error::from(std::io::ErrorKind::StorageFull),
+        }
+        .into();
+        assert!(is_retryable(&cross_device));
+
+        let post_process: anyhow::Error = PlexifyError::PostProcessFailed {
+            step: "MP4Box -inter 500 {output}".to_string(),
+            reason: "No such file or directory".to_string(),
+        }
+        .into();
+        assert!(!is_retryable(&post_process));
+
+        // Context added on the way up does not hide the variant
+        let wrapped = anyhow::Error::from(PlexifyError::SubtitleMissing(PathBuf::from("a.vtt")))
+            .context("processing job");
+        assert!(!is_retryable(&wrapped));
+    }
+}
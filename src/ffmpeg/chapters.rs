@@ -0,0 +1,106 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// `silencedetect` filter used to find the gaps between chapters: at least two
+/// seconds below -30 dB
+pub const SILENCE_FILTER: &str = "silencedetect=noise=-30dB:d=2";
+
+/// Chapters shorter than this are merged into the one before, so a quiet passage
+/// in the middle of a song does not split it
+const MIN_CHAPTER_SECS: f64 = 30.0;
+
+/// Where the chapter metadata for an output is written while it encodes
+pub fn chapters_path(output_path: &Path) -> PathBuf {
+    let mut name = output_path.as_os_str().to_owned();
+    name.push(".chapters.txt");
+    PathBuf::from(name)
+}
+
+/// The `silence_end` timestamps (in seconds) from ffmpeg's silencedetect log, e.g.
+/// `[silencedetect @ 0x5581] silence_end: 184.52 | silence_duration: 2.61`
+pub fn parse_silence_ends(stderr: &str) -> Vec<f64> {
+    stderr
+        .lines()
+        .filter_map(|line| {
+            let value = line.split("silence_end:").nth(1)?;
+            value.split_whitespace().next()?.parse().ok()
+        })
+        .collect()
+}
+
+/// Chapter start times: the beginning, then wherever the sound comes back after a
+/// silence, skipping any that would leave a chapter shorter than `MIN_CHAPTER_SECS`
+pub fn chapter_starts(silence_ends: &[f64], duration: f64) -> Vec<f64> {
+    let mut starts = vec![0.0];
+    for &end in silence_ends {
+        let last = *starts.last().unwrap_or(&0.0);
+        if end - last >= MIN_CHAPTER_SECS && duration - end >= MIN_CHAPTER_SECS {
+            starts.push(end);
+        }
+    }
+    starts
+}
+
+/// An `;FFMETADATA1` file with one chapter per start, the last running to `duration`
+pub fn ffmetadata(starts: &[f64], duration: f64) -> String {
+    let millis = |secs: f64| (secs * 1000.0).round() as u64;
+    let mut metadata = String::from(";FFMETADATA1\n");
+    for (index, start) in starts.iter().enumerate() {
+        let end = starts.get(index + 1).copied().unwrap_or(duration);
+        writeln!(metadata, "\n[CHAPTER]").unwrap();
+        writeln!(metadata, "TIMEBASE=1/1000").unwrap();
+        writeln!(metadata, "START={}", millis(*start)).unwrap();
+        writeln!(metadata, "END={}", millis(end)).unwrap();
+        writeln!(metadata, "title=Chapter {}", index + 1).unwrap();
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SILENCEDETECT_LOG: &str = "\
+Input #0, matroska,webm, from 'concert.mkv':
+  Duration: 00:10:00.00, start: 0.000000, bitrate: 1536 kb/s
+[silencedetect @ 0x55d1c8e0f8c0] silence_start: 0.0
+[silencedetect @ 0x55d1c8e0f8c0] silence_end: 2.5 | silence_duration: 2.5
+[silencedetect @ 0x55d1c8e0f8c0] silence_start: 181.02
+[silencedetect @ 0x55d1c8e0f8c0] silence_end: 184.52 | silence_duration: 3.5
+[silencedetect @ 0x55d1c8e0f8c0] silence_start: 197.4
+[silencedetect @ 0x55d1c8e0f8c0] silence_end: 199.9 | silence_duration: 2.5
+[silencedetect @ 0x55d1c8e0f8c0] silence_start: 402.11
+[silencedetect @ 0x55d1c8e0f8c0] silence_end: 405 | silence_duration: 2.89
+[silencedetect @ 0x55d1c8e0f8c0] silence_start: 588.3
+[silencedetect @ 0x55d1c8e0f8c0] silence_end: 591.8 | silence_duration: 3.5
+size=N/A time=00:10:00.00 bitrate=N/A speed= 812x
+";
+
+    #[test]
+    fn test_parse_silencedetect_into_chapters() {
+        let ends = parse_silence_ends(SILENCEDETECT_LOG);
+        assert_eq!(ends, vec![2.5, 184.52, 199.9, 405.0, 591.8]);
+
+        // The lead-in, the short gap after 184.52 and the fade-out don't make chapters
+        let starts = chapter_starts(&ends, 600.0);
+        assert_eq!(starts, vec![0.0, 184.52, 405.0]);
+
+        assert!(parse_silence_ends("no silence here\n").is_empty());
+        assert_eq!(chapter_starts(&[], 600.0), vec![0.0]);
+    }
+
+    #[test]
+    fn test_ffmetadata() {
+        let metadata = ffmetadata(&[0.0, 184.52], 600.0);
+        assert_eq!(
+            metadata,
+            ";FFMETADATA1\n\
+             \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=184520\ntitle=Chapter 1\n\
+             \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=184520\nEND=600000\ntitle=Chapter 2\n"
+        );
+        assert_eq!(
+            chapters_path(Path::new("/work/concert.mp4")),
+            Path::new("/work/concert.mp4.chapters.txt")
+        );
+    }
+}
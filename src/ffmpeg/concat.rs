@@ -0,0 +1,44 @@
+use std::path::{Path, PathBuf};
+
+/// Where the concat demuxer's list of parts for an output is written while it encodes
+pub fn concat_list_path(output_path: &Path) -> PathBuf {
+    let mut name = output_path.as_os_str().to_owned();
+    name.push(".concat.txt");
+    PathBuf::from(name)
+}
+
+/// A concat demuxer list playing `inputs` back to back, in order
+pub fn concat_list(inputs: &[PathBuf]) -> String {
+    inputs
+        .iter()
+        .map(|input| {
+            // Quotes can't be escaped inside a quoted string, only between them
+            let quoted = input.to_string_lossy().replace('\'', r"'\''");
+            format!("file '{quoted}'\n")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concat_list_keeps_order_and_escapes_quotes() {
+        let inputs = [
+            PathBuf::from("/media/Show S01E01 part1.mkv"),
+            PathBuf::from("/media/Show S01E01 part2.mkv"),
+            PathBuf::from("/media/Bob's Show part3.mkv"),
+        ];
+        assert_eq!(
+            concat_list(&inputs),
+            "file '/media/Show S01E01 part1.mkv'\n\
+             file '/media/Show S01E01 part2.mkv'\n\
+             file '/media/Bob'\\''s Show part3.mkv'\n"
+        );
+        assert_eq!(
+            concat_list_path(Path::new("/work/Show S01E01.mp4")),
+            Path::new("/work/Show S01E01.mp4.concat.txt")
+        );
+    }
+}
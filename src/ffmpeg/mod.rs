@@ -1,11 +1,27 @@
 use anyhow::{anyhow, Result};
-use std::path::Path;
-use std::process::Stdio;
-use tokio::process::Command;
-use tracing::{debug, error, info};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader};
+use tokio::process::{ChildStdout, Command};
+use tracing::{debug, error, info, warn};
 
 use crate::config::Config;
-use crate::job::{Job, MediaFileType, QualitySettings};
+use crate::error::PlexifyError;
+use crate::job::{AudioMode, Job, MediaFileType, QualitySettings, SubtitleMode, VideoCodec};
+
+pub mod chapters;
+pub mod concat;
+pub mod permissions;
+pub mod post_process;
+
+use permissions::OutputPermissions;
+use post_process::PostProcessStep;
+
+/// Filter chain converting PQ/HLG HDR video to BT.709 SDR
+const TONEMAP_FILTER: &str = "zscale=t=linear:npl=100,format=gbrpf32le,zscale=p=bt709,tonemap=tonemap=hable:desat=0,zscale=t=bt709:m=bt709:r=tv,format=yuv420p";
 
 /// Builder for constructing FFmpeg commands with a fluent API
 #[derive(Debug, Default)]
@@ -40,8 +56,20 @@ impl FFmpegCommandBuilder {
     /// Add a single input file
     pub fn with_input<P: AsRef<Path>>(mut self, input_path: P) -> Self {
         self.args.push("-i".to_string());
-        self.args
-            .push(input_path.as_ref().to_string_lossy().to_string());
+        self.args.push(path_arg(input_path.as_ref()));
+        self
+    }
+
+    /// Add the parts listed in a concat demuxer file as one input
+    pub fn with_concat_input<P: AsRef<Path>>(mut self, list_path: P) -> Self {
+        self.args.extend_from_slice(&[
+            "-f".to_string(),
+            "concat".to_string(),
+            "-safe".to_string(),
+            "0".to_string(),
+            "-i".to_string(),
+            path_arg(list_path.as_ref()),
+        ]);
         self
     }
 
@@ -49,8 +77,7 @@ impl FFmpegCommandBuilder {
     pub fn with_inputs<P: AsRef<Path>>(mut self, input_paths: &[P]) -> Self {
         for input_path in input_paths {
             self.args.push("-i".to_string());
-            self.args
-                .push(input_path.as_ref().to_string_lossy().to_string());
+            self.args.push(path_arg(input_path.as_ref()));
         }
         self
     }
@@ -64,16 +91,22 @@ impl FFmpegCommandBuilder {
         self
     }
 
-    /// Add video encoding settings using H.264 with configurable preset and CRF
+    /// Add video encoding settings for the configured codec, preset and CRF
     pub fn with_video_encoding(mut self, quality_settings: &QualitySettings) -> Self {
+        let codec = quality_settings.video_codec;
         self.args.extend_from_slice(&[
             "-c:v".to_string(),
-            "libx264".to_string(),
+            codec.encoder().to_string(),
             "-preset".to_string(),
             quality_settings.ffmpeg_preset.clone(),
             "-crf".to_string(),
             quality_settings.ffmpeg_crf.clone(),
         ]);
+        // Apple players (and Plex clients built on them) only play HEVC in MP4 tagged hvc1
+        if codec == VideoCodec::H265 {
+            self.args
+                .extend_from_slice(&["-tag:v".to_string(), "hvc1".to_string()]);
+        }
         self
     }
 
@@ -88,6 +121,74 @@ impl FFmpegCommandBuilder {
         self
     }
 
+    /// Downmix the audio to two channels
+    pub fn with_stereo_downmix(mut self) -> Self {
+        self.args
+            .extend_from_slice(&["-ac".to_string(), "2".to_string()]);
+        self
+    }
+
+    /// Copy every stream as-is and move the MP4 index to the front for streaming
+    pub fn with_faststart_remux(mut self) -> Self {
+        self.args.extend_from_slice(&[
+            "-map".to_string(),
+            "0".to_string(),
+            "-c".to_string(),
+            "copy".to_string(),
+            "-movflags".to_string(),
+            "+faststart".to_string(),
+        ]);
+        self
+    }
+
+    /// Take chapters from the metadata of the given input
+    pub fn with_chapters_from(mut self, input_index: usize) -> Self {
+        self.args.push("-map_chapters".to_string());
+        self.args.push(input_index.to_string());
+        self
+    }
+
+    /// Stop writing the output after this many seconds
+    pub fn with_duration_limit(mut self, secs: u64) -> Self {
+        self.args.push("-t".to_string());
+        self.args.push(secs.to_string());
+        self
+    }
+
+    /// Set a container metadata tag
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.args.push("-metadata".to_string());
+        self.args.push(format!("{key}={value}"));
+        self
+    }
+
+    /// Apply a video filter chain
+    pub fn with_video_filter(mut self, filter: &str) -> Self {
+        self.args.push("-vf".to_string());
+        self.args.push(filter.to_string());
+        self
+    }
+
+    /// Copy the first mapped audio stream and transcode the second to AAC stereo,
+    /// making the AAC track the default so every client can play something
+    pub fn with_dual_audio_encoding(mut self, quality_settings: &QualitySettings) -> Self {
+        self.args.extend_from_slice(&[
+            "-c:a:0".to_string(),
+            "copy".to_string(),
+            "-c:a:1".to_string(),
+            "aac".to_string(),
+            "-b:a:1".to_string(),
+            quality_settings.ffmpeg_audio_bitrate.clone(),
+            "-ac:a:1".to_string(),
+            "2".to_string(),
+            "-disposition:a:0".to_string(),
+            "0".to_string(),
+            "-disposition:a:1".to_string(),
+            "default".to_string(),
+        ]);
+        self
+    }
+
     /// Add subtitle encoding settings using mov_text format for MP4 containers
     pub fn with_subtitle_encoding(mut self) -> Self {
         self.args
@@ -95,6 +196,23 @@ impl FFmpegCommandBuilder {
         self
     }
 
+    /// Tag an output subtitle stream with its language (e.g. `eng`) so players can label it
+    pub fn with_subtitle_language(mut self, index: usize, language: &str) -> Self {
+        self.args.push(format!("-metadata:s:s:{index}"));
+        self.args.push(format!("language={language}"));
+        self
+    }
+
+    /// Emit machine-readable progress on stdout (`key=value` blocks) instead of stats on stderr
+    pub fn with_progress_reporting(mut self) -> Self {
+        self.args.extend_from_slice(&[
+            "-progress".to_string(),
+            "pipe:1".to_string(),
+            "-nostats".to_string(),
+        ]);
+        self
+    }
+
     /// Enable output file overwriting
     pub fn with_overwrite(mut self) -> Self {
         self.args.push("-y".to_string());
@@ -103,8 +221,7 @@ impl FFmpegCommandBuilder {
 
     /// Add the output file path
     pub fn with_output<P: AsRef<Path>>(mut self, output_path: P) -> Self {
-        self.args
-            .push(output_path.as_ref().to_string_lossy().to_string());
+        self.args.push(path_arg(output_path.as_ref()));
         self
     }
 
@@ -112,235 +229,1840 @@ impl FFmpegCommandBuilder {
     pub fn build(self) -> Vec<String> {
         self.args
     }
+}
+
+/// Render a command as a line that can be pasted into a POSIX shell
+pub fn render_command(cmd: &Command) -> String {
+    let cmd = cmd.as_std();
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| shell_quote(&part.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    /// Build the command arguments and apply them to a tokio Command
-    pub fn build_command(self, base_command: &mut Command) {
-        base_command.args(&self.args);
+/// Single-quote a word for the shell unless it only contains safe characters
+fn shell_quote(word: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c);
+    if !word.is_empty() && word.chars().all(is_safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
     }
 }
 
-/// FFmpeg wrapper for media transcoding
-pub struct FFmpegProcessor {
-    #[allow(dead_code)]
-    config: Config,
-    background_mode: bool,
+/// Streams to map for a job: the first video, the first audio (twice in dual-audio
+/// mode) and the first subtitle, which comes from the sidecar input when there is
+/// one. Burned-in subtitles are part of the video, so they get no stream. Audio
+/// and embedded subtitles are optional (`?`) so inputs without them still convert.
+fn stream_mappings(
+    external_subtitle: bool,
+    subtitle_mode: SubtitleMode,
+    audio_mode: AudioMode,
+) -> Vec<&'static str> {
+    let mut mappings = vec!["0:v:0", "0:a:0?"];
+    if audio_mode == AudioMode::DualAudio {
+        mappings.push("0:a:0?");
+    }
+    match (subtitle_mode, external_subtitle) {
+        (SubtitleMode::Burn, _) => {}
+        (SubtitleMode::Mux, true) => mappings.push("1:s:0"),
+        (SubtitleMode::Mux, false) => mappings.push("0:s:0?"),
+    }
+    mappings
 }
 
-impl FFmpegProcessor {
-    pub fn new(config: Config, background_mode: bool) -> Self {
-        Self {
-            config,
-            background_mode,
+/// Optionally mapped streams the input does not have, given its stream types
+/// as reported by ffprobe (`video`, `audio`, `subtitle`, ...)
+fn absent_streams(
+    stream_types: &[String],
+    external_subtitle: bool,
+    subtitle_mode: SubtitleMode,
+) -> Vec<&'static str> {
+    let has = |kind: &str| stream_types.iter().any(|t| t == kind);
+    let mut absent = Vec::new();
+    if !has("audio") {
+        absent.push("audio");
+    }
+    if subtitle_mode == SubtitleMode::Mux && !external_subtitle && !has("subtitle") {
+        absent.push("subtitle");
+    }
+    absent
+}
+
+/// Windows refuses paths of this length or longer unless they carry the `\\?\` prefix
+#[cfg(windows)]
+const WINDOWS_MAX_PATH: usize = 260;
+
+/// A path as an ffmpeg argument. Callers check it is UTF-8 first (see `ensure_utf8`),
+/// since a lossy conversion would point ffmpeg at a different file.
+fn path_arg(path: &Path) -> String {
+    let arg = path.to_string_lossy().into_owned();
+    #[cfg(windows)]
+    if arg.len() >= WINDOWS_MAX_PATH && path.is_absolute() && !arg.starts_with(r"\\") {
+        return format!(r"\\?\{}", arg.replace('/', "\\"));
+    }
+    arg
+}
+
+/// Add the job's media as the first input, joining the parts of a split episode
+/// with the concat demuxer
+fn with_media_input(
+    builder: FFmpegCommandBuilder,
+    job: &Job,
+    input_path: &Path,
+    output_path: &Path,
+) -> FFmpegCommandBuilder {
+    if job.extra_inputs.is_empty() {
+        builder.with_input(input_path)
+    } else {
+        builder.with_concat_input(concat::concat_list_path(output_path))
+    }
+}
+
+/// The chapters written for this encode, if the job wants them and detection worked
+fn auto_chapters_path(job: &Job, output_path: &Path) -> Option<PathBuf> {
+    let path = chapters::chapters_path(output_path);
+    (job.auto_chapters && path.exists()).then_some(path)
+}
+
+/// Give `output` the modification time of `source`
+fn set_mtime(output: &Path, modified: SystemTime) -> std::io::Result<()> {
+    std::fs::File::options()
+        .write(true)
+        .open(output)?
+        .set_modified(modified)
+}
+
+/// Refuse paths ffmpeg cannot be given as a (UTF-8) argument
+fn ensure_utf8(path: &Path) -> Result<(), PlexifyError> {
+    match path.to_str() {
+        Some(_) => Ok(()),
+        None => Err(PlexifyError::NonUtf8Path(path.to_path_buf())),
+    }
+}
+
+/// `subtitles=` filter burning subtitles into the video, either from a subtitle file
+/// or from a subtitle stream (`si`) of a media file. The path is escaped twice: once
+/// as the filter's option value and once for the filtergraph around it.
+fn subtitles_filter(path: &Path, stream_index: Option<usize>) -> String {
+    let option_value = escape_filter_chars(&path.to_string_lossy(), "\\':");
+    let mut filter = format!(
+        "subtitles={}",
+        escape_filter_chars(&option_value, "\\'[],;")
+    );
+    if let Some(stream_index) = stream_index {
+        filter.push_str(&format!(":si={stream_index}"));
+    }
+    filter
+}
+
+/// Backslash-escape every character of `special` in `value`
+fn escape_filter_chars(value: &str, special: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(c) {
+            escaped.push('\\');
         }
+        escaped.push(c);
     }
+    escaped
+}
 
-    pub async fn process_job(
-        &self,
-        job: &Job,
-        media_root: Option<&Path>,
-        work_folder: Option<&Path>,
-    ) -> Result<()> {
-        let input_path = job.full_input_path(media_root);
-        let output_path = if let Some(work_folder) = work_folder {
-            job.work_folder_output_path(work_folder)
-        } else {
-            job.full_output_path(media_root)
+/// Whether an MP4 file has its `moov` index after the media data, so players have
+/// to fetch the end of the file before they can start streaming
+pub async fn needs_faststart(path: &Path) -> Result<bool> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let mut offset = 0;
+
+    // Walk the top-level boxes until we see either the index or the media data
+    while offset + 8 <= len {
+        file.seek(SeekFrom::Start(offset)).await?;
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header).await?;
+        let size = match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+            // 64-bit size follows the box type
+            1 => {
+                let mut large_size = [0u8; 8];
+                file.read_exact(&mut large_size).await?;
+                u64::from_be_bytes(large_size)
+            }
+            // Box extends to the end of the file
+            0 => len - offset,
+            size => u64::from(size),
         };
 
-        info!("🚀 Starting conversion for: {:?}", input_path);
-
-        // Ensure input file exists
-        if !input_path.exists() {
-            return Err(anyhow!("Input file does not exist: {input_path:?}"));
+        match &header[4..8] {
+            b"moov" => return Ok(false),
+            b"mdat" => return Ok(true),
+            _ => {}
         }
+        if size < 8 {
+            return Err(anyhow!("Invalid MP4 box size {size} in {path:?}"));
+        }
+        offset += size;
+    }
 
-        // Create output directory if it doesn't exist
-        if let Some(parent) = output_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+    Err(anyhow!("No moov or mdat box found in {path:?}"))
+}
+
+/// The tone-mapping filter to use for a source with this ffprobe `color_transfer`,
+/// or `None` when the source is SDR (or unknown) and should pass through untouched
+fn tonemap_filter(color_transfer: Option<&str>) -> Option<&'static str> {
+    match color_transfer? {
+        // PQ (HDR10/Dolby Vision base layer) and HLG
+        "smpte2084" | "arib-std-b67" => Some(TONEMAP_FILTER),
+        _ => None,
+    }
+}
+
+/// ISO 639-2 code for the language suffix of a sidecar subtitle's name, e.g. `eng` for
+/// `Episode.en.srt` or `Episode.eng.srt`. MP4 only stores three-letter codes.
+fn subtitle_language_from_name(path: &Path) -> Option<String> {
+    let stem = Path::new(path.file_stem()?);
+    let suffix = stem.extension()?.to_str()?.to_ascii_lowercase();
+    if !suffix.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let language = match suffix.as_str() {
+        "en" => "eng",
+        "sv" => "swe",
+        "no" | "nb" => "nor",
+        "da" => "dan",
+        "fi" => "fin",
+        "de" => "ger",
+        "fr" => "fre",
+        "es" => "spa",
+        "it" => "ita",
+        "nl" => "dut",
+        "pt" => "por",
+        "pl" => "pol",
+        "ru" => "rus",
+        "ja" => "jpn",
+        "ko" => "kor",
+        "zh" => "chi",
+        _ if suffix.len() == 3 && suffix != "und" => suffix.as_str(),
+        _ => return None,
+    };
+    Some(language.to_string())
+}
+
+/// Progress snapshot parsed from ffmpeg's `-progress` output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FFmpegProgress {
+    /// Position in the output that has been encoded so far
+    pub out_time: Duration,
+    /// Encoding speed relative to realtime (e.g. 1.5 for 1.5x)
+    pub speed: Option<f64>,
+    /// Total duration of the input, if known
+    pub total_duration: Option<Duration>,
+}
+
+impl FFmpegProgress {
+    /// Fraction of the input encoded so far (0.0-1.0), if the total duration is known
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.total_duration?.as_secs_f64();
+        if total <= 0.0 {
+            return None;
         }
+        Some((self.out_time.as_secs_f64() / total).clamp(0.0, 1.0))
+    }
 
-        let mut ffmpeg_builder = FFmpegCommandBuilder::new()
-            .with_common_flags()
-            .with_video_encoding(&job.quality_settings)
-            .with_audio_encoding(&job.quality_settings)
-            .with_subtitle_encoding()
-            .with_overwrite()
-            .with_output(&output_path);
-
-        // Add format-specific flags, inputs, and mappings
-        ffmpeg_builder = match job.file_type {
-            MediaFileType::WebM => {
-                if let Some(vtt_path) = job.full_subtitle_path(media_root) {
-                    // Check if subtitle file exists
-                    if !vtt_path.exists() {
-                        return Err(anyhow!("Required subtitle file not found: {vtt_path:?}"));
-                    }
+    /// Apply a single `key=value` line from ffmpeg's progress output.
+    /// Returns true when a complete progress block has been read.
+    pub fn apply_line(&mut self, line: &str) -> bool {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            return false;
+        };
 
-                    ffmpeg_builder
-                        .with_inputs(&[&input_path, &vtt_path])
-                        .with_stream_mapping(&["0:v:0", "0:a:0", "1:s:0"])
-                } else {
-                    return Err(anyhow!("WebM job missing subtitle path"));
+        match key {
+            // Despite its name, out_time_ms is reported in microseconds
+            "out_time_us" | "out_time_ms" => {
+                if let Ok(micros) = value.parse::<u64>() {
+                    self.out_time = Duration::from_micros(micros);
                 }
             }
-            MediaFileType::Mkv => ffmpeg_builder
-                .with_subtitle_duration_fix()
-                .with_input(&input_path)
-                .with_stream_mapping(&["0:v:0", "0:a:0", "0:s:0"]),
-        };
+            "speed" => {
+                self.speed = value.trim_end_matches('x').trim().parse().ok();
+            }
+            "progress" => return true,
+            _ => {}
+        }
+        false
+    }
+}
 
-        // Create the base command (with optional nice for background mode)
-        let mut cmd = if self.background_mode {
-            let mut c = Command::new("nice");
-            c.args(["-n", "19"]);
-            c.arg("ffmpeg");
-            c
-        } else {
-            Command::new("ffmpeg")
-        };
+/// Tracks when ffmpeg's `out_time` last moved forward, to spot hung encodes
+/// without penalizing slow ones
+#[derive(Debug, Clone)]
+pub struct StallDetector {
+    window: Duration,
+    last_out_time: Option<Duration>,
+    last_advanced: Instant,
+}
+
+impl StallDetector {
+    pub fn new(window: Duration, now: Instant) -> Self {
+        Self {
+            window,
+            last_out_time: None,
+            last_advanced: now,
+        }
+    }
 
-        // Apply the built arguments to the command
-        ffmpeg_builder.build_command(&mut cmd);
+    /// Record a progress update; only a later `out_time` counts as progress
+    pub fn observe(&mut self, out_time: Duration, now: Instant) {
+        if self.last_out_time.is_none_or(|last| out_time > last) {
+            self.last_out_time = Some(out_time);
+            self.last_advanced = now;
+        }
+    }
 
-        // Set up stdio
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
+    /// Whether `out_time` has not advanced for the whole window
+    pub fn is_stalled(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_advanced) >= self.window
+    }
+}
 
-        debug!("Executing FFmpeg command: {:?}", cmd);
+/// Amount the CRF is raised by for each oversize retry
+const CRF_RETRY_STEP: u32 = 4;
+
+/// How much larger than its input (in percent) an output may come out before
+/// `--oversize` applies; small overshoots are normal for already efficient sources
+pub const DEFAULT_OVERSIZE_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// What to do when an encode comes out larger than its source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OversizeAction {
+    /// Keep the output but log a warning (default)
+    #[default]
+    Warn,
+    /// Discard the output and leave the original in place, marked so scans skip it
+    KeepOriginal,
+    /// Re-encode with a higher CRF until it fits or the CRF cap is reached
+    Retry,
+}
 
-        // Execute FFmpeg
-        let output = cmd.output().await?;
+/// Post-encode check that the output is not larger than the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct OversizePolicy {
+    pub action: OversizeAction,
+    /// How much larger than the input (in percent) the output may be
+    pub threshold_percent: f64,
+    /// Highest CRF a retry may use, on the x264 scale (raised by the codec's CRF offset)
+    pub crf_cap: u32,
+}
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            error!("FFmpeg failed: {}", stderr);
-            return Err(anyhow!("FFmpeg conversion failed: {stderr}"));
+impl Default for OversizePolicy {
+    fn default() -> Self {
+        Self {
+            action: OversizeAction::default(),
+            threshold_percent: DEFAULT_OVERSIZE_THRESHOLD_PERCENT,
+            crf_cap: 32,
         }
+    }
+}
 
-        info!(
-            "✅ Conversion successful: {:?} -> {:?}",
-            input_path, output_path
-        );
-        Ok(())
+impl OversizePolicy {
+    /// Whether the output exceeds the input by more than the threshold
+    pub fn is_oversized(&self, input_size: u64, output_size: u64) -> bool {
+        output_size as f64 > input_size as f64 * (1.0 + self.threshold_percent / 100.0)
     }
 
-    /// Move completed file from work folder to media folder
-    pub async fn move_to_destination(
-        &self,
-        job: &Job,
-        media_root: Option<&Path>,
-        work_folder: &Path,
-    ) -> Result<()> {
-        let work_output_path = job.work_folder_output_path(work_folder);
-        let final_output_path = job.full_output_path(media_root);
+    /// The CRF to retry with, or `None` once the cap has been reached
+    pub fn next_crf(&self, crf: &str, codec: VideoCodec) -> Option<String> {
+        let crf: u32 = crf.trim().parse().ok()?;
+        let next = crf
+            .saturating_add(CRF_RETRY_STEP)
+            .min(self.crf_cap.saturating_add(codec.crf_offset()));
+        (next > crf).then(|| next.to_string())
+    }
+}
 
-        // Ensure the work folder output file exists
-        if !work_output_path.exists() {
-            return Err(anyhow!(
-                "Work folder output file does not exist: {work_output_path:?}"
-            ));
-        }
+/// Rough size of an output relative to its input, used to estimate the space a
+/// job needs before it starts (re-encodes rarely come out larger)
+const OUTPUT_SIZE_FACTOR: f64 = 1.0;
+
+/// Parse a byte size such as `500M`, `10G` or `1048576` (binary units)
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let digits_end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits_end);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{value}'"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(format!("unknown size unit in '{value}' (use K, M, G or T)")),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
 
-        // Create final output directory if it doesn't exist
-        if let Some(parent) = final_output_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+/// Fail if `available` bytes do not cover `required` plus the `min_free` reserve
+pub fn check_free_space(
+    path: &Path,
+    required: u64,
+    min_free: u64,
+    available: u64,
+) -> Result<(), PlexifyError> {
+    let required = required.saturating_add(min_free);
+    if available < required {
+        return Err(PlexifyError::InsufficientSpace {
+            path: path.to_path_buf(),
+            required,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// The folder to encode into: `work_folder`, unless `available` bytes there do not
+/// cover `required` plus the `min_free` reserve and there is a `spill_dir` to use
+/// instead (e.g. when the work folder is a tmpfs)
+pub fn choose_work_folder<'a>(
+    work_folder: &'a Path,
+    spill_dir: Option<&'a Path>,
+    required: u64,
+    min_free: u64,
+    available: Option<u64>,
+) -> &'a Path {
+    match (spill_dir, available) {
+        (Some(spill_dir), Some(available))
+            if check_free_space(work_folder, required, min_free, available).is_err() =>
+        {
+            spill_dir
         }
+        _ => work_folder,
+    }
+}
 
-        // Move the file from work folder to final location
-        tokio::fs::copy(&work_output_path, &final_output_path).await?;
-        tokio::fs::remove_file(&work_output_path).await?;
+/// Free space on the volume holding `path`, which need not exist yet
+fn available_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|ancestor| ancestor.exists())?;
+    fs4::available_space(existing).ok()
+}
 
-        info!(
-            "📁 Moved completed file: {:?} -> {:?}",
-            work_output_path, final_output_path
-        );
+/// Result of a successful `process_job`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodeOutcome {
+    /// Quality settings of the final encode (the CRF may have been raised)
+    pub quality_settings: QualitySettings,
+    /// Arguments of the ffmpeg run that produced the final output
+    pub ffmpeg_args: Vec<String>,
+    /// The output was larger than the input and was discarded
+    pub kept_original: bool,
+    /// Modification time of the source before the encode, when `--preserve-mtime`
+    /// should copy it; an in-place remux overwrites the source later
+    pub source_modified: Option<SystemTime>,
+}
 
-        Ok(())
-    }
-    pub async fn disable_source_files(&self, job: &Job, media_root: Option<&Path>) -> Result<()> {
-        let input_path = job.full_input_path(media_root);
-        let disabled_input = input_path.with_extension(format!(
-            "{}.disabled",
-            input_path
-                .extension()
-                .unwrap_or_default()
-                .to_str()
-                .unwrap_or("")
-        ));
+/// What ffprobe reported about a job's input. It is probed once per job, as
+/// several encode arguments depend on it; fields are `None` when unknown.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputProbe {
+    /// Codec type of every stream (`video`, `audio`, `subtitle`, ...), in stream order
+    pub stream_types: Option<Vec<String>>,
+    /// Transfer characteristics of the first video stream, probed for tone-mapping
+    pub color_transfer: Option<String>,
+    /// Language tag of the subtitle stream that gets muxed
+    pub subtitle_language: Option<String>,
+}
 
-        // Rename input file
-        tokio::fs::rename(&input_path, &disabled_input).await?;
-        debug!(
-            "Renamed input file: {:?} -> {:?}",
-            input_path, disabled_input
-        );
+/// Callback invoked with each progress update while ffmpeg runs
+pub type ProgressCallback = Arc<dyn Fn(&FFmpegProgress) + Send + Sync>;
 
-        // Rename subtitle file if it exists (WebM)
-        if let Some(vtt_path) = job.full_subtitle_path(media_root) {
-            if vtt_path.exists() {
-                let disabled_vtt = vtt_path.with_extension("vtt.disabled");
-                tokio::fs::rename(&vtt_path, &disabled_vtt).await?;
-                debug!(
-                    "Renamed subtitle file: {:?} -> {:?}",
-                    vtt_path, disabled_vtt
-                );
-            }
+/// FFmpeg wrapper for media transcoding
+pub struct FFmpegProcessor {
+    config: Config,
+    background_mode: bool,
+    job_timeout: Option<Duration>,
+    stall_timeout: Option<Duration>,
+    oversize_policy: OversizePolicy,
+    min_free_space: u64,
+    progress_callback: Option<ProgressCallback>,
+    no_move: bool,
+    spill_dir: Option<PathBuf>,
+    output_permissions: OutputPermissions,
+    preserve_mtime: bool,
+    post_processors: Vec<PostProcessStep>,
+}
+
+impl FFmpegProcessor {
+    pub fn new(config: Config, background_mode: bool) -> Self {
+        Self {
+            config,
+            background_mode,
+            job_timeout: None,
+            stall_timeout: None,
+            oversize_policy: OversizePolicy::default(),
+            min_free_space: 0,
+            progress_callback: None,
+            no_move: false,
+            spill_dir: None,
+            output_permissions: OutputPermissions::default(),
+            preserve_mtime: false,
+            post_processors: Vec::new(),
         }
+    }
 
-        Ok(())
+    /// Run these external tools, in order, on each output before it is moved into place
+    pub fn with_post_processors(mut self, post_processors: Vec<PostProcessStep>) -> Self {
+        self.post_processors = post_processors;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::job::{Job, MediaFileType, PostProcessingSettings, QualitySettings};
-    use std::path::PathBuf;
-    use tempfile::TempDir;
+    /// Give outputs the modification time of their source, so libraries sorted by
+    /// date added keep their order
+    pub fn with_preserve_mtime(mut self, preserve_mtime: bool) -> Self {
+        self.preserve_mtime = preserve_mtime;
+        self
+    }
 
-    #[tokio::test]
-    async fn test_ffmpeg_processor_creation() {
-        let config = Config::default();
-        let processor = FFmpegProcessor::new(config, false);
-        assert!(!processor.background_mode);
+    /// Set the mode and ownership of outputs once they are in the media folder
+    pub fn with_output_permissions(mut self, output_permissions: OutputPermissions) -> Self {
+        self.output_permissions = output_permissions;
+        self
     }
 
-    #[tokio::test]
-    async fn test_background_mode() {
-        let config = Config::default();
-        let processor = FFmpegProcessor::new(config, true);
-        assert!(processor.background_mode);
+    /// Encode in this directory instead when a job's output would not fit in the work folder
+    pub fn with_spill_dir(mut self, spill_dir: Option<PathBuf>) -> Self {
+        self.spill_dir = spill_dir;
+        self
     }
 
-    #[test]
-    fn test_ffmpeg_command_builder_basic() {
-        let args = FFmpegCommandBuilder::new()
-            .with_common_flags()
-            .with_overwrite()
-            .build();
+    /// Encode to a hidden file next to the destination and rename it into place,
+    /// instead of encoding in the work folder and moving the result
+    pub fn with_no_move(mut self, no_move: bool) -> Self {
+        self.no_move = no_move;
+        self
+    }
 
-        assert_eq!(
-            args,
-            vec![
-                "-fflags",
-                "+genpts",
-                "-avoid_negative_ts",
-                "make_zero",
-                "-y"
-            ]
-        );
+    /// Report encoding progress parsed from ffmpeg while a job runs
+    pub fn with_progress_callback(mut self, progress_callback: Option<ProgressCallback>) -> Self {
+        self.progress_callback = progress_callback;
+        self
     }
 
-    #[test]
-    fn test_ffmpeg_command_builder_webm() {
-        let quality = QualitySettings {
-            ffmpeg_preset: "fast".to_string(),
-            ffmpeg_crf: "20".to_string(),
-            ffmpeg_audio_bitrate: "192k".to_string(),
-        };
+    /// Set the maximum time a single FFmpeg run may take before it is killed
+    pub fn with_job_timeout(mut self, job_timeout: Option<Duration>) -> Self {
+        self.job_timeout = job_timeout;
+        self
+    }
 
-        let args = FFmpegCommandBuilder::new()
-            .with_common_flags()
+    /// Kill FFmpeg if its output position stops advancing for this long
+    pub fn with_stall_timeout(mut self, stall_timeout: Option<Duration>) -> Self {
+        self.stall_timeout = stall_timeout;
+        self
+    }
+
+    /// Decide what happens when an output is larger than its input
+    pub fn with_oversize_policy(mut self, oversize_policy: OversizePolicy) -> Self {
+        self.oversize_policy = oversize_policy;
+        self
+    }
+
+    /// Bytes that must stay free on the work and destination volumes after a job
+    pub fn with_min_free_space(mut self, min_free_space: u64) -> Self {
+        self.min_free_space = min_free_space;
+        self
+    }
+
+    /// The work folder a job should encode into, falling back to the spill directory
+    /// when the estimated output would overflow `work_folder`
+    pub async fn work_folder_for(
+        &self,
+        job: &Job,
+        media_root: Option<&Path>,
+        work_folder: &Path,
+    ) -> PathBuf {
+        let Some(spill_dir) = &self.spill_dir else {
+            return work_folder.to_path_buf();
+        };
+        // Every part of a split episode ends up in the one output
+        let mut input_size = 0;
+        for path in job.input_paths(media_root) {
+            match tokio::fs::metadata(&path).await {
+                Ok(metadata) => input_size += metadata.len(),
+                // The encode reports the missing input
+                Err(_) => return work_folder.to_path_buf(),
+            }
+        }
+        let required = (input_size as f64 * OUTPUT_SIZE_FACTOR) as u64;
+        let chosen = choose_work_folder(
+            work_folder,
+            Some(spill_dir),
+            required,
+            self.min_free_space,
+            available_space(work_folder),
+        );
+        if chosen != work_folder {
+            info!(
+                "💾 Not enough room in {:?} for {}, encoding in {:?}",
+                work_folder,
+                job.display_name(),
+                chosen
+            );
+        }
+        chosen.to_path_buf()
+    }
+
+    pub async fn process_job(
+        &self,
+        job: &Job,
+        media_root: Option<&Path>,
+        work_folder: Option<&Path>,
+    ) -> Result<EncodeOutcome> {
+        let source_modified = self.source_modified(job, media_root);
+        if !self.no_move {
+            let mut outcome = self.encode_job(job, media_root, work_folder).await?;
+            outcome.source_modified = source_modified;
+            if !outcome.kept_original {
+                let (_, output_path) = self.job_paths(job, media_root, work_folder);
+                if let Err(e) = post_process::run_steps(&self.post_processors, &output_path).await {
+                    // The job fails for good, so nothing will move this output anymore
+                    if let Err(e) = tokio::fs::remove_file(&output_path).await {
+                        debug!("No output to remove at {:?}: {}", output_path, e);
+                    }
+                    return Err(e);
+                }
+                // Without a work folder the output is already at its destination
+                if work_folder.is_none() {
+                    self.finish_output(job, media_root, source_modified);
+                }
+            }
+            return Ok(outcome);
+        }
+
+        // The rename stays on one volume, so the library never sees a partial file
+        let staging_path = job.staging_output_path(media_root);
+        let result = match self.encode_job(job, media_root, work_folder).await {
+            Ok(mut outcome) if !outcome.kept_original => {
+                outcome.source_modified = source_modified;
+                post_process::run_steps(&self.post_processors, &staging_path)
+                    .await
+                    .map(|()| outcome)
+            }
+            result => result,
+        };
+        match &result {
+            Ok(outcome) if !outcome.kept_original => {
+                let final_output_path = job.full_output_path(media_root);
+                if let Err(e) = tokio::fs::rename(&staging_path, &final_output_path).await {
+                    if let Err(e) = tokio::fs::remove_file(&staging_path).await {
+                        debug!("No staged output to remove at {:?}: {}", staging_path, e);
+                    }
+                    return Err(e.into());
+                }
+                debug!("{:?} -> {:?}", staging_path, final_output_path);
+                self.finish_output(job, media_root, source_modified);
+            }
+            Ok(_) => {}
+            Err(_) => {
+                if let Err(e) = tokio::fs::remove_file(&staging_path).await {
+                    debug!("No partial output to remove at {:?}: {}", staging_path, e);
+                }
+            }
+        }
+        result
+    }
+
+    /// The source's modification time if `--preserve-mtime` needs it, read before
+    /// the encode since an in-place remux replaces the source
+    fn source_modified(&self, job: &Job, media_root: Option<&Path>) -> Option<SystemTime> {
+        if !self.preserve_mtime {
+            return None;
+        }
+        let input_path = job.full_input_path(media_root);
+        match std::fs::metadata(&input_path).and_then(|m| m.modified()) {
+            Ok(modified) => Some(modified),
+            Err(e) => {
+                warn!(
+                    "Could not read modification time of {:?}: {}",
+                    input_path, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Run the encode (and any oversize retries) into the work or staging path
+    async fn encode_job(
+        &self,
+        job: &Job,
+        media_root: Option<&Path>,
+        work_folder: Option<&Path>,
+    ) -> Result<EncodeOutcome> {
+        let (input_path, output_path) = self.job_paths(job, media_root, work_folder);
+
+        info!("🚀 Starting conversion for: {}", job.display_name());
+        debug!("Input: {:?}, output: {:?}", input_path, output_path);
+
+        // Ensure the input (and every further part) exists
+        let input_paths = job.input_paths(media_root);
+        if let Some(missing) = input_paths.iter().find(|path| !path.exists()) {
+            return Err(PlexifyError::InputMissing(missing.clone()).into());
+        }
+
+        // Refuse to start an encode that would fill up the disk halfway through
+        let mut input_size = 0;
+        for path in &input_paths {
+            input_size += tokio::fs::metadata(path).await?.len();
+        }
+        let required = (input_size as f64 * OUTPUT_SIZE_FACTOR) as u64;
+        let mut volumes = vec![output_path.clone()];
+        if work_folder.is_some() && !self.no_move {
+            volumes.push(job.full_output_path(media_root));
+        }
+        for volume in &volumes {
+            match available_space(volume) {
+                Some(available) => {
+                    check_free_space(volume, required, self.min_free_space, available)?
+                }
+                None => debug!("Could not query free space for {:?}", volume),
+            }
+        }
+
+        // Create output directory if it doesn't exist
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let probe = self.probe_input(job, &input_path, media_root).await;
+        let mut quality_settings = job.quality_settings.clone();
+        let mut ffmpeg_args = self
+            .encode(
+                job,
+                &quality_settings,
+                &input_path,
+                &output_path,
+                media_root,
+                &probe,
+            )
+            .await?;
+
+        info!("✅ Conversion successful: {}", job.display_name());
+
+        // A remux only moves the index and a subtitle is tiny, so their size is not worth judging
+        if matches!(job.file_type, MediaFileType::Mp4 | MediaFileType::Subtitle) {
+            return Ok(EncodeOutcome {
+                quality_settings,
+                ffmpeg_args,
+                kept_original: false,
+                source_modified: None,
+            });
+        }
+
+        // Check the output did not end up larger than the source
+        loop {
+            let output_size = tokio::fs::metadata(&output_path).await?.len();
+            if !self.oversize_policy.is_oversized(input_size, output_size) {
+                break;
+            }
+
+            match self.oversize_policy.action {
+                OversizeAction::Warn => {
+                    warn!(
+                        "⚠️ Output is larger than the input ({} > {} bytes): {:?}",
+                        output_size, input_size, output_path
+                    );
+                    break;
+                }
+                OversizeAction::KeepOriginal => {
+                    warn!(
+                        "📦 Output is larger than the input ({} > {} bytes), keeping the original: {:?}",
+                        output_size, input_size, input_path
+                    );
+                    tokio::fs::remove_file(&output_path).await?;
+                    // Without it, every later scan would queue the same encode again
+                    let marker = Job::kept_original_marker_path(&input_path);
+                    tokio::fs::write(&marker, format!("{} > {} bytes\n", output_size, input_size))
+                        .await?;
+                    return Ok(EncodeOutcome {
+                        quality_settings,
+                        ffmpeg_args,
+                        kept_original: true,
+                        source_modified: None,
+                    });
+                }
+                OversizeAction::Retry => {
+                    match self
+                        .oversize_policy
+                        .next_crf(&quality_settings.ffmpeg_crf, quality_settings.video_codec)
+                    {
+                        Some(crf) => {
+                            info!(
+                                "🔁 Output is larger than the input ({} > {} bytes), retrying with CRF {}",
+                                output_size, input_size, crf
+                            );
+                            quality_settings.ffmpeg_crf = crf;
+                            ffmpeg_args = self
+                                .encode(
+                                    job,
+                                    &quality_settings,
+                                    &input_path,
+                                    &output_path,
+                                    media_root,
+                                    &probe,
+                                )
+                                .await?;
+                        }
+                        None => {
+                            warn!(
+                                "⚠️ Output is still larger than the input at CRF {}: {:?}",
+                                quality_settings.ffmpeg_crf, output_path
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(EncodeOutcome {
+            quality_settings,
+            ffmpeg_args,
+            kept_original: false,
+            source_modified: None,
+        })
+    }
+
+    /// Run one ffmpeg encode of the job's input with the given quality settings
+    async fn encode(
+        &self,
+        job: &Job,
+        quality_settings: &QualitySettings,
+        input_path: &Path,
+        output_path: &Path,
+        media_root: Option<&Path>,
+        probe: &InputProbe,
+    ) -> Result<Vec<String>> {
+        let concat_list_path = concat::concat_list_path(output_path);
+        if !job.extra_inputs.is_empty() {
+            let inputs = job.input_paths(media_root);
+            tokio::fs::write(&concat_list_path, concat::concat_list(&inputs)).await?;
+        }
+        let chapters_path = chapters::chapters_path(output_path);
+        if job.auto_chapters {
+            let inputs = job.input_paths(media_root);
+            if let Err(e) = self.write_chapters(&inputs, &chapters_path).await {
+                // A hung detection pass fails the job like a hung encode would
+                if matches!(
+                    e.downcast_ref::<PlexifyError>(),
+                    Some(PlexifyError::Timeout(_) | PlexifyError::Stalled(_))
+                ) {
+                    return Err(e);
+                }
+                warn!(
+                    "Could not detect chapters in {}, converting without them: {}",
+                    job.display_name(),
+                    e
+                );
+            }
+        }
+        let result = self
+            .run_encode(
+                job,
+                quality_settings,
+                input_path,
+                output_path,
+                media_root,
+                probe,
+            )
+            .await;
+        for path in [&concat_list_path, &chapters_path] {
+            if path.exists() {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+        }
+        result
+    }
+
+    /// Find silent gaps in the inputs and write them as chapters in ffmetadata format.
+    /// Each further part's gaps are offset by the length of the parts before it.
+    async fn write_chapters(&self, input_paths: &[PathBuf], chapters_path: &Path) -> Result<()> {
+        let mut silence_ends = Vec::new();
+        let mut duration = 0.0;
+        for input_path in input_paths {
+            let offset = duration;
+            silence_ends.extend(
+                self.silence_ends(input_path)
+                    .await?
+                    .into_iter()
+                    .map(|end| end + offset),
+            );
+            duration += self.get_duration(input_path).await?;
+        }
+
+        let starts = chapters::chapter_starts(&silence_ends, duration);
+        info!("📑 Found {} chapter(s) in {:?}", starts.len(), input_paths);
+        tokio::fs::write(chapters_path, chapters::ffmetadata(&starts, duration)).await?;
+        Ok(())
+    }
+
+    /// Where the sound comes back after each silent gap of the input, in seconds.
+    /// The pass runs under the same job and stall timeouts as the encode.
+    async fn silence_ends(&self, input_path: &Path) -> Result<Vec<f64>> {
+        let mut cmd = self.build_base_command();
+        cmd.args(["-hide_banner", "-progress", "pipe:1", "-nostats", "-i"])
+            .arg(input_path)
+            .args([
+                "-map",
+                "0:a:0",
+                "-af",
+                chapters::SILENCE_FILTER,
+                "-f",
+                "null",
+                "-",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let output = self.execute_with_limits(cmd, None, None).await?;
+        if !output.status.success() {
+            return Err(PlexifyError::FfmpegFailed {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            }
+            .into());
+        }
+
+        Ok(chapters::parse_silence_ends(&String::from_utf8_lossy(
+            &output.stderr,
+        )))
+    }
+
+    async fn run_encode(
+        &self,
+        job: &Job,
+        quality_settings: &QualitySettings,
+        input_path: &Path,
+        output_path: &Path,
+        media_root: Option<&Path>,
+        probe: &InputProbe,
+    ) -> Result<Vec<String>> {
+        let args = self.job_args(
+            job,
+            quality_settings,
+            input_path,
+            output_path,
+            media_root,
+            probe,
+        )?;
+        let total_duration = if self.progress_callback.is_some() {
+            self.input_duration(job, input_path, media_root)
+                .await
+                .ok()
+                // A sample stops early, so it is done at its own length
+                .map(|secs| {
+                    job.sample_secs
+                        .map_or(secs, |sample| secs.min(sample as f64))
+                })
+                // Broken or streaming inputs can report `inf` or `NaN`
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        } else {
+            None
+        };
+
+        // Create the base command (with optional nice for background mode)
+        let mut cmd = self.build_base_command();
+        cmd.args(&args);
+
+        // Set up stdio
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        debug!("Executing FFmpeg command: {:?}", cmd);
+
+        // Execute FFmpeg
+        let output = self.execute_ffmpeg_command(cmd, total_duration).await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            error!("FFmpeg failed: {}", stderr);
+            return Err(PlexifyError::FfmpegFailed {
+                stderr: stderr.into_owned(),
+            }
+            .into());
+        }
+
+        Ok(args)
+    }
+
+    /// The full ffmpeg command a worker would run for this job, without running it
+    pub async fn job_command(
+        &self,
+        job: &Job,
+        media_root: Option<&Path>,
+        work_folder: Option<&Path>,
+    ) -> Result<Command> {
+        let (input_path, output_path) = self.job_paths(job, media_root, work_folder);
+        let probe = self.probe_input(job, &input_path, media_root).await;
+        let args = self.job_args(
+            job,
+            &job.quality_settings,
+            &input_path,
+            &output_path,
+            media_root,
+            &probe,
+        )?;
+        let mut cmd = self.build_base_command();
+        cmd.args(&args);
+        Ok(cmd)
+    }
+
+    /// Input path and the path ffmpeg writes to (next to the destination with
+    /// `--no-move`, otherwise in the work folder when there is one)
+    fn job_paths(
+        &self,
+        job: &Job,
+        media_root: Option<&Path>,
+        work_folder: Option<&Path>,
+    ) -> (PathBuf, PathBuf) {
+        let input_path = job.full_input_path(media_root);
+        let output_path = if self.no_move {
+            job.staging_output_path(media_root)
+        } else if let Some(work_folder) = work_folder {
+            job.work_folder_output_path(work_folder)
+        } else {
+            job.full_output_path(media_root)
+        };
+        (input_path, output_path)
+    }
+
+    /// Probe what the encode arguments of the job depend on. Failures only leave
+    /// the affected field unknown.
+    async fn probe_input(
+        &self,
+        job: &Job,
+        input_path: &Path,
+        media_root: Option<&Path>,
+    ) -> InputProbe {
+        let mut probe = InputProbe::default();
+        // A remux or subtitle conversion has no options that depend on the input
+        if matches!(job.file_type, MediaFileType::Mp4 | MediaFileType::Subtitle) {
+            return probe;
+        }
+
+        match self.get_stream_types(input_path).await {
+            Ok(stream_types) => probe.stream_types = Some(stream_types),
+            Err(e) => debug!("Could not probe streams of {:?}: {}", input_path, e),
+        }
+        if job.tonemap {
+            match self.get_color_transfer(input_path).await {
+                Ok(color_transfer) => probe.color_transfer = color_transfer,
+                Err(e) => warn!("Could not probe HDR metadata, not tone-mapping: {}", e),
+            }
+        }
+        if job.subtitle_mode == SubtitleMode::Mux {
+            let subtitle_path = job.full_subtitle_path(media_root);
+            let subtitle_source = subtitle_path.as_deref().unwrap_or(input_path);
+            match self.get_subtitle_language(subtitle_source).await {
+                Ok(language) => probe.subtitle_language = language,
+                Err(e) => debug!(
+                    "Could not probe subtitle language of {:?}: {}",
+                    subtitle_source, e
+                ),
+            }
+            // ffprobe reports no language for .srt/.vtt sidecars, but their name often does
+            if probe.subtitle_language.is_none() {
+                probe.subtitle_language = subtitle_path
+                    .as_deref()
+                    .and_then(subtitle_language_from_name);
+            }
+        }
+        probe
+    }
+
+    /// Build the ffmpeg arguments for one encode of the job
+    fn job_args(
+        &self,
+        job: &Job,
+        quality_settings: &QualitySettings,
+        input_path: &Path,
+        output_path: &Path,
+        media_root: Option<&Path>,
+        probe: &InputProbe,
+    ) -> Result<Vec<String>> {
+        ensure_utf8(input_path)?;
+        ensure_utf8(output_path)?;
+        for part in &job.extra_inputs {
+            ensure_utf8(part)?;
+        }
+
+        // ffmpeg picks the subtitle codec from the output extension
+        if job.file_type == MediaFileType::Subtitle {
+            return Ok(FFmpegCommandBuilder::new()
+                .with_input(input_path)
+                .with_overwrite()
+                .with_output(output_path)
+                .build());
+        }
+
+        let mut ffmpeg_builder = FFmpegCommandBuilder::new().with_common_flags();
+
+        // A remux copies every stream, so none of the encoding options apply
+        if job.file_type == MediaFileType::Mp4 {
+            if job.subtitle_mode == SubtitleMode::Burn {
+                return Err(anyhow!(
+                    "Cannot burn subtitles into {:?}: a stream-copy remux does not re-encode the video",
+                    input_path
+                ));
+            }
+            ffmpeg_builder = with_media_input(ffmpeg_builder, job, input_path, output_path);
+            if let Some(chapters_path) = auto_chapters_path(job, output_path) {
+                ffmpeg_builder = ffmpeg_builder
+                    .with_input(chapters_path)
+                    .with_chapters_from(1);
+            }
+            ffmpeg_builder = ffmpeg_builder.with_faststart_remux();
+            if let Some(secs) = job.sample_secs {
+                ffmpeg_builder = ffmpeg_builder.with_duration_limit(secs);
+            }
+            return Ok(ffmpeg_builder
+                .with_overwrite()
+                .with_output(output_path)
+                .build());
+        }
+
+        let subtitle_path = job.full_subtitle_path(media_root);
+        match &subtitle_path {
+            Some(path) if !path.exists() => {
+                return Err(PlexifyError::SubtitleMissing(path.clone()).into());
+            }
+            Some(path) => ensure_utf8(path)?,
+            None if job.file_type == MediaFileType::WebM => {
+                return Err(anyhow!("WebM job missing subtitle path"));
+            }
+            _ => {}
+        }
+        if !job.extra_inputs.is_empty()
+            && (subtitle_path.is_some() || job.subtitle_mode == SubtitleMode::Burn)
+        {
+            return Err(anyhow!(
+                "Cannot add subtitles to {}: they would only cover its first part",
+                job.display_name()
+            ));
+        }
+
+        // Add format-specific flags, inputs, and mappings. Burned-in subtitle files
+        // are read by the filter, not as an input.
+        if job.file_type == MediaFileType::Mkv {
+            ffmpeg_builder = ffmpeg_builder.with_subtitle_duration_fix();
+        }
+        // Inputs after the media itself
+        let mut inputs = Vec::new();
+        if let Some(path) = subtitle_path.as_deref() {
+            if job.subtitle_mode == SubtitleMode::Mux {
+                inputs.push(path);
+            }
+        }
+        let chapters_path = auto_chapters_path(job, output_path);
+        if let Some(path) = chapters_path.as_deref() {
+            inputs.push(path);
+        }
+        ffmpeg_builder =
+            with_media_input(ffmpeg_builder, job, input_path, output_path).with_inputs(&inputs);
+        if chapters_path.is_some() {
+            ffmpeg_builder = ffmpeg_builder.with_chapters_from(inputs.len());
+        }
+        ffmpeg_builder = ffmpeg_builder.with_stream_mapping(&stream_mappings(
+            subtitle_path.is_some(),
+            job.subtitle_mode,
+            job.audio_mode,
+        ));
+        if let Some(stream_types) = &probe.stream_types {
+            let absent = absent_streams(stream_types, subtitle_path.is_some(), job.subtitle_mode);
+            if !absent.is_empty() {
+                info!(
+                    "🔇 No {} stream in {}, converting without it",
+                    absent.join(" or "),
+                    job.display_name()
+                );
+            }
+        }
+
+        let mut video_filters = Vec::new();
+        if job.tonemap {
+            match tonemap_filter(probe.color_transfer.as_deref()) {
+                Some(filter) => {
+                    info!("🌈 Tone-mapping HDR source to SDR: {}", job.display_name());
+                    video_filters.push(filter.to_string());
+                }
+                None => debug!("Source is not HDR, keeping colors as-is: {:?}", input_path),
+            }
+        }
+        if job.subtitle_mode == SubtitleMode::Burn {
+            // Without a sidecar, burn the first subtitle stream of the input itself
+            match &subtitle_path {
+                Some(path) => video_filters.push(subtitles_filter(path, None)),
+                None if probe
+                    .stream_types
+                    .as_ref()
+                    .is_some_and(|types| !types.iter().any(|t| t == "subtitle")) =>
+                {
+                    warn!(
+                        "No subtitles to burn into {}, converting without them",
+                        job.display_name()
+                    );
+                }
+                None => video_filters.push(subtitles_filter(input_path, Some(0))),
+            }
+        }
+        if !video_filters.is_empty() {
+            ffmpeg_builder = ffmpeg_builder.with_video_filter(&video_filters.join(","));
+        }
+
+        ffmpeg_builder = ffmpeg_builder.with_video_encoding(quality_settings);
+        ffmpeg_builder = match job.audio_mode {
+            AudioMode::Aac => ffmpeg_builder.with_audio_encoding(quality_settings),
+            AudioMode::DualAudio => ffmpeg_builder.with_dual_audio_encoding(quality_settings),
+        };
+        // Dual audio already adds a stereo AAC track next to the original
+        if job.downmix_stereo && job.audio_mode == AudioMode::Aac {
+            ffmpeg_builder = ffmpeg_builder.with_stereo_downmix();
+        }
+        if job.subtitle_mode == SubtitleMode::Mux {
+            ffmpeg_builder = ffmpeg_builder.with_subtitle_encoding();
+            // mov_text drops the source's language tag unless it is set explicitly
+            if let Some(language) = &probe.subtitle_language {
+                ffmpeg_builder = ffmpeg_builder.with_subtitle_language(0, language);
+            }
+        }
+
+        if job.tag_metadata {
+            ffmpeg_builder = ffmpeg_builder
+                .with_metadata("title", &job.metadata_title())
+                .with_metadata("encoder", "plexify");
+        }
+
+        if let Some(secs) = job.sample_secs {
+            ffmpeg_builder = ffmpeg_builder.with_duration_limit(secs);
+        }
+
+        // Stall detection needs the progress output too, but only probe the
+        // duration when someone is listening for progress
+        if self.progress_callback.is_some() || self.stall_timeout.is_some() {
+            ffmpeg_builder = ffmpeg_builder.with_progress_reporting();
+        }
+
+        Ok(ffmpeg_builder
+            .with_overwrite()
+            .with_output(output_path)
+            .build())
+    }
+
+    /// Spawn the FFmpeg command and wait for it to finish, killing the child
+    /// process if it exceeds the configured job timeout or stops making progress
+    pub async fn execute_ffmpeg_command(
+        &self,
+        cmd: Command,
+        total_duration: Option<Duration>,
+    ) -> Result<Output> {
+        self.execute_with_limits(cmd, self.progress_callback.clone(), total_duration)
+            .await
+    }
+
+    /// Run `cmd` under the job and stall timeouts, forwarding progress to `callback`
+    async fn execute_with_limits(
+        &self,
+        mut cmd: Command,
+        callback: Option<ProgressCallback>,
+        total_duration: Option<Duration>,
+    ) -> Result<Output> {
+        // Ensure the child is killed if we stop waiting on it (e.g. on timeout)
+        cmd.kill_on_drop(true);
+        let mut child = cmd.spawn()?;
+
+        let stall_detector = self
+            .stall_timeout
+            .map(|window| Arc::new(Mutex::new(StallDetector::new(window, Instant::now()))));
+
+        // Parse progress from stdout while the process runs
+        let progress_task = match child.stdout.take() {
+            Some(stdout) if callback.is_some() || stall_detector.is_some() => Some(tokio::spawn(
+                Self::read_progress(stdout, callback, stall_detector.clone(), total_duration),
+            )),
+            _ => None,
+        };
+
+        let wait = async {
+            match &stall_detector {
+                Some(detector) => tokio::select! {
+                    output = child.wait_with_output() => Ok(output?),
+                    window = Self::wait_for_stall(Arc::clone(detector)) => {
+                        error!("FFmpeg made no progress for {:?}, killed process", window);
+                        Err(PlexifyError::Stalled(window).into())
+                    }
+                },
+                None => Ok(child.wait_with_output().await?),
+            }
+        };
+
+        let result = match self.job_timeout {
+            Some(job_timeout) => match tokio::time::timeout(job_timeout, wait).await {
+                Ok(result) => result,
+                Err(_) => {
+                    error!("FFmpeg timed out after {:?}, killed process", job_timeout);
+                    Err(PlexifyError::Timeout(job_timeout).into())
+                }
+            },
+            None => wait.await,
+        };
+
+        if let Some(task) = progress_task {
+            if result.is_ok() {
+                // The process has exited, so stdout is at EOF; let the reader finish
+                let _ = task.await;
+            } else {
+                task.abort();
+            }
+        }
+
+        result
+    }
+
+    /// Read ffmpeg `-progress` output and forward each completed block to the
+    /// callback and stall detector
+    async fn read_progress(
+        stdout: ChildStdout,
+        callback: Option<ProgressCallback>,
+        stall_detector: Option<Arc<Mutex<StallDetector>>>,
+        total_duration: Option<Duration>,
+    ) {
+        let mut lines = BufReader::new(stdout).lines();
+        let mut progress = FFmpegProgress {
+            total_duration,
+            ..FFmpegProgress::default()
+        };
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            if progress.apply_line(&line) {
+                if let Some(detector) = &stall_detector {
+                    detector
+                        .lock()
+                        .unwrap()
+                        .observe(progress.out_time, Instant::now());
+                }
+                if let Some(callback) = &callback {
+                    callback(&progress);
+                }
+            }
+        }
+    }
+
+    /// Resolve with the stall window once the detector reports a stall
+    async fn wait_for_stall(detector: Arc<Mutex<StallDetector>>) -> Duration {
+        let window = detector.lock().unwrap().window;
+        let check_interval = (window / 4).clamp(Duration::from_millis(50), Duration::from_secs(1));
+        loop {
+            tokio::time::sleep(check_interval).await;
+            if detector.lock().unwrap().is_stalled(Instant::now()) {
+                return window;
+            }
+        }
+    }
+
+    /// Create the base FFmpeg command using the configured binary path,
+    /// wrapped in `nice` when running in background mode
+    pub fn build_base_command(&self) -> Command {
+        if self.background_mode {
+            let mut cmd = Command::new("nice");
+            cmd.args(["-n", "19"]);
+            cmd.arg(&self.config.ffmpeg_path);
+            cmd
+        } else {
+            Command::new(&self.config.ffmpeg_path)
+        }
+    }
+
+    /// Combined duration in seconds of the job's input and any further parts
+    async fn input_duration(
+        &self,
+        job: &Job,
+        input_path: &Path,
+        media_root: Option<&Path>,
+    ) -> Result<f64> {
+        let mut duration = self.get_duration(input_path).await?;
+        for part in job.input_paths(media_root).iter().skip(1) {
+            duration += self.get_duration(part).await?;
+        }
+        Ok(duration)
+    }
+
+    /// Run ffprobe for `entries` (e.g. `stream=codec_name`) of the streams matching
+    /// `select` (e.g. `v:0`, or every stream when `None`), returning one value per line
+    async fn probe_entries(
+        &self,
+        path: &Path,
+        select: Option<&str>,
+        entries: &str,
+    ) -> Result<String> {
+        let mut cmd = Command::new(&self.config.ffprobe_path);
+        cmd.args(["-v", "error"]);
+        if let Some(select) = select {
+            cmd.args(["-select_streams", select]);
+        }
+        let output = cmd
+            .args([
+                "-show_entries",
+                entries,
+                "-of",
+                "default=noprint_wrappers=1:nokey=1",
+            ])
+            .arg(path)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(PlexifyError::ProbeFailed {
+                path: path.to_path_buf(),
+                stderr: stderr.into_owned(),
+            }
+            .into());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Get the duration of a media file in seconds using ffprobe
+    pub async fn get_duration(&self, path: &Path) -> Result<f64> {
+        self.probe_entries(path, None, "format=duration")
+            .await?
+            .parse::<f64>()
+            .map_err(|e| anyhow!("Invalid duration reported by ffprobe for {path:?}: {e}"))
+    }
+
+    /// Codec name of the first video stream (e.g. `h264`), if the file has one
+    pub async fn get_video_codec(&self, path: &Path) -> Result<Option<String>> {
+        let codec = self
+            .probe_entries(path, Some("v:0"), "stream=codec_name")
+            .await?;
+        Ok((!codec.is_empty()).then_some(codec))
+    }
+
+    /// Language tag of the first subtitle stream (e.g. `eng`), or `None` if it is
+    /// missing or `und`
+    pub async fn get_subtitle_language(&self, path: &Path) -> Result<Option<String>> {
+        let language = self
+            .probe_entries(path, Some("s:0"), "stream_tags=language")
+            .await?;
+        Ok((!language.is_empty() && language != "und").then_some(language))
+    }
+
+    /// Codec type of every stream in the file, in stream order
+    pub async fn get_stream_types(&self, path: &Path) -> Result<Vec<String>> {
+        Ok(self
+            .probe_entries(path, None, "stream=codec_type")
+            .await?
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+
+    /// Get the transfer characteristics of the first video stream (e.g. `smpte2084`
+    /// for HDR10), or `None` if ffprobe reports nothing
+    pub async fn get_color_transfer(&self, path: &Path) -> Result<Option<String>> {
+        let transfer = self
+            .probe_entries(path, Some("v:0"), "stream=color_transfer")
+            .await?;
+        Ok((!transfer.is_empty() && transfer != "unknown").then_some(transfer))
+    }
+
+    /// Move completed file from work folder to media folder, giving it
+    /// `source_modified` (from the `EncodeOutcome`) as its modification time
+    pub async fn move_to_destination(
+        &self,
+        job: &Job,
+        media_root: Option<&Path>,
+        work_folder: &Path,
+        source_modified: Option<SystemTime>,
+    ) -> Result<()> {
+        let work_output_path = job.work_folder_output_path(work_folder);
+        let final_output_path = job.full_output_path(media_root);
+
+        // Ensure the work folder output file exists
+        if !work_output_path.exists() {
+            return Err(PlexifyError::WorkOutputMissing(work_output_path).into());
+        }
+
+        // Create final output directory if it doesn't exist
+        if let Some(parent) = final_output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Move the file from work folder to final location, copying it over when the
+        // work folder is on another filesystem
+        match tokio::fs::rename(&work_output_path, &final_output_path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                tokio::fs::copy(&work_output_path, &final_output_path)
+                    .await
+                    .map_err(|source| PlexifyError::CrossDevice {
+                        from: work_output_path.clone(),
+                        to: final_output_path.clone(),
+                        source,
+                    })?;
+                tokio::fs::remove_file(&work_output_path).await?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        self.finish_output(job, media_root, source_modified);
+
+        info!("📁 Moved completed file: {}", job.display_name());
+        debug!("{:?} -> {:?}", work_output_path, final_output_path);
+
+        Ok(())
+    }
+
+    /// Apply the configured output timestamps and permissions. The output is already in
+    /// place, so a failure (e.g. chown without the privileges for it) is only warned about.
+    fn finish_output(
+        &self,
+        job: &Job,
+        media_root: Option<&Path>,
+        source_modified: Option<SystemTime>,
+    ) {
+        let input_path = job.full_input_path(media_root);
+        let output_path = job.full_output_path(media_root);
+        // Before the permissions, which may make the output read-only
+        if let Some(modified) = source_modified {
+            if let Err(e) = set_mtime(&output_path, modified) {
+                warn!(
+                    "Could not copy modification time to {:?}: {}",
+                    output_path, e
+                );
+            }
+        }
+        if !self.output_permissions.is_empty() {
+            if let Err(e) = self.output_permissions.apply(&input_path, &output_path) {
+                warn!("Could not set permissions of {:?}: {}", output_path, e);
+            }
+        }
+    }
+
+    pub async fn disable_source_files(&self, job: &Job, media_root: Option<&Path>) -> Result<()> {
+        let input_path = job.full_input_path(media_root);
+        let disabled_input = Job::disabled_path(&input_path);
+
+        // Rename input file
+        tokio::fs::rename(&input_path, &disabled_input).await?;
+        debug!(
+            "Renamed input file: {:?} -> {:?}",
+            input_path, disabled_input
+        );
+
+        // And the further parts of a split episode
+        for part in job.input_paths(media_root).iter().skip(1) {
+            let disabled_part = Job::disabled_path(part);
+            tokio::fs::rename(part, &disabled_part).await?;
+            debug!("Renamed input part: {:?} -> {:?}", part, disabled_part);
+        }
+
+        // Rename the subtitle sidecar if there is one
+        if let Some(subtitle_path) = job.full_subtitle_path(media_root) {
+            if subtitle_path.exists() {
+                let mut disabled_subtitle = subtitle_path.clone().into_os_string();
+                disabled_subtitle.push(".disabled");
+                tokio::fs::rename(&subtitle_path, &disabled_subtitle).await?;
+                debug!(
+                    "Renamed subtitle file: {:?} -> {:?}",
+                    subtitle_path, disabled_subtitle
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::{Job, MediaFileType, PostProcessingSettings, QualityPreset, QualitySettings};
+    #[cfg(unix)]
+    use crate::test_support;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_ffmpeg_processor_creation() {
+        let config = Config::default();
+        let processor = FFmpegProcessor::new(config, false);
+        assert!(!processor.background_mode);
+    }
+
+    #[tokio::test]
+    async fn test_background_mode() {
+        let config = Config::default();
+        let processor = FFmpegProcessor::new(config, true);
+        assert!(processor.background_mode);
+    }
+
+    #[test]
+    fn test_base_command_uses_default_binary() {
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let cmd = processor.build_base_command();
+        assert_eq!(cmd.as_std().get_program(), "ffmpeg");
+    }
+
+    #[test]
+    fn test_base_command_uses_configured_binary() {
+        let config = Config {
+            ffmpeg_path: "/opt/ffmpeg/bin/ffmpeg".to_string(),
+            ..Config::default()
+        };
+        let processor = FFmpegProcessor::new(config, false);
+        let cmd = processor.build_base_command();
+        assert_eq!(cmd.as_std().get_program(), "/opt/ffmpeg/bin/ffmpeg");
+    }
+
+    #[test]
+    fn test_background_base_command_uses_configured_binary() {
+        let config = Config {
+            ffmpeg_path: "/opt/ffmpeg/bin/ffmpeg".to_string(),
+            ..Config::default()
+        };
+        let processor = FFmpegProcessor::new(config, true);
+        let cmd = processor.build_base_command();
+        assert_eq!(cmd.as_std().get_program(), "nice");
+
+        let args: Vec<_> = cmd.as_std().get_args().collect();
+        assert_eq!(args, vec!["-n", "19", "/opt/ffmpeg/bin/ffmpeg"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_times_out() {
+        let processor = FFmpegProcessor::new(Config::default(), false)
+            .with_job_timeout(Some(Duration::from_millis(200)));
+
+        // Simulate a hung ffmpeg process with a long-running command
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+
+        let start = std::time::Instant::now();
+        let result = processor.execute_ffmpeg_command(cmd, None).await;
+
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+        assert!(matches!(
+            error.downcast_ref::<PlexifyError>(),
+            Some(PlexifyError::Timeout(_))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_process_job_missing_inputs_are_typed() {
+        let temp_dir = TempDir::new().unwrap();
+        let processor = FFmpegProcessor::new(Config::default(), false);
+
+        let missing = Job::new(
+            PathBuf::from("missing.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            temp_dir.path(),
+        );
+        let error = processor
+            .process_job(&missing, None, Some(temp_dir.path()))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PlexifyError>(),
+            Some(PlexifyError::InputMissing(_))
+        ));
+
+        // A WebM input without its .vtt sidecar
+        std::fs::write(temp_dir.path().join("video.webm"), "webm").unwrap();
+        let no_subtitle = Job::new(
+            PathBuf::from("video.webm"),
+            MediaFileType::WebM,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            temp_dir.path(),
+        );
+        let error = processor
+            .process_job(&no_subtitle, None, Some(temp_dir.path()))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PlexifyError>(),
+            Some(PlexifyError::SubtitleMissing(_))
+        ));
+
+        let error = processor
+            .move_to_destination(&no_subtitle, None, temp_dir.path(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PlexifyError>(),
+            Some(PlexifyError::WorkOutputMissing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_within_timeout() {
+        let processor = FFmpegProcessor::new(Config::default(), false)
+            .with_job_timeout(Some(Duration::from_secs(10)));
+
+        let mut cmd = Command::new("true");
+        cmd.stdout(Stdio::piped());
+
+        let output = processor.execute_ffmpeg_command(cmd, None).await.unwrap();
+        assert!(output.status.success());
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_reports_progress() {
+        let updates = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&updates);
+        let processor = FFmpegProcessor::new(Config::default(), false).with_progress_callback(
+            Some(Arc::new(move |progress: &FFmpegProgress| {
+                recorded.lock().unwrap().push(progress.clone());
+            })),
+        );
+
+        // Simulate ffmpeg's -progress output
+        let mut cmd = Command::new("printf");
+        cmd.arg("out_time_us=5000000\\nspeed=2.0x\\nprogress=continue\\nout_time_us=10000000\\nspeed=2.5x\\nprogress=end\\n");
+        cmd.stdout(Stdio::piped());
+
+        let output = processor
+            .execute_ffmpeg_command(cmd, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        assert!(output.status.success());
+
+        let updates = updates.lock().unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].out_time, Duration::from_secs(5));
+        assert_eq!(updates[0].speed, Some(2.0));
+        assert_eq!(updates[0].fraction(), Some(0.5));
+        assert_eq!(updates[1].fraction(), Some(1.0));
+    }
+
+    #[test]
+    fn test_stall_detector() {
+        let start = Instant::now();
+        let mut detector = StallDetector::new(Duration::from_secs(30), start);
+
+        assert!(!detector.is_stalled(start + Duration::from_secs(29)));
+        detector.observe(Duration::from_secs(5), start + Duration::from_secs(20));
+        assert!(!detector.is_stalled(start + Duration::from_secs(40)));
+
+        // Repeated or earlier timestamps do not count as progress
+        detector.observe(Duration::from_secs(5), start + Duration::from_secs(45));
+        detector.observe(Duration::from_secs(4), start + Duration::from_secs(48));
+        assert!(detector.is_stalled(start + Duration::from_secs(50)));
+
+        detector.observe(Duration::from_secs(6), start + Duration::from_secs(55));
+        assert!(!detector.is_stalled(start + Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_kills_stalled_process() {
+        let processor = FFmpegProcessor::new(Config::default(), false)
+            .with_stall_timeout(Some(Duration::from_millis(300)));
+
+        // Report progress once, then hang without advancing
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            "printf 'out_time_us=1000000\\nprogress=continue\\n'; sleep 30",
+        ]);
+        cmd.stdout(Stdio::piped());
+
+        let start = std::time::Instant::now();
+        let result = processor.execute_ffmpeg_command(cmd, None).await;
+
+        assert!(result.unwrap_err().to_string().contains("stalled"));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_with_steady_progress_is_not_stalled() {
+        let processor = FFmpegProcessor::new(Config::default(), false)
+            .with_stall_timeout(Some(Duration::from_millis(500)));
+
+        // Slow but always advancing
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            "for i in 1 2 3 4 5; do printf \"out_time_us=${i}000000\\nprogress=continue\\n\"; sleep 0.2; done",
+        ]);
+        cmd.stdout(Stdio::piped());
+
+        let output = processor.execute_ffmpeg_command(cmd, None).await.unwrap();
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_progress_line_parsing() {
+        let mut progress = FFmpegProgress::default();
+        assert!(!progress.apply_line("frame=120"));
+        assert!(!progress.apply_line("out_time_ms=2500000"));
+        assert!(!progress.apply_line("speed= 0.75x"));
+        assert!(!progress.apply_line("garbage"));
+        assert!(progress.apply_line("progress=continue"));
+
+        assert_eq!(progress.out_time, Duration::from_millis(2500));
+        assert_eq!(progress.speed, Some(0.75));
+        assert_eq!(progress.fraction(), None);
+
+        progress.speed = None;
+        assert!(!progress.apply_line("speed=N/A"));
+        assert_eq!(progress.speed, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_duration_with_missing_ffprobe() {
+        let config = Config {
+            ffprobe_path: "/nonexistent/ffprobe".to_string(),
+            ..Config::default()
+        };
+        let processor = FFmpegProcessor::new(config, false);
+        let result = processor.get_duration(Path::new("video.mp4")).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_ffmpeg_command_builder_basic() {
+        let args = FFmpegCommandBuilder::new()
+            .with_common_flags()
+            .with_overwrite()
+            .build();
+
+        assert_eq!(
+            args,
+            vec![
+                "-fflags",
+                "+genpts",
+                "-avoid_negative_ts",
+                "make_zero",
+                "-y"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ffmpeg_command_builder_webm() {
+        let quality = QualitySettings {
+            ffmpeg_preset: "fast".to_string(),
+            ffmpeg_crf: "20".to_string(),
+            ffmpeg_audio_bitrate: "192k".to_string(),
+            video_codec: VideoCodec::H264,
+        };
+
+        let args = FFmpegCommandBuilder::new()
+            .with_common_flags()
             .with_inputs(&["/path/to/video.webm", "/path/to/video.vtt"])
             .with_stream_mapping(&["0:v:0", "0:a:0", "1:s:0"])
             .with_video_encoding(&quality)
@@ -350,106 +2072,1099 @@ mod tests {
             .with_output("/path/to/output.mp4")
             .build();
 
-        let expected = vec![
-            "-fflags",
-            "+genpts",
-            "-avoid_negative_ts",
-            "make_zero",
-            "-i",
-            "/path/to/video.webm",
-            "-i",
-            "/path/to/video.vtt",
-            "-map",
-            "0:v:0",
-            "-map",
-            "0:a:0",
-            "-map",
-            "1:s:0",
-            "-c:v",
-            "libx264",
-            "-preset",
-            "fast",
-            "-crf",
-            "20",
-            "-c:a",
-            "aac",
-            "-b:a",
-            "192k",
-            "-c:s",
-            "mov_text",
-            "-y",
-            "/path/to/output.mp4",
+        let expected = vec![
+            "-fflags",
+            "+genpts",
+            "-avoid_negative_ts",
+            "make_zero",
+            "-i",
+            "/path/to/video.webm",
+            "-i",
+            "/path/to/video.vtt",
+            "-map",
+            "0:v:0",
+            "-map",
+            "0:a:0",
+            "-map",
+            "1:s:0",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "fast",
+            "-crf",
+            "20",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "192k",
+            "-c:s",
+            "mov_text",
+            "-y",
+            "/path/to/output.mp4",
+        ];
+
+        assert_eq!(args, expected);
+    }
+
+    #[test]
+    fn test_oversize_decision() {
+        let policy = OversizePolicy {
+            action: OversizeAction::Retry,
+            threshold_percent: 10.0,
+            crf_cap: 30,
+        };
+
+        assert!(!policy.is_oversized(1000, 900));
+        assert!(!policy.is_oversized(1000, 1100));
+        assert!(policy.is_oversized(1000, 1101));
+        assert!(!OversizePolicy::default().is_oversized(1000, 1100));
+        assert!(OversizePolicy::default().is_oversized(1000, 1101));
+
+        assert_eq!(
+            policy.next_crf("23", VideoCodec::H264),
+            Some("27".to_string())
+        );
+        assert_eq!(
+            policy.next_crf("28", VideoCodec::H264),
+            Some("30".to_string())
+        );
+        assert_eq!(policy.next_crf("30", VideoCodec::H264), None);
+        assert_eq!(policy.next_crf("not a number", VideoCodec::H264), None);
+
+        // x265 CRFs run higher, e.g. the ultrafast preset starts at 33
+        assert_eq!(
+            policy.next_crf("30", VideoCodec::H265),
+            Some("34".to_string())
+        );
+        assert_eq!(
+            policy.next_crf("33", VideoCodec::H265),
+            Some("35".to_string())
+        );
+        assert_eq!(policy.next_crf("35", VideoCodec::H265), None);
+        assert_eq!(
+            policy.next_crf(&u32::MAX.to_string(), VideoCodec::H264),
+            None
+        );
+    }
+
+    #[test]
+    fn test_choose_work_folder_spills_when_full() {
+        let tmpfs = Path::new("/dev/shm/plexify");
+        let spill = Path::new("/mnt/scratch");
+
+        // Fits in the tmpfs, including the reserve
+        assert_eq!(
+            choose_work_folder(tmpfs, Some(spill), 1000, 500, Some(1500)),
+            tmpfs
+        );
+        // Would overflow it
+        assert_eq!(
+            choose_work_folder(tmpfs, Some(spill), 1000, 500, Some(1499)),
+            spill
+        );
+        // Nowhere to spill to, or free space unknown: keep the work folder
+        assert_eq!(choose_work_folder(tmpfs, None, 1000, 0, Some(10)), tmpfs);
+        assert_eq!(choose_work_folder(tmpfs, Some(spill), 1000, 0, None), tmpfs);
+    }
+
+    #[test]
+    fn test_free_space_check() {
+        let path = Path::new("/work/output.mp4");
+        assert!(check_free_space(path, 1000, 0, 1000).is_ok());
+        assert!(check_free_space(path, 1000, 500, 2000).is_ok());
+
+        let error = check_free_space(path, 1000, 500, 1499).unwrap_err();
+        assert!(matches!(
+            error,
+            PlexifyError::InsufficientSpace {
+                required: 1500,
+                available: 1499,
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("Not enough free space"));
+        assert!(error.is_retryable());
+
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("500M"), Ok(500 << 20));
+        assert_eq!(parse_size("10GB"), Ok(10 << 30));
+        assert_eq!(parse_size("1.5g"), Ok(3 << 29));
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("G").is_err());
+    }
+
+    #[test]
+    fn test_tonemap_filter() {
+        assert_eq!(tonemap_filter(Some("smpte2084")), Some(TONEMAP_FILTER));
+        assert_eq!(tonemap_filter(Some("arib-std-b67")), Some(TONEMAP_FILTER));
+
+        // SDR and unknown sources pass through
+        assert_eq!(tonemap_filter(Some("bt709")), None);
+        assert_eq!(tonemap_filter(None), None);
+
+        let args = FFmpegCommandBuilder::new()
+            .with_video_filter(TONEMAP_FILTER)
+            .with_video_encoding(&QualitySettings::default())
+            .build();
+        assert_eq!(args[0], "-vf");
+        assert!(args[1].contains("tonemap=tonemap=hable"));
+        assert!(args[1].ends_with("format=yuv420p"));
+        assert_eq!(args[2], "-c:v");
+    }
+
+    #[test]
+    fn test_dual_audio_arguments() {
+        assert_eq!(
+            stream_mappings(false, SubtitleMode::Mux, AudioMode::Aac),
+            vec!["0:v:0", "0:a:0?", "0:s:0?"]
+        );
+        assert_eq!(
+            stream_mappings(true, SubtitleMode::Mux, AudioMode::DualAudio),
+            vec!["0:v:0", "0:a:0?", "0:a:0?", "1:s:0"]
+        );
+
+        let args = FFmpegCommandBuilder::new()
+            .with_stream_mapping(&stream_mappings(
+                false,
+                SubtitleMode::Mux,
+                AudioMode::DualAudio,
+            ))
+            .with_dual_audio_encoding(&QualitySettings::default())
+            .build();
+
+        assert_eq!(
+            args,
+            vec![
+                "-map",
+                "0:v:0",
+                "-map",
+                "0:a:0?",
+                "-map",
+                "0:a:0?",
+                "-map",
+                "0:s:0?",
+                "-c:a:0",
+                "copy",
+                "-c:a:1",
+                "aac",
+                "-b:a:1",
+                "128k",
+                "-ac:a:1",
+                "2",
+                "-disposition:a:0",
+                "0",
+                "-disposition:a:1",
+                "default",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ffmpeg_command_builder_mkv() {
+        let quality = QualitySettings {
+            ffmpeg_preset: "veryfast".to_string(),
+            ffmpeg_crf: "23".to_string(),
+            ffmpeg_audio_bitrate: "128k".to_string(),
+            video_codec: VideoCodec::H264,
+        };
+
+        let args = FFmpegCommandBuilder::new()
+            .with_common_flags()
+            .with_subtitle_duration_fix()
+            .with_input("/path/to/video.mkv")
+            .with_stream_mapping(&["0:v:0", "0:a:0", "0:s:0"])
+            .with_video_encoding(&quality)
+            .with_audio_encoding(&quality)
+            .with_subtitle_encoding()
+            .with_overwrite()
+            .with_output("/path/to/output.mp4")
+            .build();
+
+        let expected = vec![
+            "-fflags",
+            "+genpts",
+            "-avoid_negative_ts",
+            "make_zero",
+            "-fix_sub_duration",
+            "-i",
+            "/path/to/video.mkv",
+            "-map",
+            "0:v:0",
+            "-map",
+            "0:a:0",
+            "-map",
+            "0:s:0",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-crf",
+            "23",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "128k",
+            "-c:s",
+            "mov_text",
+            "-y",
+            "/path/to/output.mp4",
+        ];
+
+        assert_eq!(args, expected);
+    }
+
+    #[tokio::test]
+    async fn test_render_job_command() {
+        let job = Job::new(
+            PathBuf::from("Movies/Heat (1995)/Heat.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            Path::new("/media"),
+        );
+        let processor = FFmpegProcessor::new(Config::default(), true);
+
+        let cmd = processor
+            .job_command(&job, Some(Path::new("/media")), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            render_command(&cmd),
+            "nice -n 19 ffmpeg -fflags +genpts -avoid_negative_ts make_zero -fix_sub_duration \
+             -i '/media/Movies/Heat (1995)/Heat.mkv' -map 0:v:0 -map '0:a:0?' -map '0:s:0?' \
+             -c:v libx264 -preset veryfast -crf 23 -c:a aac -b:a 128k -c:s mov_text \
+             -y '/media/Movies/Heat (1995)/Heat.mp4'"
+        );
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[tokio::test]
+    async fn test_remux_uses_stream_copy() {
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("/media/clip.mp4"),
+            MediaFileType::Mp4,
+            QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            Path::new("/media"),
+        )
+        .with_downmix_stereo(true);
+        assert!(job.replaces_input());
+
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                Path::new("/work/clip.mp4"),
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            args,
+            vec![
+                "-fflags",
+                "+genpts",
+                "-avoid_negative_ts",
+                "make_zero",
+                "-i",
+                "/media/clip.mp4",
+                "-map",
+                "0",
+                "-c",
+                "copy",
+                "-movflags",
+                "+faststart",
+                "-y",
+                "/work/clip.mp4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_h265_video_encoding_args() {
+        let quality = QualityPreset::Quality.to_quality_settings(VideoCodec::H265);
+        let args = FFmpegCommandBuilder::new()
+            .with_video_encoding(&quality)
+            .build();
+        assert_eq!(
+            args,
+            vec!["-c:v", "libx265", "-preset", "slow", "-crf", "23", "-tag:v", "hvc1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subtitle_conversion_args() {
+        let job = Job::new(
+            PathBuf::from("Series/Show/Season 01/Show S01E01.srt"),
+            MediaFileType::Subtitle,
+            QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            Path::new("/media"),
+        );
+        assert_eq!(
+            job.output_path,
+            PathBuf::from("/media/Series/Show/Season 01/Show S01E01.vtt")
+        );
+        assert_eq!(job.subtitle_path, None);
+
+        let cmd = FFmpegProcessor::new(Config::default(), false)
+            .job_command(&job, Some(Path::new("/media")), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            render_command(&cmd),
+            "ffmpeg -i '/media/Series/Show/Season 01/Show S01E01.srt' \
+             -y '/media/Series/Show/Season 01/Show S01E01.vtt'"
+        );
+
+        // And back again
+        let job = Job::new(
+            PathBuf::from("/media/clip.vtt"),
+            MediaFileType::Subtitle,
+            QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            Path::new("/media"),
+        );
+        assert_eq!(job.output_path, PathBuf::from("/media/clip.srt"));
+    }
+
+    #[tokio::test]
+    async fn test_needs_faststart() {
+        fn mp4_box(kind: &[u8; 4], payload: usize) -> Vec<u8> {
+            let mut data = ((8 + payload) as u32).to_be_bytes().to_vec();
+            data.extend_from_slice(kind);
+            data.extend(std::iter::repeat_n(0, payload));
+            data
+        }
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let streaming = temp_dir.path().join("streaming.mp4");
+        let data = [
+            mp4_box(b"ftyp", 8),
+            mp4_box(b"moov", 16),
+            mp4_box(b"mdat", 64),
+        ]
+        .concat();
+        std::fs::write(&streaming, data).unwrap();
+        assert!(!needs_faststart(&streaming).await.unwrap());
+
+        let index_last = temp_dir.path().join("index_last.mp4");
+        let data = [
+            mp4_box(b"ftyp", 8),
+            mp4_box(b"mdat", 64),
+            mp4_box(b"moov", 16),
+        ]
+        .concat();
+        std::fs::write(&index_last, data).unwrap();
+        assert!(needs_faststart(&index_last).await.unwrap());
+
+        let garbage = temp_dir.path().join("garbage.mp4");
+        std::fs::write(&garbage, [0u8; 4]).unwrap();
+        assert!(needs_faststart(&garbage).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tag_metadata_arguments() {
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let metadata_args = |args: &[String]| {
+            args.windows(2)
+                .filter(|w| w[0] == "-metadata")
+                .map(|w| w[1].clone())
+                .collect::<Vec<_>>()
+        };
+        let job = |path: &str| {
+            Job::new(
+                PathBuf::from(path),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                crate::job::PostProcessingSettings::default(),
+                Path::new("/media"),
+            )
+        };
+
+        let episode = job("Series/Breaking Bad/Season 01/Breaking Bad - s01e03 - Gray Matter.mkv");
+        let args = processor
+            .job_args(
+                &episode,
+                &episode.quality_settings,
+                &episode.input_path,
+                &episode.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert!(metadata_args(&args).is_empty());
+
+        let episode = episode.with_tag_metadata(true);
+        let args = processor
+            .job_args(
+                &episode,
+                &episode.quality_settings,
+                &episode.input_path,
+                &episode.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            metadata_args(&args),
+            vec!["title=Breaking Bad - S01E03", "encoder=plexify"]
+        );
+
+        let movie = job("Movies/Heat (1995)/Heat (1995).mkv").with_tag_metadata(true);
+        let args = processor
+            .job_args(
+                &movie,
+                &movie.quality_settings,
+                &movie.input_path,
+                &movie.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            metadata_args(&args),
+            vec!["title=Heat (1995)", "encoder=plexify"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ass_subtitle_mux_and_burn_arguments() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("Show's Season");
+        std::fs::create_dir_all(&media_root).unwrap();
+        std::fs::write(media_root.join("episode.webm"), "").unwrap();
+        std::fs::write(media_root.join("episode.ass"), "").unwrap();
+        let ass_path = media_root.join("episode.ass");
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("episode.webm"),
+            MediaFileType::WebM,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            &media_root,
+        )
+        .with_subtitle_path(Some(ass_path.clone()));
+        let args_for = |job: &Job| {
+            let job = job.clone();
+            let processor = &processor;
+            async move {
+                processor
+                    .job_args(
+                        &job,
+                        &job.quality_settings,
+                        &job.input_path,
+                        &job.output_path,
+                        None,
+                        &InputProbe::default(),
+                    )
+                    .unwrap()
+            }
+        };
+
+        // Muxed: the .ass is a second input converted to mov_text
+        let args = args_for(&job).await;
+        assert!(args.contains(&ass_path.to_string_lossy().to_string()));
+        assert!(args.windows(2).any(|w| w[0] == "-map" && w[1] == "1:s:0"));
+        assert!(args
+            .windows(2)
+            .any(|w| w[0] == "-c:s" && w[1] == "mov_text"));
+        assert!(!args.contains(&"-vf".to_string()));
+
+        // Burned: no subtitle input, stream or codec, just the filter
+        let args = args_for(&job.clone().with_subtitle_mode(SubtitleMode::Burn)).await;
+        assert_eq!(args.iter().filter(|a| *a == "-i").count(), 1);
+        assert!(!args.iter().any(|a| a.contains(":s:0") || a == "-c:s"));
+        let filter = &args[args.iter().position(|a| a == "-vf").unwrap() + 1];
+        let escaped_root = media_root.to_string_lossy().replace('\'', r"\\\'");
+        assert_eq!(filter, &format!("subtitles={escaped_root}/episode.ass"));
+        assert!(!args.iter().any(|a| a.contains(":si=")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_chapters_cover_every_part() {
+        let temp_dir = TempDir::new().unwrap();
+        let ffmpeg = test_support::script(
+            temp_dir.path(),
+            "ffmpeg",
+            "case \"$*\" in\n\
+             *part1*) echo '[silencedetect @ 0x1] silence_end: 50 | silence_duration: 3' >&2;;\n\
+             *) echo '[silencedetect @ 0x1] silence_end: 40 | silence_duration: 3' >&2;;\n\
+             esac\n",
+        );
+        let ffprobe = test_support::script(temp_dir.path(), "ffprobe", "echo 100.0\n");
+        let processor = FFmpegProcessor::new(
+            Config {
+                ffmpeg_path: ffmpeg.to_string_lossy().to_string(),
+                ffprobe_path: ffprobe.to_string_lossy().to_string(),
+                ..Config::default()
+            },
+            false,
+        );
+
+        let chapters_path = temp_dir.path().join("chapters.txt");
+        let inputs = [
+            temp_dir.path().join("concert part1.mkv"),
+            temp_dir.path().join("concert part2.mkv"),
         ];
+        processor
+            .write_chapters(&inputs, &chapters_path)
+            .await
+            .unwrap();
 
-        assert_eq!(args, expected);
+        // The second part's gap comes 100 seconds in, after the first part
+        assert_eq!(
+            std::fs::read_to_string(&chapters_path).unwrap(),
+            chapters::ffmetadata(&[0.0, 50.0, 140.0], 200.0)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_chapter_detection_obeys_job_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let ffmpeg = test_support::script(temp_dir.path(), "ffmpeg", "exec sleep 5\n");
+        let processor = FFmpegProcessor::new(
+            Config {
+                ffmpeg_path: ffmpeg.to_string_lossy().to_string(),
+                ..Config::default()
+            },
+            false,
+        )
+        .with_job_timeout(Some(Duration::from_millis(200)));
+
+        let err = processor
+            .silence_ends(&temp_dir.path().join("concert.mkv"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexifyError>(),
+            Some(PlexifyError::Timeout(_))
+        ));
     }
 
     #[test]
-    fn test_ffmpeg_command_builder_mkv() {
-        let quality = QualitySettings {
-            ffmpeg_preset: "veryfast".to_string(),
-            ffmpeg_crf: "23".to_string(),
-            ffmpeg_audio_bitrate: "128k".to_string(),
+    fn test_burn_without_subtitles_is_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        std::fs::write(media_root.join("movie.mkv"), "").unwrap();
+        let job = Job::new(
+            PathBuf::from("movie.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            media_root,
+        )
+        .with_subtitle_mode(SubtitleMode::Burn);
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let args_with = |stream_types: &[&str]| {
+            let probe = InputProbe {
+                stream_types: Some(stream_types.iter().map(|t| t.to_string()).collect()),
+                ..InputProbe::default()
+            };
+            processor
+                .job_args(
+                    &job,
+                    &job.quality_settings,
+                    &job.input_path,
+                    &job.output_path,
+                    None,
+                    &probe,
+                )
+                .unwrap()
         };
 
-        let args = FFmpegCommandBuilder::new()
-            .with_common_flags()
-            .with_subtitle_duration_fix()
-            .with_input("/path/to/video.mkv")
-            .with_stream_mapping(&["0:v:0", "0:a:0", "0:s:0"])
-            .with_video_encoding(&quality)
-            .with_audio_encoding(&quality)
-            .with_subtitle_encoding()
-            .with_overwrite()
-            .with_output("/path/to/output.mp4")
-            .build();
+        let args = args_with(&["video", "audio"]);
+        assert!(!args.iter().any(|a| a.contains("subtitles=")));
 
-        let expected = vec![
-            "-fflags",
-            "+genpts",
-            "-avoid_negative_ts",
-            "make_zero",
-            "-fix_sub_duration",
-            "-i",
-            "/path/to/video.mkv",
-            "-map",
-            "0:v:0",
-            "-map",
-            "0:a:0",
-            "-map",
-            "0:s:0",
-            "-c:v",
-            "libx264",
-            "-preset",
-            "veryfast",
-            "-crf",
-            "23",
-            "-c:a",
-            "aac",
-            "-b:a",
-            "128k",
-            "-c:s",
-            "mov_text",
-            "-y",
-            "/path/to/output.mp4",
-        ];
+        let args = args_with(&["video", "audio", "subtitle"]);
+        let filter = &args[args.iter().position(|a| a == "-vf").unwrap() + 1];
+        assert!(filter.ends_with(":si=0"));
+    }
 
-        assert_eq!(args, expected);
+    #[test]
+    fn test_subtitle_language_from_name() {
+        let language = |name: &str| subtitle_language_from_name(Path::new(name));
+        assert_eq!(language("Show/Episode.en.srt"), Some("eng".to_string()));
+        assert_eq!(language("Episode.swe.vtt"), Some("swe".to_string()));
+        assert_eq!(language("Episode.SV.srt"), Some("swe".to_string()));
+        assert_eq!(language("Episode.srt"), None);
+        assert_eq!(language("Episode.und.srt"), None);
+        assert_eq!(language("Show.S01E01.srt"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_muxed_subtitle_keeps_language_tag() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        std::fs::write(media_root.join("movie.mkv"), "").unwrap();
+
+        // Reports a Swedish subtitle stream and nothing else
+        let ffprobe = test_support::script(
+            media_root,
+            "ffprobe",
+            "case \"$*\" in *stream_tags=language*) echo swe;; esac\n",
+        );
+        let config = Config {
+            ffprobe_path: ffprobe.to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        let processor = FFmpegProcessor::new(config, false);
+        let job = Job::new(
+            PathBuf::from("movie.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            media_root,
+        );
+        let probe = processor.probe_input(&job, &job.input_path, None).await;
+        assert_eq!(probe.subtitle_language, Some("swe".to_string()));
+
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                &job.output_path,
+                None,
+                &probe,
+            )
+            .unwrap();
+        assert!(args
+            .windows(2)
+            .any(|w| w[0] == "-metadata:s:s:0" && w[1] == "language=swe"));
+
+        // Burned subtitles have no stream to tag, so the language isn't probed
+        let burned = job.with_subtitle_mode(SubtitleMode::Burn);
+        let probe = processor
+            .probe_input(&burned, &burned.input_path, None)
+            .await;
+        assert_eq!(probe.subtitle_language, None);
+        let args = processor
+            .job_args(
+                &burned,
+                &burned.quality_settings,
+                &burned.input_path,
+                &burned.output_path,
+                None,
+                &probe,
+            )
+            .unwrap();
+        assert!(!args.iter().any(|a| a.starts_with("-metadata:s:s")));
+    }
+
+    #[tokio::test]
+    async fn test_non_ascii_and_non_utf8_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path().join("Amélie (2001)");
+        std::fs::create_dir_all(&media_root).unwrap();
+        std::fs::write(media_root.join("Amélie – 日本語.mkv"), "").unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("Amélie – 日本語.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            &media_root,
+        );
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                &job.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert!(args.contains(&job.input_path.to_str().unwrap().to_string()));
+        assert_eq!(args.last().unwrap(), job.output_path.to_str().unwrap());
+
+        // A lossy conversion would send ffmpeg to another file, so it is an error instead
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+
+            let name = std::ffi::OsStr::from_bytes(b"Am\xe9lie.mkv");
+            std::fs::write(media_root.join(name), "").unwrap();
+            let job = Job::new(
+                PathBuf::from(name),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                &media_root,
+            );
+            let error = processor
+                .job_args(
+                    &job,
+                    &job.quality_settings,
+                    &job.input_path,
+                    &job.output_path,
+                    None,
+                    &InputProbe::default(),
+                )
+                .unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<PlexifyError>(),
+                Some(PlexifyError::NonUtf8Path(path)) if *path == job.input_path
+            ));
+            assert!(!crate::error::is_retryable(&error));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sample_encode_arguments() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        std::fs::write(media_root.join("movie.mkv"), "").unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("movie.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings {
+                disable_source_files: true,
+            },
+            media_root,
+        );
+        let sample = job.clone().with_sample(60);
+
+        // The sample does not stand in for the real output or touch the source
+        assert_eq!(sample.output_path, media_root.join("movie.sample.mp4"));
+        assert_ne!(sample.output_path, job.output_path);
+        assert!(!sample.post_processing.disable_source_files);
+        std::fs::write(&sample.output_path, "").unwrap();
+        assert!(!job.output_exists(None));
+
+        let args = processor
+            .job_args(
+                &sample,
+                &sample.quality_settings,
+                &sample.input_path,
+                &sample.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert!(args.windows(2).any(|w| w[0] == "-t" && w[1] == "60"));
+        assert_eq!(args.last().unwrap(), &sample.output_path.to_string_lossy());
+
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                &job.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert!(!args.contains(&"-t".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_split_episode_arguments() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        for part in ["Show S01E01 part1.mkv", "Show S01E01 part2.mkv"] {
+            std::fs::write(media_root.join(part), "").unwrap();
+        }
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("Show S01E01 part1.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            media_root,
+        )
+        .with_extra_inputs(&[PathBuf::from("Show S01E01 part2.mkv")], media_root);
+        assert_eq!(job.output_path, media_root.join("Show S01E01.mp4"));
+        assert_eq!(
+            job.input_paths(None),
+            [
+                media_root.join("Show S01E01 part1.mkv"),
+                media_root.join("Show S01E01 part2.mkv")
+            ]
+        );
+
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                &job.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        // The parts are read through one concat input, so mappings still refer to input 0
+        let list_path = concat::concat_list_path(&job.output_path);
+        let start = args.iter().position(|arg| arg == "-f").unwrap();
+        assert_eq!(
+            args[start..start + 6],
+            [
+                "-f",
+                "concat",
+                "-safe",
+                "0",
+                "-i",
+                &list_path.to_string_lossy()
+            ]
+        );
+        assert_eq!(args.iter().filter(|arg| *arg == "-i").count(), 1);
+        assert!(args.windows(2).any(|w| w[0] == "-map" && w[1] == "0:v:0"));
+
+        // Burned-in subtitles would only cover the first part
+        let burned = job.with_subtitle_mode(SubtitleMode::Burn);
+        assert!(processor
+            .job_args(
+                &burned,
+                &burned.quality_settings,
+                &burned.input_path,
+                &burned.output_path,
+                None,
+                &InputProbe::default()
+            )
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_auto_chapters_are_mapped_from_metadata_input() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        std::fs::write(media_root.join("concert.mkv"), "").unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("concert.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            media_root,
+        )
+        .with_auto_chapters(true);
+        let processor = &processor;
+        let args_for = |job: Job| async move {
+            processor
+                .job_args(
+                    &job,
+                    &job.quality_settings,
+                    &job.input_path,
+                    &job.output_path,
+                    None,
+                    &InputProbe::default(),
+                )
+                .unwrap()
+        };
+
+        // Detection failed or found nothing to write: no chapters input
+        let args = args_for(job.clone()).await;
+        assert!(!args.contains(&"-map_chapters".to_string()));
+
+        let chapters_path = chapters::chapters_path(&job.output_path);
+        std::fs::write(&chapters_path, chapters::ffmetadata(&[0.0, 184.52], 600.0)).unwrap();
+        let args = args_for(job.clone()).await;
+        let inputs: Vec<&String> = args
+            .windows(2)
+            .filter(|w| w[0] == "-i")
+            .map(|w| &w[1])
+            .collect();
+        assert_eq!(inputs[1], &chapters_path.to_string_lossy());
+        assert!(args
+            .windows(2)
+            .any(|w| w[0] == "-map_chapters" && w[1] == "1"));
+
+        // Jobs that didn't ask for chapters ignore a leftover file
+        let args = args_for(job.with_auto_chapters(false)).await;
+        assert!(!args.contains(&"-map_chapters".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_optional_stream_mappings() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        std::fs::write(media_root.join("movie.mkv"), "").unwrap();
+        std::fs::write(media_root.join("episode.webm"), "").unwrap();
+        std::fs::write(media_root.join("episode.vtt"), "").unwrap();
+
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let maps_for = |name: &str, file_type: MediaFileType| {
+            let job = Job::new(
+                PathBuf::from(name),
+                file_type,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                media_root,
+            );
+            let processor = &processor;
+            async move {
+                let args = processor
+                    .job_args(
+                        &job,
+                        &job.quality_settings,
+                        &job.input_path,
+                        &job.output_path,
+                        None,
+                        &InputProbe::default(),
+                    )
+                    .unwrap();
+                args.windows(2)
+                    .filter(|w| w[0] == "-map")
+                    .map(|w| w[1].clone())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Embedded audio and subtitles may be missing, the video may not
+        assert_eq!(
+            maps_for("movie.mkv", MediaFileType::Mkv).await,
+            vec!["0:v:0", "0:a:0?", "0:s:0?"]
+        );
+        // The sidecar is checked up front, so only the audio is optional
+        assert_eq!(
+            maps_for("episode.webm", MediaFileType::WebM).await,
+            vec!["0:v:0", "0:a:0?", "1:s:0"]
+        );
+
+        let streams = |types: &[&str]| types.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            absent_streams(&streams(&["video"]), false, SubtitleMode::Mux),
+            vec!["audio", "subtitle"]
+        );
+        assert_eq!(
+            absent_streams(&streams(&["video", "audio"]), true, SubtitleMode::Mux),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            absent_streams(&streams(&["video", "audio"]), false, SubtitleMode::Burn),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            absent_streams(&streams(&["video", "subtitle"]), false, SubtitleMode::Mux),
+            vec!["audio"]
+        );
     }
 
     #[test]
-    fn test_ffmpeg_command_builder_build_command() {
-        let quality = QualitySettings::default();
-        let builder = FFmpegCommandBuilder::new()
-            .with_common_flags()
-            .with_video_encoding(&quality);
+    fn test_subtitles_filter_escaping() {
+        assert_eq!(
+            subtitles_filter(Path::new("/media/Show/episode.ass"), None),
+            "subtitles=/media/Show/episode.ass"
+        );
+        assert_eq!(
+            subtitles_filter(Path::new("/media/Show/episode.mkv"), Some(0)),
+            "subtitles=/media/Show/episode.mkv:si=0"
+        );
+        // Windows paths: drive colon and backslashes are escaped at both levels
+        assert_eq!(
+            subtitles_filter(Path::new(r"C:\Videos\Show\episode.ass"), None),
+            r"subtitles=C\\:\\\\Videos\\\\Show\\\\episode.ass"
+        );
+        // Option-level escapes for ' and :, then filtergraph-level escapes on top
+        assert_eq!(
+            subtitles_filter(Path::new("/media/It's: [Part 1], done;.ass"), None),
+            r"subtitles=/media/It\\\'s\\: \[Part 1\]\, done\;.ass"
+        );
+    }
 
-        let mut cmd = Command::new("ffmpeg");
-        builder.build_command(&mut cmd);
+    #[tokio::test]
+    async fn test_burn_embedded_subtitles_arguments() {
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("/media/movie.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/media"),
+        )
+        .with_subtitle_mode(SubtitleMode::Burn);
+
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                &job.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        let filter = &args[args.iter().position(|a| a == "-vf").unwrap() + 1];
+        assert_eq!(filter, "subtitles=/media/movie.mkv:si=0");
+        assert!(!args.iter().any(|a| a.contains(":s:0") || a == "-c:s"));
+        // Burning needs the video re-encoded
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "libx264"));
+
+        // A stream-copy remux cannot burn anything in
+        let remux = Job::new(
+            PathBuf::from("/media/movie.mp4"),
+            MediaFileType::Mp4,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/media"),
+        )
+        .with_subtitle_mode(SubtitleMode::Burn);
+        assert!(processor
+            .job_args(
+                &remux,
+                &remux.quality_settings,
+                &remux.input_path,
+                &remux.output_path,
+                None,
+                &InputProbe::default()
+            )
+            .is_err());
+    }
 
-        // We can't easily test the internal state of Command, but we can verify
-        // the builder doesn't panic when applied to a command
-        assert_eq!(cmd.as_std().get_program(), "ffmpeg");
+    #[tokio::test]
+    async fn test_downmix_stereo_arguments() {
+        let processor = FFmpegProcessor::new(Config::default(), false);
+        let job = Job::new(
+            PathBuf::from("/media/movie.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            crate::job::PostProcessingSettings::default(),
+            Path::new("/media"),
+        );
+        let has_downmix = |args: &[String]| args.windows(2).any(|w| w[0] == "-ac" && w[1] == "2");
+
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                &job.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert!(!has_downmix(&args));
+
+        let job = job.with_downmix_stereo(true);
+        let args = processor
+            .job_args(
+                &job,
+                &job.quality_settings,
+                &job.input_path,
+                &job.output_path,
+                None,
+                &InputProbe::default(),
+            )
+            .unwrap();
+        assert!(has_downmix(&args));
+        let audio_bitrate = args.iter().position(|a| a == "-b:a").unwrap();
+        let downmix = args.iter().position(|a| a == "-ac").unwrap();
+        assert!(downmix > audio_bitrate);
     }
 
     #[test]
@@ -555,7 +3270,7 @@ mod tests {
 
         // Move the file - since job now has absolute paths, pass None for media_root
         processor
-            .move_to_destination(&job, None, &work_folder)
+            .move_to_destination(&job, None, &work_folder, None)
             .await
             .unwrap();
 
@@ -567,4 +3282,99 @@ mod tests {
         let content = tokio::fs::read_to_string(&final_path).await.unwrap();
         assert_eq!(content, "test content");
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_move_to_destination_sets_output_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let work_folder = temp_dir.path().join("work");
+        let media_folder = temp_dir.path().join("media");
+        tokio::fs::create_dir_all(&work_folder).await.unwrap();
+        tokio::fs::create_dir_all(&media_folder).await.unwrap();
+        let input_path = media_folder.join("test.mkv");
+        std::fs::write(&input_path, "source").unwrap();
+        std::fs::set_permissions(&input_path, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            &media_folder,
+        );
+        let mode_of = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+
+        // A configured mode wins over the source's
+        std::fs::write(job.work_folder_output_path(&work_folder), "output").unwrap();
+        FFmpegProcessor::new(Config::default(), false)
+            .with_output_permissions(OutputPermissions {
+                match_source: true,
+                mode: Some(0o664),
+                owner: None,
+            })
+            .move_to_destination(&job, None, &work_folder, None)
+            .await
+            .unwrap();
+        assert_eq!(mode_of(&job.full_output_path(None)), 0o664);
+
+        std::fs::write(job.work_folder_output_path(&work_folder), "output").unwrap();
+        FFmpegProcessor::new(Config::default(), false)
+            .with_output_permissions(OutputPermissions {
+                match_source: true,
+                ..OutputPermissions::default()
+            })
+            .move_to_destination(&job, None, &work_folder, None)
+            .await
+            .unwrap();
+        assert_eq!(mode_of(&job.full_output_path(None)), 0o640);
+    }
+
+    #[tokio::test]
+    async fn test_move_to_destination_preserves_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_folder = temp_dir.path().join("work");
+        let media_folder = temp_dir.path().join("media");
+        tokio::fs::create_dir_all(&work_folder).await.unwrap();
+        tokio::fs::create_dir_all(&media_folder).await.unwrap();
+        let input_path = media_folder.join("test.mkv");
+        let added = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        std::fs::write(&input_path, "source").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&input_path)
+            .unwrap()
+            .set_modified(added)
+            .unwrap();
+
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            &media_folder,
+        );
+        let modified = |path: &Path| std::fs::metadata(path).unwrap().modified().unwrap();
+
+        std::fs::write(job.work_folder_output_path(&work_folder), "output").unwrap();
+        FFmpegProcessor::new(Config::default(), false)
+            .move_to_destination(&job, None, &work_folder, None)
+            .await
+            .unwrap();
+        assert_ne!(modified(&job.full_output_path(None)), added);
+
+        // Read before the encode, which may overwrite the source in place
+        let processor = FFmpegProcessor::new(Config::default(), false).with_preserve_mtime(true);
+        let source_modified = processor.source_modified(&job, None);
+        assert_eq!(source_modified, Some(added));
+        std::fs::write(&input_path, "remuxed in place").unwrap();
+
+        std::fs::write(job.work_folder_output_path(&work_folder), "output").unwrap();
+        processor
+            .move_to_destination(&job, None, &work_folder, source_modified)
+            .await
+            .unwrap();
+        assert_eq!(modified(&job.full_output_path(None)), added);
+    }
 }
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use std::path::Path;
+#[cfg(unix)]
+use tracing::warn;
+
+/// Mode and ownership to give outputs once they are in the media folder, for when the
+/// worker runs as a different user than the media server
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputPermissions {
+    /// Copy the mode and ownership of the source file
+    pub match_source: bool,
+    /// File mode such as `0o644`, taking precedence over the source's
+    pub mode: Option<u32>,
+    /// User and group ids, taking precedence over the source's
+    pub owner: Option<Owner>,
+}
+
+/// A numeric `uid[:gid]` owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Owner {
+    pub uid: u32,
+    pub gid: Option<u32>,
+}
+
+/// Parse an octal file mode such as `644` or `0o664`
+pub fn parse_mode(value: &str) -> Result<u32, String> {
+    let digits = value.trim().trim_start_matches("0o");
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if mode <= 0o7777 => Ok(mode),
+        _ => Err(format!("invalid file mode '{value}' (use octal, e.g. 644)")),
+    }
+}
+
+/// Parse a numeric owner such as `1000` or `1000:1000`
+pub fn parse_owner(value: &str) -> Result<Owner, String> {
+    let invalid = || format!("invalid owner '{value}' (use uid or uid:gid)");
+    let (uid, gid) = match value.split_once(':') {
+        Some((uid, gid)) => (uid, Some(gid)),
+        None => (value, None),
+    };
+    Ok(Owner {
+        uid: uid.trim().parse().map_err(|_| invalid())?,
+        gid: gid
+            .map(|gid| gid.trim().parse().map_err(|_| invalid()))
+            .transpose()?,
+    })
+}
+
+impl OutputPermissions {
+    pub fn is_empty(&self) -> bool {
+        !self.match_source && self.mode.is_none() && self.owner.is_none()
+    }
+
+    /// Set the mode and ownership of `output`, looking at `source` when matching it.
+    /// Ownership is only changed where it differs, and failing to change it (e.g.
+    /// when not running as root) is a warning; the mode is still applied.
+    /// Does nothing on platforms without unix permissions.
+    #[cfg(unix)]
+    pub fn apply(&self, source: &Path, output: &Path) -> Result<()> {
+        use std::os::unix::fs::{chown, MetadataExt, PermissionsExt};
+
+        let source_metadata = if self.match_source {
+            Some(std::fs::metadata(source)?)
+        } else {
+            None
+        };
+        let mode = self
+            .mode
+            .or_else(|| source_metadata.as_ref().map(|m| m.mode() & 0o7777));
+        let (uid, gid) = match (self.owner, &source_metadata) {
+            (Some(owner), _) => (Some(owner.uid), owner.gid),
+            (None, Some(metadata)) => (Some(metadata.uid()), Some(metadata.gid())),
+            (None, None) => (None, None),
+        };
+        let output_metadata = std::fs::metadata(output)?;
+        let uid = uid.filter(|&uid| uid != output_metadata.uid());
+        let gid = gid.filter(|&gid| gid != output_metadata.gid());
+
+        // Ownership first: chown clears setuid/setgid bits the mode may ask for
+        if uid.is_some() || gid.is_some() {
+            if let Err(e) = chown(output, uid, gid) {
+                warn!(
+                    "Failed to change owner of {:?} to {:?}:{:?}: {}",
+                    output, uid, gid, e
+                );
+            }
+        }
+        if let Some(mode) = mode {
+            std::fs::set_permissions(output, std::fs::Permissions::from_mode(mode))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn apply(&self, _source: &Path, _output: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mode_and_owner() {
+        assert_eq!(parse_mode("644"), Ok(0o644));
+        assert_eq!(parse_mode("0o2775"), Ok(0o2775));
+        assert!(parse_mode("999").is_err());
+        assert!(parse_mode("rw-r--r--").is_err());
+
+        assert_eq!(
+            parse_owner("1000:100"),
+            Ok(Owner {
+                uid: 1000,
+                gid: Some(100)
+            })
+        );
+        assert_eq!(
+            parse_owner("1000"),
+            Ok(Owner {
+                uid: 1000,
+                gid: None
+            })
+        );
+        assert!(parse_owner("plex:plex").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply_skips_matching_owner() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("output.mp4");
+        std::fs::write(&output, "output").unwrap();
+        let metadata = std::fs::metadata(&output).unwrap();
+
+        OutputPermissions {
+            match_source: false,
+            mode: Some(0o640),
+            owner: Some(Owner {
+                uid: metadata.uid(),
+                gid: Some(metadata.gid()),
+            }),
+        }
+        .apply(temp_dir.path(), &output)
+        .unwrap();
+        assert_eq!(
+            std::fs::metadata(&output).unwrap().permissions().mode() & 0o7777,
+            0o640
+        );
+    }
+}
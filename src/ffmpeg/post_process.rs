@@ -0,0 +1,219 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use crate::commands::hooks::split_words;
+use crate::error::PlexifyError;
+
+/// What a failing post-processing step does to its job
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StepFailure {
+    /// Fail the job, leaving the output out of the media folder
+    #[default]
+    Fail,
+    /// Log a warning and carry on with the next step
+    Warn,
+}
+
+/// An external tool run on each output after it is encoded and before it is moved
+/// into the media folder, e.g. `MP4Box -inter 500 {output}`
+///
+/// Written as `[fail:|warn:]COMMAND`; without a prefix a failing step fails the job.
+/// The command is split into words like a hook (see `Hook`) and `{output}` is
+/// replaced with the path of the encoded file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostProcessStep {
+    command: String,
+    args: Vec<String>,
+    on_failure: StepFailure,
+}
+
+impl PostProcessStep {
+    pub fn parse(step: &str) -> Result<Self> {
+        let (on_failure, command) = if let Some(command) = step.strip_prefix("warn:") {
+            (StepFailure::Warn, command)
+        } else if let Some(command) = step.strip_prefix("fail:") {
+            (StepFailure::Fail, command)
+        } else {
+            (StepFailure::Fail, step)
+        };
+        let args = split_words(command)?;
+        if args.is_empty() {
+            return Err(anyhow!("Post-processing command is empty"));
+        }
+        Ok(Self {
+            command: command.trim().to_string(),
+            args,
+            on_failure,
+        })
+    }
+
+    /// The program and arguments with `{output}` substituted
+    pub fn render(&self, output_path: &Path) -> Vec<String> {
+        let output = output_path.to_string_lossy();
+        self.args
+            .iter()
+            .map(|arg| arg.replace("{output}", &output))
+            .collect()
+    }
+
+    /// Where the step's program is: the path as written when it has a directory
+    /// part, else the first match on `PATH`. Checked at startup, so a mistyped tool
+    /// fails the worker instead of every job it encodes.
+    pub fn resolve_program(&self) -> Result<PathBuf> {
+        let program = Path::new(&self.args[0]);
+        let found = if program.components().count() > 1 {
+            program.is_file().then(|| program.to_path_buf())
+        } else {
+            std::env::var_os("PATH").and_then(|paths| {
+                std::env::split_paths(&paths)
+                    .flat_map(|dir| executable_candidates(&dir.join(program)))
+                    .find(|candidate| candidate.is_file())
+            })
+        };
+        found.ok_or_else(|| {
+            anyhow!(
+                "Post-processing program '{}' of step '{}' was not found",
+                self.args[0],
+                self.command
+            )
+        })
+    }
+
+    /// Run the step on an output
+    async fn run(&self, output_path: &Path) -> Result<(), PlexifyError> {
+        let args = self.render(output_path);
+        debug!("Running post-processing step: {:?}", args);
+        let failed = |reason: String| PlexifyError::PostProcessFailed {
+            step: self.command.clone(),
+            reason,
+        };
+        let output = Command::new(&args[0])
+            .args(&args[1..])
+            .output()
+            .await
+            .map_err(|e| failed(e.to_string()))?;
+        if !output.status.success() {
+            return Err(failed(format!(
+                "exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Names a program on `PATH` may have: as given, plus `.exe` on Windows
+fn executable_candidates(path: &Path) -> Vec<PathBuf> {
+    let mut candidates = vec![path.to_path_buf()];
+    if cfg!(windows) && path.extension().is_none() {
+        candidates.push(path.with_extension("exe"));
+    }
+    candidates
+}
+
+/// Run the steps on an output in order, stopping at the first that fails the job
+pub async fn run_steps(steps: &[PostProcessStep], output_path: &Path) -> Result<()> {
+    for step in steps {
+        match step.run(output_path).await {
+            Ok(()) => info!("🔧 Post-processed {:?} with '{}'", output_path, step),
+            Err(e) if step.on_failure == StepFailure::Warn => warn!("⚠️ {}", e),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+impl FromStr for PostProcessStep {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for PostProcessStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_post_process_step() {
+        let step = PostProcessStep::parse("MP4Box -inter 500 {output}").unwrap();
+        assert_eq!(step.on_failure, StepFailure::Fail);
+        assert_eq!(
+            step.render(&PathBuf::from("/work/Show S01E01.mp4")),
+            vec!["MP4Box", "-inter", "500", "/work/Show S01E01.mp4"]
+        );
+
+        let step =
+            PostProcessStep::parse("warn:mkvpropedit '{output}' --add-track-statistics-tags")
+                .unwrap();
+        assert_eq!(step.on_failure, StepFailure::Warn);
+        assert_eq!(
+            step.to_string(),
+            "mkvpropedit '{output}' --add-track-statistics-tags"
+        );
+
+        assert!(PostProcessStep::parse("warn:").is_err());
+        assert!(PostProcessStep::parse("MP4Box 'unclosed").is_err());
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_steps_run_in_order_on_output() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("Show S01E01.mp4");
+        std::fs::write(&output, "encoded").unwrap();
+        let append = |text: &str| format!(r#"sh -c 'printf " {text}" >> "$0"' {{output}}"#);
+
+        let steps = [
+            PostProcessStep::parse(&append("one")).unwrap(),
+            PostProcessStep::parse("warn:false").unwrap(),
+            PostProcessStep::parse(&append("two")).unwrap(),
+        ];
+        run_steps(&steps, &output).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "encoded one two");
+
+        // A failing step stops the pipeline and fails the job
+        let steps = [
+            PostProcessStep::parse("false").unwrap(),
+            PostProcessStep::parse(&append("three")).unwrap(),
+        ];
+        let error = run_steps(&steps, &output).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PlexifyError>(),
+            Some(PlexifyError::PostProcessFailed { step, .. }) if step == "false"
+        ));
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "encoded one two");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_program() {
+        let step = PostProcessStep::parse("sh -c true").unwrap();
+        assert!(step.resolve_program().unwrap().ends_with("sh"));
+        let step = PostProcessStep::parse("/bin/sh -c true").unwrap();
+        assert_eq!(step.resolve_program().unwrap(), PathBuf::from("/bin/sh"));
+
+        let error = PostProcessStep::parse("warn:MP4Bxo -inter 500 {output}")
+            .unwrap()
+            .resolve_program()
+            .unwrap_err();
+        assert!(error.to_string().contains("'MP4Bxo'"));
+        assert!(PostProcessStep::parse("/nonexistent/MP4Box {output}")
+            .unwrap()
+            .resolve_program()
+            .is_err());
+    }
+}
@@ -3,6 +3,7 @@ use glob::Pattern;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tracing::{debug, trace};
 
 /// Handles .plexifyignore files with gitignore-style pattern matching
@@ -12,6 +13,12 @@ pub struct IgnoreFilter {
     patterns_by_dir: HashMap<PathBuf, Vec<IgnorePattern>>,
     /// The root directory for the ignore filter
     root: PathBuf,
+    /// Memoized decisions for directories, so parent lookups don't re-evaluate
+    /// (and re-stat) every ancestor for every file
+    dir_cache: Arc<Mutex<HashMap<PathBuf, bool>>>,
+    /// For each directory encountered, the pattern source directories that apply to
+    /// its contents, ordered from the root to the most specific
+    sources_cache: Arc<Mutex<HashMap<PathBuf, Arc<[PathBuf]>>>>,
 }
 
 /// A single ignore pattern with its metadata
@@ -25,6 +32,8 @@ struct IgnorePattern {
     negation: bool,
     /// Whether this pattern should match directories only (ends with /)
     directory_only: bool,
+    /// Line number in the source .plexifyignore file (1-based)
+    line: usize,
 }
 
 impl IgnoreFilter {
@@ -33,6 +42,8 @@ impl IgnoreFilter {
         let mut filter = Self {
             patterns_by_dir: HashMap::new(),
             root,
+            dir_cache: Arc::default(),
+            sources_cache: Arc::default(),
         };
 
         // Load all .plexifyignore files in the tree
@@ -73,19 +84,18 @@ impl IgnoreFilter {
             .lines()
             .enumerate()
             .filter_map(|(line_num, line)| {
-                let trimmed = line.trim();
-
                 // Skip empty lines and comments
-                if trimmed.is_empty() || trimmed.starts_with('#') {
-                    return None;
-                }
+                let cleaned = clean_ignore_line(line)?;
 
-                match IgnorePattern::new(trimmed) {
-                    Ok(pattern) => Some(pattern),
+                match IgnorePattern::new(&cleaned) {
+                    Ok(pattern) => Some(IgnorePattern {
+                        line: line_num + 1,
+                        ..pattern
+                    }),
                     Err(e) => {
                         debug!(
                             "Invalid pattern '{}' in {}:{}: {}",
-                            trimmed,
+                            cleaned,
                             ignore_file.display(),
                             line_num + 1,
                             e
@@ -109,89 +119,41 @@ impl IgnoreFilter {
     }
 
     /// Check if a path should be ignored
-    /// Returns true if the path should be ignored, false otherwise
-    pub fn should_ignore(&self, path: &Path) -> bool {
-        let relative_path = match path.strip_prefix(&self.root) {
-            Ok(rel) => rel,
-            Err(_) => {
-                // Path is not under root, don't ignore
-                return false;
+    /// Returns true if the path should be ignored, false otherwise.
+    /// `is_dir` comes from the caller (e.g. a walkdir entry) to avoid an extra stat.
+    pub fn should_ignore(&self, path: &Path, is_dir: bool) -> bool {
+        if is_dir {
+            if let Some(&ignored) = self.dir_cache.lock().unwrap().get(path) {
+                return ignored;
             }
-        };
-
-        // Convert to forward slashes for consistent matching
-        let path_str = relative_path.to_string_lossy().replace("\\", "/");
-        let is_dir = path.is_dir();
-
-        trace!(
-            "Checking if path should be ignored: {} (is_dir: {})",
-            path_str,
-            is_dir
-        );
+        }
 
-        // First check if any parent directory is ignored
-        if !is_dir {
-            let mut current_parent = path.parent();
-            while let Some(parent) = current_parent {
-                if parent != self.root && self.should_ignore(parent) {
+        // The path's own patterns win; a matching negation re-includes it even
+        // when an ancestor directory is ignored
+        let ignored = match self.evaluate(path, is_dir, |_, _| {}) {
+            Some(ignored) => ignored,
+            None => {
+                let parent_ignored = self.ignored_parent(path).is_some();
+                if parent_ignored {
                     trace!(
                         "Path '{}' ignored because parent directory is ignored",
-                        path_str
+                        path.display()
                     );
-                    return true;
                 }
-                current_parent = parent.parent();
+                parent_ignored
             }
-        }
-
-        // Check patterns from all applicable directories, starting from root to specific
-        let mut ignored = false;
-
-        // Get all directories that could have patterns affecting this path
-        let mut applicable_dirs: Vec<_> = self
-            .patterns_by_dir
-            .keys()
-            .filter(|dir| {
-                // Include if the pattern directory is an ancestor of the path
-                path.starts_with(dir) || dir == &&self.root
-            })
-            .collect();
-
-        // Sort by depth (root first, then deeper directories)
-        applicable_dirs.sort_by_key(|dir| dir.components().count());
-
-        for dir in applicable_dirs {
-            if let Some(patterns) = self.patterns_by_dir.get(dir) {
-                // Calculate relative path from this pattern directory
-                let pattern_relative_path = if dir == &self.root {
-                    path_str.clone()
-                } else {
-                    match path.strip_prefix(dir) {
-                        Ok(rel) => rel.to_string_lossy().replace("\\", "/"),
-                        Err(_) => path_str.clone(), // Fallback to full relative path
-                    }
-                };
+        };
 
-                for pattern in patterns {
-                    if pattern.matches(&pattern_relative_path, is_dir)
-                        || pattern.matches(&path_str, is_dir)
-                    {
-                        ignored = !pattern.negation;
-                        trace!(
-                            "Pattern '{}' from {} {} path '{}'",
-                            pattern.original,
-                            dir.display(),
-                            if ignored { "ignores" } else { "includes" },
-                            path_str
-                        );
-                    }
-                }
-            }
+        if is_dir {
+            self.dir_cache
+                .lock()
+                .unwrap()
+                .insert(path.to_path_buf(), ignored);
         }
 
         trace!(
             "Final decision for '{}': {}",
-            path_str,
+            path.display(),
             if ignored { "IGNORE" } else { "INCLUDE" }
         );
         ignored
@@ -199,42 +161,150 @@ impl IgnoreFilter {
 
     /// Check if a directory should be skipped during traversal
     /// This is an optimized version for directory-level checking that doesn't
-    /// perform parent directory lookups to avoid infinite recursion during walkdir
+    /// perform parent directory lookups to avoid infinite recursion during walkdir.
+    /// Ignored directories are still walked when a negation pattern could
+    /// re-include something inside them. The path must be a directory.
     pub fn should_skip_dir(&self, path: &Path) -> bool {
-        if !path.is_dir() {
-            return false;
+        let ignored = self.evaluate(path, true, |_, _| {}).unwrap_or(false)
+            && !self.could_reinclude_within(path);
+        trace!(
+            "Directory skip decision for '{}': {}",
+            path.display(),
+            if ignored { "SKIP" } else { "CONTINUE" }
+        );
+        ignored
+    }
+
+    /// Explain why a path is or isn't ignored: every pattern that matched it,
+    /// in evaluation order, plus the final decision
+    pub fn explain(&self, path: &Path) -> IgnoreExplanation {
+        let is_dir = path.is_dir();
+        let ignored_parent = self.ignored_parent(path);
+
+        let mut matches = Vec::new();
+        let decision = self.evaluate(path, is_dir, |dir, pattern| {
+            matches.push(PatternMatch {
+                source: dir.join(".plexifyignore"),
+                line: pattern.line,
+                pattern: pattern.to_string(),
+                negation: pattern.negation,
+            });
+        });
+        let ignored = decision.unwrap_or(ignored_parent.is_some());
+
+        IgnoreExplanation {
+            path: path.to_path_buf(),
+            is_dir,
+            ignored_parent,
+            matches,
+            ignored,
+        }
+    }
+
+    /// Find the closest ancestor directory (below the root) that is ignored
+    fn ignored_parent(&self, path: &Path) -> Option<PathBuf> {
+        let mut current_parent = path.parent();
+        while let Some(parent) = current_parent {
+            if parent != self.root && self.should_ignore(parent, true) {
+                return Some(parent.to_path_buf());
+            }
+            current_parent = parent.parent();
         }
+        None
+    }
 
+    /// Check whether a negation pattern could match something inside this directory,
+    /// in which case it must still be walked even if the directory itself is ignored
+    fn could_reinclude_within(&self, dir: &Path) -> bool {
+        self.patterns_by_dir.iter().any(|(source_dir, patterns)| {
+            // Negations in ignore files inside the directory may apply to its contents
+            if source_dir.starts_with(dir) {
+                return patterns.iter().any(|p| p.negation);
+            }
+
+            let Ok(relative_dir) = dir.strip_prefix(source_dir) else {
+                return false;
+            };
+            let relative_dir = format!("{}/", relative_dir.to_string_lossy().replace("\\", "/"));
+
+            patterns.iter().filter(|p| p.negation).any(|p| {
+                // Only root-anchored patterns are tied to a specific location;
+                // everything else can match at any depth
+                let Some(anchored) = p.original.strip_prefix('/') else {
+                    return true;
+                };
+                let literal_prefix = anchored.split(['*', '?', '[']).next().unwrap_or_default();
+                literal_prefix.starts_with(&relative_dir)
+                    || relative_dir.starts_with(literal_prefix)
+            })
+        })
+    }
+
+    /// Pattern source directories that apply inside `dir`, root first. Built from the
+    /// parent's list, so each directory is computed once.
+    fn applicable_sources(&self, dir: &Path) -> Arc<[PathBuf]> {
+        if let Some(sources) = self.sources_cache.lock().unwrap().get(dir) {
+            return Arc::clone(sources);
+        }
+
+        let mut sources: Vec<PathBuf> = match dir.parent() {
+            Some(parent) if dir != self.root && dir.starts_with(&self.root) => {
+                self.applicable_sources(parent).to_vec()
+            }
+            // The root's patterns always apply
+            _ if self.patterns_by_dir.contains_key(&self.root) => vec![self.root.clone()],
+            _ => Vec::new(),
+        };
+        if dir != self.root && self.patterns_by_dir.contains_key(dir) {
+            sources.push(dir.to_path_buf());
+        }
+
+        let sources: Arc<[PathBuf]> = sources.into();
+        self.sources_cache
+            .lock()
+            .unwrap()
+            .insert(dir.to_path_buf(), Arc::clone(&sources));
+        sources
+    }
+
+    /// Run every applicable pattern against the path, from the root to the most
+    /// specific directory, calling `on_match` for each pattern that matches.
+    /// The last matching pattern decides: returns Some(true) if the path is
+    /// ignored, Some(false) if it is re-included, or None if nothing matched.
+    fn evaluate(
+        &self,
+        path: &Path,
+        is_dir: bool,
+        mut on_match: impl FnMut(&Path, &IgnorePattern),
+    ) -> Option<bool> {
         let relative_path = match path.strip_prefix(&self.root) {
             Ok(rel) => rel,
             Err(_) => {
-                // Path is not under root, don't skip
-                return false;
+                // Path is not under root, don't ignore
+                return None;
             }
         };
 
         // Convert to forward slashes for consistent matching
         let path_str = relative_path.to_string_lossy().replace("\\", "/");
 
-        trace!("Checking if directory should be skipped: {}", path_str);
+        trace!(
+            "Checking if path should be ignored: {} (is_dir: {})",
+            path_str,
+            is_dir
+        );
 
         // Check patterns from all applicable directories, starting from root to specific
-        let mut ignored = false;
-
-        // Get all directories that could have patterns affecting this path
-        let mut applicable_dirs: Vec<_> = self
-            .patterns_by_dir
-            .keys()
-            .filter(|dir| {
-                // Include if the pattern directory is an ancestor of the path
-                path.starts_with(dir) || dir == &&self.root
-            })
-            .collect();
+        let mut decision = None;
 
-        // Sort by depth (root first, then deeper directories)
-        applicable_dirs.sort_by_key(|dir| dir.components().count());
+        // A directory's own .plexifyignore applies to it; a file only sees its parents'
+        let containing_dir = if is_dir {
+            path
+        } else {
+            path.parent().unwrap_or(&self.root)
+        };
 
-        for dir in applicable_dirs {
+        for dir in self.applicable_sources(containing_dir).iter() {
             if let Some(patterns) = self.patterns_by_dir.get(dir) {
                 // Calculate relative path from this pattern directory
                 let pattern_relative_path = if dir == &self.root {
@@ -247,40 +317,70 @@ impl IgnoreFilter {
                 };
 
                 for pattern in patterns {
-                    if pattern.matches(&pattern_relative_path, true)
-                        || pattern.matches(&path_str, true)
+                    if pattern.matches(&pattern_relative_path, is_dir)
+                        || pattern.matches(&path_str, is_dir)
                     {
-                        ignored = !pattern.negation;
+                        decision = Some(!pattern.negation);
                         trace!(
-                            "Pattern '{}' from {} {} directory '{}'",
+                            "Pattern '{}' from {} {} path '{}'",
                             pattern.original,
                             dir.display(),
-                            if ignored { "ignores" } else { "includes" },
+                            if pattern.negation {
+                                "includes"
+                            } else {
+                                "ignores"
+                            },
                             path_str
                         );
+                        on_match(dir, pattern);
                     }
                 }
             }
         }
 
-        trace!(
-            "Directory skip decision for '{}': {}",
-            path_str,
-            if ignored { "SKIP" } else { "CONTINUE" }
-        );
-        ignored
+        decision
     }
 }
 
+/// A pattern that matched a path, as reported by [`IgnoreFilter::explain`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternMatch {
+    /// The .plexifyignore file the pattern came from
+    pub source: PathBuf,
+    /// Line number of the pattern in its source file (1-based)
+    pub line: usize,
+    /// The pattern as written (including any leading '!' or trailing '/')
+    pub pattern: String,
+    /// Whether the pattern re-includes the path
+    pub negation: bool,
+}
+
+/// Why a path is or isn't ignored
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnoreExplanation {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Ancestor directory that is ignored, which ignores this path regardless of its own patterns
+    pub ignored_parent: Option<PathBuf>,
+    /// Matching patterns in evaluation order; the last one decides
+    pub matches: Vec<PatternMatch>,
+    /// Final decision
+    pub ignored: bool,
+}
+
 impl IgnorePattern {
     /// Create a new ignore pattern from a string
     fn new(pattern_str: &str) -> Result<Self> {
-        let mut pattern_str = pattern_str.trim();
+        let mut pattern_str = pattern_str;
 
-        // Check for negation
+        // Check for negation (a leading "\!" matches a literal '!')
         let negation = pattern_str.starts_with('!');
         if negation {
             pattern_str = &pattern_str[1..];
+        } else if let Some(stripped) = pattern_str.strip_prefix('\\') {
+            if stripped.starts_with('!') {
+                pattern_str = stripped;
+            }
         }
 
         // Check for directory-only pattern
@@ -300,6 +400,7 @@ impl IgnorePattern {
             original: pattern_str.to_string(),
             negation,
             directory_only,
+            line: 0,
         })
     }
 
@@ -328,6 +429,49 @@ impl IgnorePattern {
     }
 }
 
+impl std::fmt::Display for IgnorePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negation {
+            write!(f, "!")?;
+        }
+        write!(f, "{}", self.original)?;
+        if self.directory_only {
+            write!(f, "/")?;
+        }
+        Ok(())
+    }
+}
+
+/// Clean a raw .plexifyignore line following gitignore rules.
+/// Returns None for blank lines and comments (lines starting with '#').
+/// A leading "\#" matches a literal '#', and trailing spaces are dropped
+/// unless escaped with a backslash ("\ ").
+fn clean_ignore_line(line: &str) -> Option<String> {
+    let line = line.trim_start().trim_end_matches(['\r', '\n']);
+
+    if line.starts_with('#') {
+        return None;
+    }
+
+    // Drop trailing spaces that aren't escaped
+    let mut end = line.len();
+    while line[..end].ends_with(' ') && !line[..end - 1].ends_with('\\') {
+        end -= 1;
+    }
+    let line = &line[..end];
+
+    if line.is_empty() {
+        return None;
+    }
+
+    // Unescape the leading hash and any escaped spaces
+    let line = line
+        .strip_prefix('\\')
+        .filter(|rest| rest.starts_with('#'))
+        .unwrap_or(line);
+    Some(line.replace("\\ ", " "))
+}
+
 /// Convert gitignore-style patterns to glob patterns
 fn convert_gitignore_to_glob(pattern: &str) -> String {
     let mut result = String::new();
@@ -368,13 +512,13 @@ mod tests {
         let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
 
         // Should ignore
-        assert!(filter.should_ignore(&root.join("test.tmp")));
-        assert!(filter.should_ignore(&root.join("Downloads")));
-        assert!(filter.should_ignore(&root.join("tools")));
+        assert!(filter.should_ignore(&root.join("test.tmp"), false));
+        assert!(filter.should_ignore(&root.join("Downloads"), true));
+        assert!(filter.should_ignore(&root.join("tools"), true));
 
         // Should not ignore
-        assert!(!filter.should_ignore(&root.join("video.mkv")));
-        assert!(!filter.should_ignore(&root.join("Anime")));
+        assert!(!filter.should_ignore(&root.join("video.mkv"), false));
+        assert!(!filter.should_ignore(&root.join("Anime"), true));
     }
 
     #[test]
@@ -399,15 +543,15 @@ mod tests {
         let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
 
         // Root patterns should apply
-        assert!(filter.should_ignore(&root.join("test.tmp")));
+        assert!(filter.should_ignore(&root.join("test.tmp"), false));
 
         // Nested patterns should apply
-        assert!(filter.should_ignore(&root.join("Series/old")));
-        assert!(filter.should_ignore(&root.join("Series/old/episode.mkv")));
+        assert!(filter.should_ignore(&root.join("Series/old"), true));
+        assert!(filter.should_ignore(&root.join("Series/old/episode.mkv"), false));
 
         // Should not ignore
-        assert!(!filter.should_ignore(&root.join("Series/show.mkv")));
-        assert!(!filter.should_ignore(&root.join("Series/important.mkv")));
+        assert!(!filter.should_ignore(&root.join("Series/show.mkv"), false));
+        assert!(!filter.should_ignore(&root.join("Series/important.mkv"), false));
     }
 
     #[test]
@@ -424,10 +568,10 @@ mod tests {
         let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
 
         // Should ignore directory
-        assert!(filter.should_ignore(&root.join("temp")));
+        assert!(filter.should_ignore(&root.join("temp"), true));
 
         // Should not ignore file with similar name
-        assert!(!filter.should_ignore(&root.join("temp_file")));
+        assert!(!filter.should_ignore(&root.join("temp_file"), false));
     }
 
     #[test]
@@ -444,13 +588,204 @@ mod tests {
         let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
 
         // Should ignore .mkv files
-        assert!(filter.should_ignore(&root.join("video.mkv")));
+        assert!(filter.should_ignore(&root.join("video.mkv"), false));
 
         // Should not ignore important.mkv due to negation
-        assert!(!filter.should_ignore(&root.join("important.mkv")));
+        assert!(!filter.should_ignore(&root.join("important.mkv"), false));
 
         // Should not ignore other files
-        assert!(!filter.should_ignore(&root.join("test.mp4")));
+        assert!(!filter.should_ignore(&root.join("test.mp4"), false));
+    }
+
+    #[test]
+    fn test_negation_reincludes_file_in_ignored_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::write(root.join(".plexifyignore"), "old/\n!old/keep.mkv").unwrap();
+        fs::create_dir_all(root.join("old/nested")).unwrap();
+        fs::write(root.join("old/keep.mkv"), "").unwrap();
+        fs::write(root.join("old/drop.mkv"), "").unwrap();
+        fs::write(root.join("old/nested/drop.mkv"), "").unwrap();
+
+        let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
+
+        // The directory itself is still ignored...
+        assert!(filter.should_ignore(&root.join("old"), true));
+        // ...but must be walked so the negation can take effect
+        assert!(!filter.should_skip_dir(&root.join("old")));
+
+        assert!(!filter.should_ignore(&root.join("old/keep.mkv"), false));
+        assert!(filter.should_ignore(&root.join("old/drop.mkv"), false));
+        assert!(filter.should_ignore(&root.join("old/nested"), true));
+        assert!(filter.should_ignore(&root.join("old/nested/drop.mkv"), false));
+    }
+
+    #[test]
+    fn test_ignored_directory_pruned_without_relevant_negation() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::write(
+            root.join(".plexifyignore"),
+            "old/\nDownloads/\n!/Downloads/keep.mkv",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("old")).unwrap();
+        fs::create_dir_all(root.join("Downloads")).unwrap();
+
+        let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
+
+        // The anchored negation can only apply inside Downloads/
+        assert!(filter.should_skip_dir(&root.join("old")));
+        assert!(!filter.should_skip_dir(&root.join("Downloads")));
+    }
+
+    #[test]
+    fn test_walker_file_type_matches_stat_results() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::write(
+            root.join(".plexifyignore"),
+            "*.tmp\nDownloads/\nold/\n!old/keep.mkv",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("Series/Season 01")).unwrap();
+        fs::write(root.join("Series/.plexifyignore"), "extras\n!*.keep.tmp").unwrap();
+        fs::create_dir_all(root.join("Series/extras/deep")).unwrap();
+        fs::create_dir_all(root.join("Downloads/sub")).unwrap();
+        fs::create_dir_all(root.join("old/nested")).unwrap();
+        for file in [
+            "movie.mkv",
+            "junk.tmp",
+            "Downloads/sub/video.mkv",
+            "old/keep.mkv",
+            "old/nested/drop.mkv",
+            "Series/Season 01/S01E01.mkv",
+            "Series/extras/deep/bonus.mkv",
+            "Series/notes.keep.tmp",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
+        let entries: Vec<_> = walkdir::WalkDir::new(root)
+            .min_depth(1)
+            .into_iter()
+            .map(|e| e.unwrap())
+            .collect();
+
+        let first_pass: Vec<bool> = entries
+            .iter()
+            .map(|e| filter.should_ignore(e.path(), e.file_type().is_dir()))
+            .collect();
+
+        for (entry, ignored) in entries.iter().zip(&first_pass) {
+            // Fresh filter, no cache, is_dir from stat
+            let fresh = IgnoreFilter::new(root.to_path_buf()).unwrap();
+            assert_eq!(
+                fresh.explain(entry.path()).ignored,
+                *ignored,
+                "{:?}",
+                entry.path()
+            );
+            // Cached decisions are stable
+            assert_eq!(
+                filter.should_ignore(entry.path(), entry.file_type().is_dir()),
+                *ignored
+            );
+        }
+
+        let ignored_names: Vec<_> = entries
+            .iter()
+            .zip(&first_pass)
+            .filter(|(_, ignored)| **ignored)
+            .map(|(e, _)| e.path().strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert!(ignored_names.contains(&PathBuf::from("Downloads/sub/video.mkv")));
+        assert!(ignored_names.contains(&PathBuf::from("Series/extras/deep/bonus.mkv")));
+        assert!(!ignored_names.contains(&PathBuf::from("old/keep.mkv")));
+        assert!(!ignored_names.contains(&PathBuf::from("Series/notes.keep.tmp")));
+    }
+
+    #[test]
+    fn test_applicable_sources_ordered_root_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::write(root.join(".plexifyignore"), "*.tmp").unwrap();
+        fs::write(root.join("a/b/.plexifyignore"), "*.log").unwrap();
+        fs::write(root.join("a/b/c/.plexifyignore"), "*.bak").unwrap();
+
+        let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
+
+        assert_eq!(
+            filter.applicable_sources(&root.join("a/b/c")).to_vec(),
+            vec![root.to_path_buf(), root.join("a/b"), root.join("a/b/c")]
+        );
+        assert_eq!(
+            filter.applicable_sources(&root.join("a")).to_vec(),
+            vec![root.to_path_buf()]
+        );
+
+        // Files only see their parents' patterns
+        assert!(filter.should_ignore(&root.join("a/b/c/x.log"), false));
+        assert!(!filter.should_ignore(&root.join("a/x.log"), false));
+    }
+
+    #[test]
+    fn test_clean_ignore_line() {
+        assert_eq!(clean_ignore_line("# comment"), None);
+        assert_eq!(clean_ignore_line("   "), None);
+        assert_eq!(clean_ignore_line(""), None);
+        assert_eq!(clean_ignore_line("*.tmp   "), Some("*.tmp".to_string()));
+        assert_eq!(clean_ignore_line("  *.tmp\r"), Some("*.tmp".to_string()));
+        assert_eq!(clean_ignore_line("\\#notes"), Some("#notes".to_string()));
+        assert_eq!(clean_ignore_line("file#1"), Some("file#1".to_string()));
+        assert_eq!(clean_ignore_line("name\\ "), Some("name ".to_string()));
+        assert_eq!(clean_ignore_line("name\\   "), Some("name ".to_string()));
+        assert_eq!(
+            clean_ignore_line("My Show\\ \\ "),
+            Some("My Show  ".to_string())
+        );
+        assert_eq!(clean_ignore_line("\\!keep"), Some("\\!keep".to_string()));
+    }
+
+    #[test]
+    fn test_escaped_hash_and_trailing_spaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::write(
+            root.join(".plexifyignore"),
+            "# a comment\n\\#notes\nplain.mkv   \nspaced\\ \n\\!bang.mkv\n",
+        )
+        .unwrap();
+
+        fs::write(root.join("#notes"), "").unwrap();
+        fs::write(root.join("plain.mkv"), "").unwrap();
+        fs::write(root.join("spaced "), "").unwrap();
+        fs::write(root.join("spaced"), "").unwrap();
+        fs::write(root.join("!bang.mkv"), "").unwrap();
+        fs::write(root.join("bang.mkv"), "").unwrap();
+        fs::write(root.join("a comment"), "").unwrap();
+
+        let filter = IgnoreFilter::new(root.to_path_buf()).unwrap();
+
+        // Escaped leading hash matches the literal file
+        assert!(filter.should_ignore(&root.join("#notes"), false));
+        // Unescaped trailing spaces are ignored
+        assert!(filter.should_ignore(&root.join("plain.mkv"), false));
+        // Escaped trailing space is part of the name
+        assert!(filter.should_ignore(&root.join("spaced "), false));
+        assert!(!filter.should_ignore(&root.join("spaced"), false));
+        // Escaped '!' is a literal, not a negation
+        assert!(filter.should_ignore(&root.join("!bang.mkv"), false));
+        assert!(!filter.should_ignore(&root.join("bang.mkv"), false));
+        // Comment lines are not patterns
+        assert!(!filter.should_ignore(&root.join("a comment"), false));
     }
 
     #[test]
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Bytes read from disk at a time while hashing
+const CHECKSUM_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Algorithm used to checksum media files so bit rot can be detected later
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    /// Don't checksum (default, since hashing reads every byte of the file)
+    #[default]
+    None,
+    /// CRC-32, fast and enough to catch accidental corruption
+    Crc32,
+}
+
+/// A file checksum and the algorithm that produced it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub value: String,
+}
+
+impl Checksum {
+    /// Checksum a file, or `None` when the algorithm is `ChecksumAlgorithm::None`
+    pub async fn compute(path: &Path, algorithm: ChecksumAlgorithm) -> Result<Option<Self>> {
+        let value = match algorithm {
+            ChecksumAlgorithm::None => return Ok(None),
+            ChecksumAlgorithm::Crc32 => {
+                let mut file = tokio::fs::File::open(path).await?;
+                let mut hasher = crc32fast::Hasher::new();
+                let mut buffer = vec![0; CHECKSUM_BUFFER_SIZE];
+                loop {
+                    let read = file.read(&mut buffer).await?;
+                    if read == 0 {
+                        break;
+                    }
+                    hasher.update(&buffer[..read]);
+                }
+                format!("{:08x}", hasher.finalize())
+            }
+        };
+        Ok(Some(Self { algorithm, value }))
+    }
+
+    /// Recompute the checksum of a file with the same algorithm and compare
+    pub async fn matches(&self, path: &Path) -> Result<bool> {
+        Ok(Self::compute(path, self.algorithm).await?.as_ref() == Some(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_checksum_detects_modified_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("episode.mkv");
+        std::fs::write(&path, "123456789").unwrap();
+
+        assert_eq!(
+            Checksum::compute(&path, ChecksumAlgorithm::None)
+                .await
+                .unwrap(),
+            None
+        );
+        let checksum = Checksum::compute(&path, ChecksumAlgorithm::Crc32)
+            .await
+            .unwrap()
+            .unwrap();
+        // The CRC-32 check value
+        assert_eq!(checksum.value, "cbf43926");
+        assert!(checksum.matches(&path).await.unwrap());
+
+        std::fs::write(&path, "123456780").unwrap();
+        assert!(!checksum.matches(&path).await.unwrap());
+    }
+}
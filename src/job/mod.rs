@@ -1,19 +1,128 @@
 use anyhow::{anyhow, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+mod checksum;
+mod template;
+
+pub use checksum::{Checksum, ChecksumAlgorithm};
+pub use template::OutputTemplate;
+
+/// Extension of the marker files written next to outputs plexify finished. Scans
+/// leave an input alone while its output has one, even if the output was moved away.
+pub const DONE_MARKER_EXTENSION: &str = "plexify-done";
+
+/// Extension of the marker left next to an input whose encode came out larger than
+/// it and was discarded (`--oversize keep-original`), so scans don't queue it again
+pub const KEPT_ORIGINAL_MARKER_EXTENSION: &str = "plexify-kept";
+
+/// Text subtitle sidecars that can go into an MP4, in order of preference
+pub const SUBTITLE_EXTENSIONS: &[&str] = &["vtt", "ass"];
+
+/// Schema version written into new job files. Bump it when a change to `Job`
+/// needs more than a `#[serde(default)]` to read older files, and upgrade them
+/// in `Job::migrate`.
+pub const JOB_SCHEMA_VERSION: u32 = 1;
+
+/// Retryable failures a job gets before it is moved to `_failed`
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Environment variables recorded in each job, since they change how it encodes
+const ENV_SNAPSHOT_PREFIXES: &[&str] = &["FFMPEG_", "PLEXIFY_FFMPEG", "PLEXIFY_FFPROBE"];
+
+/// Every WebVTT file starts with this
+const VTT_HEADER: &[u8] = b"WEBVTT";
+
+/// Image-based subtitle sidecars, which MP4 cannot carry
+pub const IMAGE_SUBTITLE_EXTENSIONS: &[&str] = &["idx", "sub"];
+
 /// Represents a media file that needs to be transcoded
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Job {
+    /// Schema version of the job file; files from before it was recorded read as 0
+    #[serde(default)]
+    pub version: u32,
     pub id: String,
     pub input_path: PathBuf,
+    /// Further parts of a split episode, joined after `input_path` in this order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_inputs: Vec<PathBuf>,
     pub output_path: PathBuf,
     pub subtitle_path: Option<PathBuf>,
     pub file_type: MediaFileType,
     pub quality_settings: QualitySettings,
     pub post_processing: PostProcessingSettings,
+    #[serde(default)]
+    pub audio_mode: AudioMode,
+    /// Tone-map HDR sources to SDR (SDR sources are left alone)
+    #[serde(default)]
+    pub tonemap: bool,
+    /// Add chapters at the silent gaps of the audio (e.g. between songs of a concert)
+    #[serde(default)]
+    pub auto_chapters: bool,
+    /// Downmix multichannel audio to stereo so dialog isn't lost on stereo clients
+    #[serde(default)]
+    pub downmix_stereo: bool,
+    /// Write a friendly `title` and an `encoder=plexify` tag into the output
+    #[serde(default)]
+    pub tag_metadata: bool,
+    /// Whether subtitles are muxed as a stream or burned into the video
+    #[serde(default)]
+    pub subtitle_mode: SubtitleMode,
+    /// Checksum of the input taken when the job was queued, for `verify --checksums`
+    #[serde(default)]
+    pub input_checksum: Option<Checksum>,
+    /// Free-form note, e.g. why this job has non-default settings. Meant for
+    /// hand-edited job files; shown when the job is claimed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Encoder-related environment variables in effect when the job was queued,
+    /// to explain later why two files encoded differently
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_snapshot: BTreeMap<String, String>,
+    /// Only encode this many seconds from the start, as a preview of the settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_secs: Option<u64>,
+    /// How often a worker already returned this job to the queue after a retryable failure
+    #[serde(default)]
+    pub retry_count: u32,
+    /// Retryable failures allowed before the job is failed for good
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_max_retries() -> u32 {
+    DEFAULT_MAX_RETRIES
+}
+
+/// The `FFMPEG_*` and ffmpeg/ffprobe path variables currently set
+pub fn env_snapshot() -> BTreeMap<String, String> {
+    // `vars()` panics on a non-Unicode variable anywhere in the environment
+    std::env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(key, _)| {
+            ENV_SNAPSHOT_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix))
+        })
+        .collect()
+}
+
+/// The name and part number of a split episode's file stem, e.g. `Show S01E01` and 2
+/// for `Show S01E01 - Part 2` (also `pt2` and `cd2`)
+pub fn split_part(stem: &str) -> Option<(&str, u32)> {
+    let re =
+        Regex::new(r"(?i)^(.*?)[\s._-]*\b(?:part|pt|cd)[\s._-]?(\d{1,2})$").expect("valid regex");
+    let captures = re.captures(stem)?;
+    let base = captures.get(1)?.as_str();
+    if base.is_empty() {
+        return None;
+    }
+    Some((base, captures[2].parse().ok()?))
 }
 
 /// Quality settings for video encoding
@@ -22,6 +131,46 @@ pub struct QualitySettings {
     pub ffmpeg_preset: String,
     pub ffmpeg_crf: String,
     pub ffmpeg_audio_bitrate: String,
+    /// Jobs queued before codecs were selectable are H.264
+    #[serde(default)]
+    pub video_codec: VideoCodec,
+}
+
+/// Video codec new encodes are written in
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    /// H.264 with libx264, which every client plays (default)
+    #[default]
+    H264,
+    /// H.265/HEVC with libx265, smaller files but not every client can play it
+    H265,
+}
+
+impl VideoCodec {
+    /// The ffmpeg encoder for this codec
+    pub fn encoder(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::H265 => "libx265",
+        }
+    }
+
+    /// How much higher this codec's CRF is than x264's for about the same quality
+    pub fn crf_offset(self) -> u32 {
+        match self {
+            VideoCodec::H264 => 0,
+            VideoCodec::H265 => 5,
+        }
+    }
+
+    /// CRF used when neither a preset nor `FFMPEG_CRF` sets one (the encoder's own default)
+    fn default_crf(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "23",
+            VideoCodec::H265 => "28",
+        }
+    }
 }
 
 /// Predefined quality presets for different use cases
@@ -45,13 +194,72 @@ pub struct PostProcessingSettings {
     pub disable_source_files: bool,
 }
 
+/// How the audio track is written to the output
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+pub enum AudioMode {
+    /// Transcode the first audio track to AAC (default)
+    #[default]
+    Aac,
+    /// Copy the original audio and add a default AAC stereo track for compatibility
+    DualAudio,
+}
+
+/// How subtitles end up in the output
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+pub enum SubtitleMode {
+    /// Mux them as a mov_text stream (styling is lost) (default)
+    #[default]
+    Mux,
+    /// Burn them into the video, keeping styling but re-encoding every frame
+    Burn,
+}
+
 /// Supported media file types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MediaFileType {
-    /// WebM file with external VTT subtitle
+    /// WebM file with an external VTT (or ASS) subtitle
     WebM,
-    /// MKV file with embedded subtitles
+    /// MKV file with embedded subtitles, or an external ASS subtitle next to it
     Mkv,
+    /// MP4 file remuxed in place (stream copy) to move the index up front for streaming
+    Mp4,
+    /// Subtitle sidecar converted to the other text format (`.srt` <-> `.vtt`); no video involved
+    Subtitle,
+}
+
+/// Text subtitle formats a subtitle job converts between
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SubtitleFormat {
+    /// WebVTT, which WebM inputs and browsers use
+    Vtt,
+    /// SubRip
+    Srt,
+}
+
+impl SubtitleFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SubtitleFormat::Vtt => "vtt",
+            SubtitleFormat::Srt => "srt",
+        }
+    }
+
+    /// The format of a subtitle file, from its extension (case-insensitive)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "vtt" => Some(SubtitleFormat::Vtt),
+            "srt" => Some(SubtitleFormat::Srt),
+            _ => None,
+        }
+    }
+
+    /// The format a subtitle job in this format converts to
+    pub fn other(self) -> Self {
+        match self {
+            SubtitleFormat::Vtt => SubtitleFormat::Srt,
+            SubtitleFormat::Srt => SubtitleFormat::Vtt,
+        }
+    }
 }
 
 /// Episode metadata extracted from file paths for prioritization
@@ -63,10 +271,34 @@ pub struct EpisodeMetadata {
     pub content_type: ContentType,
 }
 
+/// Where specials (season 0) go when prioritizing episodes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SpecialsOrder {
+    /// After all regular seasons of the show (default)
+    #[default]
+    Last,
+    /// Before the first season
+    First,
+}
+
+impl EpisodeMetadata {
+    /// Specials live in `Season 00` or `Specials` and are numbered `S00Exx`
+    pub fn is_special(&self) -> bool {
+        self.season_number == 0
+    }
+
+    /// Season number to sort by, with specials placed according to `specials`
+    pub fn season_sort_key(&self, specials: SpecialsOrder) -> u32 {
+        match (self.is_special(), specials) {
+            (true, SpecialsOrder::Last) => u32::MAX,
+            _ => self.season_number,
+        }
+    }
+}
+
 /// Content type for media files
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ContentType {
-    Movie,
     Series,
 }
 
@@ -89,24 +321,213 @@ impl Job {
         let output_path = match file_type {
             MediaFileType::WebM => absolute_input_path.with_extension("mp4"),
             MediaFileType::Mkv => absolute_input_path.with_extension("mp4"),
+            MediaFileType::Mp4 => absolute_input_path.clone(),
+            MediaFileType::Subtitle => {
+                let target = SubtitleFormat::from_path(&absolute_input_path)
+                    .map_or(SubtitleFormat::Vtt, SubtitleFormat::other);
+                absolute_input_path.with_extension(target.extension())
+            }
         };
 
+        // A WebM has no subtitles of its own, so it expects a .vtt next to it. Which
+        // sidecar is actually there is looked up when the job is built for the queue.
         let subtitle_path = match file_type {
             MediaFileType::WebM => Some(absolute_input_path.with_extension("vtt")),
-            MediaFileType::Mkv => None, // MKV uses embedded subtitles
+            MediaFileType::Mkv | MediaFileType::Mp4 | MediaFileType::Subtitle => None,
         };
 
         Self {
+            version: JOB_SCHEMA_VERSION,
             id: Uuid::new_v4().to_string(),
             input_path: absolute_input_path,
+            extra_inputs: Vec::new(),
             output_path,
             subtitle_path,
             file_type,
             quality_settings,
             post_processing,
+            audio_mode: AudioMode::default(),
+            tonemap: false,
+            auto_chapters: false,
+            downmix_stereo: false,
+            tag_metadata: false,
+            subtitle_mode: SubtitleMode::default(),
+            input_checksum: None,
+            notes: None,
+            env_snapshot: BTreeMap::new(),
+            sample_secs: None,
+            retry_count: 0,
+            max_retries: DEFAULT_MAX_RETRIES,
         }
     }
 
+    /// Whether another retryable failure should fail the job instead of requeueing it
+    pub fn retries_exhausted(&self) -> bool {
+        self.retry_count >= self.max_retries
+    }
+
+    /// Whether the job file was written by an older plexify and should be migrated
+    pub fn needs_migration(&self) -> bool {
+        self.version < JOB_SCHEMA_VERSION
+    }
+
+    /// Upgrade a job read from an older job file to the current schema
+    pub fn migrate(mut self) -> Self {
+        // Version 0 files predate the version field. Every field added since has a
+        // serde default, so only the version needs updating.
+        self.version = JOB_SCHEMA_VERSION;
+        self
+    }
+
+    /// The first text subtitle sidecar next to an input, e.g. `video.vtt` or `video.ass`
+    pub fn external_subtitle(input_path: &Path) -> Option<PathBuf> {
+        SUBTITLE_EXTENSIONS
+            .iter()
+            .map(|ext| input_path.with_extension(ext))
+            .find(|path| path.exists())
+    }
+
+    /// An image-based subtitle sidecar next to an input (`.idx`/`.sub`), which has to be skipped
+    pub fn image_subtitle(input_path: &Path) -> Option<PathBuf> {
+        IMAGE_SUBTITLE_EXTENSIONS
+            .iter()
+            .map(|ext| input_path.with_extension(ext))
+            .find(|path| path.exists())
+    }
+
+    /// Use this subtitle file instead of the default for the file type
+    pub fn with_subtitle_path(mut self, subtitle_path: Option<PathBuf>) -> Self {
+        self.subtitle_path = subtitle_path;
+        self
+    }
+
+    /// Record a checksum of the input
+    pub fn with_input_checksum(mut self, input_checksum: Option<Checksum>) -> Self {
+        self.input_checksum = input_checksum;
+        self
+    }
+
+    /// Attach a note to the job
+    pub fn with_notes(mut self, notes: Option<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Encode only the first `secs` seconds into `<name>.sample.<ext>`, so the real
+    /// output still counts as missing, and leave the source alone
+    pub fn with_sample(mut self, secs: u64) -> Self {
+        let extension = self
+            .output_path
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.output_path = self
+            .output_path
+            .with_extension(format!("sample.{extension}"));
+        self.sample_secs = Some(secs);
+        self.post_processing.disable_source_files = false;
+        self
+    }
+
+    /// Join further parts of a split episode after the input, writing one output named
+    /// without the part number (`Show S01E01 part1.mkv` -> `Show S01E01.mp4`)
+    pub fn with_extra_inputs(mut self, parts: &[PathBuf], media_root: &Path) -> Self {
+        self.extra_inputs = parts.iter().map(|part| media_root.join(part)).collect();
+        let input_stem = self.input_path.file_stem().and_then(|stem| stem.to_str());
+        let output_name = self.output_path.file_name().and_then(|name| name.to_str());
+        if let (Some(stem), Some(name)) = (input_stem, output_name) {
+            if let Some((base, _)) = split_part(stem).filter(|_| name.starts_with(stem)) {
+                self.output_path = self
+                    .output_path
+                    .with_file_name(name.replacen(stem, base, 1));
+            }
+        }
+        self
+    }
+
+    /// Every input of the job in order: the input, then any further parts
+    pub fn input_paths(&self, media_root: Option<&Path>) -> Vec<PathBuf> {
+        let mut paths = vec![self.full_input_path(media_root)];
+        paths.extend(self.extra_inputs.iter().map(|part| match media_root {
+            Some(root) => root.join(part),
+            None => part.clone(),
+        }));
+        paths
+    }
+
+    /// Record the environment the job was queued with
+    pub fn with_env_snapshot(mut self, env_snapshot: BTreeMap<String, String>) -> Self {
+        self.env_snapshot = env_snapshot;
+        self
+    }
+
+    /// Where a source ends up once disabled, e.g. `video.mkv.disabled`
+    pub fn disabled_path(path: &Path) -> PathBuf {
+        path.with_extension(format!(
+            "{}.disabled",
+            path.extension().unwrap_or_default().to_str().unwrap_or("")
+        ))
+    }
+
+    /// Mux or burn in the subtitles
+    pub fn with_subtitle_mode(mut self, subtitle_mode: SubtitleMode) -> Self {
+        self.subtitle_mode = subtitle_mode;
+        self
+    }
+
+    /// Whether the subtitle comes from a sidecar `.ass` file
+    pub fn has_ass_subtitle(&self) -> bool {
+        self.subtitle_path
+            .as_ref()
+            .and_then(|path| path.extension())
+            .is_some_and(|ext| ext == "ass")
+    }
+
+    /// Tag the output with a title and encoder
+    pub fn with_tag_metadata(mut self, tag_metadata: bool) -> Self {
+        self.tag_metadata = tag_metadata;
+        self
+    }
+
+    /// Whether the output overwrites the input (in-place MP4 remux)
+    pub fn replaces_input(&self) -> bool {
+        self.output_path == self.input_path
+    }
+
+    /// Downmix the audio to two channels
+    pub fn with_downmix_stereo(mut self, downmix_stereo: bool) -> Self {
+        self.downmix_stereo = downmix_stereo;
+        self
+    }
+
+    /// Tone-map the video to SDR when the source turns out to be HDR
+    pub fn with_tonemap(mut self, tonemap: bool) -> Self {
+        self.tonemap = tonemap;
+        self
+    }
+
+    /// Add chapter markers where the audio goes silent
+    pub fn with_auto_chapters(mut self, auto_chapters: bool) -> Self {
+        self.auto_chapters = auto_chapters;
+        self
+    }
+
+    /// Set how the audio track is written to the output
+    pub fn with_audio_mode(mut self, audio_mode: AudioMode) -> Self {
+        self.audio_mode = audio_mode;
+        self
+    }
+
+    /// Replace the default output path with one rendered from a template
+    pub fn with_output_template(
+        mut self,
+        template: &OutputTemplate,
+        media_root: &Path,
+    ) -> Result<Self> {
+        self.output_path = template.render(&self.input_path, media_root)?;
+        Ok(self)
+    }
+
     /// Get the job file name for the queue
     pub fn job_filename(&self) -> String {
         format!("{}.job", self.id)
@@ -143,10 +564,33 @@ impl Job {
                     Err(anyhow!("WebM job should have subtitle path"))
                 }
             }
-            MediaFileType::Mkv => Ok(true), // MKV doesn't need external subtitles
+            // MKV sidecars are optional: they are only picked up when present
+            MediaFileType::Mkv | MediaFileType::Mp4 | MediaFileType::Subtitle => Ok(true),
         }
     }
 
+    /// For WebM files with a `.vtt` sidecar, check the file is a WebVTT file: not
+    /// empty and starting with the `WEBVTT` header. ffmpeg would otherwise only fail
+    /// on it deep into the encode.
+    pub fn has_valid_subtitle(&self, media_root: Option<&Path>) -> Result<bool> {
+        let Some(subtitle_path) = self.full_subtitle_path(media_root) else {
+            return Ok(true);
+        };
+        if self.file_type != MediaFileType::WebM
+            || subtitle_path.extension().is_none_or(|ext| ext != "vtt")
+        {
+            return Ok(true);
+        }
+
+        let mut header = Vec::with_capacity(VTT_HEADER.len() + 3);
+        std::fs::File::open(&subtitle_path)?
+            .take(VTT_HEADER.len() as u64 + 3)
+            .read_to_end(&mut header)?;
+        // Editors on Windows like to start the file with a byte order mark
+        let header = header.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&header);
+        Ok(header.starts_with(VTT_HEADER))
+    }
+
     /// Get the full input path (for absolute paths, returns as-is; for relative paths, joins with media_root)
     pub fn full_input_path(&self, media_root: Option<&Path>) -> PathBuf {
         if self.input_path.is_absolute() {
@@ -171,6 +615,20 @@ impl Job {
         }
     }
 
+    /// Marker written next to a finished output with `--done-marker` (`video.mp4.plexify-done`)
+    pub fn done_marker_path(output_path: &Path) -> PathBuf {
+        let mut marker = output_path.as_os_str().to_owned();
+        marker.push(format!(".{DONE_MARKER_EXTENSION}"));
+        PathBuf::from(marker)
+    }
+
+    /// Marker written next to an input whose larger output was discarded (`video.mkv.plexify-kept`)
+    pub fn kept_original_marker_path(input_path: &Path) -> PathBuf {
+        let mut marker = input_path.as_os_str().to_owned();
+        marker.push(format!(".{KEPT_ORIGINAL_MARKER_EXTENSION}"));
+        PathBuf::from(marker)
+    }
+
     /// Get the work folder output path (where the file is written during transcoding)
     pub fn work_folder_output_path(&self, work_folder: &Path) -> PathBuf {
         // Create a unique filename for the work folder based on job ID and original filename
@@ -182,6 +640,16 @@ impl Job {
         work_folder.join(format!("{}_{}", self.id, output_filename))
     }
 
+    /// Hidden file next to the destination that `work --no-move` encodes into
+    /// (`.<id>_video.mp4`), so players and scanners skip it until it is renamed
+    pub fn staging_output_path(&self, media_root: Option<&Path>) -> PathBuf {
+        let final_output_path = self.full_output_path(media_root);
+        let directory = final_output_path.parent().unwrap_or(Path::new(""));
+        let staged = self.work_folder_output_path(directory);
+        let file_name = staged.file_name().unwrap_or_default().to_string_lossy();
+        directory.join(format!(".{}", file_name))
+    }
+
     /// Get the full subtitle path if it exists (for absolute paths, returns as-is; for relative paths, joins with media_root)
     pub fn full_subtitle_path(&self, media_root: Option<&Path>) -> Option<PathBuf> {
         self.subtitle_path.as_ref().map(|path| {
@@ -207,9 +675,39 @@ impl Job {
         format!("{stem}.job")
     }
 
+    /// Short name for log lines: `Show - S01E02` for episodes, else the file name
+    pub fn display_name(&self) -> String {
+        if let Some(metadata) = self.extract_episode_metadata() {
+            return format!(
+                "{} - S{:02}E{:02}",
+                metadata.series_name, metadata.season_number, metadata.episode_number
+            );
+        }
+        self.input_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.input_path.display().to_string())
+    }
+
+    /// Title to tag the output with: `Show - S01E02` for episodes, else the file stem
+    pub fn metadata_title(&self) -> String {
+        if self.extract_episode_metadata().is_some() {
+            return self.display_name();
+        }
+        self.input_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().trim().to_string())
+            .unwrap_or_else(|| self.display_name())
+    }
+
     /// Extract episode metadata from the job's input path for prioritization
     pub fn extract_episode_metadata(&self) -> Option<EpisodeMetadata> {
-        let path_str = self.input_path.to_str()?;
+        Self::episode_metadata_from_path(&self.input_path)
+    }
+
+    /// Extract episode metadata from a `Series/` or `Anime/` path
+    pub fn episode_metadata_from_path(path: &Path) -> Option<EpisodeMetadata> {
+        let path_str = path.to_str()?;
 
         // Try to match different episode patterns
         if let Some(metadata) = Self::try_parse_series_pattern(path_str, "Series") {
@@ -228,8 +726,9 @@ impl Job {
         // Matches: /path/Series/Show Name/Season XX/... SxxExx ...
         // Matches: /path/Series/Show Name {tvdb-123}/Season XX/... SxxExx ...
         // Matches: /path/Series/Show Name/Season XX - Extra/... SxxExx ...
+        // Matches: /path/Series/Show Name/Specials/... S00Exx ...
         let pattern = format!(
-            r"{}/([^/]+?)(?:\s*\{{tvdb-\d+\}})?/Season\s+(\d{{2}})(?:\s*-[^/]*)?/.*?[Ss](\d{{2}})[Ee](\d{{2}})",
+            r"{}/([^/]+?)(?:\s*\{{tvdb-\d+\}})?/(?:Season\s+(\d{{2}})(?:\s*-[^/]*)?|Specials)/.*?[Ss](\d{{2}})[Ee](\d{{2}})",
             regex::escape(content_prefix)
         );
 
@@ -237,7 +736,11 @@ impl Job {
         let captures = re.captures(path_str)?;
 
         let series_name = captures.get(1)?.as_str().trim().to_string();
-        let season_number: u32 = captures.get(2)?.as_str().parse().ok()?;
+        // A `Specials` folder has no number: it is season 0
+        let season_number: u32 = match captures.get(2) {
+            Some(season) => season.as_str().parse().ok()?,
+            None => 0,
+        };
         let episode_season: u32 = captures.get(3)?.as_str().parse().ok()?;
         let episode_number: u32 = captures.get(4)?.as_str().parse().ok()?;
 
@@ -256,66 +759,61 @@ impl Job {
 }
 
 impl QualitySettings {
-    /// Create quality settings from environment variables with defaults
-    pub fn from_env() -> Self {
+    /// Create quality settings for a codec from environment variables, defaulting
+    /// to the codec's own CRF
+    pub fn from_env(codec: VideoCodec) -> Self {
         use std::env;
         Self {
             ffmpeg_preset: env::var("FFMPEG_PRESET").unwrap_or_else(|_| "veryfast".to_string()),
-            ffmpeg_crf: env::var("FFMPEG_CRF").unwrap_or_else(|_| "23".to_string()),
+            ffmpeg_crf: env::var("FFMPEG_CRF").unwrap_or_else(|_| codec.default_crf().to_string()),
             ffmpeg_audio_bitrate: env::var("FFMPEG_AUDIO_BITRATE")
                 .unwrap_or_else(|_| "128k".to_string()),
+            video_codec: codec,
         }
     }
 
-    /// Create quality settings from a preset, with optional environment variable overrides
-    pub fn from_preset(preset: QualityPreset) -> Self {
+    /// Create quality settings from a preset for a codec, with optional environment
+    /// variable overrides
+    pub fn from_preset(preset: QualityPreset, codec: VideoCodec) -> Self {
         use std::env;
-        let base = preset.to_quality_settings();
+        let base = preset.to_quality_settings(codec);
 
         Self {
             ffmpeg_preset: env::var("FFMPEG_PRESET").unwrap_or(base.ffmpeg_preset),
             ffmpeg_crf: env::var("FFMPEG_CRF").unwrap_or(base.ffmpeg_crf),
             ffmpeg_audio_bitrate: env::var("FFMPEG_AUDIO_BITRATE")
                 .unwrap_or(base.ffmpeg_audio_bitrate),
+            video_codec: codec,
         }
     }
 
-    /// Create quality settings from a preset name string
-    pub fn from_preset_name(preset_name: &str) -> Result<Self> {
+    /// Create quality settings for a codec from a preset name string
+    pub fn from_preset_name(preset_name: &str, codec: VideoCodec) -> Result<Self> {
         let preset = QualityPreset::from_name(preset_name)?;
-        Ok(Self::from_preset(preset))
+        Ok(Self::from_preset(preset, codec))
     }
 }
 
 impl QualityPreset {
-    /// Convert preset to quality settings
-    pub fn to_quality_settings(&self) -> QualitySettings {
-        match self {
-            QualityPreset::Fast => QualitySettings {
-                ffmpeg_preset: "veryfast".to_string(),
-                ffmpeg_crf: "23".to_string(),
-                ffmpeg_audio_bitrate: "128k".to_string(),
-            },
-            QualityPreset::Balanced => QualitySettings {
-                ffmpeg_preset: "medium".to_string(),
-                ffmpeg_crf: "20".to_string(),
-                ffmpeg_audio_bitrate: "192k".to_string(),
-            },
-            QualityPreset::Quality => QualitySettings {
-                ffmpeg_preset: "slow".to_string(),
-                ffmpeg_crf: "18".to_string(),
-                ffmpeg_audio_bitrate: "256k".to_string(),
-            },
-            QualityPreset::UltraFast => QualitySettings {
-                ffmpeg_preset: "ultrafast".to_string(),
-                ffmpeg_crf: "28".to_string(),
-                ffmpeg_audio_bitrate: "96k".to_string(),
-            },
-            QualityPreset::Archive => QualitySettings {
-                ffmpeg_preset: "veryslow".to_string(),
-                ffmpeg_crf: "15".to_string(),
-                ffmpeg_audio_bitrate: "320k".to_string(),
-            },
+    /// Convert preset to quality settings for a codec. CRF scales differ between
+    /// encoders: x265 reaches about the same quality as x264 at a CRF ~5 higher.
+    pub fn to_quality_settings(&self, codec: VideoCodec) -> QualitySettings {
+        let (ffmpeg_preset, x264_crf, x265_crf, ffmpeg_audio_bitrate) = match self {
+            QualityPreset::Fast => ("veryfast", "23", "28", "128k"),
+            QualityPreset::Balanced => ("medium", "20", "25", "192k"),
+            QualityPreset::Quality => ("slow", "18", "23", "256k"),
+            QualityPreset::UltraFast => ("ultrafast", "28", "33", "96k"),
+            QualityPreset::Archive => ("veryslow", "15", "20", "320k"),
+        };
+        let ffmpeg_crf = match codec {
+            VideoCodec::H264 => x264_crf,
+            VideoCodec::H265 => x265_crf,
+        };
+        QualitySettings {
+            ffmpeg_preset: ffmpeg_preset.to_string(),
+            ffmpeg_crf: ffmpeg_crf.to_string(),
+            ffmpeg_audio_bitrate: ffmpeg_audio_bitrate.to_string(),
+            video_codec: codec,
         }
     }
 
@@ -334,7 +832,6 @@ impl QualityPreset {
     }
 
     /// Get all available preset names
-    #[allow(dead_code)]
     pub fn all_names() -> Vec<&'static str> {
         vec!["fast", "balanced", "quality", "ultrafast", "archive"]
     }
@@ -358,6 +855,7 @@ impl Default for QualitySettings {
             ffmpeg_preset: "veryfast".to_string(),
             ffmpeg_crf: "23".to_string(),
             ffmpeg_audio_bitrate: "128k".to_string(),
+            video_codec: VideoCodec::default(),
         }
     }
 }
@@ -427,7 +925,7 @@ mod tests {
         std::env::set_var("FFMPEG_CRF", "20");
         std::env::set_var("FFMPEG_AUDIO_BITRATE", "192k");
 
-        let quality = QualitySettings::from_env();
+        let quality = QualitySettings::from_env(VideoCodec::H264);
         assert_eq!(quality.ffmpeg_preset, "fast");
         assert_eq!(quality.ffmpeg_crf, "20");
         assert_eq!(quality.ffmpeg_audio_bitrate, "192k");
@@ -438,6 +936,64 @@ mod tests {
         std::env::remove_var("FFMPEG_AUDIO_BITRATE");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_env_snapshot_skips_non_unicode_vars() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let _guard = ENV_TEST_MUTEX.lock().unwrap();
+
+        let value = std::ffi::OsStr::from_bytes(b"fa\xe7ade");
+        std::env::set_var("FFMPEG_SNAPSHOT_TEST_BYTES", value);
+        std::env::set_var("SNAPSHOT_TEST_BYTES", value);
+        std::env::set_var("FFMPEG_SNAPSHOT_TEST_PRESET", "slow");
+        let snapshot = env_snapshot();
+        std::env::remove_var("FFMPEG_SNAPSHOT_TEST_BYTES");
+        std::env::remove_var("SNAPSHOT_TEST_BYTES");
+        std::env::remove_var("FFMPEG_SNAPSHOT_TEST_PRESET");
+
+        assert!(!snapshot.contains_key("FFMPEG_SNAPSHOT_TEST_BYTES"));
+        assert_eq!(
+            snapshot
+                .get("FFMPEG_SNAPSHOT_TEST_PRESET")
+                .map(String::as_str),
+            Some("slow")
+        );
+    }
+
+    #[test]
+    fn test_env_snapshot_records_ffmpeg_vars() {
+        let _guard = ENV_TEST_MUTEX.lock().unwrap();
+
+        std::env::set_var("FFMPEG_SNAPSHOT_TEST_CRF", "19");
+        std::env::set_var("SNAPSHOT_TEST_UNRELATED", "1");
+        let snapshot = env_snapshot();
+        std::env::remove_var("FFMPEG_SNAPSHOT_TEST_CRF");
+        std::env::remove_var("SNAPSHOT_TEST_UNRELATED");
+
+        assert_eq!(
+            snapshot.get("FFMPEG_SNAPSHOT_TEST_CRF").map(String::as_str),
+            Some("19")
+        );
+        assert!(!snapshot.contains_key("SNAPSHOT_TEST_UNRELATED"));
+
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/test/media"),
+        );
+        // Left out of the job file when nothing was recorded
+        let json = serde_json::to_value(&job).unwrap();
+        assert!(json.get("env_snapshot").is_none());
+
+        let job = job.with_env_snapshot(snapshot.clone());
+        let json = serde_json::to_string(&job).unwrap();
+        let deserialized: Job = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.env_snapshot, snapshot);
+    }
+
     #[test]
     fn test_absolute_paths() {
         let quality = QualitySettings::default();
@@ -515,24 +1071,56 @@ mod tests {
     #[test]
     fn test_quality_presets() {
         // Test Fast preset
-        let fast = QualityPreset::Fast.to_quality_settings();
+        let fast = QualityPreset::Fast.to_quality_settings(VideoCodec::H264);
         assert_eq!(fast.ffmpeg_preset, "veryfast");
         assert_eq!(fast.ffmpeg_crf, "23");
         assert_eq!(fast.ffmpeg_audio_bitrate, "128k");
 
         // Test Quality preset
-        let quality = QualityPreset::Quality.to_quality_settings();
+        let quality = QualityPreset::Quality.to_quality_settings(VideoCodec::H264);
         assert_eq!(quality.ffmpeg_preset, "slow");
         assert_eq!(quality.ffmpeg_crf, "18");
         assert_eq!(quality.ffmpeg_audio_bitrate, "256k");
 
         // Test Balanced preset
-        let balanced = QualityPreset::Balanced.to_quality_settings();
+        let balanced = QualityPreset::Balanced.to_quality_settings(VideoCodec::H264);
         assert_eq!(balanced.ffmpeg_preset, "medium");
         assert_eq!(balanced.ffmpeg_crf, "20");
         assert_eq!(balanced.ffmpeg_audio_bitrate, "192k");
     }
 
+    #[test]
+    fn test_quality_presets_for_h265() {
+        let crfs: Vec<_> = QualityPreset::all_names()
+            .into_iter()
+            .map(|name| {
+                let settings = QualityPreset::from_name(name)
+                    .unwrap()
+                    .to_quality_settings(VideoCodec::H265);
+                assert_eq!(settings.video_codec, VideoCodec::H265);
+                (name, settings.ffmpeg_crf)
+            })
+            .collect();
+        assert_eq!(
+            crfs,
+            vec![
+                ("fast", "28".to_string()),
+                ("balanced", "25".to_string()),
+                ("quality", "23".to_string()),
+                ("ultrafast", "33".to_string()),
+                ("archive", "20".to_string()),
+            ]
+        );
+
+        // H.264 keeps the original mapping
+        assert_eq!(
+            QualityPreset::Quality
+                .to_quality_settings(VideoCodec::H264)
+                .ffmpeg_crf,
+            "18"
+        );
+    }
+
     #[test]
     fn test_preset_from_name() {
         assert_eq!(
@@ -554,7 +1142,7 @@ mod tests {
 
     #[test]
     fn test_quality_settings_from_preset() {
-        let settings = QualitySettings::from_preset(QualityPreset::Quality);
+        let settings = QualitySettings::from_preset(QualityPreset::Quality, VideoCodec::H264);
         assert_eq!(settings.ffmpeg_preset, "slow");
         assert_eq!(settings.ffmpeg_crf, "18");
         assert_eq!(settings.ffmpeg_audio_bitrate, "256k");
@@ -562,13 +1150,13 @@ mod tests {
 
     #[test]
     fn test_quality_settings_from_preset_name() {
-        let settings = QualitySettings::from_preset_name("balanced").unwrap();
+        let settings = QualitySettings::from_preset_name("balanced", VideoCodec::H264).unwrap();
         assert_eq!(settings.ffmpeg_preset, "medium");
         assert_eq!(settings.ffmpeg_crf, "20");
         assert_eq!(settings.ffmpeg_audio_bitrate, "192k");
 
         // Test invalid name
-        assert!(QualitySettings::from_preset_name("invalid").is_err());
+        assert!(QualitySettings::from_preset_name("invalid", VideoCodec::H264).is_err());
     }
 
     #[test]
@@ -580,7 +1168,7 @@ mod tests {
         std::env::set_var("FFMPEG_PRESET", "custom");
         std::env::set_var("FFMPEG_CRF", "25");
 
-        let settings = QualitySettings::from_preset(QualityPreset::Quality);
+        let settings = QualitySettings::from_preset(QualityPreset::Quality, VideoCodec::H264);
         assert_eq!(settings.ffmpeg_preset, "custom"); // Overridden by env
         assert_eq!(settings.ffmpeg_crf, "25"); // Overridden by env
         assert_eq!(settings.ffmpeg_audio_bitrate, "256k"); // From preset
@@ -596,6 +1184,7 @@ mod tests {
             ffmpeg_preset: "medium".to_string(),
             ffmpeg_crf: "18".to_string(),
             ffmpeg_audio_bitrate: "256k".to_string(),
+            video_codec: VideoCodec::H264,
         };
         let post_processing = PostProcessingSettings {
             disable_source_files: false,
@@ -627,6 +1216,140 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_audio_mode_serialization() {
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/test/media"),
+        )
+        .with_audio_mode(AudioMode::DualAudio);
+
+        let mut json = serde_json::to_value(&job).unwrap();
+        let deserialized: Job = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(deserialized.audio_mode, AudioMode::DualAudio);
+
+        // Jobs queued before audio modes existed keep the single AAC track
+        json.as_object_mut().unwrap().remove("audio_mode");
+        let deserialized: Job = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.audio_mode, AudioMode::Aac);
+    }
+
+    #[test]
+    fn test_has_valid_subtitle() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        std::fs::write(media_root.join("video.webm"), "").unwrap();
+        let job = Job::new(
+            PathBuf::from("video.webm"),
+            MediaFileType::WebM,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            media_root,
+        );
+        let valid_with = |content: &[u8]| {
+            std::fs::write(media_root.join("video.vtt"), content).unwrap();
+            job.has_valid_subtitle(Some(media_root)).unwrap()
+        };
+
+        assert!(valid_with(b"WEBVTT\n\n00:00.000 --> 00:01.000\nHello\n"));
+        assert!(valid_with(b"\xEF\xBB\xBFWEBVTT - with a BOM\n"));
+        assert!(!valid_with(b""));
+        assert!(!valid_with(
+            b"1\n00:00:00,000 --> 00:00:01,000\nAn SRT in disguise\n"
+        ));
+    }
+
+    #[test]
+    fn test_input_checksum_serialization() {
+        let checksum = Checksum {
+            algorithm: ChecksumAlgorithm::Crc32,
+            value: "cbf43926".to_string(),
+        };
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/test/media"),
+        )
+        .with_input_checksum(Some(checksum.clone()));
+
+        let mut json = serde_json::to_value(&job).unwrap();
+        assert_eq!(json["input_checksum"]["algorithm"], "crc32");
+        let deserialized: Job = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(deserialized.input_checksum, Some(checksum));
+
+        // Jobs queued before checksums existed have none
+        json.as_object_mut().unwrap().remove("input_checksum");
+        let deserialized: Job = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.input_checksum, None);
+    }
+
+    #[test]
+    fn test_notes_serialization() {
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/test/media"),
+        );
+        // Jobs without a note don't get an empty field
+        let json = serde_json::to_value(&job).unwrap();
+        assert!(json.get("notes").is_none());
+        let deserialized: Job = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.notes, None);
+
+        let job = job.with_notes(Some("CRF 18, grainy source".to_string()));
+        let json = serde_json::to_string_pretty(&job).unwrap();
+        let deserialized: Job = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.notes.as_deref(), Some("CRF 18, grainy source"));
+    }
+
+    #[test]
+    fn test_migrate_unversioned_job() {
+        // A job file from before audio modes, subtitle modes, checksums and notes
+        let json = r#"{
+            "id": "0b7c1f2e",
+            "input_path": "/media/Show/Season 01/episode.mkv",
+            "output_path": "/media/Show/Season 01/episode.mp4",
+            "subtitle_path": null,
+            "file_type": "Mkv",
+            "quality_settings": {
+                "ffmpeg_preset": "medium",
+                "ffmpeg_crf": "23",
+                "ffmpeg_audio_bitrate": "128k"
+            },
+            "post_processing": { "disable_source_files": true }
+        }"#;
+        let job: Job = serde_json::from_str(json).unwrap();
+        assert_eq!(job.version, 0);
+        assert!(job.needs_migration());
+        assert_eq!(job.audio_mode, AudioMode::Aac);
+        assert_eq!(job.subtitle_mode, SubtitleMode::Mux);
+        assert_eq!(job.quality_settings.video_codec, VideoCodec::H264);
+        assert_eq!(job.notes, None);
+
+        let migrated = job.clone().migrate();
+        assert_eq!(migrated.version, JOB_SCHEMA_VERSION);
+        assert!(!migrated.needs_migration());
+        assert_eq!(migrated.id, job.id);
+        assert_eq!(migrated.input_path, job.input_path);
+
+        // New jobs start out current
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            Path::new("/test/media"),
+        );
+        assert!(!job.needs_migration());
+    }
+
     #[test]
     fn test_work_folder_output_path() {
         let quality = QualitySettings::default();
@@ -659,6 +1382,16 @@ mod tests {
             .ends_with("movie.mp4"));
     }
 
+    #[test]
+    fn test_split_part() {
+        assert_eq!(split_part("Show S01E01 part2"), Some(("Show S01E01", 2)));
+        assert_eq!(split_part("Show.S01E01.Pt.10"), Some(("Show.S01E01", 10)));
+        assert_eq!(split_part("Movie (1999) - CD1"), Some(("Movie (1999)", 1)));
+        assert_eq!(split_part("part1"), None);
+        assert_eq!(split_part("Counterpart1"), None);
+        assert_eq!(split_part("Show S01E01"), None);
+    }
+
     #[test]
     fn test_post_processing_defaults() {
         let settings = PostProcessingSettings::default();
@@ -687,6 +1420,28 @@ mod tests {
         assert_eq!(metadata.content_type, ContentType::Series);
     }
 
+    #[test]
+    fn test_display_name() {
+        let media_root = PathBuf::from("/media");
+        let episode = Job::new(
+            PathBuf::from("Series/Breaking Bad/Season 01/Breaking Bad - s01e03 - Gray Matter.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            &media_root,
+        );
+        assert_eq!(episode.display_name(), "Breaking Bad - S01E03");
+
+        let movie = Job::new(
+            PathBuf::from("/media/Movies/Heat (1995)/Heat.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            &media_root,
+        );
+        assert_eq!(movie.display_name(), "Heat.mkv");
+    }
+
     #[test]
     fn test_episode_metadata_extraction_series_with_tvdb() {
         let quality = QualitySettings::default();
@@ -757,6 +1512,43 @@ mod tests {
         assert_eq!(metadata.content_type, ContentType::Series);
     }
 
+    #[test]
+    fn test_episode_metadata_extraction_specials() {
+        for path in [
+            "/media/Series/Doctor Who (2005)/Season 00/Doctor Who S00E01 The Christmas Invasion.mkv",
+            "/media/Series/Doctor Who (2005)/Specials/Doctor Who S00E01 The Christmas Invasion.mkv",
+        ] {
+            let metadata = Job::episode_metadata_from_path(Path::new(path)).unwrap();
+            assert_eq!(metadata.series_name, "Doctor Who (2005)");
+            assert_eq!(metadata.season_number, 0);
+            assert_eq!(metadata.episode_number, 1);
+            assert!(metadata.is_special());
+        }
+
+        // A `Specials` folder only holds season 0
+        assert!(Job::episode_metadata_from_path(Path::new(
+            "/media/Series/Doctor Who (2005)/Specials/Doctor Who S01E01 Rose.mkv"
+        ))
+        .is_none());
+
+        let special = Job::episode_metadata_from_path(Path::new(
+            "/media/Series/Show/Specials/Show S00E01.mkv",
+        ))
+        .unwrap();
+        let regular = Job::episode_metadata_from_path(Path::new(
+            "/media/Series/Show/Season 01/Show S01E01.mkv",
+        ))
+        .unwrap();
+        assert!(
+            special.season_sort_key(SpecialsOrder::Last)
+                > regular.season_sort_key(SpecialsOrder::Last)
+        );
+        assert!(
+            special.season_sort_key(SpecialsOrder::First)
+                < regular.season_sort_key(SpecialsOrder::First)
+        );
+    }
+
     #[test]
     fn test_episode_metadata_extraction_movie_returns_none() {
         let quality = QualitySettings::default();
@@ -0,0 +1,218 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use super::Job;
+
+/// Variables that can be used in an output template
+const TEMPLATE_VARIABLES: &[&str] = &[
+    "root",
+    "relative_dir",
+    "stem",
+    "ext",
+    "series",
+    "season",
+    "episode",
+];
+
+/// Output path template such as `{root}/Transcoded/{relative_dir}/{stem}.mp4`
+///
+/// Available variables:
+/// - `{root}`: the media root
+/// - `{relative_dir}`: directory of the input relative to the media root
+/// - `{stem}`: input file name without extension
+/// - `{ext}`: input file extension
+/// - `{series}`, `{season}`, `{episode}`: parsed from `Series/` and `Anime/` paths
+///   (season and episode are zero-padded to two digits)
+///
+/// Relative results are resolved against the media root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTemplate {
+    template: String,
+}
+
+impl OutputTemplate {
+    /// Parse a template, rejecting unknown variables and unbalanced braces
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or_else(|| anyhow!("Unclosed '{{' in output template '{template}'"))?;
+            let name = &rest[start + 1..end];
+            if !TEMPLATE_VARIABLES.contains(&name) {
+                return Err(anyhow!(
+                    "Unknown output template variable '{{{name}}}'. Available: {}",
+                    TEMPLATE_VARIABLES
+                        .iter()
+                        .map(|v| format!("{{{v}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            rest = &rest[end + 1..];
+        }
+        if rest.contains('}') {
+            return Err(anyhow!("Unmatched '}}' in output template '{template}'"));
+        }
+
+        Ok(Self {
+            template: template.to_string(),
+        })
+    }
+
+    /// Render the output path for an input file under `media_root`
+    pub fn render(&self, input_path: &Path, media_root: &Path) -> Result<PathBuf> {
+        let absolute_input = if input_path.is_absolute() {
+            input_path.to_path_buf()
+        } else {
+            media_root.join(input_path)
+        };
+
+        let relative_dir = absolute_input
+            .parent()
+            .and_then(|parent| parent.strip_prefix(media_root).ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Input {:?} is not inside the media root {:?}",
+                    absolute_input,
+                    media_root
+                )
+            })?;
+        let stem = absolute_input
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ext = absolute_input
+            .extension()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let episode = Job::episode_metadata_from_path(&absolute_input);
+
+        let mut rendered = self.template.clone();
+        for name in TEMPLATE_VARIABLES {
+            let placeholder = format!("{{{name}}}");
+            if !rendered.contains(&placeholder) {
+                continue;
+            }
+
+            let value = match *name {
+                "root" => media_root.to_string_lossy().into_owned(),
+                "relative_dir" => relative_dir.to_string_lossy().into_owned(),
+                "stem" => stem.clone(),
+                "ext" => ext.clone(),
+                _ => {
+                    let metadata = episode.as_ref().ok_or_else(|| {
+                        anyhow!(
+                            "Output template uses {placeholder} but no series/season/episode could be parsed from {absolute_input:?}"
+                        )
+                    })?;
+                    match *name {
+                        "series" => metadata.series_name.clone(),
+                        "season" => format!("{:02}", metadata.season_number),
+                        _ => format!("{:02}", metadata.episode_number),
+                    }
+                }
+            };
+            rendered = rendered.replace(&placeholder, &value);
+        }
+
+        // Collapse empty segments left by e.g. an empty {relative_dir}
+        let rendered: PathBuf = Path::new(&rendered)
+            .components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .collect();
+
+        Ok(if rendered.is_absolute() {
+            rendered
+        } else {
+            media_root.join(rendered)
+        })
+    }
+}
+
+impl FromStr for OutputTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for OutputTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_relative_dir_template() {
+        let template =
+            OutputTemplate::parse("{root}/Transcoded/{relative_dir}/{stem}.mp4").unwrap();
+        let media_root = Path::new("/media");
+
+        assert_eq!(
+            template
+                .render(Path::new("Movies/Heat (1995)/Heat (1995).mkv"), media_root)
+                .unwrap(),
+            PathBuf::from("/media/Transcoded/Movies/Heat (1995)/Heat (1995).mp4")
+        );
+
+        // Files at the root leave no empty path segment behind
+        assert_eq!(
+            template
+                .render(Path::new("/media/video.webm"), media_root)
+                .unwrap(),
+            PathBuf::from("/media/Transcoded/video.mp4")
+        );
+    }
+
+    #[test]
+    fn test_render_episode_template() {
+        let template = OutputTemplate::parse(
+            "/library/{series}/Season {season}/{series} - s{season}e{episode}.mp4",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template
+                .render(
+                    Path::new("Series/Breaking Bad {tvdb-81189}/Season 01/Breaking Bad S01E02 Cat's in the Bag.mkv"),
+                    Path::new("/media"),
+                )
+                .unwrap(),
+            PathBuf::from("/library/Breaking Bad/Season 01/Breaking Bad - s01e02.mp4")
+        );
+
+        // Episode variables are unavailable for movies
+        assert!(template
+            .render(Path::new("Movies/Heat (1995).mkv"), Path::new("/media"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_relative_template_and_extension() {
+        let template = OutputTemplate::parse("converted/{stem}.{ext}.mp4").unwrap();
+
+        assert_eq!(
+            template
+                .render(Path::new("Downloads/clip.webm"), Path::new("/media"))
+                .unwrap(),
+            PathBuf::from("/media/converted/clip.webm.mp4")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_templates() {
+        assert!(OutputTemplate::parse("{root}/{title}.mp4").is_err());
+        assert!(OutputTemplate::parse("{root}/{stem.mp4").is_err());
+        assert!(OutputTemplate::parse("{root}/stem}.mp4").is_err());
+        assert!("{root}/{stem}.mp4".parse::<OutputTemplate>().is_ok());
+    }
+}
@@ -1,11 +1,18 @@
 pub mod commands;
 pub mod config;
+pub mod error;
 pub mod ffmpeg;
 pub mod ignore;
 pub mod job;
+pub mod metrics;
+pub mod notification;
+pub mod output;
 pub mod queue;
 pub mod worker;
 
+#[cfg(all(test, unix))]
+mod test_support;
+
 use clap::ValueEnum;
 
 /// Job prioritization methods for the work command
@@ -15,4 +22,6 @@ pub enum JobPriority {
     None,
     /// Prioritize episodes within series, older created jobs first
     Episode,
+    /// Process jobs in random order (reproducible with a shuffle seed)
+    Shuffle,
 }
@@ -21,6 +21,9 @@
 //! # Process jobs from the queue
 //! plexify work /path/to/media
 //!
+//! # Watch a directory and convert new files as they arrive
+//! plexify watch /path/to/media
+//!
 //! # Clean up temporary files
 //! plexify clean /path/to/media
 //!
@@ -28,25 +31,59 @@
 //! plexify validate /path/to/media
 //! ```
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
-use tracing::{error, info};
+use output::ColorChoice;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod commands;
 mod config;
+mod error;
 mod ffmpeg;
 mod ignore;
 mod job;
+mod metrics;
+mod notification;
+mod output;
 mod queue;
 mod worker;
 
+#[cfg(all(test, unix))]
+mod test_support;
+
 use commands::{
-    add::AddCommand, clean::CleanCommand, scan::ScanCommand, validate::ValidateCommand,
+    add::AddCommand,
+    clean::CleanCommand,
+    dashboard::Dashboard,
+    doctor::DoctorCommand,
+    hooks::Hook,
+    ignore::IgnoreExplainCommand,
+    migrate::MigrateCommand,
+    pause::{PauseCommand, ResumeCommand},
+    presets::PresetsCommand,
+    rename_template::RenameTemplate,
+    scan::ScanCommand,
+    throttle::DEFAULT_THROTTLE_BATCH,
+    undo::UndoCommand,
+    validate::{GroupBy, ShowLayout, ValidateCommand},
+    verify::VerifyCommand,
+    watch::WatchCommand,
     work::WorkCommand,
 };
+use ffmpeg::permissions::{self, OutputPermissions, Owner};
+use ffmpeg::post_process::PostProcessStep;
+use ffmpeg::{OversizeAction, OversizePolicy};
+use job::{
+    AudioMode, ChecksumAlgorithm, OutputTemplate, SpecialsOrder, SubtitleFormat, SubtitleMode,
+    VideoCodec,
+};
+use notification::Notifier;
 use plexify::JobPriority;
+use queue::{DiskLimit, DiskLimits, QueueFs};
 
 /// Plexify - A simple, distributed media transcoding CLI
 #[derive(Parser)]
@@ -59,6 +96,27 @@ use plexify::JobPriority;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Increase log output (-v for debug, -vv for trace). RUST_LOG takes precedence if set
+    #[arg(long, short, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Only log warnings and errors. RUST_LOG takes precedence if set
+    #[arg(long, short, global = true)]
+    quiet: bool,
+    /// Use colors, emoji and progress bars (auto: only on a terminal without NO_COLOR)
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+impl Cli {
+    /// Tracing filter for the requested verbosity
+    fn log_filter(&self) -> &'static str {
+        match (self.quiet, self.verbose) {
+            (true, _) => "plexify=warn",
+            (false, 0) => "plexify=info",
+            (false, 1) => "plexify=debug",
+            (false, _) => "plexify=trace",
+        }
+    }
 }
 
 /// Available commands
@@ -67,13 +125,54 @@ enum Commands {
     /// Create a transcoding job for an individual media file
     Add {
         /// Path to the media file to process
-        file: PathBuf,
+        #[arg(required_unless_present = "input_glob_from_stdin")]
+        file: Option<PathBuf>,
+        /// Read paths from stdin, one per line, and add each as it arrives
+        /// (e.g. `fd -e mkv | plexify add --input-glob-from-stdin`)
+        #[arg(long, conflicts_with = "file")]
+        input_glob_from_stdin: bool,
         /// Path to the work directory (defaults to current working directory)
         #[arg(long, short = 'w')]
         work_dir: Option<PathBuf>,
         /// Quality preset for encoding. Available: fast, balanced, quality, ultrafast, archive
         #[arg(long, short = 'p')]
         preset: Option<String>,
+        /// Output path template, e.g. "{root}/Transcoded/{relative_dir}/{stem}.mp4"
+        #[arg(long)]
+        output_template: Option<OutputTemplate>,
+        /// How to write audio: aac, or dual-audio to keep the original track and add AAC stereo
+        #[arg(long, default_value = "aac", value_enum)]
+        audio: AudioMode,
+        /// Tone-map HDR sources to SDR for clients that show them washed out
+        #[arg(long)]
+        tonemap: bool,
+        /// Add chapter markers at silent gaps in the audio (e.g. between songs of a concert)
+        #[arg(long)]
+        auto_chapters: bool,
+        /// Downmix multichannel (e.g. 5.1) audio to stereo for stereo-only clients
+        #[arg(long)]
+        downmix_stereo: bool,
+        /// Tag outputs with a friendly title (e.g. "Show - S01E02") and encoder=plexify
+        #[arg(long)]
+        tag_metadata: bool,
+        /// What to do with `.ass` sidecar subtitles: mux (as mov_text, losing styling) or burn
+        #[arg(long, default_value = "mux", value_enum)]
+        ass_subtitles: SubtitleMode,
+        /// Burn subtitles (sidecar or the first embedded stream) into the video; forces a re-encode
+        #[arg(long)]
+        burn_subtitles: bool,
+        /// Report the job that would be created (or why it would be skipped) without queueing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Note stored in the job file, e.g. why it has non-default settings
+        #[arg(long)]
+        notes: Option<String>,
+        /// Video codec to encode in; preset CRFs are scaled to match (h265 needs a capable client)
+        #[arg(long, default_value = "h264", value_enum)]
+        codec: VideoCodec,
+        /// Only encode this many seconds, to `<name>.sample.mp4`, to check a preset (source is kept)
+        #[arg(long, value_name = "SECONDS")]
+        sample: Option<u64>,
     },
     /// Scan a directory for media files and create transcoding jobs
     Scan {
@@ -85,6 +184,88 @@ enum Commands {
         /// Quality preset for encoding. Available: fast, balanced, quality, ultrafast, archive
         #[arg(long, short = 'p')]
         preset: Option<String>,
+        /// Skip files that are still being written (e.g. active downloads)
+        #[arg(long)]
+        skip_growing: bool,
+        /// Sleep this many milliseconds every --throttle-batch filesystem entries (0 = off)
+        #[arg(long, default_value_t = 0)]
+        throttle: u64,
+        /// Number of filesystem entries between throttle pauses
+        #[arg(long, default_value_t = DEFAULT_THROTTLE_BATCH)]
+        throttle_batch: usize,
+        /// Only process files whose path relative to the media directory matches this glob
+        #[arg(long = "match")]
+        match_glob: Option<glob::Pattern>,
+        /// Skip files whose path relative to the media directory matches this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<glob::Pattern>,
+        /// Skip inputs whose content is identical to another input (hashes every file)
+        #[arg(long)]
+        dedupe: bool,
+        /// When inputs share a stem (e.g. episode.mkv and episode.webm), only queue the first extension listed
+        #[arg(long, value_delimiter = ',', default_value = "mkv,webm")]
+        input_ext_priority: Vec<String>,
+        /// Output path template. Variables: {root}, {relative_dir}, {stem}, {ext}, {series}, {season}, {episode}
+        #[arg(long)]
+        output_template: Option<OutputTemplate>,
+        /// Skip files shorter than this, e.g. trailers and intros (needs ffprobe, e.g. 2m)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        min_duration: Option<Duration>,
+        /// Skip files longer than this (needs ffprobe, e.g. 4h)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        max_duration: Option<Duration>,
+        /// How to write audio: aac, or dual-audio to keep the original track and add AAC stereo
+        #[arg(long, default_value = "aac", value_enum)]
+        audio: AudioMode,
+        /// Tone-map HDR sources to SDR for clients that show them washed out
+        #[arg(long)]
+        tonemap: bool,
+        /// Add chapter markers at silent gaps in the audio (e.g. between songs of a concert)
+        #[arg(long)]
+        auto_chapters: bool,
+        /// Join .mkv episodes split into `part1`, `part2`, ... files into one output
+        #[arg(long)]
+        combine_parts: bool,
+        /// Downmix multichannel (e.g. 5.1) audio to stereo for stereo-only clients
+        #[arg(long)]
+        downmix_stereo: bool,
+        /// Tag outputs with a friendly title (e.g. "Show - S01E02") and encoder=plexify
+        #[arg(long)]
+        tag_metadata: bool,
+        /// What to do with `.ass` sidecar subtitles: mux (as mov_text, losing styling) or burn
+        #[arg(long, default_value = "mux", value_enum)]
+        ass_subtitles: SubtitleMode,
+        /// Burn subtitles (sidecar or the first embedded stream) into the video; forces a re-encode
+        #[arg(long)]
+        burn_subtitles: bool,
+        /// With --remux-mp4, don't remux MP4 files whose video already uses one of these codecs,
+        /// e.g. h264 (needs ffprobe). Other containers are always converted.
+        #[arg(long, value_delimiter = ',', requires = "remux_mp4")]
+        skip_if_codec: Vec<String>,
+        /// Video codec to encode in; preset CRFs are scaled to match (h265 needs a capable client)
+        #[arg(long, default_value = "h264", value_enum)]
+        codec: VideoCodec,
+        /// Convert `.srt`/`.vtt` sidecars into this format with subtitle-only jobs (video untouched)
+        #[arg(long, value_enum)]
+        convert_subs: Option<SubtitleFormat>,
+        /// Record a checksum of each queued input for `verify --checksums` (reads every file)
+        #[arg(long, default_value = "none", value_enum)]
+        checksum: ChecksumAlgorithm,
+        /// Skip inputs recorded in `_completed`, even if their output was moved or deleted
+        #[arg(long)]
+        skip_completed: bool,
+        /// Delete completed job records older than this many days (default: keep forever)
+        #[arg(long)]
+        keep_completed_days: Option<u64>,
+        /// Also remux MP4 files whose index is at the end (stream copy, no re-encode)
+        #[arg(long)]
+        remux_mp4: bool,
+        /// Write the jobs this scan would create to a JSON plan instead of enqueueing them
+        #[arg(long, conflicts_with = "plan_in")]
+        plan_out: Option<PathBuf>,
+        /// Enqueue exactly the jobs in a plan written by --plan-out (skips scanning)
+        #[arg(long)]
+        plan_in: Option<PathBuf>,
     },
     /// Process jobs from the queue
     Work {
@@ -99,6 +280,121 @@ enum Commands {
         /// Job prioritization method
         #[arg(long, default_value = "none", value_enum)]
         priority: JobPriority,
+        /// Seed for `--priority shuffle` to get a reproducible order
+        #[arg(long)]
+        shuffle_seed: Option<u64>,
+        /// With `--priority episode`, process specials (season 0) first or last in each show
+        #[arg(long, default_value = "last", value_enum)]
+        specials: SpecialsOrder,
+        /// Skip to another series while this many jobs of a series are in progress across workers
+        #[arg(long)]
+        max_per_series: Option<usize>,
+        /// Most jobs in progress per disk across workers: N for every disk, or PATH=N for the
+        /// disk PATH is on (repeatable)
+        #[arg(long, value_name = "[PATH=]N")]
+        concurrency_per_disk: Vec<DiskLimit>,
+        /// Kill FFmpeg and return the job to the queue if it runs longer than this (e.g. 2h, 90m)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        job_timeout: Option<std::time::Duration>,
+        /// Kill FFmpeg and return the job to the queue if it makes no progress for this long (e.g. 5m)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        stall_timeout: Option<Duration>,
+        /// What to do when an output is larger than its input: warn, keep-original or retry (with a higher CRF)
+        #[arg(long, default_value = "warn", value_enum)]
+        oversize: OversizeAction,
+        /// How much larger than the input (in percent) an output may be before --oversize applies
+        #[arg(long, default_value_t = ffmpeg::DEFAULT_OVERSIZE_THRESHOLD_PERCENT)]
+        oversize_threshold: f64,
+        /// Highest CRF that --oversize retry may raise to (on the h264 scale; h265 jobs may go 5 higher)
+        #[arg(long, default_value_t = 32)]
+        output_crf_cap: u32,
+        /// Free space to keep on the work and destination disks; jobs that would dip below fail up front (e.g. 10G)
+        #[arg(long, default_value = "0", value_parser = ffmpeg::parse_size)]
+        min_free_space: u64,
+        /// Let at most this many workers sharing the work dir read their input at once
+        #[arg(long)]
+        max_concurrent_reads: Option<usize>,
+        /// Stop claiming new jobs while the 1-minute load average is above this (Linux only)
+        #[arg(long)]
+        max_load: Option<f64>,
+        /// Filesystem the queue lives on; `nfs` claims jobs with exclusive lockfiles
+        #[arg(long, value_enum, default_value_t = QueueFs::Local)]
+        queue_fs: QueueFs,
+        /// Delete completed job records older than this many days (default: keep forever)
+        #[arg(long)]
+        keep_completed_days: Option<u64>,
+        /// Process the jobs queued at startup, print a summary and exit
+        #[arg(long)]
+        once: bool,
+        /// With --once, keep going after a failed job (use --continue-on-error=false to stop)
+        #[arg(long, default_value_t = true, action = clap::ArgAction::Set, requires = "once")]
+        continue_on_error: bool,
+        /// Transcode only this file right away, bypassing the queue (for debugging)
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Print the ffmpeg command for each queued job and exit without running anything
+        #[arg(long)]
+        print_command: bool,
+        /// Write a `<output>.plexify-done` marker next to each finished output
+        #[arg(long)]
+        done_marker: bool,
+        /// Run this command after each finished job. Variables: {id}, {name}, {input}, {output}, {series}, {season}, {episode}
+        #[arg(long)]
+        on_complete: Option<Hook>,
+        /// Run this command when a job fails for good (moved to _failed). Also has {error}
+        #[arg(long)]
+        on_failure: Option<Hook>,
+        /// POST a JSON summary to this URL when the queue drains and when the worker exits
+        #[arg(long)]
+        notify_webhook: Option<String>,
+        /// Serve Prometheus metrics on this address (e.g. 127.0.0.1:9184)
+        #[arg(long)]
+        metrics_addr: Option<std::net::SocketAddr>,
+        /// Show a live dashboard with the current job, queue counts and recent jobs
+        #[arg(long)]
+        tui: bool,
+        /// Encode next to the destination and rename into place instead of moving out of the work folder
+        #[arg(long)]
+        no_move: bool,
+        /// Encode here instead when an output would not fit in the work dir (e.g. a tmpfs)
+        #[arg(long)]
+        spill_dir: Option<PathBuf>,
+        /// Give outputs the mode and owner of their source file (unix only)
+        #[arg(long)]
+        match_source_permissions: bool,
+        /// Octal mode for outputs, e.g. 644 (unix only)
+        #[arg(long, value_parser = permissions::parse_mode)]
+        output_mode: Option<u32>,
+        /// Numeric uid[:gid] to own outputs, e.g. 1000:1000 (unix only, usually needs root)
+        #[arg(long, value_parser = permissions::parse_owner)]
+        output_owner: Option<Owner>,
+        /// Give outputs the modification time of their source, so date-added sorting is kept
+        #[arg(long, alias = "copy-timestamps")]
+        preserve_mtime: bool,
+        /// Run this tool on each output before it is moved, e.g. 'MP4Box -inter 500 {output}'.
+        /// Repeatable, run in order; prefix with warn: to only log a failure instead of failing the job
+        #[arg(long = "post-process", value_name = "[warn:]COMMAND")]
+        post_processors: Vec<PostProcessStep>,
+    },
+    /// Watch a directory, enqueue new media files once they settle and process them
+    Watch {
+        /// Path to the media directory to watch
+        path: PathBuf,
+        /// Path to the work directory (defaults to current working directory)
+        #[arg(long, short = 'w')]
+        work_dir: Option<PathBuf>,
+        /// Quality preset to use for new jobs (fast, balanced, quality, archive)
+        #[arg(long, short)]
+        preset: Option<String>,
+        /// Run worker in background with low priority
+        #[arg(long, short)]
+        background: bool,
+        /// Job prioritization method
+        #[arg(long, default_value = "none", value_enum)]
+        priority: JobPriority,
+        /// How long a file must stop changing before it is enqueued (e.g. 30s, 2m)
+        #[arg(long, default_value = "30s", value_parser = humantime::parse_duration)]
+        settle_time: std::time::Duration,
     },
     /// Remove all temporary files and directories
     Clean {
@@ -107,57 +403,337 @@ enum Commands {
         /// Path to the work directory (defaults to current working directory)
         #[arg(long, short = 'w')]
         work_dir: Option<PathBuf>,
+        /// Delete .disabled sources whose converted .mp4 is verified with ffprobe (leaves the queue alone)
+        #[arg(long)]
+        orphans: bool,
+        /// Remove directories left empty after cleanup (keeps the media root and queue dirs)
+        #[arg(long)]
+        prune_empty: bool,
     },
     /// Validate Plex naming scheme conformity
     Validate {
-        /// Path to the media directory to validate
+        /// Path to the media directory (or a single media file) to validate
         path: PathBuf,
+        /// Library root that paths are matched against when validating a subtree or a single file
+        /// (default: the path itself; for a file, the folder above its Series/Movies/Anime folder)
+        #[arg(long)]
+        input_root: Option<PathBuf>,
+        /// Remove empty directories (bottom-up) after validation
+        #[arg(long)]
+        prune_empty: bool,
+        /// Apply safe fixes: move episodes into the --layout and their season folder, and lowercase
+        /// media file extensions. Renames are logged for `plexify undo`
+        #[arg(long)]
+        fix: bool,
+        /// Flag well-named episodes outside this show layout (moved with --fix):
+        /// seasons (`Show/Season 01/`) or flat (single-season shows directly in `Show/`)
+        #[arg(long, value_enum)]
+        layout: Option<ShowLayout>,
+        /// Keep descriptive season folder names (`Season 11 - Arc Name`) in suggested paths
+        #[arg(long)]
+        keep_season_suffix: bool,
+        /// Episode filename template for suggested paths, e.g. "{show} - S{season}E{episode} - {title}.{ext}"
+        #[arg(long)]
+        rename_template: Option<RenameTemplate>,
+        /// Sleep this many milliseconds every --throttle-batch filesystem entries (0 = off)
+        #[arg(long, default_value_t = 0)]
+        throttle: u64,
+        /// Number of filesystem entries between throttle pauses
+        #[arg(long, default_value_t = DEFAULT_THROTTLE_BATCH)]
+        throttle_batch: usize,
+        /// Only process files whose path relative to the media directory matches this glob
+        #[arg(long = "match")]
+        match_glob: Option<glob::Pattern>,
+        /// Skip files whose path relative to the media directory matches this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<glob::Pattern>,
+        /// Group issues in the report by content type, show, or directory
+        #[arg(long, value_enum)]
+        group_by: Option<GroupBy>,
+        /// Print only the counts and the per-type issue summary, e.g. for nightly logs
+        #[arg(long, alias = "report-summary-only")]
+        summary_only: bool,
+        /// Exit with status 2 if any issues remain (after --fix, if given)
+        #[arg(long)]
+        fail_on_issues: bool,
+        /// Work directory that --fix writes its undo log to (defaults to current working directory)
+        #[arg(long, short = 'w')]
+        work_dir: Option<PathBuf>,
+    },
+    /// Reverse the renames of a `validate --fix` run from its undo log
+    Undo {
+        /// Undo log written by `validate --fix` (in the work directory's `_undo/` folder)
+        log: PathBuf,
+    },
+    /// Check that ffmpeg and ffprobe work by transcoding a tiny generated clip
+    Doctor,
+    /// List the quality presets accepted by --preset and their encoder settings
+    Presets {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+        /// Show the CRFs the presets use for this codec
+        #[arg(long, default_value = "h264", value_enum)]
+        codec: VideoCodec,
+    },
+    /// Stop workers from claiming new jobs; running jobs finish
+    Pause {
+        /// Path to the work directory (defaults to current working directory)
+        #[arg(long, short = 'w')]
+        work_dir: Option<PathBuf>,
+    },
+    /// Let paused workers claim jobs again
+    Resume {
+        /// Path to the work directory (defaults to current working directory)
+        #[arg(long, short = 'w')]
+        work_dir: Option<PathBuf>,
+    },
+    /// Check files against what the queue recorded about them
+    Verify {
+        /// Path to the work directory (defaults to current working directory)
+        #[arg(long, short = 'w')]
+        work_dir: Option<PathBuf>,
+        /// Recompute the checksums recorded by `scan --checksum` to detect bit rot
+        #[arg(long)]
+        checksums: bool,
+    },
+    /// Upgrade job files written by an older plexify to the current schema
+    Migrate {
+        /// Path to the work directory (defaults to current working directory)
+        #[arg(long, short = 'w')]
+        work_dir: Option<PathBuf>,
+    },
+    /// Debug .plexifyignore patterns
+    Ignore {
+        #[command(subcommand)]
+        command: IgnoreCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum IgnoreCommands {
+    /// Show every pattern that matches a path and whether it ends up ignored
+    Explain {
+        /// Path to check (relative paths are resolved against the media directory)
+        path: PathBuf,
+        /// Path to the media directory (defaults to current working directory)
+        #[arg(long, short = 'r')]
+        root: Option<PathBuf>,
     },
 }
 
+impl Commands {
+    /// The media directory the command operates on, if any
+    fn media_root(&self) -> Option<&Path> {
+        match self {
+            Commands::Add { file, .. } => file.as_deref().and_then(Path::parent),
+            Commands::Scan { path, .. }
+            | Commands::Work { path, .. }
+            | Commands::Watch { path, .. }
+            | Commands::Clean { path, .. }
+            | Commands::Validate { path, .. } => Some(path),
+            Commands::Ignore {
+                command: IgnoreCommands::Explain { root, .. },
+            } => root.as_deref(),
+            Commands::Doctor
+            | Commands::Presets { .. }
+            | Commands::Pause { .. }
+            | Commands::Resume { .. }
+            | Commands::Verify { .. }
+            | Commands::Migrate { .. }
+            | Commands::Undo { .. } => None,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
+    let cli = Cli::parse();
+
+    // Pick up .env files before anything reads the environment
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let mut env_dirs = vec![cwd.as_path()];
+    env_dirs.extend(cli.command.media_root());
+    let env_files = config::load_env_files(&env_dirs);
+
+    // Initialize tracing (an explicit RUST_LOG wins over -q/-v)
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| cli.log_filter().into());
+    let filter_description = filter.to_string();
+    output::configure(cli.color);
+    // The dashboard needs a terminal, and takes over the logs while it is drawn
+    let tui_requested = matches!(cli.command, Commands::Work { tui: true, .. });
+    let dashboard = (tui_requested && !output::is_plain()).then(Dashboard::new);
+    let writer = if output::is_plain() {
+        BoxMakeWriter::new(output::PlainMakeWriter)
+    } else if let Some(dashboard) = &dashboard {
+        BoxMakeWriter::new(dashboard.clone())
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     tracing_subscriber::registry()
+        .with(filter)
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "plexify=info".into()),
+            tracing_subscriber::fmt::layer()
+                .with_ansi(!output::is_plain())
+                .with_writer(writer),
         )
-        .with(tracing_subscriber::fmt::layer())
         .init();
+    debug!("Log filter: {}", filter_description);
 
-    let cli = Cli::parse();
+    for env_file in &env_files {
+        info!("Loaded environment from {:?}", env_file);
+    }
+    if tui_requested && dashboard.is_none() {
+        warn!("--tui needs a terminal with colors enabled, using plain output");
+    }
 
     let result = match cli.command {
         Commands::Add {
             file,
+            input_glob_from_stdin,
             work_dir,
             preset,
+            output_template,
+            audio,
+            tonemap,
+            auto_chapters,
+            downmix_stereo,
+            tag_metadata,
+            ass_subtitles,
+            burn_subtitles,
+            dry_run,
+            notes,
+            codec,
+            sample,
         } => {
             let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
             info!(
                 "Starting add command for file: {:?}, work: {:?}, preset: {:?}",
                 file, work_root, preset
             );
-            AddCommand::new(file, work_root, preset).execute().await
+            let add_cmd = AddCommand::new(file.unwrap_or_default(), work_root, preset)
+                .with_output_template(output_template)
+                .with_audio_mode(audio)
+                .with_tonemap(tonemap)
+                .with_auto_chapters(auto_chapters)
+                .with_downmix_stereo(downmix_stereo)
+                .with_tag_metadata(tag_metadata)
+                .with_ass_subtitles(ass_subtitles)
+                .with_burn_subtitles(burn_subtitles)
+                .with_dry_run(dry_run)
+                .with_notes(notes)
+                .with_codec(codec)
+                .with_sample(sample);
+            if input_glob_from_stdin {
+                let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+                add_cmd.execute_lines(stdin).await.map(|_| ())
+            } else {
+                add_cmd.execute().await.map(|_| ())
+            }
         }
         Commands::Scan {
             path,
             work_dir,
             preset,
+            skip_growing,
+            throttle,
+            throttle_batch,
+            match_glob,
+            exclude,
+            dedupe,
+            input_ext_priority,
+            output_template,
+            min_duration,
+            max_duration,
+            audio,
+            tonemap,
+            auto_chapters,
+            combine_parts,
+            downmix_stereo,
+            tag_metadata,
+            ass_subtitles,
+            burn_subtitles,
+            skip_if_codec,
+            codec,
+            convert_subs,
+            checksum,
+            skip_completed,
+            keep_completed_days,
+            remux_mp4,
+            plan_out,
+            plan_in,
         } => {
             let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
             info!(
                 "Starting scan command for path: {:?}, work: {:?}, preset: {:?}",
                 path, work_root, preset
             );
-            ScanCommand::new(path, work_root, preset).execute().await
+            ScanCommand::new(path, work_root, preset)
+                .with_skip_growing(skip_growing)
+                .with_throttle(Duration::from_millis(throttle), throttle_batch)
+                .with_match(match_glob)
+                .with_exclude(exclude)
+                .with_dedupe(dedupe)
+                .with_input_ext_priority(input_ext_priority)
+                .with_output_template(output_template)
+                .with_duration_range(min_duration, max_duration)
+                .with_audio_mode(audio)
+                .with_tonemap(tonemap)
+                .with_auto_chapters(auto_chapters)
+                .with_combine_parts(combine_parts)
+                .with_downmix_stereo(downmix_stereo)
+                .with_tag_metadata(tag_metadata)
+                .with_ass_subtitles(ass_subtitles)
+                .with_burn_subtitles(burn_subtitles)
+                .with_skip_if_codec(skip_if_codec)
+                .with_codec(codec)
+                .with_convert_subs(convert_subs)
+                .with_checksum(checksum)
+                .with_skip_completed(skip_completed)
+                .with_keep_completed_days(keep_completed_days)
+                .with_remux_mp4(remux_mp4)
+                .with_plan_out(plan_out)
+                .with_plan_in(plan_in)
+                .execute()
+                .await
         }
         Commands::Work {
             path,
             work_dir,
             background,
             priority,
+            shuffle_seed,
+            specials,
+            max_per_series,
+            concurrency_per_disk,
+            job_timeout,
+            stall_timeout,
+            oversize,
+            oversize_threshold,
+            output_crf_cap,
+            min_free_space,
+            queue_fs,
+            keep_completed_days,
+            max_concurrent_reads,
+            max_load,
+            once,
+            continue_on_error,
+            done_marker,
+            on_complete,
+            on_failure,
+            print_command,
+            file,
+            notify_webhook,
+            metrics_addr,
+            tui: _,
+            no_move,
+            spill_dir,
+            match_source_permissions,
+            output_mode,
+            output_owner,
+            preserve_mtime,
+            post_processors,
         } => {
             let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
             info!(
@@ -165,28 +741,183 @@ async fn main() -> Result<()> {
                 path, work_root, background, priority
             );
             WorkCommand::new(path, work_root, background, priority)
+                .with_shuffle_seed(shuffle_seed)
+                .with_specials_order(specials)
+                .with_max_per_series(max_per_series)
+                .with_disk_limits(DiskLimits::from_limits(&concurrency_per_disk)?)
+                .with_job_timeout(job_timeout)
+                .with_stall_timeout(stall_timeout)
+                .with_oversize_policy(OversizePolicy {
+                    action: oversize,
+                    threshold_percent: oversize_threshold,
+                    crf_cap: output_crf_cap,
+                })
+                .with_min_free_space(min_free_space)
+                .with_queue_fs(queue_fs)
+                .with_keep_completed_days(keep_completed_days)
+                .with_max_concurrent_reads(max_concurrent_reads)
+                .with_max_load(max_load)
+                .with_once(once)
+                .with_continue_on_error(continue_on_error)
+                .with_done_marker(done_marker)
+                .with_on_complete(on_complete)
+                .with_on_failure(on_failure)
+                .with_print_command(print_command)
+                .with_file(file)
+                .with_notifier(notify_webhook.map(Notifier::webhook))
+                .with_metrics_addr(metrics_addr)
+                .with_dashboard(dashboard)
+                .with_no_move(no_move)
+                .with_spill_dir(spill_dir)
+                .with_output_permissions(OutputPermissions {
+                    match_source: match_source_permissions,
+                    mode: output_mode,
+                    owner: output_owner,
+                })
+                .with_preserve_mtime(preserve_mtime)
+                .with_post_processors(post_processors)
                 .execute()
                 .await
         }
-        Commands::Clean { path, work_dir } => {
+        Commands::Watch {
+            path,
+            work_dir,
+            preset,
+            background,
+            priority,
+            settle_time,
+        } => {
+            let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
+            info!(
+                "Starting watch command for path: {:?}, work: {:?}, preset: {:?}",
+                path, work_root, preset
+            );
+            WatchCommand::new(path, work_root, preset, background, priority, settle_time)
+                .execute()
+                .await
+        }
+        Commands::Clean {
+            path,
+            work_dir,
+            orphans,
+            prune_empty,
+        } => {
             let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
             info!(
                 "Starting clean command for path: {:?}, work: {:?}",
                 path, work_root
             );
-            CleanCommand::new(path, work_root).execute().await
+            CleanCommand::new(path, work_root)
+                .with_orphans(orphans)
+                .with_prune_empty(prune_empty)
+                .execute()
+                .await
         }
-        Commands::Validate { path } => {
+        Commands::Validate {
+            path,
+            input_root,
+            prune_empty,
+            fix,
+            keep_season_suffix,
+            rename_template,
+            layout,
+            throttle,
+            throttle_batch,
+            match_glob,
+            exclude,
+            group_by,
+            summary_only,
+            fail_on_issues,
+            work_dir,
+        } => {
             info!("Starting validate command for path: {:?}", path);
-            let validate_cmd = ValidateCommand::new(path);
+            let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
+            let validate_cmd = ValidateCommand::new(path)
+                .with_input_root(input_root)
+                .with_prune_empty(prune_empty)
+                .with_fix(fix)
+                .with_keep_season_suffix(keep_season_suffix)
+                .with_rename_template(rename_template)
+                .with_layout(layout)
+                .with_throttle(Duration::from_millis(throttle), throttle_batch)
+                .with_match(match_glob)
+                .with_exclude(exclude)
+                .with_group_by(group_by)
+                .with_summary_only(summary_only)
+                .with_work_root(Some(work_root));
             match validate_cmd.execute().await {
                 Ok(report) => {
                     validate_cmd.print_report(&report);
+                    let remaining = report.remaining_issues();
+                    if fail_on_issues && remaining > 0 {
+                        error!("Validation failed: {} issues remain", remaining);
+                        std::process::exit(2);
+                    }
                     Ok(())
                 }
                 Err(e) => Err(e),
             }
         }
+        Commands::Pause { work_dir } => {
+            let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
+            PauseCommand::new(work_root).execute().await
+        }
+        Commands::Resume { work_dir } => {
+            let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
+            ResumeCommand::new(work_root).execute().await
+        }
+        Commands::Verify {
+            work_dir,
+            checksums,
+        } => {
+            let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
+            VerifyCommand::new(work_root)
+                .with_checksums(checksums)
+                .execute()
+                .await
+        }
+        Commands::Undo { log } => {
+            let report = UndoCommand::new(log).execute().await?;
+            if report.skipped.is_empty() {
+                Ok(())
+            } else {
+                Err(anyhow!(
+                    "{} file(s) were left in place, the undo log was kept",
+                    report.skipped.len()
+                ))
+            }
+        }
+        Commands::Migrate { work_dir } => {
+            let work_root = work_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
+            MigrateCommand::new(work_root).execute().await.map(|_| ())
+        }
+        Commands::Doctor => {
+            let doctor_cmd = DoctorCommand::new(config::Config::from_env());
+            match doctor_cmd.execute().await {
+                Ok(report) => {
+                    doctor_cmd.print_report(&report);
+                    if report.all_passed() {
+                        Ok(())
+                    } else {
+                        Err(anyhow!("Some doctor checks failed"))
+                    }
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Commands::Presets { json, codec } => PresetsCommand::new()
+            .with_json(json)
+            .with_codec(codec)
+            .execute(),
+        Commands::Ignore {
+            command: IgnoreCommands::Explain { path, root },
+        } => {
+            let media_root = root.unwrap_or_else(|| std::env::current_dir().unwrap());
+            let explain_cmd = IgnoreExplainCommand::new(media_root, path);
+            explain_cmd
+                .execute()
+                .map(|explanation| explain_cmd.print_explanation(&explanation))
+        }
     };
 
     if let Err(e) = result {
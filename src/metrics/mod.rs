@@ -0,0 +1,208 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+/// Worker metrics exposed in the Prometheus text format
+#[derive(Debug, Default)]
+pub struct WorkerMetrics {
+    jobs_completed: AtomicU64,
+    jobs_failed: AtomicU64,
+    /// Progress of the current job (0.0-1.0), stored as f64 bits
+    current_job_progress: AtomicU64,
+    /// Encoding speed reported by ffmpeg (e.g. 1.5 = 1.5x realtime), stored as f64 bits
+    encode_speed: AtomicU64,
+}
+
+impl WorkerMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_completed(&self) {
+        self.jobs_completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_failed(&self) {
+        self.jobs_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_progress(&self, progress: f64) {
+        self.current_job_progress
+            .store(progress.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn set_encode_speed(&self, speed: f64) {
+        self.encode_speed.store(speed.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Reset the per-job gauges once a job finishes
+    pub fn reset_job_gauges(&self) {
+        self.set_progress(0.0);
+        self.set_encode_speed(0.0);
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counters = [
+            (
+                "plexify_jobs_completed_total",
+                "Total number of jobs completed by this worker",
+                self.jobs_completed.load(Ordering::Relaxed),
+            ),
+            (
+                "plexify_jobs_failed_total",
+                "Total number of jobs that failed on this worker",
+                self.jobs_failed.load(Ordering::Relaxed),
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} counter");
+            let _ = writeln!(out, "{name} {value}");
+        }
+
+        let gauges = [
+            (
+                "plexify_current_job_progress",
+                "Progress of the job currently being encoded (0-1)",
+                f64::from_bits(self.current_job_progress.load(Ordering::Relaxed)),
+            ),
+            (
+                "plexify_encode_speed",
+                "Encoding speed of the current job relative to realtime",
+                f64::from_bits(self.encode_speed.load(Ordering::Relaxed)),
+            ),
+        ];
+        for (name, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            let _ = writeln!(out, "{name} {value}");
+        }
+
+        out
+    }
+}
+
+/// Bind the metrics listener
+pub async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(
+        "📈 Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+    Ok(listener)
+}
+
+/// Serve `/metrics` on the given listener until the task is dropped
+pub async fn serve(listener: TcpListener, metrics: Arc<WorkerMetrics>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                debug!("Metrics request from {}", peer);
+                let metrics = Arc::clone(&metrics);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, &metrics).await {
+                        debug!("Metrics connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to accept metrics connection: {}", e),
+        }
+    }
+}
+
+/// Answer a single HTTP request with the rendered metrics or a 404
+async fn handle_connection(mut stream: TcpStream, metrics: &WorkerMetrics) -> Result<()> {
+    let mut buf = [0u8; 1024];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let request_line = request.lines().next().unwrap_or_default();
+
+    let response = if request_line.starts_with("GET /metrics") {
+        let body = metrics.render();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = WorkerMetrics::new();
+        metrics.record_completed();
+        metrics.record_completed();
+        metrics.record_failed();
+        metrics.set_progress(0.5);
+        metrics.set_encode_speed(1.25);
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE plexify_jobs_completed_total counter"));
+        assert!(text.contains("plexify_jobs_completed_total 2\n"));
+        assert!(text.contains("plexify_jobs_failed_total 1\n"));
+        assert!(text.contains("# TYPE plexify_current_job_progress gauge"));
+        assert!(text.contains("plexify_current_job_progress 0.5\n"));
+        assert!(text.contains("plexify_encode_speed 1.25\n"));
+    }
+
+    #[test]
+    fn test_progress_is_clamped_and_reset() {
+        let metrics = WorkerMetrics::new();
+        metrics.set_progress(1.7);
+        assert!(metrics
+            .render()
+            .contains("plexify_current_job_progress 1\n"));
+
+        metrics.reset_job_gauges();
+        assert!(metrics
+            .render()
+            .contains("plexify_current_job_progress 0\n"));
+        assert!(metrics.render().contains("plexify_encode_speed 0\n"));
+    }
+
+    #[tokio::test]
+    async fn test_scrape_metrics_endpoint() {
+        let metrics = Arc::new(WorkerMetrics::new());
+        metrics.record_completed();
+
+        let listener = bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(listener, Arc::clone(&metrics)));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("plexify_jobs_completed_total 1"));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        server.abort();
+    }
+}
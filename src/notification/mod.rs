@@ -0,0 +1,183 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use ureq::Agent;
+
+/// Event that triggered a notification
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// The queue became empty after the worker processed jobs
+    QueueDrained,
+    /// The worker is shutting down
+    WorkerStopped,
+}
+
+/// Summary of a worker run sent to notification sinks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotificationPayload {
+    pub event: NotificationEvent,
+    pub jobs_completed: u64,
+    pub jobs_failed: u64,
+    pub bytes_saved: i64,
+    pub elapsed_secs: u64,
+}
+
+/// Destination for worker notifications
+pub trait NotificationSink: Send + Sync {
+    fn send(&self, payload: &NotificationPayload) -> Result<()>;
+}
+
+/// How long a webhook may take to connect, and then to answer, before it is given up on
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sink that POSTs the payload as JSON to a webhook URL
+pub struct WebhookSink {
+    url: String,
+    agent: Agent,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        Self::with_timeout(url, WEBHOOK_TIMEOUT)
+    }
+
+    /// A hung webhook would otherwise stall the worker that notifies it
+    fn with_timeout(url: String, timeout: Duration) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_connect(Some(timeout))
+            .timeout_recv_response(Some(timeout))
+            .build()
+            .into();
+        Self { url, agent }
+    }
+}
+
+impl NotificationSink for WebhookSink {
+    fn send(&self, payload: &NotificationPayload) -> Result<()> {
+        self.agent
+            .post(&self.url)
+            .send_json(payload)
+            .map_err(|e| anyhow!("Webhook request to {} failed: {e}", self.url))?;
+        Ok(())
+    }
+}
+
+/// Sends notifications without ever failing the caller
+#[derive(Clone)]
+pub struct Notifier {
+    sink: Arc<dyn NotificationSink>,
+}
+
+impl Notifier {
+    pub fn new(sink: Arc<dyn NotificationSink>) -> Self {
+        Self { sink }
+    }
+
+    /// Create a notifier that posts to the given webhook URL
+    pub fn webhook(url: String) -> Self {
+        Self::new(Arc::new(WebhookSink::new(url)))
+    }
+
+    /// Deliver a notification, logging (but otherwise ignoring) any failure
+    pub async fn notify(&self, payload: NotificationPayload) {
+        let sink = Arc::clone(&self.sink);
+        debug!("Sending notification: {:?}", payload);
+
+        let result = tokio::task::spawn_blocking(move || sink.send(&payload)).await;
+        match result {
+            Ok(Ok(())) => info!("🔔 Notification sent"),
+            Ok(Err(e)) => warn!("Failed to send notification: {}", e),
+            Err(e) => warn!("Notification task failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Sink that records payloads instead of sending them
+    #[derive(Default)]
+    struct RecordingSink {
+        payloads: Mutex<Vec<NotificationPayload>>,
+    }
+
+    impl NotificationSink for RecordingSink {
+        fn send(&self, payload: &NotificationPayload) -> Result<()> {
+            self.payloads.lock().unwrap().push(payload.clone());
+            Ok(())
+        }
+    }
+
+    struct FailingSink;
+
+    impl NotificationSink for FailingSink {
+        fn send(&self, _payload: &NotificationPayload) -> Result<()> {
+            Err(anyhow!("connection refused"))
+        }
+    }
+
+    fn sample_payload() -> NotificationPayload {
+        NotificationPayload {
+            event: NotificationEvent::QueueDrained,
+            jobs_completed: 3,
+            jobs_failed: 1,
+            bytes_saved: 1024,
+            elapsed_secs: 42,
+        }
+    }
+
+    #[test]
+    fn test_payload_serialization() {
+        let json = serde_json::to_value(sample_payload()).unwrap();
+
+        assert_eq!(json["event"], "queue_drained");
+        assert_eq!(json["jobs_completed"], 3);
+        assert_eq!(json["jobs_failed"], 1);
+        assert_eq!(json["bytes_saved"], 1024);
+        assert_eq!(json["elapsed_secs"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_notifier_delivers_payload_to_sink() {
+        let sink = Arc::new(RecordingSink::default());
+        let notifier = Notifier::new(sink.clone());
+
+        notifier.notify(sample_payload()).await;
+
+        let payloads = sink.payloads.lock().unwrap();
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0], sample_payload());
+    }
+
+    #[tokio::test]
+    async fn test_notifier_ignores_sink_failures() {
+        let notifier = Notifier::new(Arc::new(FailingSink));
+
+        // Must not panic or propagate the error
+        notifier.notify(sample_payload()).await;
+    }
+
+    #[test]
+    fn test_webhook_sink_unreachable_url() {
+        let sink = WebhookSink::new("http://127.0.0.1:9/hook".to_string());
+        assert!(sink.send(&sample_payload()).is_err());
+    }
+
+    #[test]
+    fn test_webhook_sink_times_out_on_silent_server() {
+        // Accepts the connection but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let sink = WebhookSink::with_timeout(url, Duration::from_millis(200));
+
+        let start = std::time::Instant::now();
+        assert!(sink.send(&sample_payload()).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+}
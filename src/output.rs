@@ -0,0 +1,153 @@
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Whether output is plain text: no colors, no emoji and no progress bars
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// When to use colors, emoji and progress bars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only on a terminal, and only if `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Resolve the choice against the environment: `true` means plain output
+    fn is_plain(self) -> bool {
+        match self {
+            ColorChoice::Always => false,
+            ColorChoice::Never => true,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+                    || !io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Apply the color choice for the rest of the process
+pub fn configure(choice: ColorChoice) {
+    let plain = choice.is_plain();
+    PLAIN.store(plain, Ordering::Relaxed);
+    console::set_colors_enabled(!plain);
+    console::set_colors_enabled_stderr(!plain);
+}
+
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// A progress bar that stays hidden in plain mode
+pub fn progress_bar(len: u64) -> ProgressBar {
+    if is_plain() {
+        ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::hidden())
+    } else {
+        ProgressBar::new(len)
+    }
+}
+
+/// A spinner that stays hidden in plain mode
+pub fn spinner() -> ProgressBar {
+    if is_plain() {
+        ProgressBar::with_draw_target(None, ProgressDrawTarget::hidden())
+    } else {
+        ProgressBar::new_spinner()
+    }
+}
+
+/// Text as it should be printed: unchanged normally, stripped in plain mode
+pub fn for_terminal(text: &str) -> String {
+    if is_plain() {
+        plain_text(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Remove ANSI escapes and emoji from text
+pub fn plain_text(text: &str) -> String {
+    strip_emoji(&console::strip_ansi_codes(text))
+}
+
+/// Remove emoji (and the space after one) so log lines read as plain text
+pub fn strip_emoji(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_emoji(c) {
+            // Drop the modifiers that belong to it and the space separating it from the text
+            while chars.peek().is_some_and(|&next| is_emoji_modifier(next)) {
+                chars.next();
+            }
+            if chars.peek() == Some(&' ') {
+                chars.next();
+            }
+        } else if !is_emoji_modifier(c) {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2300..=0x23FF | 0x2600..=0x27BF | 0x2B00..=0x2BFF
+    )
+}
+
+/// Variation selectors and zero-width joiners that only make sense after an emoji
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c as u32, 0xFE0E | 0xFE0F | 0x200D)
+}
+
+/// Log writer for plain mode that strips emoji from every line
+pub struct PlainMakeWriter;
+
+impl<'a> MakeWriter<'a> for PlainMakeWriter {
+    type Writer = PlainWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        PlainWriter(io::stdout())
+    }
+}
+
+pub struct PlainWriter(io::Stdout);
+
+impl Write for PlainWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The fmt layer writes each event in one call, so emoji are never split
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(strip_emoji(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_strips_emoji_and_ansi() {
+        assert_eq!(strip_emoji("📊 Scanned 3 files"), "Scanned 3 files");
+        assert_eq!(strip_emoji("⚠️  Issues found: 2"), " Issues found: 2");
+        assert_eq!(strip_emoji("✅ Done → next"), "Done → next");
+        assert_eq!(
+            plain_text(&format!(
+                "{} ok",
+                console::style("❌").red().force_styling(true)
+            )),
+            "ok"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// One `--concurrency-per-disk` value: `N` for every disk, or `PATH=N` for the disk
+/// that `PATH` is on
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskLimit {
+    pub path: Option<PathBuf>,
+    pub max: usize,
+}
+
+impl FromStr for DiskLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (path, max) = match s.rsplit_once('=') {
+            Some((path, max)) => (Some(PathBuf::from(path)), max),
+            None => (None, s),
+        };
+        let max = max
+            .trim()
+            .parse()
+            .ok()
+            .filter(|max| *max > 0)
+            .ok_or_else(|| anyhow!("Invalid disk limit '{s}': use N or PATH=N with N above 0"))?;
+        Ok(Self { path, max })
+    }
+}
+
+/// Most jobs that may be in progress at once per storage device, so an HDD is not
+/// made to seek between several inputs while an SSD takes more
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiskLimits {
+    /// Limit for devices without one of their own
+    default: Option<usize>,
+    devices: HashMap<u64, usize>,
+}
+
+impl DiskLimits {
+    /// Resolve each `PATH=N` to the device the path is on
+    pub fn from_limits(limits: &[DiskLimit]) -> Result<Self> {
+        let mut disk_limits = Self::default();
+        for limit in limits {
+            match &limit.path {
+                Some(path) => {
+                    let device = device_of(path).ok_or_else(|| {
+                        anyhow!(
+                            "Cannot tell which disk {:?} is on for --concurrency-per-disk",
+                            path
+                        )
+                    })?;
+                    disk_limits.devices.insert(device, limit.max);
+                }
+                None => disk_limits.default = Some(limit.max),
+            }
+        }
+        Ok(disk_limits)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.devices.is_empty()
+    }
+
+    /// Devices whose in-progress jobs (counted per device) have reached their limit
+    pub fn devices_at_cap(&self, in_progress: &HashMap<u64, usize>) -> HashSet<u64> {
+        in_progress
+            .iter()
+            .filter(|(device, count)| {
+                self.devices
+                    .get(device)
+                    .copied()
+                    .or(self.default)
+                    .is_some_and(|max| **count >= max)
+            })
+            .map(|(device, _)| *device)
+            .collect()
+    }
+}
+
+/// Count inputs per device they are on; inputs whose device is unknown are left out
+pub fn count_by_device<'a>(inputs: impl IntoIterator<Item = &'a Path>) -> HashMap<u64, usize> {
+    let mut counts = HashMap::new();
+    for device in inputs.into_iter().filter_map(device_of) {
+        *counts.entry(device).or_default() += 1;
+    }
+    counts
+}
+
+/// The device a path (or, if it does not exist, its closest existing parent) is on.
+/// Always `None` on platforms without device ids.
+pub fn device_of(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        path.ancestors()
+            .find_map(|ancestor| std::fs::metadata(ancestor).ok())
+            .map(|metadata| metadata.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_disk_limit() {
+        assert_eq!(
+            "2".parse::<DiskLimit>().unwrap(),
+            DiskLimit { path: None, max: 2 }
+        );
+        assert_eq!(
+            "/mnt/ssd=4".parse::<DiskLimit>().unwrap(),
+            DiskLimit {
+                path: Some(PathBuf::from("/mnt/ssd")),
+                max: 4
+            }
+        );
+        assert!("/mnt/hdd=0".parse::<DiskLimit>().is_err());
+        assert!("/mnt/hdd".parse::<DiskLimit>().is_err());
+    }
+
+    #[test]
+    fn test_devices_at_cap() {
+        // An SSD taking three jobs, a default of one for the HDDs
+        let limits = DiskLimits {
+            default: Some(1),
+            devices: HashMap::from([(1, 3)]),
+        };
+        let in_progress = HashMap::from([(1, 2), (2, 1), (3, 0)]);
+        assert_eq!(limits.devices_at_cap(&in_progress), HashSet::from([2]));
+
+        let in_progress = HashMap::from([(1, 3), (2, 0)]);
+        assert_eq!(limits.devices_at_cap(&in_progress), HashSet::from([1]));
+
+        // Only the listed disk is limited without a default
+        let limits = DiskLimits {
+            default: None,
+            devices: HashMap::from([(1, 1)]),
+        };
+        let in_progress = HashMap::from([(1, 1), (2, 5)]);
+        assert_eq!(limits.devices_at_cap(&in_progress), HashSet::from([1]));
+    }
+
+    #[test]
+    fn test_count_by_device() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("a.mkv");
+        std::fs::write(&existing, "").unwrap();
+        // A missing input is counted on the disk its folder is on
+        let missing = temp_dir.path().join("Show/b.mkv");
+
+        let counts = count_by_device([existing.as_path(), missing.as_path()]);
+        if cfg!(unix) {
+            let device = device_of(temp_dir.path()).unwrap();
+            assert_eq!(counts, HashMap::from([(device, 2)]));
+            let limits = DiskLimits::from_limits(&[DiskLimit {
+                path: Some(temp_dir.path().to_path_buf()),
+                max: 2,
+            }])
+            .unwrap();
+            assert_eq!(limits.devices_at_cap(&counts), HashSet::from([device]));
+        } else {
+            assert!(counts.is_empty());
+        }
+    }
+}
@@ -1,17 +1,79 @@
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs as async_fs;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
-use crate::job::Job;
+use crate::error::PlexifyError;
+use crate::job::{Checksum, Job, QualitySettings, SpecialsOrder, JOB_SCHEMA_VERSION};
+
+mod disk_limits;
+
+pub use disk_limits::{DiskLimit, DiskLimits};
+
+/// Claim files older than this are left over from a crashed worker and get removed
+const STALE_CLAIM_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Filesystem the queue lives on, which decides how jobs are claimed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum QueueFs {
+    /// Claim by renaming the job into `_in_progress`
+    #[default]
+    Local,
+    /// Take an exclusive `<job>.claim` lockfile before renaming, since a rename
+    /// alone is not atomic across all NFS setups
+    Nfs,
+}
+
+/// Series and disks that already have as many jobs in progress as they may
+#[derive(Debug, Default)]
+struct ClaimCaps {
+    series: HashSet<String>,
+    devices: HashSet<u64>,
+}
+
+impl ClaimCaps {
+    fn is_empty(&self) -> bool {
+        self.series.is_empty() && self.devices.is_empty()
+    }
+
+    /// Whether `job` has to wait for a slot in its series or on its disk
+    fn blocks(&self, job: &Job, media_root: &Path) -> bool {
+        if job
+            .extract_episode_metadata()
+            .is_some_and(|metadata| self.series.contains(&metadata.series_name))
+        {
+            return true;
+        }
+        !self.devices.is_empty()
+            && disk_limits::device_of(&job.full_input_path(Some(media_root)))
+                .is_some_and(|device| self.devices.contains(&device))
+    }
+}
 
 /// Manages the job queue with atomic operations for distributed processing
 pub struct JobQueue {
-    #[allow(dead_code)]
     pub media_root: PathBuf,
     pub queue_dir: PathBuf,
     pub in_progress_dir: PathBuf,
     pub completed_dir: PathBuf,
+    /// Job files that could not be deserialized are moved here
+    pub corrupt_dir: PathBuf,
+    /// Jobs that failed in a way retrying cannot fix
+    pub failed_dir: PathBuf,
+    /// While this file exists, workers do not claim new jobs
+    pub pause_marker: PathBuf,
+    /// Seed for `JobPriority::Shuffle`; random when unset
+    shuffle_seed: Option<u64>,
+    /// Where specials go when claiming with `JobPriority::Episode`
+    specials_order: SpecialsOrder,
+    queue_fs: QueueFs,
+    /// Most jobs of one series that may be in progress at once
+    max_per_series: Option<usize>,
+    /// Most jobs reading from one disk that may be in progress at once
+    disk_limits: DiskLimits,
 }
 
 impl JobQueue {
@@ -20,15 +82,72 @@ impl JobQueue {
         let queue_dir = queue_root.join("_queue");
         let in_progress_dir = queue_root.join("_in_progress");
         let completed_dir = queue_root.join("_completed");
+        let corrupt_dir = queue_root.join("_corrupt");
+        let failed_dir = queue_root.join("_failed");
+        let pause_marker = queue_root.join("_paused");
 
         Self {
             media_root,
             queue_dir,
             in_progress_dir,
             completed_dir,
+            corrupt_dir,
+            failed_dir,
+            pause_marker,
+            shuffle_seed: None,
+            specials_order: SpecialsOrder::default(),
+            queue_fs: QueueFs::Local,
+            max_per_series: None,
+            disk_limits: DiskLimits::default(),
         }
     }
 
+    /// Use a fixed seed for shuffled claiming so the order is reproducible
+    pub fn with_shuffle_seed(mut self, shuffle_seed: Option<u64>) -> Self {
+        self.shuffle_seed = shuffle_seed;
+        self
+    }
+
+    /// Claim specials before or after the regular seasons of a show
+    pub fn with_specials_order(mut self, specials_order: SpecialsOrder) -> Self {
+        self.specials_order = specials_order;
+        self
+    }
+
+    /// Pick the claim strategy for the filesystem the queue lives on
+    pub fn with_queue_fs(mut self, queue_fs: QueueFs) -> Self {
+        self.queue_fs = queue_fs;
+        self
+    }
+
+    /// Skip series that already have this many jobs in `_in_progress`, so one big
+    /// series cannot take every worker. Workers claiming at the same moment may
+    /// briefly overshoot the cap.
+    pub fn with_max_per_series(mut self, max_per_series: Option<usize>) -> Self {
+        self.max_per_series = max_per_series;
+        self
+    }
+
+    /// Skip jobs whose input is on a disk that already has its limit of jobs in
+    /// `_in_progress`. Like the series cap, workers claiming at the same moment may
+    /// briefly overshoot it.
+    pub fn with_disk_limits(mut self, disk_limits: DiskLimits) -> Self {
+        self.disk_limits = disk_limits;
+        self
+    }
+
+    /// Check whether a path is one of the queue's own directories
+    pub fn is_queue_dir(path: &Path) -> bool {
+        path.file_name().is_some_and(|name| {
+            name == "_queue"
+                || name == "_in_progress"
+                || name == "_completed"
+                || name == "_corrupt"
+                || name == "_failed"
+                || name == "_read_slots"
+        })
+    }
+
     /// Initialize queue directories
     pub async fn init(&self) -> Result<()> {
         async_fs::create_dir_all(&self.queue_dir).await?;
@@ -50,8 +169,15 @@ impl JobQueue {
             return Ok(()); // Job is already being created by another process
         }
 
-        // Write job file
-        match async_fs::write(&job_path, job_content.as_bytes()).await {
+        // Write to a temporary file and rename it into place, so a crash mid-write
+        // never leaves a partial `.job` file for workers to claim
+        let temp_path = self.queue_dir.join(format!("{job_filename}.tmp"));
+        let result = match async_fs::write(&temp_path, job_content.as_bytes()).await {
+            Ok(_) => async_fs::rename(&temp_path, &job_path).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
             Ok(_) => {
                 debug!("Created job: {}", job_filename);
                 // Remove lock directory
@@ -59,9 +185,14 @@ impl JobQueue {
                 Ok(())
             }
             Err(e) => {
-                // Clean up lock directory on error
+                // Clean up temp file and lock directory on error
+                let _ = async_fs::remove_file(&temp_path).await;
                 let _ = async_fs::remove_dir(&lock_dir).await;
-                Err(anyhow!("Failed to create job file: {e}"))
+                Err(PlexifyError::JobFileCreate {
+                    path: job_path,
+                    source: e,
+                }
+                .into())
             }
         }
     }
@@ -73,18 +204,23 @@ impl JobQueue {
     ) -> Result<Option<ClaimedJob<'_>>> {
         match priority {
             Some(crate::JobPriority::Episode) => self.claim_prioritized_job().await,
+            Some(crate::JobPriority::Shuffle) => self.claim_shuffled_job().await,
             _ => self.claim_first_available_job().await,
         }
     }
 
     /// Claim the first available job (original behavior)
     async fn claim_first_available_job(&self) -> Result<Option<ClaimedJob<'_>>> {
+        let capped = self.claim_caps().await?;
         let mut entries = async_fs::read_dir(&self.queue_dir).await?;
 
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
             if let Some(extension) = path.extension() {
                 if extension == "job" {
+                    if self.is_capped(&path, &capped).await {
+                        continue;
+                    }
                     if let Some(claimed_job) = self.try_claim_job_file(&path).await? {
                         return Ok(Some(claimed_job));
                     }
@@ -95,9 +231,8 @@ impl JobQueue {
         Ok(None)
     }
 
-    /// Claim a job with episode prioritization
-    async fn claim_prioritized_job(&self) -> Result<Option<ClaimedJob<'_>>> {
-        // First, collect all available job files
+    /// Collect all `.job` files currently in the queue
+    async fn list_job_files(&self) -> Result<Vec<PathBuf>> {
         let mut job_files = Vec::new();
         let mut entries = async_fs::read_dir(&self.queue_dir).await?;
 
@@ -110,6 +245,174 @@ impl JobQueue {
             }
         }
 
+        Ok(job_files)
+    }
+
+    /// Read the queued jobs without claiming them, in file name order
+    pub async fn pending_jobs(&self) -> Result<Vec<Job>> {
+        let mut job_files = self.list_job_files().await?;
+        job_files.sort();
+
+        let mut jobs = Vec::new();
+        for job_path in job_files {
+            let content = match async_fs::read_to_string(&job_path).await {
+                Ok(content) => content,
+                // Claimed by a worker since we listed the queue
+                Err(_) => continue,
+            };
+            match serde_json::from_str(&content) {
+                Ok(job) => jobs.push(job),
+                Err(e) => warn!("Skipping unreadable job file {:?}: {}", job_path, e),
+            }
+        }
+        Ok(jobs)
+    }
+
+    /// Input paths of every job recorded in `_completed`, except samples, which
+    /// leave the input still to be converted
+    pub async fn completed_inputs(&self) -> Result<HashSet<PathBuf>> {
+        let mut inputs = HashSet::new();
+        let mut entries = match async_fs::read_dir(&self.completed_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(inputs),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "job") {
+                continue;
+            }
+            let content = async_fs::read_to_string(&path).await?;
+            match serde_json::from_str::<Job>(&content) {
+                Ok(job) if job.sample_secs.is_none() => {
+                    inputs.insert(job.input_path);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Skipping unreadable completed job {:?}: {}", path, e),
+            }
+        }
+
+        Ok(inputs)
+    }
+
+    /// Every job recorded in `_completed` with its `<job>.result.json` sidecar, if any
+    pub async fn completed_jobs(&self) -> Result<Vec<(Job, Option<JobResult>)>> {
+        let mut jobs = Vec::new();
+        let mut entries = match async_fs::read_dir(&self.completed_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(jobs),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "job") {
+                continue;
+            }
+            let content = async_fs::read_to_string(&path).await?;
+            let job: Job = match serde_json::from_str(&content) {
+                Ok(job) => job,
+                Err(e) => {
+                    warn!("Skipping unreadable completed job {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let result = match async_fs::read_to_string(path.with_extension("result.json")).await {
+                Ok(content) => serde_json::from_str(&content).ok(),
+                Err(_) => None,
+            };
+            jobs.push((job, result));
+        }
+
+        jobs.sort_by(|(a, _), (b, _)| a.input_path.cmp(&b.input_path));
+        Ok(jobs)
+    }
+
+    /// Apply a completed-job retention of `keep_days` days; `None` keeps everything
+    pub async fn apply_completed_retention(&self, keep_days: Option<u64>) -> Result<()> {
+        let Some(keep_days) = keep_days else {
+            return Ok(());
+        };
+        let pruned = self
+            .prune_completed(Duration::from_secs(keep_days.saturating_mul(24 * 60 * 60)))
+            .await?;
+        if pruned > 0 {
+            info!(
+                "🧹 Pruned {} completed jobs older than {} days",
+                pruned, keep_days
+            );
+        }
+        Ok(())
+    }
+
+    /// Delete completed job records (and their result sidecars) last modified more
+    /// than `max_age` ago. Returns how many jobs were pruned.
+    pub async fn prune_completed(&self, max_age: Duration) -> Result<usize> {
+        let mut entries = match async_fs::read_dir(&self.completed_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut pruned = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "job") {
+                continue;
+            }
+            let age = entry
+                .metadata()
+                .await
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok());
+            if age.is_none_or(|age| age <= max_age) {
+                continue;
+            }
+
+            async_fs::remove_file(&path).await?;
+            let sidecar = path.with_extension("result.json");
+            if sidecar.exists() {
+                async_fs::remove_file(&sidecar).await?;
+            }
+            debug!("Pruned completed job {:?}", path);
+            pruned += 1;
+        }
+
+        Ok(pruned)
+    }
+
+    /// Claim a random job, deterministic for a given seed and queue contents
+    async fn claim_shuffled_job(&self) -> Result<Option<ClaimedJob<'_>>> {
+        let mut job_files = self.list_job_files().await?;
+
+        // Directory order is filesystem-dependent, so sort before shuffling
+        job_files.sort();
+        let mut rng = match self.shuffle_seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        rng.shuffle(&mut job_files);
+
+        let capped = self.claim_caps().await?;
+        for job_path in job_files {
+            if self.is_capped(&job_path, &capped).await {
+                continue;
+            }
+            if let Some(claimed_job) = self.try_claim_job_file(&job_path).await? {
+                return Ok(Some(claimed_job));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Claim a job with episode prioritization
+    async fn claim_prioritized_job(&self) -> Result<Option<ClaimedJob<'_>>> {
+        // First, collect all available job files
+        let job_files = self.list_job_files().await?;
+
         if job_files.is_empty() {
             return Ok(None);
         }
@@ -117,19 +420,23 @@ impl JobQueue {
         // Load all jobs and extract metadata for sorting
         let mut jobs_with_metadata = Vec::new();
         for job_path in job_files {
-            // Try to read the job file
+            // Try to read the job file (it may have been claimed by another worker meanwhile)
             if let Ok(content) = async_fs::read_to_string(&job_path).await {
-                if let Ok(job) = serde_json::from_str::<Job>(&content) {
-                    let metadata = job.extract_episode_metadata();
-                    jobs_with_metadata.push((job_path, job, metadata));
+                match serde_json::from_str::<Job>(&content) {
+                    Ok(job) => {
+                        let metadata = job.extract_episode_metadata();
+                        jobs_with_metadata.push((job_path, job, metadata));
+                    }
+                    Err(e) => self.quarantine(&job_path, &e).await,
                 }
             }
         }
 
         // Sort jobs by priority:
         // 1. Episode jobs first (with metadata)
-        // 2. Within episodes: by series name, then season, then episode
+        // 2. Within episodes: by series name, then season (specials first or last), then episode
         // 3. Non-episode jobs last (maintain original order)
+        let specials = self.specials_order;
         jobs_with_metadata.sort_by(|a, b| {
             match (&a.2, &b.2) {
                 (Some(meta_a), Some(meta_b)) => {
@@ -137,7 +444,11 @@ impl JobQueue {
                     meta_a
                         .series_name
                         .cmp(&meta_b.series_name)
-                        .then(meta_a.season_number.cmp(&meta_b.season_number))
+                        .then(
+                            meta_a
+                                .season_sort_key(specials)
+                                .cmp(&meta_b.season_sort_key(specials)),
+                        )
                         .then(meta_a.episode_number.cmp(&meta_b.episode_number))
                 }
                 (Some(_), None) => std::cmp::Ordering::Less, // Episode jobs first
@@ -146,8 +457,12 @@ impl JobQueue {
             }
         });
 
-        // Try to claim jobs in priority order
-        for (job_path, _, _) in jobs_with_metadata {
+        // Try to claim jobs in priority order, moving on to the next series or disk when one is at its cap
+        let capped = self.claim_caps().await?;
+        for (job_path, job, _) in jobs_with_metadata {
+            if capped.blocks(&job, &self.media_root) {
+                continue;
+            }
             if let Some(claimed_job) = self.try_claim_job_file(&job_path).await? {
                 return Ok(Some(claimed_job));
             }
@@ -156,46 +471,213 @@ impl JobQueue {
         Ok(None)
     }
 
+    /// Series and disks with as many jobs in `_in_progress` as they may have (none
+    /// without caps)
+    async fn claim_caps(&self) -> Result<ClaimCaps> {
+        if self.max_per_series.is_none() && self.disk_limits.is_empty() {
+            return Ok(ClaimCaps::default());
+        }
+        let mut per_series: HashMap<String, usize> = HashMap::new();
+        let mut inputs = Vec::new();
+        let mut entries = match async_fs::read_dir(&self.in_progress_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ClaimCaps::default()),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "job") {
+                continue;
+            }
+            // The job may have finished since we listed the directory
+            let Ok(content) = async_fs::read_to_string(&path).await else {
+                continue;
+            };
+            let Ok(job) = serde_json::from_str::<Job>(&content) else {
+                continue;
+            };
+            if let Some(metadata) = job.extract_episode_metadata() {
+                *per_series.entry(metadata.series_name).or_default() += 1;
+            }
+            if !self.disk_limits.is_empty() {
+                inputs.push(job.full_input_path(Some(&self.media_root)));
+            }
+        }
+
+        let series = match self.max_per_series {
+            Some(max_per_series) => per_series
+                .into_iter()
+                .filter(|(_, count)| *count >= max_per_series)
+                .map(|(series, _)| series)
+                .collect(),
+            None => HashSet::new(),
+        };
+        let per_device = disk_limits::count_by_device(inputs.iter().map(PathBuf::as_path));
+        Ok(ClaimCaps {
+            series,
+            devices: self.disk_limits.devices_at_cap(&per_device),
+        })
+    }
+
+    /// Whether a queued job belongs to a capped series or reads from a capped disk
+    async fn is_capped(&self, job_path: &Path, capped: &ClaimCaps) -> bool {
+        if capped.is_empty() {
+            return false;
+        }
+        let Ok(content) = async_fs::read_to_string(job_path).await else {
+            return false;
+        };
+        serde_json::from_str::<Job>(&content).is_ok_and(|job| capped.blocks(&job, &self.media_root))
+    }
+
     /// Try to atomically claim a specific job file
     async fn try_claim_job_file(
         &self,
         job_path: &std::path::Path,
     ) -> Result<Option<ClaimedJob<'_>>> {
-        let job_name = job_path
+        let file_name = job_path
             .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| anyhow!("Invalid job filename"))?;
+            .ok_or_else(|| anyhow!("Invalid job filename: {:?}", job_path))?;
+        // Only used for logs and the `_completed`/`_failed` name, so a job file
+        // someone named with odd bytes still gets processed
+        let job_name = file_name.to_string_lossy();
 
-        let in_progress_path = self.in_progress_dir.join(job_name);
+        let in_progress_path = self.in_progress_dir.join(file_name);
 
-        // Atomically move job from queue to in_progress
-        match async_fs::rename(job_path, &in_progress_path).await {
-            Ok(_) => {
-                debug!("Claimed job: {}", job_name);
-
-                // Read and deserialize job content
-                let content = async_fs::read_to_string(&in_progress_path).await?;
-                let job: Job = serde_json::from_str(&content)?;
-
-                Ok(Some(ClaimedJob {
-                    queue: self,
-                    job_name: job_name.to_string(),
-                    job,
-                    in_progress_path,
-                }))
+        let claimed = match self.queue_fs {
+            QueueFs::Local => {
+                // Atomically move job from queue to in_progress
+                async_fs::rename(job_path, &in_progress_path).await.is_ok()
+            }
+            QueueFs::Nfs => self.claim_with_lockfile(job_path, &in_progress_path).await,
+        };
+
+        if !claimed {
+            // Job was claimed by another worker
+            return Ok(None);
+        }
+        debug!("Claimed job: {}", job_name);
+
+        // Read and deserialize job content
+        let content = async_fs::read_to_string(&in_progress_path).await?;
+        let job: Job = match serde_json::from_str(&content) {
+            Ok(job) => job,
+            Err(e) => {
+                self.quarantine(&in_progress_path, &e).await;
+                return Ok(None);
             }
-            Err(_) => {
-                // Job was claimed by another worker
-                Ok(None)
+        };
+
+        Ok(Some(ClaimedJob {
+            queue: self,
+            job_name: job_name.to_string(),
+            job,
+            in_progress_path,
+        }))
+    }
+
+    /// Claim a job on NFS: only the worker that creates `<job>.claim` with
+    /// `O_EXCL` may move the job into `_in_progress`
+    async fn claim_with_lockfile(&self, job_path: &Path, in_progress_path: &Path) -> bool {
+        let mut claim_name = job_path.as_os_str().to_owned();
+        claim_name.push(".claim");
+        let claim_path = PathBuf::from(claim_name);
+
+        let created = async_fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&claim_path)
+            .await;
+        if let Err(e) = created {
+            if e.kind() == std::io::ErrorKind::AlreadyExists {
+                Self::remove_stale_claim(&claim_path).await;
             }
+            return false;
+        }
+
+        // The job may have been claimed and the lockfile released before we got here
+        let claimed = job_path.exists()
+            && match async_fs::rename(job_path, in_progress_path).await {
+                Ok(_) => true,
+                // A retransmitted NFS rename can report failure after it went through;
+                // the job was still queued under our lock, so if it moved, we moved it
+                Err(_) => !job_path.exists() && in_progress_path.exists(),
+            };
+
+        let _ = async_fs::remove_file(&claim_path).await;
+        claimed
+    }
+
+    /// Remove a lockfile left behind by a worker that died mid-claim
+    async fn remove_stale_claim(claim_path: &Path) {
+        let age = async_fs::metadata(claim_path)
+            .await
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if age.is_some_and(|age| age > STALE_CLAIM_AGE) {
+            warn!("Removing stale claim file {:?}", claim_path);
+            let _ = async_fs::remove_file(claim_path).await;
+        }
+    }
+
+    /// Move a job file that can't be deserialized out of the way so workers keep going
+    async fn quarantine(&self, job_path: &Path, error: &serde_json::Error) {
+        let Some(job_name) = job_path.file_name() else {
+            return;
+        };
+        let corrupt_path = self.corrupt_dir.join(job_name);
+
+        warn!(
+            "Quarantining corrupt job file {:?} ({}) to {:?}",
+            job_path, error, corrupt_path
+        );
+        let result = match async_fs::create_dir_all(&self.corrupt_dir).await {
+            Ok(_) => async_fs::rename(job_path, &corrupt_path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to quarantine {:?}: {}", job_path, e);
+        }
+    }
+
+    /// Stop workers from claiming new jobs
+    pub async fn pause(&self) -> Result<()> {
+        if let Some(parent) = self.pause_marker.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        async_fs::write(&self.pause_marker, "").await?;
+        Ok(())
+    }
+
+    /// Let workers claim jobs again
+    pub async fn resume(&self) -> Result<()> {
+        match async_fs::remove_file(&self.pause_marker).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
         }
     }
 
-    /// Check if a job already exists in the queue
+    /// Whether the queue is paused
+    pub fn is_paused(&self) -> bool {
+        self.pause_marker.exists()
+    }
+
+    /// Check if a job for the same input is already queued or being worked on. Job
+    /// files are named by random ids, so their contents are compared. This reads the
+    /// whole queue; use `active_inputs` to check many files.
     pub async fn job_exists(&self, job: &Job) -> Result<bool> {
-        let job_filename = job.job_filename();
-        let job_path = self.queue_dir.join(job_filename);
-        Ok(job_path.exists())
+        Ok(self.active_inputs().await?.contains(&job.input_path))
+    }
+
+    /// Input paths of every job in `_queue` and `_in_progress`
+    pub async fn active_inputs(&self) -> Result<HashSet<PathBuf>> {
+        let mut inputs = HashSet::new();
+        for dir in [&self.queue_dir, &self.in_progress_dir] {
+            inputs.extend(read_jobs(dir).await?.into_iter().map(|job| job.input_path));
+        }
+        Ok(inputs)
     }
 
     /// Clean up all queue directories
@@ -209,25 +691,170 @@ impl JobQueue {
         if self.completed_dir.exists() {
             async_fs::remove_dir_all(&self.completed_dir).await?;
         }
+        if self.corrupt_dir.exists() {
+            async_fs::remove_dir_all(&self.corrupt_dir).await?;
+        }
+        if self.failed_dir.exists() {
+            async_fs::remove_dir_all(&self.failed_dir).await?;
+        }
         Ok(())
     }
 
     /// Get count of pending jobs
     #[allow(dead_code)]
     pub async fn pending_count(&self) -> Result<usize> {
-        let mut count = 0;
-        let mut entries = async_fs::read_dir(&self.queue_dir).await?;
+        count_jobs(&self.queue_dir).await
+    }
 
-        while let Some(entry) = entries.next_entry().await? {
-            if let Some(extension) = entry.path().extension() {
-                if extension == "job" {
-                    count += 1;
+    /// Rewrite job files written by an older plexify in the current schema. Claimed
+    /// jobs are left alone, since a worker holds them. The queue must be paused, or
+    /// a worker could claim a job between reading and rewriting it and run it twice.
+    pub async fn migrate_jobs(&self) -> Result<MigrationReport> {
+        if !self.is_paused() {
+            return Err(anyhow!(
+                "Pause the queue before migrating it, so workers don't claim jobs while they are rewritten"
+            ));
+        }
+
+        let mut report = MigrationReport::default();
+        for dir in [&self.queue_dir, &self.completed_dir, &self.failed_dir] {
+            let mut entries = match async_fs::read_dir(dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().is_none_or(|extension| extension != "job") {
+                    continue;
+                }
+                let content = match async_fs::read_to_string(&path).await {
+                    Ok(content) => content,
+                    // Claimed by a worker since we listed the directory
+                    Err(_) => continue,
+                };
+                let job: Job = match serde_json::from_str(&content) {
+                    Ok(job) => job,
+                    Err(e) => {
+                        warn!("Skipping unreadable job file {:?}: {}", path, e);
+                        report.unreadable += 1;
+                        continue;
+                    }
+                };
+                if job.version > JOB_SCHEMA_VERSION {
+                    warn!(
+                        "Skipping {:?}: written by a newer plexify (schema version {})",
+                        path, job.version
+                    );
+                    report.newer += 1;
+                    continue;
                 }
+                if !job.needs_migration() {
+                    report.current += 1;
+                    continue;
+                }
+
+                let from_version = job.version;
+                let temp_path = path.with_extension("job.tmp");
+                async_fs::write(&temp_path, serde_json::to_string_pretty(&job.migrate())?).await?;
+                async_fs::rename(&temp_path, &path).await?;
+                debug!(
+                    "Migrated {:?} from schema version {} to {}",
+                    path, from_version, JOB_SCHEMA_VERSION
+                );
+                report.migrated += 1;
             }
         }
+        Ok(report)
+    }
+
+    /// Number of jobs in each queue directory
+    pub async fn counts(&self) -> Result<QueueCounts> {
+        Ok(QueueCounts {
+            pending: count_jobs(&self.queue_dir).await?,
+            in_progress: count_jobs(&self.in_progress_dir).await?,
+            completed: count_jobs(&self.completed_dir).await?,
+            failed: count_jobs(&self.failed_dir).await?,
+        })
+    }
+}
+
+/// Outcome of upgrading the job files in a queue to the current schema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub migrated: usize,
+    /// Already in the current schema
+    pub current: usize,
+    /// Written by a newer plexify, so left untouched
+    pub newer: usize,
+    pub unreadable: usize,
+}
+
+/// How many jobs are queued, running, done and failed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueCounts {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+/// Count the `.job` files in a queue directory
+async fn count_jobs(dir: &Path) -> Result<usize> {
+    let mut count = 0;
+    let mut entries = async_fs::read_dir(dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some(extension) = entry.path().extension() {
+            if extension == "job" {
+                count += 1;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Read the `.job` files in a queue directory, skipping any that were claimed or
+/// moved meanwhile or can't be parsed
+async fn read_jobs(dir: &Path) -> Result<Vec<Job>> {
+    let mut jobs = Vec::new();
+    let mut entries = match async_fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(jobs),
+        Err(e) => return Err(e.into()),
+    };
 
-        Ok(count)
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "job") {
+            continue;
+        }
+        let Ok(content) = async_fs::read_to_string(&path).await else {
+            continue;
+        };
+        match serde_json::from_str(&content) {
+            Ok(job) => jobs.push(job),
+            Err(e) => debug!("Skipping unreadable job file {:?}: {}", path, e),
+        }
     }
+
+    Ok(jobs)
+}
+
+/// What a worker actually did for a job, stored next to the completed `.job` file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JobResult {
+    pub quality_settings: QualitySettings,
+    /// Arguments passed to ffmpeg, including the codecs used
+    pub ffmpeg_args: Vec<String>,
+    pub input_size: Option<u64>,
+    pub output_size: Option<u64>,
+    pub elapsed_secs: f64,
+    /// Checksum of the finished output, taken when the job carried an input checksum
+    #[serde(default)]
+    pub output_checksum: Option<Checksum>,
 }
 
 /// Represents a job that has been claimed by a worker
@@ -247,6 +874,26 @@ impl<'a> ClaimedJob<'a> {
         Ok(())
     }
 
+    /// Mark the job as completed and write a `<job>.result.json` sidecar describing the run
+    pub async fn complete_with_result(self, result: &JobResult) -> Result<()> {
+        let result_name = format!(
+            "{}.result.json",
+            self.job_name.strip_suffix(".job").unwrap_or(&self.job_name)
+        );
+        let result_path = self.queue.completed_dir.join(result_name);
+        async_fs::write(&result_path, serde_json::to_string_pretty(result)?).await?;
+        self.complete().await
+    }
+
+    /// Move the job to `_failed` so it is not retried
+    pub async fn fail(self) -> Result<()> {
+        async_fs::create_dir_all(&self.queue.failed_dir).await?;
+        let failed_path = self.queue.failed_dir.join(&self.job_name);
+        async_fs::rename(&self.in_progress_path, failed_path).await?;
+        warn!("Moved job to failed: {}", self.job_name);
+        Ok(())
+    }
+
     /// Return the job to the queue (e.g., on failure)
     pub async fn return_to_queue(self) -> Result<()> {
         let queue_path = self.queue.queue_dir.join(&self.job_name);
@@ -255,6 +902,20 @@ impl<'a> ClaimedJob<'a> {
         Ok(())
     }
 
+    /// Return the job to the queue after a retryable failure, counting the attempt
+    pub async fn retry(mut self) -> Result<()> {
+        self.job.retry_count += 1;
+        // Rewrite in place first, so the queued file is never partial
+        let temp_path = self.in_progress_path.with_extension("job.tmp");
+        async_fs::write(&temp_path, serde_json::to_string_pretty(&self.job)?).await?;
+        async_fs::rename(&temp_path, &self.in_progress_path).await?;
+        warn!(
+            "Retrying job ({}/{}): {}",
+            self.job.retry_count, self.job.max_retries, self.job_name
+        );
+        self.return_to_queue().await
+    }
+
     /// Get the job name
     pub fn job_name(&self) -> &str {
         &self.job_name
@@ -338,6 +999,237 @@ mod tests {
         assert!(queue.claim_job(None).await.unwrap().is_none());
     }
 
+    #[test]
+    async fn test_job_exists_matches_input_of_queued_and_claimed_jobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+        let new_job = |name: &str| {
+            Job::new(
+                PathBuf::from(name),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                temp_dir.path(),
+            )
+        };
+
+        queue.enqueue_job(&new_job("queued.mkv")).await.unwrap();
+        queue.enqueue_job(&new_job("claimed.mkv")).await.unwrap();
+        let claimed = queue.claim_job(None).await.unwrap().unwrap();
+
+        // A fresh job for the same input gets a new id, but is still a duplicate
+        assert!(queue.job_exists(&new_job("queued.mkv")).await.unwrap());
+        assert!(queue.job_exists(&new_job("claimed.mkv")).await.unwrap());
+        assert!(!queue.job_exists(&new_job("other.mkv")).await.unwrap());
+
+        claimed.complete().await.unwrap();
+    }
+
+    #[test]
+    async fn test_retry_counts_attempts() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+        let job = Job::new(
+            PathBuf::from("test.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            temp_dir.path(),
+        );
+        queue.enqueue_job(&job).await.unwrap();
+
+        let claimed = queue.claim_job(None).await.unwrap().unwrap();
+        assert_eq!(claimed.job.retry_count, 0);
+        claimed.retry().await.unwrap();
+
+        let claimed = queue.claim_job(None).await.unwrap().unwrap();
+        assert_eq!(claimed.job.retry_count, 1);
+        assert_eq!(claimed.job.id, job.id);
+        assert_eq!(
+            std::fs::read_dir(&queue.in_progress_dir).unwrap().count(),
+            1
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    async fn test_claim_job_with_non_utf8_name() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+
+        // A hand-made job file whose name is not valid UTF-8
+        let job = Job::new(
+            PathBuf::from("Amélie (2001).mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            temp_dir.path(),
+        );
+        let name = std::ffi::OsStr::from_bytes(b"Am\xe9lie.job");
+        std::fs::write(
+            queue.queue_dir.join(name),
+            serde_json::to_string(&job).unwrap(),
+        )
+        .unwrap();
+
+        let claimed = queue.claim_job(None).await.unwrap().unwrap();
+        assert_eq!(claimed.job.input_path, job.input_path);
+        assert!(queue.in_progress_dir.join(name).exists());
+        claimed.complete().await.unwrap();
+        assert_eq!(std::fs::read_dir(&queue.completed_dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    async fn test_partial_temp_job_is_never_claimed() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+
+        // Simulate a process that died halfway through writing a job
+        async_fs::write(
+            queue.queue_dir.join("abc.job.tmp"),
+            "{\"id\": \"abc\", \"inp",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(queue.pending_count().await.unwrap(), 0);
+        assert!(queue.claim_job(None).await.unwrap().is_none());
+        assert!(queue
+            .claim_job(Some(crate::JobPriority::Episode))
+            .await
+            .unwrap()
+            .is_none());
+
+        // A completed enqueue leaves only the final .job file behind
+        let job = Job::new(
+            PathBuf::from("test.webm"),
+            MediaFileType::WebM,
+            QualitySettings::default(),
+            PostProcessingSettings::default(),
+            temp_dir.path(),
+        );
+        queue.enqueue_job(&job).await.unwrap();
+        assert!(queue.queue_dir.join(job.job_filename()).exists());
+        assert!(!queue
+            .queue_dir
+            .join(format!("{}.tmp", job.job_filename()))
+            .exists());
+
+        let claimed = queue.claim_job(None).await.unwrap().unwrap();
+        assert!(claimed.job.input_path.ends_with("test.webm"));
+    }
+
+    #[test]
+    async fn test_corrupt_job_is_quarantined() {
+        for priority in [None, Some(crate::JobPriority::Episode)] {
+            let temp_dir = TempDir::new().unwrap();
+            let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+            queue.init().await.unwrap();
+
+            async_fs::write(queue.queue_dir.join("garbage.job"), "not json at all")
+                .await
+                .unwrap();
+            for name in ["a.webm", "b.webm"] {
+                let job = Job::new(
+                    PathBuf::from(name),
+                    MediaFileType::WebM,
+                    QualitySettings::default(),
+                    PostProcessingSettings::default(),
+                    temp_dir.path(),
+                );
+                queue.enqueue_job(&job).await.unwrap();
+            }
+
+            // Both valid jobs are processed despite the garbage file
+            let mut claimed_inputs = Vec::new();
+            while let Some(claimed) = queue.claim_job(priority.clone()).await.unwrap() {
+                claimed_inputs.push(claimed.job.input_path.clone());
+                claimed.complete().await.unwrap();
+            }
+            assert_eq!(claimed_inputs.len(), 2);
+
+            assert!(queue.corrupt_dir.join("garbage.job").exists());
+            assert!(!queue.queue_dir.join("garbage.job").exists());
+            assert!(!queue.in_progress_dir.join("garbage.job").exists());
+        }
+    }
+
+    #[test]
+    async fn test_complete_with_result_writes_sidecar() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+
+        let job = Job::new(
+            PathBuf::from("movie.mkv"),
+            MediaFileType::Mkv,
+            QualitySettings::default(),
+            PostProcessingSettings {
+                disable_source_files: false,
+            },
+            temp_dir.path(),
+        );
+        queue.enqueue_job(&job).await.unwrap();
+
+        let claimed = queue.claim_job(None).await.unwrap().unwrap();
+        let result = JobResult {
+            quality_settings: claimed.job.quality_settings.clone(),
+            ffmpeg_args: vec!["-c:v".to_string(), "libx264".to_string()],
+            input_size: Some(2000),
+            output_size: Some(1000),
+            elapsed_secs: 12.5,
+            output_checksum: None,
+        };
+        claimed.complete_with_result(&result).await.unwrap();
+
+        let sidecar = queue.completed_dir.join(format!("{}.result.json", job.id));
+        let stored: JobResult =
+            serde_json::from_str(&std::fs::read_to_string(sidecar).unwrap()).unwrap();
+        assert_eq!(stored, result);
+        assert!(queue.completed_dir.join(format!("{}.job", job.id)).exists());
+    }
+
+    #[test]
+    async fn test_prune_completed_removes_only_old_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf());
+        queue.init().await.unwrap();
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        let old_job = queue.completed_dir.join("old.job");
+        let old_sidecar = queue.completed_dir.join("old.result.json");
+        let new_job = queue.completed_dir.join("new.job");
+        for path in [&old_job, &old_sidecar, &new_job] {
+            std::fs::write(path, "{}").unwrap();
+        }
+        std::fs::File::options()
+            .write(true)
+            .open(&old_job)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - 30 * day)
+            .unwrap();
+
+        let pruned = queue.prune_completed(7 * day).await.unwrap();
+
+        assert_eq!(pruned, 1);
+        assert!(!old_job.exists());
+        assert!(!old_sidecar.exists());
+        assert!(new_job.exists());
+
+        // A retention too long to count in seconds keeps everything
+        queue
+            .apply_completed_retention(Some(u64::MAX))
+            .await
+            .unwrap();
+        assert!(new_job.exists());
+    }
+
     #[test]
     async fn test_episode_prioritization() {
         let temp_dir = TempDir::new().unwrap();
@@ -420,6 +1312,229 @@ mod tests {
         assert!(claimed_order[4].contains("The Matrix"));
     }
 
+    #[test]
+    async fn test_max_per_series_spreads_claims() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf())
+            .with_max_per_series(Some(1));
+        queue.init().await.unwrap();
+
+        for path in [
+            "Series/Big Show/Season 01/Big Show S01E01 One.mkv",
+            "Series/Big Show/Season 01/Big Show S01E02 Two.mkv",
+            "Series/Big Show/Season 01/Big Show S01E03 Three.mkv",
+            "Series/Small Show/Season 01/Small Show S01E01 One.mkv",
+            "Movies/Heat (1995)/Heat (1995).mkv",
+        ] {
+            let job = Job::new(
+                PathBuf::from(path),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                media_root,
+            );
+            queue.enqueue_job(&job).await.unwrap();
+        }
+
+        // Claims are held, as by workers still busy with them
+        let mut held = Vec::new();
+        while let Some(claimed) = queue
+            .claim_job(Some(crate::JobPriority::Episode))
+            .await
+            .unwrap()
+        {
+            held.push(claimed);
+        }
+        let names: Vec<String> = held
+            .iter()
+            .map(|claimed| claimed.job.display_name())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Big Show - S01E01",
+                "Small Show - S01E01",
+                "Heat (1995).mkv"
+            ]
+        );
+
+        // Finishing a job frees a slot for its series
+        held.remove(0).complete().await.unwrap();
+        let claimed = queue.claim_job(None).await.unwrap().unwrap();
+        assert!(claimed.job.display_name().starts_with("Big Show"));
+        // The other Big Show episode waits, whatever the claim order
+        assert!(queue.claim_job(None).await.unwrap().is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    async fn test_concurrency_per_disk_limits_claims() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf())
+            .with_disk_limits(
+                DiskLimits::from_limits(&[DiskLimit { path: None, max: 1 }]).unwrap(),
+            );
+        queue.init().await.unwrap();
+
+        // Every input is on the temp dir's disk
+        for path in [
+            "Movies/Heat (1995)/Heat (1995).mkv",
+            "Movies/Ronin (1998)/Ronin (1998).mkv",
+        ] {
+            let job = Job::new(
+                PathBuf::from(path),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                media_root,
+            );
+            queue.enqueue_job(&job).await.unwrap();
+        }
+
+        let held = queue.claim_job(None).await.unwrap().unwrap();
+        for priority in [None, Some(crate::JobPriority::Episode)] {
+            assert!(queue.claim_job(priority).await.unwrap().is_none());
+        }
+
+        // Finishing the job frees the disk
+        held.complete().await.unwrap();
+        assert!(queue.claim_job(None).await.unwrap().is_some());
+    }
+
+    #[test]
+    async fn test_specials_prioritization() {
+        let temp_dir = TempDir::new().unwrap();
+        let media_root = temp_dir.path();
+        let job = |path: &str| {
+            Job::new(
+                PathBuf::from(path),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                media_root,
+            )
+        };
+
+        for specials_order in [SpecialsOrder::Last, SpecialsOrder::First] {
+            let queue = JobQueue::new(media_root.to_path_buf(), media_root.to_path_buf())
+                .with_specials_order(specials_order);
+            queue.clean().await.unwrap();
+            queue.init().await.unwrap();
+            queue
+                .enqueue_job(&job("Series/Show/Specials/Show S00E01 Special.mkv"))
+                .await
+                .unwrap();
+            queue
+                .enqueue_job(&job("Series/Show/Season 01/Show S01E01 Pilot.mkv"))
+                .await
+                .unwrap();
+
+            let first = queue
+                .claim_job(Some(crate::JobPriority::Episode))
+                .await
+                .unwrap()
+                .unwrap();
+            let expected = match specials_order {
+                SpecialsOrder::Last => "S01E01",
+                SpecialsOrder::First => "S00E01",
+            };
+            assert!(first.job.input_path.to_string_lossy().contains(expected));
+        }
+    }
+
+    #[test]
+    async fn test_shuffled_order_is_reproducible_with_seed() {
+        async fn claim_all(seed: u64) -> Vec<String> {
+            let temp_dir = TempDir::new().unwrap();
+            let queue = JobQueue::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf())
+                .with_shuffle_seed(Some(seed));
+            queue.init().await.unwrap();
+
+            for episode in 1..=6 {
+                let mut job = Job::new(
+                    PathBuf::from(format!("Series/Show/Season 01/Show S01E{episode:02}.mkv")),
+                    MediaFileType::Mkv,
+                    QualitySettings::default(),
+                    PostProcessingSettings::default(),
+                    temp_dir.path(),
+                );
+                // Stable ids so the sorted file order is the same for every run
+                job.id = format!("job-{episode}");
+                queue.enqueue_job(&job).await.unwrap();
+            }
+
+            let mut order = Vec::new();
+            while let Some(claimed) = queue
+                .claim_job(Some(crate::JobPriority::Shuffle))
+                .await
+                .unwrap()
+            {
+                order.push(claimed.job.id.clone());
+                claimed.complete().await.unwrap();
+            }
+            order
+        }
+
+        let first = claim_all(42).await;
+        assert_eq!(first.len(), 6);
+        assert_eq!(first, claim_all(42).await);
+
+        let sorted: Vec<String> = (1..=6).map(|episode| format!("job-{episode}")).collect();
+        assert_ne!(first, sorted);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn test_nfs_claims_are_exclusive_under_contention() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        let queue = JobQueue::new(root.clone(), root.clone());
+        queue.init().await.unwrap();
+
+        let job_count = 50;
+        for i in 0..job_count {
+            let job = Job::new(
+                PathBuf::from(format!("video{i}.mkv")),
+                MediaFileType::Mkv,
+                QualitySettings::default(),
+                PostProcessingSettings::default(),
+                &root,
+            );
+            queue.enqueue_job(&job).await.unwrap();
+        }
+
+        // Many workers with their own queue handle race for the same jobs
+        let mut workers = Vec::new();
+        for _ in 0..16 {
+            let root = root.clone();
+            workers.push(tokio::spawn(async move {
+                let queue = JobQueue::new(root.clone(), root).with_queue_fs(QueueFs::Nfs);
+                let mut claimed = Vec::new();
+                loop {
+                    match queue.claim_job(None).await.unwrap() {
+                        Some(job) => claimed.push(job.job_name().to_string()),
+                        None if queue.pending_count().await.unwrap() == 0 => break,
+                        None => tokio::task::yield_now().await,
+                    }
+                }
+                claimed
+            }));
+        }
+
+        let mut all_claimed = Vec::new();
+        for worker in workers {
+            all_claimed.extend(worker.await.unwrap());
+        }
+        let unique: std::collections::HashSet<_> = all_claimed.iter().collect();
+        assert_eq!(all_claimed.len(), job_count);
+        assert_eq!(unique.len(), job_count);
+
+        // No lockfiles are left behind
+        let leftovers = std::fs::read_dir(&queue.queue_dir).unwrap().count();
+        assert_eq!(leftovers, 0);
+    }
+
     #[test]
     async fn test_no_prioritization() {
         let temp_dir = TempDir::new().unwrap();
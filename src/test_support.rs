@@ -0,0 +1,45 @@
+//! Stand-ins for ffmpeg and ffprobe shared by the tests that run them
+
+use std::ffi::OsString;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Fake ffmpeg body that writes `converted` to its last argument (the output path)
+pub const CONVERTING_FFMPEG: &str = "for last; do :; done\necho converted > \"$last\"\n";
+
+/// Write an executable `sh` script called `name` into `dir`
+pub fn script(dir: &Path, name: &str, body: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{body}")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+/// Write a script called `name` and point the environment variable `var` at it,
+/// e.g. `PLEXIFY_FFMPEG_PATH`. Tests using this must be `#[serial]`.
+pub fn fake_tool(var: &'static str, dir: &Path, name: &str, body: &str) -> EnvGuard {
+    EnvGuard::set(var, script(dir, name, body))
+}
+
+/// Restores an environment variable when dropped, even if the test panics
+pub struct EnvGuard {
+    var: &'static str,
+    previous: Option<OsString>,
+}
+
+impl EnvGuard {
+    pub fn set(var: &'static str, value: impl Into<OsString>) -> Self {
+        let previous = std::env::var_os(var);
+        std::env::set_var(var, value.into());
+        Self { var, previous }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        match &self.previous {
+            Some(value) => std::env::set_var(self.var, value),
+            None => std::env::remove_var(self.var),
+        }
+    }
+}
@@ -32,7 +32,7 @@ fn test_scan_and_clean_workflow() {
 
     // Create test files
     fs::write(temp_path.join("video1.webm"), "").unwrap();
-    fs::write(temp_path.join("video1.vtt"), "").unwrap();
+    fs::write(temp_path.join("video1.vtt"), "WEBVTT\n").unwrap();
     fs::write(temp_path.join("video2.mkv"), "").unwrap();
     fs::write(temp_path.join("video3.webm"), "").unwrap(); // No .vtt file
 
@@ -386,7 +386,7 @@ fn test_add_command_individual_files() {
 
     fs::write(&mkv_file, "test mkv content").unwrap();
     fs::write(&webm_file, "test webm content").unwrap();
-    fs::write(&vtt_file, "test vtt content").unwrap();
+    fs::write(&vtt_file, "WEBVTT\n\ntest vtt content").unwrap();
 
     // Test add command with MKV (no subtitles needed)
     let add_mkv_output = Command::new("./target/debug/plexify")
@@ -493,9 +493,13 @@ fn test_hierarchical_directory_scanning() {
     // Create media files in different subdirectories
     fs::write(temp_path.join("Movies/Action/action1.mkv"), "").unwrap();
     fs::write(temp_path.join("Movies/Comedy/comedy1.webm"), "").unwrap();
-    fs::write(temp_path.join("Movies/Comedy/comedy1.vtt"), "").unwrap();
+    fs::write(temp_path.join("Movies/Comedy/comedy1.vtt"), "WEBVTT\n").unwrap();
     fs::write(temp_path.join("TV Shows/Show1/Season 1/episode1.webm"), "").unwrap();
-    fs::write(temp_path.join("TV Shows/Show1/Season 1/episode1.vtt"), "").unwrap();
+    fs::write(
+        temp_path.join("TV Shows/Show1/Season 1/episode1.vtt"),
+        "WEBVTT\n",
+    )
+    .unwrap();
     fs::write(temp_path.join("TV Shows/Show1/Season 2/episode2.mkv"), "").unwrap();
     fs::write(temp_path.join("TV Shows/Show2/episode.mkv"), "").unwrap();
 
@@ -593,7 +597,7 @@ fn test_absolute_paths_in_jobs() {
     .unwrap();
     fs::write(
         media_path.join("Season_01/episode2.vtt"),
-        "dummy subtitle content",
+        "WEBVTT\n\ndummy subtitle content",
     )
     .unwrap();
 
@@ -928,3 +932,69 @@ fn test_work_priority_defaults() {
         "Help should show 'episode' as an option"
     );
 }
+
+/// Test that -v enables debug logging and -q hides info logging
+#[test]
+#[serial]
+fn test_verbosity_flags() {
+    build_plexify();
+    let temp_dir = TempDir::new().unwrap();
+
+    let run = |flag: &str| {
+        let output = Command::new("./target/debug/plexify")
+            .env_remove("RUST_LOG")
+            .args([flag, "validate", temp_dir.path().to_str().unwrap()])
+            .output()
+            .expect("Failed to execute validate command");
+        assert!(output.status.success(), "Validate command failed");
+        format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    };
+
+    let verbose = run("-v");
+    assert!(
+        verbose.contains("DEBUG") && verbose.contains("Log filter: plexify=debug"),
+        "Expected debug output with -v, got: {verbose}"
+    );
+
+    let quiet = run("--quiet");
+    assert!(
+        !quiet.contains("INFO") && !quiet.contains("DEBUG"),
+        "Expected no info output with --quiet, got: {quiet}"
+    );
+}
+
+/// Test that validate --fail-on-issues sets a non-zero exit code
+#[test]
+#[serial]
+fn test_validate_fail_on_issues_exit_code() {
+    build_plexify();
+    let temp_dir = TempDir::new().unwrap();
+    let media_root = temp_dir.path();
+
+    let movie_dir = media_root.join("Movies/Heat (1995)");
+    fs::create_dir_all(&movie_dir).unwrap();
+    fs::write(movie_dir.join("Heat (1995).mkv"), "").unwrap();
+
+    let validate = |extra: &[&str]| {
+        Command::new("./target/debug/plexify")
+            .arg("validate")
+            .arg(media_root)
+            .args(extra)
+            .output()
+            .expect("Failed to execute validate command")
+    };
+
+    // A clean library passes
+    assert!(validate(&["--fail-on-issues"]).status.success());
+
+    // A mislabeled file fails the run, but only when asked to
+    fs::create_dir_all(media_root.join("Series/Show")).unwrap();
+    fs::write(media_root.join("Series/Show/episode.mkv"), "").unwrap();
+
+    assert_eq!(validate(&["--fail-on-issues"]).status.code(), Some(2));
+    assert!(validate(&[]).status.success());
+}